@@ -0,0 +1,167 @@
+/// Decide whether `path` (repo-relative, `/`-separated) is a generated file
+/// according to gitignore-style `patterns`.
+///
+/// Patterns are evaluated in order and the last match wins, so a later
+/// `!pattern` can re-include something an earlier pattern matched. A pattern
+/// without a `/` matches the file name at any depth; a pattern containing `/`
+/// is matched against the whole path. A pattern that matches a directory
+/// matches everything under it, and a trailing `/` restricts a pattern to
+/// directories. `*` and `?` never cross a `/`, while a `**` segment matches
+/// any number of directories.
+pub fn is_generated(path: &str, patterns: &[String]) -> bool {
+    let mut generated = false;
+    for pattern in patterns {
+        let pattern = pattern.trim();
+        if pattern.is_empty() || pattern.starts_with('#') {
+            continue;
+        }
+        let (negated, pattern) = match pattern.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, pattern),
+        };
+        if matches_pattern(path, pattern) {
+            generated = !negated;
+        }
+    }
+    generated
+}
+
+fn matches_pattern(path: &str, pattern: &str) -> bool {
+    let (dir_only, pattern) = match pattern.strip_suffix('/') {
+        Some(dir) => (true, dir),
+        None => (false, pattern),
+    };
+    let pattern = pattern.trim_start_matches('/');
+    if pattern.is_empty() {
+        return false;
+    }
+    let path_segments: Vec<&str> = path.split('/').collect();
+    // The path itself, then each directory it sits under. A directory-only
+    // pattern skips the path, which always names a file.
+    let longest = path_segments.len() - usize::from(dir_only);
+    if pattern.contains('/') {
+        let pattern_segments: Vec<&str> = pattern.split('/').collect();
+        (1..=longest).any(|len| match_segments(&path_segments[..len], &pattern_segments))
+    } else {
+        path_segments[..longest]
+            .iter()
+            .any(|name| glob_match(name.as_bytes(), pattern.as_bytes()))
+    }
+}
+
+fn match_segments(path: &[&str], pattern: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|skip| match_segments(&path[skip..], rest)),
+        Some((segment, rest)) => match path.split_first() {
+            Some((name, path_rest)) => {
+                glob_match(name.as_bytes(), segment.as_bytes()) && match_segments(path_rest, rest)
+            }
+            None => false,
+        },
+    }
+}
+
+/// Match a single path segment against a pattern supporting `*` and `?`.
+fn glob_match(text: &[u8], pattern: &[u8]) -> bool {
+    let (mut t, mut p) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == b'?' || pattern[p] == text[t]) {
+            t += 1;
+            p += 1;
+        } else if p < pattern.len() && pattern[p] == b'*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = backtrack {
+            p = star_p + 1;
+            t = star_t + 1;
+            backtrack = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == b'*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn patterns(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_basename_pattern_matches_at_any_depth() {
+        let p = patterns(&["*.lock"]);
+        assert!(is_generated("Cargo.lock", &p));
+        assert!(is_generated("crates/app/yarn.lock", &p));
+        assert!(!is_generated("src/lock.rs", &p));
+    }
+
+    #[test]
+    fn test_directory_double_star() {
+        let p = patterns(&["dist/**"]);
+        assert!(is_generated("dist/app.js", &p));
+        assert!(is_generated("dist/assets/img/logo.png", &p));
+        assert!(!is_generated("src/dist.rs", &p));
+        assert!(!is_generated("web/dist/app.js", &p));
+    }
+
+    #[test]
+    fn test_directory_pattern_matches_files_under_it() {
+        for pattern in ["dist/", "dist"] {
+            let p = patterns(&[pattern]);
+            assert!(is_generated("dist/app.js", &p), "{pattern}");
+            assert!(is_generated("a/dist/x.js", &p), "{pattern}");
+            assert!(!is_generated("distant/x.js", &p), "{pattern}");
+        }
+        assert!(is_generated("dist", &patterns(&["dist"])));
+        assert!(!is_generated("dist", &patterns(&["dist/"])));
+
+        let p = patterns(&["web/dist/"]);
+        assert!(is_generated("web/dist/app.js", &p));
+        assert!(!is_generated("a/web/dist/app.js", &p));
+    }
+
+    #[test]
+    fn test_leading_double_star_matches_nested() {
+        let p = patterns(&["**/generated/*.rs"]);
+        assert!(is_generated("generated/schema.rs", &p));
+        assert!(is_generated("crates/api/generated/schema.rs", &p));
+        assert!(!is_generated("crates/api/generated/sub/schema.rs", &p));
+    }
+
+    #[test]
+    fn test_single_star_does_not_cross_directories() {
+        let p = patterns(&["build/*.js"]);
+        assert!(is_generated("build/app.js", &p));
+        assert!(!is_generated("build/nested/app.js", &p));
+    }
+
+    #[test]
+    fn test_negation_reincludes_file() {
+        let p = patterns(&["dist/**", "!dist/keep.js"]);
+        assert!(is_generated("dist/app.js", &p));
+        assert!(!is_generated("dist/keep.js", &p));
+    }
+
+    #[test]
+    fn test_last_match_wins() {
+        let p = patterns(&["!*.lock", "*.lock"]);
+        assert!(is_generated("Cargo.lock", &p));
+    }
+
+    #[test]
+    fn test_question_mark_and_comments() {
+        let p = patterns(&["# comment", "", "file?.txt"]);
+        assert!(is_generated("file1.txt", &p));
+        assert!(!is_generated("file10.txt", &p));
+    }
+
+    #[test]
+    fn test_no_patterns() {
+        assert!(!is_generated("Cargo.lock", &[]));
+    }
+}
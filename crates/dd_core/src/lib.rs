@@ -1,8 +1,17 @@
+pub mod generated;
+pub mod preferences;
+pub mod review_notes;
 pub mod session;
 pub mod state;
 
+pub use generated::is_generated;
+pub use preferences::{
+    Branding, ChangeMarkerStyle, DiffPalette, GeneratedFileMode, Preferences, RowDensity,
+    SplitGutter,
+};
+pub use review_notes::{ReviewNote, ReviewNotes};
 pub use session::Session;
-pub use state::{AppState, RepoTab};
+pub use state::{AppState, Appearance, RepoTab, SidebarGroup};
 
 pub fn version() -> &'static str {
     env!("CARGO_PKG_VERSION")
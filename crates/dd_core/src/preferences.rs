@@ -0,0 +1,231 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// How the diff view treats files matched by `generated_patterns`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum GeneratedFileMode {
+    /// Show the file header but fold its hunks until expanded.
+    #[default]
+    Collapse,
+    /// Leave the file out of the diff view entirely.
+    Hide,
+}
+
+/// Which line numbers each side of the split diff shows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum SplitGutter {
+    /// Old numbers on the left side, new numbers on the right.
+    #[default]
+    SideOnly,
+    /// Old and new numbers on both sides.
+    Both,
+}
+
+/// How diff lines mark themselves as added or removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum ChangeMarkerStyle {
+    /// A `+`/`-` character before the line content.
+    #[default]
+    Prefix,
+    /// A colored bar along the line's left edge, with no prefix character.
+    Bar,
+}
+
+/// How much padding surrounds each commit-list row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum RowDensity {
+    Compact,
+    #[default]
+    Comfortable,
+    Spacious,
+}
+
+/// Overrides for the diff's addition/deletion colors, e.g. blue/orange for
+/// red-green colorblindness. Hues are in degrees (0-360) and saturations in
+/// 0-1; `None` keeps the theme's success/danger color.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct DiffPalette {
+    pub add_hue: Option<f32>,
+    pub add_saturation: Option<f32>,
+    pub del_hue: Option<f32>,
+    pub del_saturation: Option<f32>,
+}
+
+/// Text and artwork for the welcome screen and empty diff, so embedders can
+/// rebrand the app. Unset fields keep the built-in defaults.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct Branding {
+    pub title: Option<String>,
+    pub welcome_message: Option<String>,
+    /// Shown in the diff area before a commit is selected.
+    pub empty_diff_message: Option<String>,
+    /// Image file shown above the welcome title.
+    pub logo: Option<PathBuf>,
+}
+
+impl Branding {
+    pub fn title(&self) -> &str {
+        self.title.as_deref().unwrap_or("DD Merge")
+    }
+
+    pub fn welcome_message(&self) -> &str {
+        self.welcome_message
+            .as_deref()
+            .unwrap_or("Open a git repository to get started")
+    }
+
+    pub fn empty_diff_message(&self) -> &str {
+        self.empty_diff_message
+            .as_deref()
+            .unwrap_or("Select a commit to view its diff")
+    }
+}
+
+/// User-configurable settings, persisted alongside the session.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Preferences {
+    /// Gitignore-style globs identifying generated files in diffs.
+    pub generated_patterns: Vec<String>,
+    pub generated_mode: GeneratedFileMode,
+    pub split_gutter: SplitGutter,
+    /// Show the commit list as single-line rows.
+    pub compact_commit_list: bool,
+    pub commit_row_density: RowDensity,
+    /// Draw branch and merge lanes beside the commit list.
+    pub show_commit_graph: bool,
+    /// List every file of HEAD's tree in the sidebar, not just changes.
+    pub show_file_browser: bool,
+    /// Commit subjects longer than this many characters are cut with an
+    /// ellipsis in the commit list.
+    pub commit_subject_max_len: usize,
+    /// Files rendered in a diff before the rest wait behind a "Show
+    /// remaining" row, so commits touching hundreds of files stay responsive.
+    pub max_diff_files: usize,
+    pub diff_palette: DiffPalette,
+    pub branding: Branding,
+    /// Old/new line-number gutters beside diff lines.
+    pub show_line_numbers: bool,
+    pub change_marker_style: ChangeMarkerStyle,
+    /// Show changed lines as a single word diff in unified mode.
+    pub unified_word_diff: bool,
+    /// Where save dialogs such as "Export Session" start browsing, e.g. a
+    /// `~/code` workspace. Open dialogs can't be given a start directory,
+    /// so they begin wherever the platform chooses.
+    pub open_dialog_dir: Option<PathBuf>,
+    /// Parent of the most recently opened repository; takes precedence over
+    /// `open_dialog_dir` so dialogs resume where the user left off.
+    pub last_opened_dir: Option<PathBuf>,
+    /// Reopen only pinned repos on startup instead of every saved tab.
+    pub open_pinned_only: bool,
+}
+
+impl Default for Preferences {
+    fn default() -> Self {
+        Self {
+            generated_patterns: vec![
+                "*.lock".to_string(),
+                "package-lock.json".to_string(),
+                "*.min.js".to_string(),
+                "*.min.css".to_string(),
+            ],
+            generated_mode: GeneratedFileMode::Collapse,
+            split_gutter: SplitGutter::SideOnly,
+            compact_commit_list: false,
+            commit_row_density: RowDensity::Comfortable,
+            show_commit_graph: true,
+            show_file_browser: false,
+            commit_subject_max_len: 72,
+            max_diff_files: 100,
+            diff_palette: DiffPalette::default(),
+            branding: Branding::default(),
+            show_line_numbers: true,
+            change_marker_style: ChangeMarkerStyle::Prefix,
+            unified_word_diff: false,
+            open_dialog_dir: None,
+            last_opened_dir: None,
+            open_pinned_only: false,
+        }
+    }
+}
+
+impl Preferences {
+    /// Directory save dialogs should start in, if any.
+    pub fn open_dialog_start_dir(&self) -> Option<&Path> {
+        self.last_opened_dir
+            .as_deref()
+            .or(self.open_dialog_dir.as_deref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_fields_use_defaults() {
+        let prefs: Preferences = serde_json::from_str("{}").unwrap();
+        assert_eq!(prefs, Preferences::default());
+    }
+
+    #[test]
+    fn test_partial_diff_palette_deserializes() {
+        let prefs: Preferences =
+            serde_json::from_str(r#"{"diff_palette": {"add_hue": 210.0}}"#).unwrap();
+        assert_eq!(prefs.diff_palette.add_hue, Some(210.0));
+        assert_eq!(prefs.diff_palette.del_hue, None);
+    }
+
+    #[test]
+    fn test_branding_overrides_only_set_fields() {
+        let prefs: Preferences =
+            serde_json::from_str(r#"{"branding": {"title": "Acme Review"}}"#).unwrap();
+        assert_eq!(prefs.branding.title(), "Acme Review");
+        assert_eq!(
+            prefs.branding.welcome_message(),
+            Branding::default().welcome_message()
+        );
+    }
+
+    #[test]
+    fn test_last_opened_dir_roundtrips_and_wins() {
+        let prefs = Preferences {
+            open_dialog_dir: Some(PathBuf::from("/home/me/code")),
+            last_opened_dir: Some(PathBuf::from("/home/me/code/work")),
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&prefs).unwrap();
+        let loaded: Preferences = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(loaded, prefs);
+        assert_eq!(
+            loaded.open_dialog_start_dir(),
+            Some(Path::new("/home/me/code/work"))
+        );
+    }
+
+    #[test]
+    fn test_open_dialog_dir_used_before_anything_opened() {
+        let prefs = Preferences {
+            open_dialog_dir: Some(PathBuf::from("/home/me/code")),
+            ..Default::default()
+        };
+        assert_eq!(
+            prefs.open_dialog_start_dir(),
+            Some(Path::new("/home/me/code"))
+        );
+        assert_eq!(Preferences::default().open_dialog_start_dir(), None);
+    }
+
+    #[test]
+    fn test_default_patterns_cover_lockfiles() {
+        let prefs = Preferences::default();
+        assert!(crate::generated::is_generated(
+            "Cargo.lock",
+            &prefs.generated_patterns
+        ));
+    }
+}
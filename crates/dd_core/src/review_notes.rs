@@ -0,0 +1,106 @@
+use serde::{Deserialize, Serialize};
+
+/// A private note attached to one line of a commit's diff.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReviewNote {
+    pub oid: String,
+    pub path: String,
+    /// New-side line number the note is attached to.
+    pub line: u32,
+    pub text: String,
+}
+
+/// Local-only review notes for one repository, keyed by commit OID, file
+/// path and line. Stored as a list because JSON maps need string keys.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ReviewNotes {
+    notes: Vec<ReviewNote>,
+}
+
+impl ReviewNotes {
+    pub fn is_empty(&self) -> bool {
+        self.notes.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.notes.len()
+    }
+
+    /// Attach `text` to a line, replacing any note already there.
+    pub fn add(&mut self, oid: &str, path: &str, line: u32, text: String) {
+        match self.position(oid, path, line) {
+            Some(index) => self.notes[index].text = text,
+            None => self.notes.push(ReviewNote {
+                oid: oid.to_string(),
+                path: path.to_string(),
+                line,
+                text,
+            }),
+        }
+    }
+
+    pub fn get(&self, oid: &str, path: &str, line: u32) -> Option<&str> {
+        self.position(oid, path, line)
+            .map(|index| self.notes[index].text.as_str())
+    }
+
+    /// Delete a line's note, returning its text.
+    pub fn remove(&mut self, oid: &str, path: &str, line: u32) -> Option<String> {
+        self.position(oid, path, line)
+            .map(|index| self.notes.remove(index).text)
+    }
+
+    /// Notes on `path` at commit `oid`, in the order they were added.
+    pub fn for_file<'a>(
+        &'a self,
+        oid: &'a str,
+        path: &'a str,
+    ) -> impl Iterator<Item = &'a ReviewNote> + 'a {
+        self.notes
+            .iter()
+            .filter(move |note| note.oid == oid && note.path == path)
+    }
+
+    fn position(&self, oid: &str, path: &str, line: u32) -> Option<usize> {
+        self.notes
+            .iter()
+            .position(|note| note.oid == oid && note.path == path && note.line == line)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_get_remove() {
+        let mut notes = ReviewNotes::default();
+        notes.add("abc", "src/main.rs", 3, "why?".into());
+        notes.add("abc", "src/main.rs", 3, "why not a loop?".into());
+        notes.add("abc", "src/lib.rs", 3, "ok".into());
+
+        assert_eq!(notes.len(), 2);
+        assert_eq!(notes.get("abc", "src/main.rs", 3), Some("why not a loop?"));
+        assert_eq!(notes.get("def", "src/main.rs", 3), None);
+        assert_eq!(notes.for_file("abc", "src/lib.rs").count(), 1);
+
+        assert_eq!(
+            notes.remove("abc", "src/main.rs", 3).as_deref(),
+            Some("why not a loop?")
+        );
+        assert_eq!(notes.remove("abc", "src/main.rs", 3), None);
+        assert_eq!(notes.len(), 1);
+    }
+
+    #[test]
+    fn test_round_trips_through_json() {
+        let mut notes = ReviewNotes::default();
+        notes.add("abc", "src/main.rs", 7, "check bounds".into());
+
+        let json = serde_json::to_string(&notes).unwrap();
+        let restored: ReviewNotes = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, notes);
+        assert_eq!(restored.get("abc", "src/main.rs", 7), Some("check bounds"));
+    }
+}
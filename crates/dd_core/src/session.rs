@@ -38,6 +38,24 @@ impl Session {
         let state: AppState = serde_json::from_str(&json)?;
         Ok(Some(state))
     }
+
+    /// Write the open repos to a user-chosen file for backup or sharing.
+    pub fn export_to(path: &std::path::Path, state: &AppState) -> Result<()> {
+        Self::save_to(path, state)
+            .with_context(|| format!("failed to export session to {}", path.display()))
+    }
+
+    /// Read a file written by [`Session::export_to`]. Unlike
+    /// [`Session::load_from`] the file must exist, and repos whose paths are
+    /// missing on this machine are dropped.
+    pub fn import_from(path: &std::path::Path) -> Result<AppState> {
+        let json = fs::read_to_string(path)
+            .with_context(|| format!("failed to read session file {}", path.display()))?;
+        let mut state: AppState = serde_json::from_str(&json)
+            .with_context(|| format!("{} is not a session file", path.display()))?;
+        state.retain_repos(|tab| tab.path.exists());
+        Ok(state)
+    }
 }
 
 #[cfg(test)]
@@ -65,6 +83,134 @@ mod tests {
         assert_eq!(loaded.active_tab, 1);
     }
 
+    #[test]
+    fn test_repo_color_roundtrip() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("session.json");
+
+        let mut state = AppState::default();
+        state.add_repo(PathBuf::from("/tmp/repo1"));
+        state.add_repo(PathBuf::from("/tmp/repo2"));
+        state.set_repo_color(1, Some(0x3366cc));
+
+        Session::save_to(&path, &state).unwrap();
+        let loaded = Session::load_from(&path).unwrap().unwrap();
+
+        assert_eq!(loaded.repos[0].color, None);
+        assert_eq!(loaded.repos[1].color, Some(0x3366cc));
+    }
+
+    #[test]
+    fn test_load_session_without_color_field() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("session.json");
+        std::fs::write(
+            &path,
+            r#"{"repos":[{"path":"/tmp/old","name":"old"}],"active_tab":0}"#,
+        )
+        .unwrap();
+
+        let loaded = Session::load_from(&path).unwrap().unwrap();
+        assert_eq!(loaded.repos[0].name, "old");
+        assert_eq!(loaded.repos[0].color, None);
+        assert_eq!(loaded.appearance, crate::Appearance::Dark);
+    }
+
+    #[test]
+    fn test_preferences_roundtrip() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("session.json");
+
+        let mut state = AppState::default();
+        state.preferences.generated_patterns = vec!["dist/**".to_string()];
+        state.preferences.generated_mode = crate::GeneratedFileMode::Hide;
+
+        Session::save_to(&path, &state).unwrap();
+        let loaded = Session::load_from(&path).unwrap().unwrap();
+
+        assert_eq!(loaded.preferences, state.preferences);
+    }
+
+    #[test]
+    fn test_sidebar_collapsed_roundtrip() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("session.json");
+
+        let state = AppState {
+            sidebar_collapsed: true,
+            ..Default::default()
+        };
+        Session::save_to(&path, &state).unwrap();
+        assert!(
+            Session::load_from(&path)
+                .unwrap()
+                .unwrap()
+                .sidebar_collapsed
+        );
+    }
+
+    #[test]
+    fn test_appearance_roundtrip() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("session.json");
+
+        let state = AppState {
+            appearance: crate::Appearance::Light,
+            ..Default::default()
+        };
+        Session::save_to(&path, &state).unwrap();
+        let loaded = Session::load_from(&path).unwrap().unwrap();
+        assert_eq!(loaded.appearance, crate::Appearance::Light);
+    }
+
+    #[test]
+    fn test_export_import_roundtrip() {
+        let dir = TempDir::new().unwrap();
+        let repo1 = dir.path().join("repo1");
+        let repo2 = dir.path().join("repo2");
+        std::fs::create_dir(&repo1).unwrap();
+        std::fs::create_dir(&repo2).unwrap();
+        let path = dir.path().join("exported.json");
+
+        let mut state = AppState::default();
+        state.add_repo(repo1);
+        state.add_repo(repo2);
+        state.set_repo_color(0, Some(0x3366cc));
+
+        Session::export_to(&path, &state).unwrap();
+        let imported = Session::import_from(&path).unwrap();
+
+        assert_eq!(imported.repos.len(), 2);
+        assert_eq!(imported.repos[0].name, "repo1");
+        assert_eq!(imported.repos[0].color, Some(0x3366cc));
+        assert_eq!(imported.active_tab, 1);
+    }
+
+    #[test]
+    fn test_import_drops_missing_repo_paths() {
+        let dir = TempDir::new().unwrap();
+        let existing = dir.path().join("existing");
+        std::fs::create_dir(&existing).unwrap();
+        let path = dir.path().join("exported.json");
+
+        let mut state = AppState::default();
+        state.add_repo(dir.path().join("gone"));
+        state.add_repo(existing.clone());
+
+        Session::export_to(&path, &state).unwrap();
+        let imported = Session::import_from(&path).unwrap();
+
+        assert_eq!(imported.repos.len(), 1);
+        assert_eq!(imported.repos[0].name, "existing");
+        assert_eq!(imported.active_tab, 0);
+    }
+
+    #[test]
+    fn test_import_missing_file_is_an_error() {
+        let dir = TempDir::new().unwrap();
+        assert!(Session::import_from(&dir.path().join("nope.json")).is_err());
+    }
+
     #[test]
     fn test_load_returns_none_when_no_file() {
         let dir = TempDir::new().unwrap();
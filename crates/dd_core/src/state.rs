@@ -1,33 +1,145 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use serde::{Deserialize, Serialize};
 
+use crate::preferences::Preferences;
+use crate::review_notes::ReviewNotes;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RepoTab {
     pub path: PathBuf,
     pub name: String,
+    /// User-chosen tab accent as `0xRRGGBB`. When `None`, the UI derives a
+    /// deterministic color from the path.
+    #[serde(default)]
+    pub color: Option<u32>,
+    /// Pinned repos are listed ahead of the rest and can be the only ones
+    /// reopened on startup.
+    #[serde(default)]
+    pub pinned: bool,
+    /// Diff layout chosen for this repo, `"unified"` or `"split"`. When
+    /// `None`, the diff view picks one from its width.
+    #[serde(default)]
+    pub diff_view_mode: Option<String>,
+    /// Local-only comments left on diff lines while reviewing.
+    #[serde(default)]
+    pub review_notes: ReviewNotes,
+    /// Commit selected in the history, reselected on the next launch.
+    #[serde(default)]
+    pub selected_oid: Option<String>,
+    /// Sidebar sections the user folded away.
+    #[serde(default)]
+    pub collapsed_groups: Vec<SidebarGroup>,
+    /// Folded branch folders in the sidebar, by tree path.
+    #[serde(default)]
+    pub collapsed_folders: Vec<String>,
+}
+
+/// A collapsible section of the repo sidebar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum SidebarGroup {
+    Branches,
+    Remotes,
+    Tags,
+    Stashes,
+    Submodules,
+    Files,
+}
+
+/// Whether the UI uses its dark or light colors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum Appearance {
+    #[default]
+    Dark,
+    Light,
 }
 
+impl Appearance {
+    /// The other appearance.
+    pub fn toggled(self) -> Self {
+        match self {
+            Appearance::Dark => Appearance::Light,
+            Appearance::Light => Appearance::Dark,
+        }
+    }
+}
+
+/// Most repositories kept in [`AppState::recent`].
+pub const MAX_RECENT_REPOS: usize = 10;
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct AppState {
     pub repos: Vec<RepoTab>,
     pub active_tab: usize,
+    #[serde(default)]
+    pub preferences: Preferences,
+    /// Whole sidebar hidden in every repo tab to give the diff more room.
+    #[serde(default)]
+    pub sidebar_collapsed: bool,
+    #[serde(default)]
+    pub appearance: Appearance,
+    /// Recently opened or closed repositories, most recent first, so a
+    /// closed tab can be reopened from the welcome screen.
+    #[serde(default)]
+    pub recent: Vec<PathBuf>,
+    /// Main window position and size as `(x, y, width, height)` when the app
+    /// last quit, reopened there if it still fits on a display.
+    #[serde(default)]
+    pub window_bounds: Option<(f32, f32, f32, f32)>,
+}
+
+/// Resolve symlinks so the same repository always maps to one stored path.
+/// Paths that cannot be resolved (e.g. no longer on disk) are kept as given.
+pub fn canonical_repo_path(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
 }
 
 impl AppState {
-    pub fn add_repo(&mut self, path: PathBuf) {
+    /// Add a repo tab and make it active. The canonical path is stored while
+    /// the tab keeps the name of the path as opened. If the repo is already
+    /// open, its existing tab is activated instead and `false` is returned.
+    pub fn add_repo(&mut self, path: PathBuf) -> bool {
+        let canonical = canonical_repo_path(&path);
+        self.remember_recent(canonical.clone());
+        if let Some(index) = self.repos.iter().position(|r| r.path == canonical) {
+            self.active_tab = index;
+            return false;
+        }
         let name = path
             .file_name()
             .map(|n| n.to_string_lossy().to_string())
             .unwrap_or_else(|| "unknown".to_string());
-        self.repos.push(RepoTab { path, name });
+        self.repos.push(RepoTab {
+            path: canonical,
+            name,
+            color: None,
+            pinned: false,
+            diff_view_mode: None,
+            review_notes: ReviewNotes::default(),
+            selected_oid: None,
+            collapsed_groups: Vec::new(),
+            collapsed_folders: Vec::new(),
+        });
         self.active_tab = self.repos.len() - 1;
+        true
     }
 
-    pub fn reorder_repos(&mut self, from: usize, to: usize) {
+    /// Move the repo at `from` to `to`, clamping `to` so the repo stays within
+    /// its pinned or unpinned group. Returns the index it was moved to, or
+    /// `None` when nothing moved.
+    pub fn reorder_repos(&mut self, from: usize, to: usize) -> Option<usize> {
         let len = self.repos.len();
-        if from == to || from >= len || to >= len {
-            return;
+        if from >= len || to >= len {
+            return None;
+        }
+        let pinned_count = self.repos.iter().filter(|r| r.pinned).count();
+        let to = if self.repos[from].pinned {
+            to.min(pinned_count - 1)
+        } else {
+            to.max(pinned_count)
+        };
+        if from == to {
+            return None;
         }
         let active_path = self.repos.get(self.active_tab).map(|r| r.path.clone());
         let repo = self.repos.remove(from);
@@ -37,11 +149,75 @@ impl AppState {
                 self.active_tab = pos;
             }
         }
+        Some(to)
+    }
+
+    /// Keep only the repos matching `keep`, re-resolving `active_tab` to the
+    /// previously active repo's new index. Falls back to the first tab when
+    /// the active repo itself was dropped.
+    pub fn retain_repos(&mut self, keep: impl FnMut(&RepoTab) -> bool) {
+        let active_path = self.repos.get(self.active_tab).map(|r| r.path.clone());
+        self.repos.retain(keep);
+        self.active_tab = active_path
+            .and_then(|path| self.repos.iter().position(|r| r.path == path))
+            .unwrap_or(0);
+    }
+
+    /// Remove the unpinned repos and return them in their tab order, e.g.
+    /// to open only pinned repos while keeping the rest for the session.
+    pub fn split_off_unpinned(&mut self) -> Vec<RepoTab> {
+        let unpinned = self.repos.iter().filter(|r| !r.pinned).cloned().collect();
+        self.retain_repos(|r| r.pinned);
+        unpinned
+    }
+
+    pub fn set_repo_color(&mut self, index: usize, color: Option<u32>) {
+        if let Some(repo) = self.repos.get_mut(index) {
+            repo.color = color;
+        }
+    }
+
+    pub fn set_repo_diff_view_mode(&mut self, index: usize, mode: Option<String>) {
+        if let Some(repo) = self.repos.get_mut(index) {
+            repo.diff_view_mode = mode;
+        }
+    }
+
+    /// Pin or unpin a repo, then move pinned repos ahead of unpinned ones.
+    /// Returns the applied order as indices into the previous list.
+    pub fn set_repo_pinned(&mut self, index: usize, pinned: bool) -> Vec<usize> {
+        if let Some(repo) = self.repos.get_mut(index) {
+            repo.pinned = pinned;
+        }
+        self.sort_pinned_first()
+    }
+
+    /// Stable-sort the repos so pinned ones come first, keeping the relative
+    /// order within each group and `active_tab` on the same repo. Returns
+    /// the applied order as indices into the previous list.
+    pub fn sort_pinned_first(&mut self) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.repos.len()).collect();
+        order.sort_by_key(|&i| !self.repos[i].pinned);
+        let mut old: Vec<Option<RepoTab>> = self.repos.drain(..).map(Some).collect();
+        self.repos = order.iter().filter_map(|&i| old[i].take()).collect();
+        if let Some(pos) = order.iter().position(|&i| i == self.active_tab) {
+            self.active_tab = pos;
+        }
+        order
+    }
+
+    /// Move `path` to the front of the recent list, dropping the oldest
+    /// entry past [`MAX_RECENT_REPOS`].
+    fn remember_recent(&mut self, path: PathBuf) {
+        self.recent.retain(|p| *p != path);
+        self.recent.insert(0, path);
+        self.recent.truncate(MAX_RECENT_REPOS);
     }
 
     pub fn remove_repo(&mut self, index: usize) {
         if index < self.repos.len() {
-            self.repos.remove(index);
+            let repo = self.repos.remove(index);
+            self.remember_recent(repo.path);
             if self.repos.is_empty() {
                 self.active_tab = 0;
             } else if self.active_tab >= self.repos.len() {
@@ -60,6 +236,13 @@ mod tests {
         let state = AppState::default();
         assert!(state.repos.is_empty());
         assert_eq!(state.active_tab, 0);
+        assert_eq!(state.appearance, Appearance::Dark);
+    }
+
+    #[test]
+    fn test_appearance_toggles() {
+        assert_eq!(Appearance::Dark.toggled(), Appearance::Light);
+        assert_eq!(Appearance::Light.toggled(), Appearance::Dark);
     }
 
     #[test]
@@ -71,6 +254,33 @@ mod tests {
         assert_eq!(state.active_tab, 0);
     }
 
+    #[test]
+    fn test_add_same_repo_twice_is_deduplicated() {
+        let mut state = AppState::default();
+        assert!(state.add_repo(PathBuf::from("/tmp/repo1")));
+        state.add_repo(PathBuf::from("/tmp/repo2"));
+        assert!(!state.add_repo(PathBuf::from("/tmp/repo1")));
+        assert_eq!(state.repos.len(), 2);
+        assert_eq!(state.active_tab, 0);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_add_repo_via_symlink_stores_canonical_path() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let target = dir.path().join("project");
+        std::fs::create_dir(&target).unwrap();
+        let link = dir.path().join("project-link");
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        let mut state = AppState::default();
+        assert!(state.add_repo(link));
+        assert!(!state.add_repo(target.clone()));
+        assert_eq!(state.repos.len(), 1);
+        assert_eq!(state.repos[0].name, "project-link");
+        assert_eq!(state.repos[0].path, target.canonicalize().unwrap());
+    }
+
     #[test]
     fn test_add_multiple_repos() {
         let mut state = AppState::default();
@@ -90,6 +300,50 @@ mod tests {
         assert_eq!(state.repos[0].name, "repo2");
     }
 
+    #[test]
+    fn test_recent_repos_are_most_recent_first() {
+        let mut state = AppState::default();
+        state.add_repo(PathBuf::from("/tmp/repo1"));
+        state.add_repo(PathBuf::from("/tmp/repo2"));
+        assert_eq!(
+            state.recent,
+            vec![PathBuf::from("/tmp/repo2"), PathBuf::from("/tmp/repo1")]
+        );
+
+        // Closing a tab makes it the most recent entry.
+        state.remove_repo(0);
+        assert_eq!(
+            state.recent,
+            vec![PathBuf::from("/tmp/repo1"), PathBuf::from("/tmp/repo2")]
+        );
+    }
+
+    #[test]
+    fn test_recent_repos_are_deduplicated() {
+        let mut state = AppState::default();
+        state.add_repo(PathBuf::from("/tmp/repo1"));
+        state.add_repo(PathBuf::from("/tmp/repo2"));
+        state.add_repo(PathBuf::from("/tmp/repo1"));
+        assert_eq!(
+            state.recent,
+            vec![PathBuf::from("/tmp/repo1"), PathBuf::from("/tmp/repo2")]
+        );
+    }
+
+    #[test]
+    fn test_recent_repos_are_capped() {
+        let mut state = AppState::default();
+        for i in 0..MAX_RECENT_REPOS + 3 {
+            state.add_repo(PathBuf::from(format!("/tmp/repo{i}")));
+        }
+        assert_eq!(state.recent.len(), MAX_RECENT_REPOS);
+        assert_eq!(
+            state.recent.first(),
+            Some(&PathBuf::from(format!("/tmp/repo{}", MAX_RECENT_REPOS + 2)))
+        );
+        assert_eq!(state.recent.last(), Some(&PathBuf::from("/tmp/repo3")));
+    }
+
     #[test]
     fn test_remove_repo_out_of_bounds() {
         let mut state = AppState::default();
@@ -164,6 +418,171 @@ mod tests {
         assert_eq!(state.active_tab, 0); // "b" is now at 0
     }
 
+    #[test]
+    fn test_retain_repos_follows_active_repo() {
+        let mut state = AppState::default();
+        state.add_repo(PathBuf::from("/tmp/a"));
+        state.add_repo(PathBuf::from("/tmp/b"));
+        state.add_repo(PathBuf::from("/tmp/c"));
+        state.active_tab = 2; // point at "c"
+        state.retain_repos(|r| r.name != "b");
+        assert_eq!(state.repos.len(), 2);
+        assert_eq!(state.active_tab, 1); // still "c"
+    }
+
+    #[test]
+    fn test_retain_repos_active_removed_falls_back_to_first() {
+        let mut state = AppState::default();
+        state.add_repo(PathBuf::from("/tmp/a"));
+        state.add_repo(PathBuf::from("/tmp/b"));
+        state.add_repo(PathBuf::from("/tmp/c"));
+        state.active_tab = 1; // point at "b"
+        state.retain_repos(|r| r.name != "b");
+        assert_eq!(state.active_tab, 0);
+    }
+
+    #[test]
+    fn test_retain_repos_all_removed() {
+        let mut state = AppState::default();
+        state.add_repo(PathBuf::from("/tmp/a"));
+        state.retain_repos(|_| false);
+        assert!(state.repos.is_empty());
+        assert_eq!(state.active_tab, 0);
+    }
+
+    #[test]
+    fn test_set_repo_color() {
+        let mut state = AppState::default();
+        state.add_repo(PathBuf::from("/tmp/a"));
+        assert_eq!(state.repos[0].color, None);
+        state.set_repo_color(0, Some(0xff8800));
+        assert_eq!(state.repos[0].color, Some(0xff8800));
+        state.set_repo_color(0, None);
+        assert_eq!(state.repos[0].color, None);
+        // Out of bounds is a no-op
+        state.set_repo_color(5, Some(0x123456));
+        assert_eq!(state.repos.len(), 1);
+    }
+
+    #[test]
+    fn test_pinned_repos_sort_ahead_of_unpinned() {
+        let mut state = AppState::default();
+        state.add_repo(PathBuf::from("/tmp/a"));
+        state.add_repo(PathBuf::from("/tmp/b"));
+        state.add_repo(PathBuf::from("/tmp/c"));
+        state.add_repo(PathBuf::from("/tmp/d"));
+        state.active_tab = 0; // point at "a"
+
+        let order = state.set_repo_pinned(3, true);
+        assert_eq!(order, vec![3, 0, 1, 2]);
+        state.set_repo_pinned(2, true); // "b"
+
+        let names: Vec<_> = state.repos.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["d", "b", "a", "c"]);
+        assert_eq!(state.active_tab, 2); // still "a"
+
+        state.set_repo_pinned(0, false); // "d"
+        let names: Vec<_> = state.repos.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["b", "d", "a", "c"]);
+    }
+
+    #[test]
+    fn test_split_off_unpinned_keeps_pinned_open() {
+        let mut state = AppState::default();
+        state.add_repo(PathBuf::from("/tmp/a"));
+        state.add_repo(PathBuf::from("/tmp/b"));
+        state.add_repo(PathBuf::from("/tmp/c"));
+        state.set_repo_pinned(1, true); // "b"
+        state.active_tab = 0; // point at "b"
+
+        let unpinned = state.split_off_unpinned();
+        let names: Vec<_> = unpinned.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["a", "c"]);
+        assert_eq!(state.repos.len(), 1);
+        assert_eq!(state.repos[0].name, "b");
+        assert_eq!(state.active_tab, 0);
+    }
+
+    #[test]
+    fn test_reorder_repos_stays_within_pinned_group() {
+        let mut state = AppState::default();
+        state.add_repo(PathBuf::from("/tmp/a"));
+        state.add_repo(PathBuf::from("/tmp/b"));
+        state.add_repo(PathBuf::from("/tmp/c"));
+        state.add_repo(PathBuf::from("/tmp/d"));
+        state.set_repo_pinned(0, true); // "a"
+        state.set_repo_pinned(1, true); // "b"
+
+        // A pinned tab dragged past the unpinned ones stops at the last pin.
+        assert_eq!(state.reorder_repos(0, 3), Some(1));
+        let names: Vec<_> = state.repos.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["b", "a", "c", "d"]);
+
+        // An unpinned tab dragged to the front stops after the pins.
+        assert_eq!(state.reorder_repos(3, 0), Some(2));
+        let names: Vec<_> = state.repos.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["b", "a", "d", "c"]);
+
+        // Clamping back onto its own slot moves nothing.
+        assert_eq!(state.reorder_repos(2, 1), None);
+    }
+
+    #[test]
+    fn test_repo_tab_without_pinned_field_deserializes() {
+        let tab: RepoTab = serde_json::from_str(r#"{"path": "/tmp/a", "name": "a"}"#).unwrap();
+        assert!(!tab.pinned);
+    }
+
+    #[test]
+    fn test_diff_view_mode_round_trips() {
+        let mut state = AppState::default();
+        state.add_repo(PathBuf::from("/tmp/a"));
+        state.add_repo(PathBuf::from("/tmp/b"));
+        state.set_repo_diff_view_mode(1, Some("split".into()));
+
+        let json = serde_json::to_string(&state).unwrap();
+        let restored: AppState = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.repos[0].diff_view_mode, None);
+        assert_eq!(restored.repos[1].diff_view_mode.as_deref(), Some("split"));
+
+        let tab: RepoTab = serde_json::from_str(r#"{"path": "/tmp/a", "name": "a"}"#).unwrap();
+        assert_eq!(tab.diff_view_mode, None);
+    }
+
+    #[test]
+    fn test_window_bounds_round_trip() {
+        let state = AppState {
+            window_bounds: Some((120.0, 80.5, 1440.0, 900.0)),
+            ..Default::default()
+        };
+
+        let json = serde_json::to_string(&state).unwrap();
+        let restored: AppState = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.window_bounds, Some((120.0, 80.5, 1440.0, 900.0)));
+
+        let older: AppState = serde_json::from_str(r#"{"repos": [], "active_tab": 0}"#).unwrap();
+        assert_eq!(older.window_bounds, None);
+    }
+
+    #[test]
+    fn test_review_notes_round_trip() {
+        let mut state = AppState::default();
+        state.add_repo(PathBuf::from("/tmp/a"));
+        state.repos[0]
+            .review_notes
+            .add("abc", "src/main.rs", 2, "rename this".into());
+
+        let json = serde_json::to_string(&state).unwrap();
+        let restored: AppState = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            restored.repos[0].review_notes.get("abc", "src/main.rs", 2),
+            Some("rename this")
+        );
+
+        let tab: RepoTab = serde_json::from_str(r#"{"path": "/tmp/a", "name": "a"}"#).unwrap();
+        assert!(tab.review_notes.is_empty());
+    }
+
     #[test]
     fn test_remove_all_repos() {
         let mut state = AppState::default();
@@ -3,7 +3,7 @@ use std::path::PathBuf;
 
 use tempfile::TempDir;
 
-use dd_core::{AppState, Session};
+use dd_core::{AppState, Session, SidebarGroup};
 
 #[test]
 fn session_roundtrip_multiple_repos() {
@@ -15,6 +15,9 @@ fn session_roundtrip_multiple_repos() {
     state.add_repo(PathBuf::from("/projects/beta"));
     state.add_repo(PathBuf::from("/projects/gamma"));
     state.active_tab = 1;
+    state.repos[1].selected_oid = Some("0123abcd".into());
+    state.repos[1].collapsed_groups = vec![SidebarGroup::Tags, SidebarGroup::Stashes];
+    state.repos[1].collapsed_folders = vec!["feature".into(), "remotes/origin".into()];
 
     Session::save_to(&path, &state).unwrap();
     let loaded = Session::load_from(&path).unwrap().unwrap();
@@ -25,6 +28,19 @@ fn session_roundtrip_multiple_repos() {
     assert_eq!(loaded.repos[1].name, "beta");
     assert_eq!(loaded.repos[2].name, "gamma");
     assert_eq!(loaded.active_tab, 1);
+
+    assert_eq!(loaded.repos[1].selected_oid.as_deref(), Some("0123abcd"));
+    assert_eq!(
+        loaded.repos[1].collapsed_groups,
+        vec![SidebarGroup::Tags, SidebarGroup::Stashes]
+    );
+    assert_eq!(
+        loaded.repos[1].collapsed_folders,
+        vec!["feature".to_string(), "remotes/origin".to_string()]
+    );
+    assert_eq!(loaded.repos[0].selected_oid, None);
+    assert!(loaded.repos[0].collapsed_groups.is_empty());
+    assert!(loaded.repos[0].collapsed_folders.is_empty());
 }
 
 #[test]
@@ -79,3 +95,22 @@ fn state_add_remove_sequence() {
     assert_eq!(state.repos[0].name, "c");
     assert_eq!(state.active_tab, 0);
 }
+
+#[test]
+fn session_load_with_stale_middle_repo_keeps_active_repo() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("session.json");
+
+    let mut state = AppState::default();
+    state.add_repo(PathBuf::from("/projects/alpha"));
+    state.add_repo(PathBuf::from("/projects/stale"));
+    state.add_repo(PathBuf::from("/projects/gamma"));
+    state.active_tab = 2; // gamma
+    Session::save_to(&path, &state).unwrap();
+
+    let mut loaded = Session::load_from(&path).unwrap().unwrap();
+    loaded.retain_repos(|tab| tab.name != "stale");
+
+    assert_eq!(loaded.repos.len(), 2);
+    assert_eq!(loaded.repos[loaded.active_tab].name, "gamma");
+}
@@ -1,3 +1,5 @@
+use anyhow::Result;
+
 #[derive(Debug, Clone)]
 pub struct CommitInfo {
     pub oid: String,
@@ -14,6 +16,110 @@ pub struct CommitInfo {
     pub parent_oids: Vec<String>,
 }
 
+impl CommitInfo {
+    /// Conventional-commit metadata for this commit. A `BREAKING CHANGE:`
+    /// footer in the body marks it breaking even without a `!` in the subject.
+    pub fn conventional(&self) -> Option<ConventionalCommit> {
+        let mut parsed = parse_conventional(&self.subject)?;
+        parsed.breaking |= self.body.lines().any(|line| {
+            line.starts_with("BREAKING CHANGE:") || line.starts_with("BREAKING-CHANGE:")
+        });
+        Some(parsed)
+    }
+}
+
+/// The parts of a `type(scope)!: description` commit subject.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConventionalCommit {
+    pub kind: String,
+    pub scope: Option<String>,
+    pub breaking: bool,
+    pub description: String,
+}
+
+/// Parse a conventional-commit subject such as `feat(ui)!: add tabs`.
+///
+/// Returns `None` when the subject does not follow the convention. A subject
+/// of the form `BREAKING CHANGE: ...` is reported as a breaking change with
+/// kind `"BREAKING CHANGE"`.
+pub fn parse_conventional(subject: &str) -> Option<ConventionalCommit> {
+    let (prefix, description) = subject.split_once(": ")?;
+    let description = description.trim();
+    if description.is_empty() {
+        return None;
+    }
+    if prefix == "BREAKING CHANGE" || prefix == "BREAKING-CHANGE" {
+        return Some(ConventionalCommit {
+            kind: "BREAKING CHANGE".to_string(),
+            scope: None,
+            breaking: true,
+            description: description.to_string(),
+        });
+    }
+
+    let (prefix, breaking) = match prefix.strip_suffix('!') {
+        Some(rest) => (rest, true),
+        None => (prefix, false),
+    };
+    let (kind, scope) = match prefix.split_once('(') {
+        Some((kind, rest)) => {
+            let scope = rest.strip_suffix(')')?;
+            if scope.is_empty() || scope.contains(['(', ')']) {
+                return None;
+            }
+            (kind, Some(scope.to_string()))
+        }
+        None => (prefix, None),
+    };
+    if kind.is_empty() || !kind.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return None;
+    }
+
+    Some(ConventionalCommit {
+        kind: kind.to_ascii_lowercase(),
+        scope,
+        breaking,
+        description: description.to_string(),
+    })
+}
+
+/// Split a raw commit message into subject and body. Line endings are
+/// normalized to LF and trailing whitespace is stripped from every line,
+/// keeping the blank lines between body paragraphs. Like `git log %s`, a
+/// subject wrapped over several lines is joined with spaces.
+pub(crate) fn split_message(raw: &str) -> (String, String) {
+    let normalized = raw.replace("\r\n", "\n").replace('\r', "\n");
+    let mut lines = normalized
+        .lines()
+        .map(str::trim_end)
+        .skip_while(|line| line.is_empty());
+    let subject: Vec<&str> = lines
+        .by_ref()
+        .take_while(|line| !line.is_empty())
+        .map(str::trim_start)
+        .collect();
+    let body: Vec<&str> = lines.skip_while(|line| line.is_empty()).collect();
+    (subject.join(" "), body.join("\n").trim_end().to_string())
+}
+
+/// Check that `selected` (newest first) is an unbroken first-parent chain
+/// and return its `(oldest, newest)` OIDs.
+pub fn contiguous_range(selected: &[CommitInfo]) -> Result<(&str, &str)> {
+    let (Some(newest), Some(oldest)) = (selected.first(), selected.last()) else {
+        anyhow::bail!("no commits selected");
+    };
+    for pair in selected.windows(2) {
+        if pair[0].parent_oids.first() != Some(&pair[1].oid) {
+            anyhow::bail!(
+                "selected commits are not contiguous: {} is not the parent of {}",
+                pair[1].short_oid,
+                pair[0].short_oid
+            );
+        }
+    }
+    Ok((&oldest.oid, &newest.oid))
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SignatureStatus {
     Good,
@@ -41,3 +147,115 @@ impl SignatureStatus {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_type_only() {
+        let cc = parse_conventional("feat: add widgets module").unwrap();
+        assert_eq!(cc.kind, "feat");
+        assert_eq!(cc.scope, None);
+        assert!(!cc.breaking);
+        assert_eq!(cc.description, "add widgets module");
+    }
+
+    #[test]
+    fn test_parse_scope_and_breaking() {
+        let cc = parse_conventional("fix(parser)!: reject empty hunks").unwrap();
+        assert_eq!(cc.kind, "fix");
+        assert_eq!(cc.scope.as_deref(), Some("parser"));
+        assert!(cc.breaking);
+        assert_eq!(cc.description, "reject empty hunks");
+    }
+
+    #[test]
+    fn test_parse_breaking_change_subject() {
+        let cc = parse_conventional("BREAKING CHANGE: drop v1 session format").unwrap();
+        assert_eq!(cc.kind, "BREAKING CHANGE");
+        assert!(cc.breaking);
+    }
+
+    #[test]
+    fn test_parse_rejects_non_conventional() {
+        assert_eq!(parse_conventional("Update README"), None);
+        assert_eq!(parse_conventional("feat:missing space"), None);
+        assert_eq!(parse_conventional("feat: "), None);
+        assert_eq!(parse_conventional("feat(: oops"), None);
+        assert_eq!(parse_conventional("feat(): empty scope"), None);
+        assert_eq!(parse_conventional("Merge branch 'x': conflicts"), None);
+    }
+
+    fn chain_commit(oid: &str, parent: Option<&str>) -> CommitInfo {
+        CommitInfo {
+            oid: oid.into(),
+            short_oid: oid.into(),
+            tree_oid: String::new(),
+            author_name: String::new(),
+            author_email: String::new(),
+            date: 0,
+            committer_name: String::new(),
+            committer_email: String::new(),
+            committer_date: 0,
+            subject: String::new(),
+            body: String::new(),
+            parent_oids: parent.into_iter().map(String::from).collect(),
+        }
+    }
+
+    #[test]
+    fn test_contiguous_range_returns_oldest_and_newest() {
+        let selected = vec![
+            chain_commit("c", Some("b")),
+            chain_commit("b", Some("a")),
+            chain_commit("a", None),
+        ];
+        assert_eq!(contiguous_range(&selected).unwrap(), ("a", "c"));
+        assert_eq!(contiguous_range(&selected[..1]).unwrap(), ("c", "c"));
+    }
+
+    #[test]
+    fn test_contiguous_range_rejects_gaps_and_empty() {
+        let selected = vec![chain_commit("c", Some("b")), chain_commit("a", None)];
+        assert!(contiguous_range(&selected).is_err());
+        assert!(contiguous_range(&[]).is_err());
+    }
+
+    #[test]
+    fn test_split_message_cleans_crlf_and_trailing_whitespace() {
+        let (subject, body) = split_message(
+            "fix: handle CRLF  \r\n\r\nFirst paragraph.\t\r\n\r\n\r\nSecond.\r\n\r\n",
+        );
+        assert_eq!(subject, "fix: handle CRLF");
+        assert_eq!(body, "First paragraph.\n\n\nSecond.");
+    }
+
+    #[test]
+    fn test_split_message_joins_wrapped_subject() {
+        let (subject, body) = split_message("a subject\nwrapped\n");
+        assert_eq!(subject, "a subject wrapped");
+        assert_eq!(body, "");
+    }
+
+    #[test]
+    fn test_breaking_footer_in_body() {
+        let commit = CommitInfo {
+            oid: String::new(),
+            short_oid: String::new(),
+            tree_oid: String::new(),
+            author_name: String::new(),
+            author_email: String::new(),
+            date: 0,
+            committer_name: String::new(),
+            committer_email: String::new(),
+            committer_date: 0,
+            subject: "refactor: rename config keys".into(),
+            body: "Details.\n\nBREAKING CHANGE: old keys are ignored".into(),
+            parent_oids: Vec::new(),
+        };
+        let cc = commit.conventional().unwrap();
+        assert_eq!(cc.kind, "refactor");
+        assert!(cc.breaking);
+    }
+}
@@ -0,0 +1,301 @@
+use anyhow::Result;
+
+use super::lfs::lfs_pointer_in_hunks;
+use super::parse::{parse_range, run_diff_tree};
+use crate::git_command::GitDirs;
+
+use super::{DiffLine, FileDiff, FileStatus, Hunk, LineOrigin, WhitespaceMode};
+
+/// One line of a combined (`diff --cc`) hunk. `origins[i]` is how the line
+/// relates to parent `i`: added relative to it, removed from it, or shared.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CombinedLine {
+    pub origins: Vec<LineOrigin>,
+    pub content: String,
+}
+
+impl CombinedLine {
+    /// Collapse the per-parent columns: a line is added only when it is new
+    /// relative to every parent, deleted when the merge result dropped it
+    /// from any parent, and context otherwise.
+    pub fn simplified_origin(&self) -> LineOrigin {
+        if self.origins.contains(&LineOrigin::Deletion) {
+            LineOrigin::Deletion
+        } else if self.origins.iter().all(|o| *o == LineOrigin::Addition) {
+            LineOrigin::Addition
+        } else {
+            LineOrigin::Context
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CombinedHunk {
+    pub header: String,
+    /// `(start, count)` in each parent, in parent order.
+    pub parent_ranges: Vec<(u32, u32)>,
+    pub new_start: u32,
+    pub new_count: u32,
+    pub lines: Vec<CombinedLine>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CombinedFileDiff {
+    pub path: String,
+    pub hunks: Vec<CombinedHunk>,
+}
+
+impl CombinedFileDiff {
+    /// The "changed vs all parents" view as a regular diff, so it renders
+    /// like any other. Old line numbers come from the first parent that
+    /// contains the line.
+    pub fn simplified(&self) -> FileDiff {
+        let hunks: Vec<Hunk> = self
+            .hunks
+            .iter()
+            .map(|hunk| {
+                let mut parent_lines: Vec<u32> =
+                    hunk.parent_ranges.iter().map(|(start, _)| *start).collect();
+                let mut new_line = hunk.new_start;
+                let mut lines = Vec::with_capacity(hunk.lines.len());
+                for line in &hunk.lines {
+                    let origin = line.simplified_origin();
+                    let in_result = !line.origins.contains(&LineOrigin::Deletion);
+                    // Result lines exist in the parents marked ' ', removed
+                    // lines in the parents marked '-'.
+                    let present = if in_result {
+                        LineOrigin::Context
+                    } else {
+                        LineOrigin::Deletion
+                    };
+                    let mut old_line_no = None;
+                    // A header with fewer ranges than columns leaves the
+                    // extra parents unnumbered.
+                    for (col, next) in line.origins.iter().zip(parent_lines.iter_mut()) {
+                        if *col == present {
+                            old_line_no.get_or_insert(*next);
+                            *next += 1;
+                        }
+                    }
+                    let new_line_no = in_result.then_some(new_line);
+                    if in_result {
+                        new_line += 1;
+                    }
+                    lines.push(DiffLine {
+                        old_line_no: (origin != LineOrigin::Addition)
+                            .then_some(old_line_no)
+                            .flatten(),
+                        new_line_no,
+                        origin,
+                        content: line.content.clone(),
+                        change_spans: Vec::new(),
+                        no_newline_at_eof: false,
+                    });
+                }
+                let (old_start, old_count) = hunk.parent_ranges.first().copied().unwrap_or((0, 0));
+                Hunk {
+                    header: hunk.header.clone(),
+                    old_start,
+                    old_count,
+                    new_start: hunk.new_start,
+                    new_count: hunk.new_count,
+                    lines,
+                }
+            })
+            .collect();
+        FileDiff {
+            path: self.path.clone(),
+            old_path: None,
+            status: FileStatus::Modified,
+            lfs: lfs_pointer_in_hunks(&hunks),
+            hunks,
+            old_mode: None,
+            new_mode: None,
+            similarity: None,
+        }
+    }
+}
+
+pub(crate) fn diff_combined(
+    git: &GitDirs,
+    oid: &str,
+    whitespace: WhitespaceMode,
+) -> Result<Vec<CombinedFileDiff>> {
+    anyhow::ensure!(
+        oid.bytes().all(|b| b.is_ascii_hexdigit()),
+        "invalid commit OID: {oid}"
+    );
+    let mut args = vec!["-p", "--cc"];
+    args.extend_from_slice(whitespace.diff_args());
+    let stdout = run_diff_tree(git, &args, oid)?;
+    Ok(parse_combined_diff(&stdout))
+}
+
+/// Parse `git diff --cc` output for a merge with any number of parents.
+/// Hunk headers carry one `@` per parent plus one, and every line one
+/// prefix column per parent.
+pub fn parse_combined_diff(input: &str) -> Vec<CombinedFileDiff> {
+    let mut files = Vec::new();
+    let mut current: Option<CombinedFileDiff> = None;
+    let mut parents = 0;
+
+    for line in input.lines() {
+        if let Some(path) = line
+            .strip_prefix("diff --cc ")
+            .or_else(|| line.strip_prefix("diff --combined "))
+        {
+            files.extend(current.take());
+            current = Some(CombinedFileDiff {
+                path: path.to_string(),
+                hunks: Vec::new(),
+            });
+            continue;
+        }
+        let Some(file) = current.as_mut() else {
+            continue;
+        };
+
+        if line.starts_with("@@@") {
+            let markers = line.bytes().take_while(|&b| b == b'@').count();
+            parents = markers - 1;
+            let mut parent_ranges = Vec::with_capacity(parents);
+            let mut result = (0, 0);
+            for part in line[markers..].split_whitespace().take(parents + 1) {
+                if let Some(range) = part.strip_prefix('-') {
+                    parent_ranges.push(parse_range(range));
+                } else if let Some(range) = part.strip_prefix('+') {
+                    result = parse_range(range);
+                }
+            }
+            file.hunks.push(CombinedHunk {
+                header: line.to_string(),
+                parent_ranges,
+                new_start: result.0,
+                new_count: result.1,
+                lines: Vec::new(),
+            });
+            continue;
+        }
+
+        let Some(hunk) = file.hunks.last_mut() else {
+            // Extended header lines (index, mode, ---/+++) before any hunk.
+            continue;
+        };
+        if line.starts_with('\\') {
+            continue;
+        }
+        let Some((columns, content)) = line.split_at_checked(parents) else {
+            continue;
+        };
+        let origins = columns
+            .chars()
+            .map(|c| match c {
+                '+' => LineOrigin::Addition,
+                '-' => LineOrigin::Deletion,
+                _ => LineOrigin::Context,
+            })
+            .collect();
+        hunk.lines.push(CombinedLine {
+            origins,
+            content: content.to_string(),
+        });
+    }
+    files.extend(current);
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OCTOPUS: &str = "\
+diff --cc notes.txt
+index 1111111,2222222,3333333..4444444
+--- a/notes.txt
++++ b/notes.txt
+@@@@ -1,2 -1,2 -1,2 +1,4 @@@@
+   shared
+ ++only in a
++ +only in b
+  -dropped from c
++++brand new
+";
+
+    #[test]
+    fn test_parse_three_parent_hunk() {
+        let files = parse_combined_diff(OCTOPUS);
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, "notes.txt");
+
+        let hunk = &files[0].hunks[0];
+        assert_eq!(hunk.parent_ranges, vec![(1, 2), (1, 2), (1, 2)]);
+        assert_eq!((hunk.new_start, hunk.new_count), (1, 4));
+        assert_eq!(hunk.lines.len(), 5);
+
+        use LineOrigin::*;
+        assert_eq!(hunk.lines[0].origins, vec![Context, Context, Context]);
+        assert_eq!(hunk.lines[0].content, "shared");
+        assert_eq!(hunk.lines[1].content, "only in a");
+        assert_eq!(hunk.lines[1].origins, vec![Context, Addition, Addition]);
+        assert_eq!(hunk.lines[2].origins, vec![Addition, Context, Addition]);
+        assert_eq!(hunk.lines[3].origins, vec![Context, Context, Deletion]);
+        assert_eq!(hunk.lines[4].origins, vec![Addition, Addition, Addition]);
+        assert_eq!(hunk.lines[4].content, "brand new");
+    }
+
+    #[test]
+    fn test_parse_two_parent_hunk() {
+        let input = "\
+diff --cc a.txt
+@@@ -1,1 -1,1 +1,2 @@@
+  keep
+ +theirs
+";
+        let files = parse_combined_diff(input);
+        let hunk = &files[0].hunks[0];
+        assert_eq!(hunk.parent_ranges.len(), 2);
+        assert_eq!(
+            hunk.lines[1].origins,
+            vec![LineOrigin::Context, LineOrigin::Addition]
+        );
+    }
+
+    #[test]
+    fn test_simplified_view_tolerates_missing_parent_ranges() {
+        let input = "\
+diff --cc a.txt
+@@@ -1,1 +1,2 @@@
+  keep
+ +theirs
+";
+        let diff = parse_combined_diff(input)[0].simplified();
+        let lines = &diff.hunks[0].lines;
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].old_line_no, Some(1));
+        assert_eq!(lines[1].new_line_no, Some(2));
+    }
+
+    #[test]
+    fn test_simplified_view_marks_changes_vs_all_parents() {
+        let diff = parse_combined_diff(OCTOPUS)[0].simplified();
+        let origins: Vec<_> = diff.hunks[0]
+            .lines
+            .iter()
+            .map(|l| l.origin.clone())
+            .collect();
+        assert_eq!(
+            origins,
+            vec![
+                LineOrigin::Context,
+                LineOrigin::Context,
+                LineOrigin::Context,
+                LineOrigin::Deletion,
+                LineOrigin::Addition,
+            ]
+        );
+        let new_numbers: Vec<_> = diff.hunks[0].lines.iter().map(|l| l.new_line_no).collect();
+        assert_eq!(new_numbers, vec![Some(1), Some(2), Some(3), None, Some(4)]);
+        // The dropped line only existed in the third parent.
+        assert_eq!(diff.hunks[0].lines[3].old_line_no, Some(2));
+    }
+}
@@ -0,0 +1,242 @@
+use super::{DiffLine, Hunk, LineOrigin};
+
+/// Which side of a hunk to grow when revealing folded context.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExpandDirection {
+    Up,
+    Down,
+}
+
+/// Reveal up to `count` unchanged lines above or below `hunks[index]`, taken
+/// from the new-side file content.
+///
+/// Expansion stops at the neighbouring hunk (or the file boundary). When the
+/// gap to a neighbour closes completely the two hunks are merged into one.
+/// Returns the number of lines that were added.
+pub fn expand_hunk_context(
+    hunks: &mut Vec<Hunk>,
+    index: usize,
+    direction: ExpandDirection,
+    count: u32,
+    new_content: &str,
+) -> u32 {
+    if index >= hunks.len() || count == 0 {
+        return 0;
+    }
+    let file_lines: Vec<&str> = new_content.lines().collect();
+
+    match direction {
+        ExpandDirection::Up => {
+            let hunk = &hunks[index];
+            let first_new = first_line(hunk.new_start, hunk.new_count);
+            let first_old = first_line(hunk.old_start, hunk.old_count);
+            let available = hidden_lines_above(hunks, index);
+            let n = count.min(available);
+            if n == 0 {
+                return 0;
+            }
+
+            let lines = context_lines(&file_lines, first_old - n, first_new - n, n);
+            let hunk = &mut hunks[index];
+            hunk.lines.splice(0..0, lines);
+            hunk.old_start = first_old - n;
+            hunk.new_start = first_new - n;
+            hunk.old_count += n;
+            hunk.new_count += n;
+            hunk.header = rebuild_header(hunk);
+
+            if n == available && index > 0 {
+                merge_with_next(hunks, index - 1);
+            }
+            n
+        }
+        ExpandDirection::Down => {
+            let hunk = &hunks[index];
+            let next_new = end_line(hunk.new_start, hunk.new_count);
+            let next_old = end_line(hunk.old_start, hunk.old_count);
+            let gap_end = match hunks.get(index + 1) {
+                Some(next) => first_line(next.new_start, next.new_count),
+                None => file_lines.len() as u32 + 1,
+            };
+            let available = gap_end.saturating_sub(next_new);
+            let n = count.min(available);
+            if n == 0 {
+                return 0;
+            }
+
+            let lines = context_lines(&file_lines, next_old, next_new, n);
+            let hunk = &mut hunks[index];
+            hunk.old_start = first_line(hunk.old_start, hunk.old_count);
+            hunk.new_start = first_line(hunk.new_start, hunk.new_count);
+            hunk.lines.extend(lines);
+            hunk.old_count += n;
+            hunk.new_count += n;
+            hunk.header = rebuild_header(hunk);
+
+            if n == available && index + 1 < hunks.len() {
+                merge_with_next(hunks, index);
+            }
+            n
+        }
+    }
+}
+
+/// Number of unchanged lines folded away between `hunks[index]` and the hunk
+/// before it (or the start of the file for the first hunk).
+pub fn hidden_lines_above(hunks: &[Hunk], index: usize) -> u32 {
+    let Some(hunk) = hunks.get(index) else {
+        return 0;
+    };
+    let gap_start = match index.checked_sub(1).map(|i| &hunks[i]) {
+        Some(prev) => end_line(prev.new_start, prev.new_count),
+        None => 1,
+    };
+    first_line(hunk.new_start, hunk.new_count).saturating_sub(gap_start)
+}
+
+/// First line covered by a hunk side. Git reports an empty side as the line
+/// *before* the change, so bump it by one in that case.
+fn first_line(start: u32, count: u32) -> u32 {
+    if count == 0 {
+        start + 1
+    } else {
+        start
+    }
+}
+
+/// Line number immediately after a hunk side.
+fn end_line(start: u32, count: u32) -> u32 {
+    first_line(start, count) + count
+}
+
+fn context_lines(file_lines: &[&str], old_start: u32, new_start: u32, n: u32) -> Vec<DiffLine> {
+    (0..n)
+        .map(|k| DiffLine {
+            origin: LineOrigin::Context,
+            content: file_lines
+                .get((new_start + k - 1) as usize)
+                .copied()
+                .unwrap_or_default()
+                .to_string(),
+            old_line_no: Some(old_start + k),
+            new_line_no: Some(new_start + k),
+            change_spans: Vec::new(),
+            no_newline_at_eof: false,
+        })
+        .collect()
+}
+
+fn merge_with_next(hunks: &mut Vec<Hunk>, index: usize) {
+    let next = hunks.remove(index + 1);
+    let hunk = &mut hunks[index];
+    hunk.old_start = first_line(hunk.old_start, hunk.old_count);
+    hunk.new_start = first_line(hunk.new_start, hunk.new_count);
+    hunk.old_count = end_line(next.old_start, next.old_count) - hunk.old_start;
+    hunk.new_count = end_line(next.new_start, next.new_count) - hunk.new_start;
+    hunk.lines.extend(next.lines);
+    hunk.header = rebuild_header(hunk);
+}
+
+/// Regenerate the `@@ -a,b +c,d @@` range while keeping any trailing
+/// function-context text from the original header.
+fn rebuild_header(hunk: &Hunk) -> String {
+    let suffix = hunk
+        .header
+        .get(2..)
+        .and_then(|rest| rest.find("@@").map(|i| &rest[i + 2..]))
+        .unwrap_or("");
+    format!(
+        "@@ -{},{} +{},{} @@{}",
+        hunk.old_start, hunk.old_count, hunk.new_start, hunk.new_count, suffix
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILE: &str = "l1\nl2\nl3\nl4\nl5\nl6\nl7\nl8\nl9\nl10\n";
+
+    fn hunk(old_start: u32, new_start: u32, contents: &[&str]) -> Hunk {
+        let n = contents.len() as u32;
+        Hunk {
+            header: format!("@@ -{old_start},{n} +{new_start},{n} @@ fn ctx()"),
+            old_start,
+            old_count: n,
+            new_start,
+            new_count: n,
+            lines: contents
+                .iter()
+                .enumerate()
+                .map(|(k, c)| DiffLine {
+                    origin: LineOrigin::Context,
+                    content: c.to_string(),
+                    old_line_no: Some(old_start + k as u32),
+                    new_line_no: Some(new_start + k as u32),
+                    change_spans: Vec::new(),
+                    no_newline_at_eof: false,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_expand_up_stops_at_file_start() {
+        let mut hunks = vec![hunk(4, 4, &["l4", "l5"])];
+        let added = expand_hunk_context(&mut hunks, 0, ExpandDirection::Up, 10, FILE);
+        assert_eq!(added, 3);
+        assert_eq!(hunks[0].new_start, 1);
+        assert_eq!(hunks[0].new_count, 5);
+        let contents: Vec<&str> = hunks[0].lines.iter().map(|l| l.content.as_str()).collect();
+        assert_eq!(contents, ["l1", "l2", "l3", "l4", "l5"]);
+        assert_eq!(hunks[0].lines[0].old_line_no, Some(1));
+        assert_eq!(hunks[0].header, "@@ -1,5 +1,5 @@ fn ctx()");
+    }
+
+    #[test]
+    fn test_expand_down_stops_at_file_end() {
+        let mut hunks = vec![hunk(7, 7, &["l7"])];
+        let added = expand_hunk_context(&mut hunks, 0, ExpandDirection::Down, 10, FILE);
+        assert_eq!(added, 3);
+        assert_eq!(hunks[0].lines.last().unwrap().content, "l10");
+        assert_eq!(hunks[0].lines.last().unwrap().new_line_no, Some(10));
+    }
+
+    #[test]
+    fn test_expand_partial_gap_keeps_hunks_separate() {
+        let mut hunks = vec![hunk(1, 1, &["l1"]), hunk(8, 8, &["l8"])];
+        let added = expand_hunk_context(&mut hunks, 1, ExpandDirection::Up, 2, FILE);
+        assert_eq!(added, 2);
+        assert_eq!(hunks.len(), 2);
+        assert_eq!(hunks[1].lines[0].content, "l6");
+    }
+
+    #[test]
+    fn test_expand_closing_gap_merges_hunks() {
+        let mut hunks = vec![hunk(1, 1, &["l1"]), hunk(5, 5, &["l5"])];
+        let added = expand_hunk_context(&mut hunks, 0, ExpandDirection::Down, 10, FILE);
+        assert_eq!(added, 3);
+        assert_eq!(hunks.len(), 1);
+        assert_eq!(hunks[0].old_count, 5);
+        assert_eq!(hunks[0].new_count, 5);
+        let contents: Vec<&str> = hunks[0].lines.iter().map(|l| l.content.as_str()).collect();
+        assert_eq!(contents, ["l1", "l2", "l3", "l4", "l5"]);
+    }
+
+    #[test]
+    fn test_hidden_lines_above() {
+        let hunks = vec![hunk(3, 3, &["l3"]), hunk(8, 8, &["l8"])];
+        assert_eq!(hidden_lines_above(&hunks, 0), 2);
+        assert_eq!(hidden_lines_above(&hunks, 1), 4);
+        assert_eq!(hidden_lines_above(&hunks, 2), 0);
+    }
+
+    #[test]
+    fn test_expand_out_of_bounds_index_is_noop() {
+        let mut hunks = vec![hunk(1, 1, &["l1"])];
+        assert_eq!(
+            expand_hunk_context(&mut hunks, 3, ExpandDirection::Up, 10, FILE),
+            0
+        );
+    }
+}
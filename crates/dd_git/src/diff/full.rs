@@ -0,0 +1,197 @@
+use std::collections::HashMap;
+
+use similar::{ChangeTag, TextDiff};
+
+use super::inline::compute_inline_changes;
+use super::{DiffLine, FileDiff, Hunk, LineOrigin};
+
+/// Diff two complete versions of `file` into a single hunk that keeps every
+/// line, for comparing whole files rather than just the changed regions.
+pub fn full_file_diff(file: &FileDiff, old: &str, new: &str) -> FileDiff {
+    let diff = TextDiff::from_lines(old, new);
+    let lines: Vec<DiffLine> = diff
+        .iter_all_changes()
+        .map(|change| {
+            let origin = match change.tag() {
+                ChangeTag::Equal => LineOrigin::Context,
+                ChangeTag::Delete => LineOrigin::Deletion,
+                ChangeTag::Insert => LineOrigin::Addition,
+            };
+            let value = change.value();
+            let content = value.strip_suffix('\n');
+            DiffLine {
+                origin,
+                content: content.unwrap_or(value).trim_end_matches('\r').to_string(),
+                old_line_no: change.old_index().map(|i| i as u32 + 1),
+                new_line_no: change.new_index().map(|i| i as u32 + 1),
+                change_spans: Vec::new(),
+                no_newline_at_eof: content.is_none(),
+            }
+        })
+        .collect();
+
+    let old_count = old.lines().count() as u32;
+    let new_count = new.lines().count() as u32;
+    // Like git, an empty side starts at line 0.
+    let (old_start, new_start) = (old_count.min(1), new_count.min(1));
+    let mut hunks = vec![Hunk {
+        header: format!("@@ -{old_start},{old_count} +{new_start},{new_count} @@"),
+        old_start,
+        old_count,
+        new_start,
+        new_count,
+        lines,
+    }];
+    compute_inline_changes(&mut hunks);
+
+    FileDiff {
+        path: file.path.clone(),
+        old_path: file.old_path.clone(),
+        status: file.status.clone(),
+        hunks,
+        lfs: file.lfs.clone(),
+        old_mode: file.old_mode.clone(),
+        new_mode: file.new_mode.clone(),
+        similarity: file.similarity,
+    }
+}
+
+/// The new version of `file` as one hunk of every line, with the lines its
+/// hunks added marked as additions. Unlike [`full_file_diff`] this needs
+/// only the new side, since the hunks already say what changed. Removed
+/// lines are left out so the result has one line per line of `new`.
+pub fn overlay_file_changes(file: &FileDiff, new: &str) -> FileDiff {
+    let in_hunks: HashMap<u32, &DiffLine> = file
+        .hunks
+        .iter()
+        .flat_map(|hunk| &hunk.lines)
+        .filter_map(|line| Some((line.new_line_no?, line)))
+        .collect();
+
+    // Between hunks, old line numbers trail new ones by what the hunks
+    // before added or removed. An empty side's start is the line before.
+    let end = |start: u32, count: u32| if count == 0 { start + 1 } else { start + count };
+    let mut hunks = file.hunks.iter().peekable();
+    let mut offset = 0i64;
+    let lines: Vec<DiffLine> = new
+        .lines()
+        .enumerate()
+        .map(|(i, content)| {
+            let new_line_no = i as u32 + 1;
+            while let Some(hunk) = hunks.next_if(|h| end(h.new_start, h.new_count) <= new_line_no) {
+                offset = end(hunk.old_start, hunk.old_count) as i64
+                    - end(hunk.new_start, hunk.new_count) as i64;
+            }
+            match in_hunks.get(&new_line_no) {
+                Some(line) => (*line).clone(),
+                None => DiffLine {
+                    origin: LineOrigin::Context,
+                    old_line_no: Some((new_line_no as i64 + offset) as u32),
+                    new_line_no: Some(new_line_no),
+                    content: content.trim_end_matches('\r').to_string(),
+                    change_spans: Vec::new(),
+                    no_newline_at_eof: false,
+                },
+            }
+        })
+        .collect();
+
+    let new_count = lines.len() as u32;
+    let new_start = new_count.min(1);
+    FileDiff {
+        path: file.path.clone(),
+        old_path: file.old_path.clone(),
+        status: file.status.clone(),
+        hunks: vec![Hunk {
+            header: format!("@@ +{new_start},{new_count} @@"),
+            old_start: 0,
+            old_count: 0,
+            new_start,
+            new_count,
+            lines,
+        }],
+        lfs: file.lfs.clone(),
+        old_mode: file.old_mode.clone(),
+        new_mode: file.new_mode.clone(),
+        similarity: file.similarity,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::FileStatus;
+
+    fn modified(path: &str) -> FileDiff {
+        FileDiff {
+            path: path.into(),
+            old_path: None,
+            status: FileStatus::Modified,
+            hunks: Vec::new(),
+            lfs: None,
+            old_mode: None,
+            new_mode: None,
+            similarity: None,
+        }
+    }
+
+    #[test]
+    fn test_keeps_every_line_of_both_versions() {
+        let full = full_file_diff(&modified("a.txt"), "one\ntwo\nthree\n", "one\n2\nthree");
+        let hunk = &full.hunks[0];
+        assert_eq!(full.hunks.len(), 1);
+        assert_eq!((hunk.old_count, hunk.new_count), (3, 3));
+
+        let origins: Vec<_> = hunk.lines.iter().map(|l| l.origin.clone()).collect();
+        use LineOrigin::*;
+        assert_eq!(
+            origins,
+            vec![Context, Deletion, Deletion, Addition, Addition]
+        );
+        // "three" lost its trailing newline, so it changed too.
+        assert!(hunk.lines[4].no_newline_at_eof);
+        assert_eq!(hunk.lines[4].content, "three");
+        assert_eq!(hunk.lines[4].new_line_no, Some(3));
+    }
+
+    #[test]
+    fn test_empty_old_side_starts_at_zero() {
+        let full = full_file_diff(&modified("a.txt"), "", "new\n");
+        assert_eq!(full.hunks[0].header, "@@ -0,0 +1,1 @@");
+    }
+
+    #[test]
+    fn test_overlay_marks_added_lines_and_keeps_old_numbers() {
+        // Old side: a b c d e. The commit removes b and appends f.
+        let mut file = modified("a.txt");
+        file.hunks = crate::diff::parse_unified_diff(
+            "diff --git a/a.txt b/a.txt
+--- a/a.txt
++++ b/a.txt
+@@ -1,3 +1,2 @@
+ a
+-b
+ c
+@@ -5 +4,2 @@
+ e
++f
+",
+        )
+        .unwrap()[0]
+            .hunks
+            .clone();
+
+        let full = overlay_file_changes(&file, "a\nc\nd\ne\nf\n");
+        let lines = &full.hunks[0].lines;
+        assert_eq!(lines.len(), 5);
+        let added: Vec<_> = lines
+            .iter()
+            .filter(|l| l.origin == LineOrigin::Addition)
+            .map(|l| l.new_line_no.unwrap())
+            .collect();
+        assert_eq!(added, vec![5]);
+        // "d" sits between the hunks, one line later on the old side.
+        assert_eq!(lines[2].content, "d");
+        assert_eq!(lines[2].old_line_no, Some(4));
+    }
+}
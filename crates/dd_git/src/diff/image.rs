@@ -0,0 +1,90 @@
+use std::path::Path;
+
+/// Raster image formats the diff view can show as pictures instead of a
+/// binary placeholder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+    Gif,
+}
+
+impl ImageFormat {
+    /// Guess the format from a file extension (case-insensitive).
+    pub fn from_path(path: &str) -> Option<Self> {
+        let ext = Path::new(path).extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "png" => Some(Self::Png),
+            "jpg" | "jpeg" => Some(Self::Jpeg),
+            "gif" => Some(Self::Gif),
+            _ => None,
+        }
+    }
+
+    /// Identify the format from the leading magic bytes of the file.
+    pub fn from_magic(bytes: &[u8]) -> Option<Self> {
+        if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+            Some(Self::Png)
+        } else if bytes.starts_with(b"\xff\xd8\xff") {
+            Some(Self::Jpeg)
+        } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+            Some(Self::Gif)
+        } else {
+            None
+        }
+    }
+}
+
+/// The two sides of an image change; a side is `None` when the file was
+/// added or deleted.
+#[derive(Debug, Clone, Default)]
+pub struct ImageVersions {
+    pub old: Option<Vec<u8>>,
+    pub new: Option<Vec<u8>>,
+}
+
+impl ImageVersions {
+    /// The format of whichever side exists, judged by its contents.
+    pub fn format(&self) -> Option<ImageFormat> {
+        self.new
+            .as_deref()
+            .or(self.old.as_deref())
+            .and_then(ImageFormat::from_magic)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(
+            ImageFormat::from_path("assets/Logo.PNG"),
+            Some(ImageFormat::Png)
+        );
+        assert_eq!(
+            ImageFormat::from_path("photo.jpeg"),
+            Some(ImageFormat::Jpeg)
+        );
+        assert_eq!(ImageFormat::from_path("icon.bin"), None);
+        assert_eq!(ImageFormat::from_path("Makefile"), None);
+    }
+
+    #[test]
+    fn test_format_from_magic() {
+        assert_eq!(
+            ImageFormat::from_magic(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"),
+            Some(ImageFormat::Png)
+        );
+        assert_eq!(
+            ImageFormat::from_magic(b"GIF89a..."),
+            Some(ImageFormat::Gif)
+        );
+        assert_eq!(
+            ImageFormat::from_magic(b"\xff\xd8\xff\xe0"),
+            Some(ImageFormat::Jpeg)
+        );
+        assert_eq!(ImageFormat::from_magic(b"hello"), None);
+    }
+}
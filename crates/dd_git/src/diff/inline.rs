@@ -1,6 +1,78 @@
+use std::ops::Range;
+
 use similar::{Algorithm, ChangeTag, TextDiff};
 
-use super::{Hunk, InlineSpan, LineOrigin};
+use super::{DiffLine, Hunk, InlineSpan, LineOrigin};
+
+/// Role of a segment in a merged word-diff line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WordTag {
+    /// Text common to both lines; the range indexes the addition.
+    Equal,
+    /// Text only in the deletion; the range indexes the deletion.
+    Removed,
+    /// Text only in the addition; the range indexes the addition.
+    Added,
+}
+
+/// Merge a paired deletion/addition into one sequence of segments, as in
+/// `git diff --word-diff`, using the lines' `change_spans`. Removed text is
+/// placed before added text at the same position. Lines without spans are
+/// treated as entirely replaced.
+pub fn merge_word_diff(del: &DiffLine, add: &DiffLine) -> Vec<(Range<usize>, WordTag)> {
+    let (old, new) = (&del.content, &add.content);
+    if del.change_spans.is_empty() && add.change_spans.is_empty() && old != new {
+        let mut segments = Vec::new();
+        if !old.is_empty() {
+            segments.push((0..old.len(), WordTag::Removed));
+        }
+        if !new.is_empty() {
+            segments.push((0..new.len(), WordTag::Added));
+        }
+        return segments;
+    }
+
+    let mut segments: Vec<(Range<usize>, WordTag)> = Vec::new();
+    let mut push = |range: Range<usize>, tag: WordTag| {
+        if range.is_empty() {
+            return;
+        }
+        match segments.last_mut() {
+            Some((prev, prev_tag)) if *prev_tag == tag && prev.end == range.start => {
+                prev.end = range.end;
+            }
+            _ => segments.push((range, tag)),
+        }
+    };
+
+    let (mut del_spans, mut add_spans) = (
+        del.change_spans.iter().peekable(),
+        add.change_spans.iter().peekable(),
+    );
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if let Some(span) = del_spans.next_if(|s| s.start <= i) {
+            push(span.start..span.end, WordTag::Removed);
+            i = span.end;
+        } else if let Some(span) = add_spans.next_if(|s| s.start <= j) {
+            push(span.start..span.end, WordTag::Added);
+            j = span.end;
+        } else {
+            // Unchanged text advances through both lines at the same rate
+            // until either reaches its next change.
+            let old_run = del_spans.peek().map_or(old.len(), |s| s.start) - i;
+            let new_run = add_spans.peek().map_or(new.len(), |s| s.start) - j;
+            let run = old_run.min(new_run);
+            if run == 0 {
+                break;
+            }
+            push(j..j + run, WordTag::Equal);
+            i += run;
+            j += run;
+        }
+    }
+    segments
+}
 
 /// Walk each hunk and compute word-level inline change spans for paired
 /// deletion/addition runs. Unpaired lines keep empty `change_spans`.
@@ -118,9 +190,67 @@ mod tests {
             old_line_no: None,
             new_line_no: None,
             change_spans: Vec::new(),
+            no_newline_at_eof: false,
         }
     }
 
+    fn paired(old: &str, new: &str) -> (DiffLine, DiffLine) {
+        let mut hunks = vec![Hunk {
+            header: String::new(),
+            old_start: 1,
+            old_count: 1,
+            new_start: 1,
+            new_count: 1,
+            lines: vec![
+                make_line(LineOrigin::Deletion, old),
+                make_line(LineOrigin::Addition, new),
+            ],
+        }];
+        compute_inline_changes(&mut hunks);
+        let mut lines = hunks.remove(0).lines.into_iter();
+        (lines.next().unwrap(), lines.next().unwrap())
+    }
+
+    fn render(del: &DiffLine, add: &DiffLine) -> String {
+        merge_word_diff(del, add)
+            .into_iter()
+            .map(|(range, tag)| match tag {
+                WordTag::Equal => add.content[range].to_string(),
+                WordTag::Removed => format!("[-{}-]", &del.content[range]),
+                WordTag::Added => format!("{{+{}+}}", &add.content[range]),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_merge_word_diff_single_word_change() {
+        let (del, add) = paired("let total = count + 1;", "let total = sum + 1;");
+        let segments = merge_word_diff(&del, &add);
+        assert_eq!(
+            segments.iter().map(|(_, tag)| *tag).collect::<Vec<_>>(),
+            vec![
+                WordTag::Equal,
+                WordTag::Removed,
+                WordTag::Added,
+                WordTag::Equal
+            ]
+        );
+        assert_eq!(render(&del, &add), "let total = [-count-]{+sum+} + 1;");
+    }
+
+    #[test]
+    fn test_merge_word_diff_insertion_only() {
+        let (del, add) = paired("hello", "hello world");
+        assert_eq!(render(&del, &add), "hello{+ world+}");
+    }
+
+    #[test]
+    fn test_merge_word_diff_without_spans_replaces_whole_line() {
+        let del = make_line(LineOrigin::Deletion, "abc");
+        let add = make_line(LineOrigin::Addition, "xyz");
+        assert_eq!(render(&del, &add), "[-abc-]{+xyz+}");
+    }
+
     #[test]
     fn test_word_diff_single_word_change() {
         let (old_spans, new_spans) = word_diff("hello world", "hello earth");
@@ -0,0 +1,78 @@
+use super::{Hunk, LineOrigin};
+
+const LFS_SPEC_PREFIX: &str = "version https://git-lfs";
+
+/// The object a Git LFS pointer file stands in for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LfsPointer {
+    /// Object ID including its hash algorithm, e.g. `sha256:4d7a…`.
+    pub oid: String,
+    /// Size of the real file in bytes.
+    pub size: u64,
+}
+
+/// Parse the text of an LFS pointer file. Returns `None` for anything that
+/// does not start with the LFS spec line or lacks an `oid` or `size`.
+pub fn parse_lfs_pointer<'a>(lines: impl IntoIterator<Item = &'a str>) -> Option<LfsPointer> {
+    let mut lines = lines.into_iter();
+    if !lines.next()?.starts_with(LFS_SPEC_PREFIX) {
+        return None;
+    }
+    let mut oid = None;
+    let mut size = None;
+    for line in lines {
+        if let Some(value) = line.strip_prefix("oid ") {
+            oid = Some(value.trim().to_string());
+        } else if let Some(value) = line.strip_prefix("size ") {
+            size = value.trim().parse().ok();
+        }
+    }
+    Some(LfsPointer {
+        oid: oid?,
+        size: size?,
+    })
+}
+
+/// The pointer a diff's hunks describe: the new side when it is a pointer,
+/// else the old side (for deleted pointers).
+pub(crate) fn lfs_pointer_in_hunks(hunks: &[Hunk]) -> Option<LfsPointer> {
+    // Pointer files are a few lines long, so a real one fits in one hunk.
+    let [hunk] = hunks else {
+        return None;
+    };
+    let side = |skip: LineOrigin| {
+        parse_lfs_pointer(
+            hunk.lines
+                .iter()
+                .filter(|line| line.origin != skip)
+                .map(|line| line.content.as_str()),
+        )
+    };
+    side(LineOrigin::Deletion).or_else(|| side(LineOrigin::Addition))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pointer() {
+        let pointer = parse_lfs_pointer([
+            "version https://git-lfs.github.com/spec/v1",
+            "oid sha256:4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393",
+            "size 12345",
+        ])
+        .unwrap();
+        assert_eq!(pointer.size, 12345);
+        assert!(pointer.oid.starts_with("sha256:4d7a"));
+    }
+
+    #[test]
+    fn test_regular_text_is_not_a_pointer() {
+        assert_eq!(parse_lfs_pointer(["hello", "size 3"]), None);
+        assert_eq!(
+            parse_lfs_pointer(["version https://git-lfs.github.com/spec/v1"]),
+            None
+        );
+    }
+}
@@ -1,13 +1,28 @@
+mod combined;
+mod context;
+mod full;
+mod image;
 mod inline;
+mod lfs;
 mod parse;
+mod patch;
 mod split;
-
-use std::path::Path;
+mod stat;
 
 use anyhow::Result;
 
+use crate::git_command::GitDirs;
+
+pub use combined::{parse_combined_diff, CombinedFileDiff, CombinedHunk, CombinedLine};
+pub use context::{expand_hunk_context, hidden_lines_above, ExpandDirection};
+pub use full::{full_file_diff, overlay_file_changes};
+pub use image::{ImageFormat, ImageVersions};
+pub use inline::{merge_word_diff, WordTag};
+pub use lfs::{parse_lfs_pointer, LfsPointer};
 pub use parse::parse_unified_diff;
+pub use patch::hunk_patch;
 pub use split::{split_hunk_lines, SplitRow};
+pub use stat::{parse_numstat, FileStat};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum LineOrigin {
@@ -32,6 +47,9 @@ pub struct DiffLine {
     pub new_line_no: Option<u32>,
     /// Byte-offset spans within `content` that were changed (word-level).
     pub change_spans: Vec<InlineSpan>,
+    /// Followed by a `\ No newline at end of file` marker: this is the last
+    /// line of its side and has no trailing newline.
+    pub no_newline_at_eof: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -50,6 +68,12 @@ pub enum FileStatus {
     Deleted,
     Modified,
     Renamed,
+    /// A new path copied from `old_path`, which stays in place. Only
+    /// reported where copy detection (`-C`) is on.
+    Copied,
+    /// The path changed kind, e.g. a regular file became a symlink. Git
+    /// reports this as a deletion plus an addition; the hunks keep both.
+    TypeChanged,
 }
 
 #[derive(Debug, Clone)]
@@ -59,12 +83,122 @@ pub struct FileDiff {
     pub old_path: Option<String>,
     pub status: FileStatus,
     pub hunks: Vec<Hunk>,
+    /// Set when the file is a Git LFS pointer; the hunks then hold the
+    /// pointer text rather than the real content.
+    pub lfs: Option<LfsPointer>,
+    /// File mode before the change, e.g. `100644`, when the diff header
+    /// states it (mode changes and deletions).
+    pub old_mode: Option<String>,
+    /// File mode after the change, when the diff header states it (mode
+    /// changes and additions).
+    pub new_mode: Option<String>,
+    /// Git's similarity index of a rename or copy, in percent.
+    pub similarity: Option<u8>,
+}
+
+impl FileDiff {
+    /// The image format implied by the file's extension. Such files are
+    /// binary in git's diff, so they come without hunks.
+    pub fn image_format(&self) -> Option<ImageFormat> {
+        ImageFormat::from_path(&self.path)
+    }
+
+    /// `(old, new)` modes when the change flipped the file's mode, such as
+    /// its executable bit.
+    pub fn mode_change(&self) -> Option<(&str, &str)> {
+        let old = self.old_mode.as_deref()?;
+        let new = self.new_mode.as_deref()?;
+        (old != new).then_some((old, new))
+    }
+}
+
+/// How whitespace-only differences are treated when computing a diff.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WhitespaceMode {
+    /// Every whitespace change is part of the diff.
+    #[default]
+    Show,
+    /// Changes at the end of lines, including CR before LF, are ignored.
+    IgnoreEol,
+    /// Whitespace is ignored when comparing lines.
+    IgnoreAll,
+}
+
+impl WhitespaceMode {
+    /// The mode after this one, wrapping back to [`WhitespaceMode::Show`].
+    pub fn next(self) -> Self {
+        match self {
+            WhitespaceMode::Show => WhitespaceMode::IgnoreEol,
+            WhitespaceMode::IgnoreEol => WhitespaceMode::IgnoreAll,
+            WhitespaceMode::IgnoreAll => WhitespaceMode::Show,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            WhitespaceMode::Show => "Show whitespace",
+            WhitespaceMode::IgnoreEol => "Ignore whitespace at line end",
+            WhitespaceMode::IgnoreAll => "Ignore all whitespace",
+        }
+    }
+
+    /// Flags passed to `git diff` / `git diff-tree` for this mode.
+    pub(crate) fn diff_args(self) -> &'static [&'static str] {
+        match self {
+            WhitespaceMode::Show => &[],
+            WhitespaceMode::IgnoreEol => &["--ignore-space-at-eol"],
+            WhitespaceMode::IgnoreAll => &["--ignore-all-space"],
+        }
+    }
+}
+
+pub(crate) fn diff_commit(
+    git: &GitDirs,
+    oid: &str,
+    whitespace: WhitespaceMode,
+) -> Result<Vec<FileDiff>> {
+    let mut files = parse::diff_commit(git, oid, whitespace)?;
+    for file in &mut files {
+        inline::compute_inline_changes(&mut file.hunks);
+    }
+    Ok(files)
+}
+
+/// Tree-to-tree diff from `from` to `to`, whatever their relationship.
+pub(crate) fn diff_range(git: &GitDirs, from: &str, to: &str) -> Result<Vec<FileDiff>> {
+    let mut files = parse::diff_range(git, from, to)?;
+    for file in &mut files {
+        inline::compute_inline_changes(&mut file.hunks);
+    }
+    Ok(files)
 }
 
-pub(crate) fn diff_commit(workdir: &Path, oid: &str) -> Result<Vec<FileDiff>> {
-    let mut files = parse::diff_commit(workdir, oid)?;
+/// What the next commit (or an amend) would add on top of `HEAD`.
+pub(crate) fn diff_staged(git: &GitDirs) -> Result<Vec<FileDiff>> {
+    let mut files = parse::diff_staged(git)?;
     for file in &mut files {
         inline::compute_inline_changes(&mut file.hunks);
     }
     Ok(files)
 }
+
+/// Working-tree edits not yet staged, including untracked files.
+pub(crate) fn diff_workdir(git: &GitDirs) -> Result<Vec<FileDiff>> {
+    let mut files = parse::diff_workdir(git)?;
+    for file in &mut files {
+        inline::compute_inline_changes(&mut file.hunks);
+    }
+    Ok(files)
+}
+
+pub(crate) fn diff_combined(
+    git: &GitDirs,
+    oid: &str,
+    whitespace: WhitespaceMode,
+) -> Result<Vec<CombinedFileDiff>> {
+    combined::diff_combined(git, oid, whitespace)
+}
+
+pub(crate) fn commit_stat(git: &GitDirs, oid: &str) -> Result<Vec<FileStat>> {
+    stat::commit_stat(git, oid)
+}
@@ -1,23 +1,36 @@
-use std::path::Path;
-use std::process::Command;
+use std::fs;
 
 use anyhow::{Context, Result};
 
-use super::{DiffLine, FileDiff, FileStatus, Hunk, LineOrigin};
+use crate::git_command::GitDirs;
 
-pub(crate) fn diff_commit(workdir: &Path, oid: &str) -> Result<Vec<FileDiff>> {
+use super::lfs::lfs_pointer_in_hunks;
+use super::{DiffLine, FileDiff, FileStatus, Hunk, LineOrigin, WhitespaceMode};
+
+pub(crate) fn diff_commit(
+    git: &GitDirs,
+    oid: &str,
+    whitespace: WhitespaceMode,
+) -> Result<Vec<FileDiff>> {
     anyhow::ensure!(
         oid.bytes().all(|b| b.is_ascii_hexdigit()),
         "invalid commit OID: {oid}"
     );
 
     // -m --first-parent: diff merge commits against their first parent.
-    // For non-merge commits these flags are no-ops.
-    let stdout = run_diff_tree(workdir, &["-m", "--first-parent"], oid)?;
-
-    // Empty output means root commit (no parent) — retry with --root.
+    // For non-merge commits these flags are no-ops. -C finds copies, which
+    // only a single commit's diff reports.
+    let mut args = vec!["-p", "-C", "-m", "--first-parent"];
+    args.extend_from_slice(whitespace.diff_args());
+    let stdout = run_diff_tree(git, &args, oid)?;
+
+    // Empty output means root commit (no parent) — retry with --root. It
+    // can also mean every change was ignored whitespace, and then --root
+    // makes no difference.
     let stdout = if stdout.trim().is_empty() {
-        run_diff_tree(workdir, &["--root"], oid)?
+        let mut args = vec!["-p", "-C", "--root"];
+        args.extend_from_slice(whitespace.diff_args());
+        run_diff_tree(git, &args, oid)?
     } else {
         stdout
     };
@@ -25,14 +38,114 @@ pub(crate) fn diff_commit(workdir: &Path, oid: &str) -> Result<Vec<FileDiff>> {
     parse_unified_diff(&stdout)
 }
 
-fn run_diff_tree(workdir: &Path, extra_args: &[&str], oid: &str) -> Result<String> {
-    let mut args = vec!["diff-tree", "-p", "--no-commit-id", "-M"];
+pub(crate) fn diff_range(git: &GitDirs, from: &str, to: &str) -> Result<Vec<FileDiff>> {
+    for oid in [from, to] {
+        anyhow::ensure!(
+            oid.bytes().all(|b| b.is_ascii_hexdigit()),
+            "invalid commit OID: {oid}"
+        );
+    }
+    let stdout = run_diff_tree(git, &["-p", from], to)?;
+    parse_unified_diff(&stdout)
+}
+
+/// Staged changes: the index against `HEAD`.
+pub(crate) fn diff_staged(git: &GitDirs) -> Result<Vec<FileDiff>> {
+    run_diff(git, &["--cached"])
+}
+
+/// Unstaged changes: the working tree against the index. Untracked files
+/// (minus ignored ones) are included as additions.
+pub(crate) fn diff_workdir(git: &GitDirs) -> Result<Vec<FileDiff>> {
+    let mut files = run_diff(git, &[])?;
+    // A file that can't be read, say one deleted since it was listed, is
+    // left out rather than failing the whole diff.
+    files.extend(
+        untracked_files(git)?
+            .into_iter()
+            .filter_map(|path| diff_untracked(git, path).ok()),
+    );
+    Ok(files)
+}
+
+fn run_diff(git: &GitDirs, extra_args: &[&str]) -> Result<Vec<FileDiff>> {
+    let output = git
+        .command()
+        .args(["diff", "-M", "--no-color", "--no-ext-diff"])
+        .args(extra_args)
+        .output()
+        .context("failed to run git diff")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("git diff failed: {}", stderr.trim());
+    }
+
+    parse_unified_diff(&String::from_utf8_lossy(&output.stdout))
+}
+
+fn untracked_files(git: &GitDirs) -> Result<Vec<String>> {
+    let output = git
+        .command()
+        .args(["ls-files", "--others", "--exclude-standard", "-z"])
+        .output()
+        .context("failed to run git ls-files")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("git ls-files failed: {}", stderr.trim());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .split('\0')
+        .filter(|path| !path.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+/// An untracked file as a new-file diff, built from its bytes rather than
+/// a `git diff --no-index` per file. Binary and empty files come without
+/// hunks, as git shows them.
+fn diff_untracked(git: &GitDirs, path: String) -> Result<FileDiff> {
+    let full_path = git.work_tree.join(&path);
+    let metadata = fs::symlink_metadata(&full_path)
+        .with_context(|| format!("failed to stat {}", full_path.display()))?;
+    // Git stores a symlink as its target path.
+    let bytes = if metadata.is_symlink() {
+        fs::read_link(&full_path)?
+            .to_string_lossy()
+            .into_owned()
+            .into_bytes()
+    } else {
+        fs::read(&full_path).with_context(|| format!("failed to read {}", full_path.display()))?
+    };
+
+    let file = FileDiff {
+        path,
+        old_path: None,
+        status: FileStatus::Added,
+        hunks: Vec::new(),
+        lfs: None,
+        old_mode: None,
+        new_mode: None,
+        similarity: None,
+    };
+    if bytes.is_empty() || bytes.contains(&0) {
+        return Ok(file);
+    }
+    let mut file = super::full_file_diff(&file, "", &String::from_utf8_lossy(&bytes));
+    file.lfs = lfs_pointer_in_hunks(&file.hunks);
+    Ok(file)
+}
+
+pub(super) fn run_diff_tree(git: &GitDirs, extra_args: &[&str], oid: &str) -> Result<String> {
+    let mut args = vec!["diff-tree", "--no-commit-id", "-M"];
     args.extend_from_slice(extra_args);
     args.push(oid);
 
-    let output = Command::new("git")
+    let output = git
+        .command()
         .args(&args)
-        .current_dir(workdir)
         .output()
         .context("failed to run git diff-tree")?;
 
@@ -45,8 +158,15 @@ fn run_diff_tree(workdir: &Path, extra_args: &[&str], oid: &str) -> Result<Strin
 }
 
 pub fn parse_unified_diff(input: &str) -> Result<Vec<FileDiff>> {
-    let mut files = Vec::new();
+    // git can emit CRLF patches on some Windows setups. `lines()` drops
+    // only one `\r`, so a CRLF file in a CRLF stream would keep one on
+    // every content line; normalizing first makes both streams parse alike.
+    let input = input.replace("\r\n", "\n");
+    let mut files: Vec<FileDiff> = Vec::new();
     let mut lines = input.lines().peekable();
+    // Mode of the last file when it was a deletion, to pair it with an
+    // addition of the same path.
+    let mut deleted_mode: Option<String> = None;
 
     while let Some(line) = lines.peek() {
         if !line.starts_with("diff --git") {
@@ -56,25 +176,50 @@ pub fn parse_unified_diff(input: &str) -> Result<Vec<FileDiff>> {
 
         // Parse file header
         let diff_line = lines.next().unwrap();
-        let (path, status) = parse_diff_header(diff_line);
+        let (mut path, status) = parse_diff_header(diff_line);
 
         // Skip extended header lines (index, old mode, new mode, etc.)
         let mut file_status = status;
         let mut old_path: Option<String> = None;
+        let mut old_mode = None;
+        let mut new_mode = None;
+        let mut similarity = None;
         while let Some(line) = lines.peek() {
             if line.starts_with("---") || line.starts_with("diff --git") || line.starts_with("@@") {
                 break;
             }
             let header_line = lines.next().unwrap();
-            if header_line.starts_with("new file") {
+            if let Some(mode) = header_line.strip_prefix("old mode ") {
+                old_mode = Some(mode.trim().to_string());
+            } else if let Some(mode) = header_line.strip_prefix("new mode ") {
+                new_mode = Some(mode.trim().to_string());
+            } else if let Some(mode) = header_line.strip_prefix("new file mode ") {
+                file_status = FileStatus::Added;
+                new_mode = Some(mode.trim().to_string());
+            } else if let Some(mode) = header_line.strip_prefix("deleted file mode ") {
+                file_status = FileStatus::Deleted;
+                old_mode = Some(mode.trim().to_string());
+            } else if header_line.starts_with("new file") {
                 file_status = FileStatus::Added;
             } else if header_line.starts_with("deleted file") {
                 file_status = FileStatus::Deleted;
+            } else if let Some(index) = header_line.strip_prefix("similarity index ") {
+                similarity = index.trim_end_matches('%').parse().ok();
             } else if let Some(from_path) = header_line.strip_prefix("rename from ") {
                 file_status = FileStatus::Renamed;
                 old_path = Some(from_path.to_string());
-            } else if header_line.starts_with("rename to") {
+            } else if let Some(to_path) = header_line.strip_prefix("rename to ") {
+                // The rename lines carry each path verbatim, so prefer them
+                // over the `diff --git` header. This keeps both sides of
+                // renames that only change case (`Readme.md` -> `README.md`).
                 file_status = FileStatus::Renamed;
+                path = to_path.to_string();
+            } else if let Some(from_path) = header_line.strip_prefix("copy from ") {
+                file_status = FileStatus::Copied;
+                old_path = Some(from_path.to_string());
+            } else if let Some(to_path) = header_line.strip_prefix("copy to ") {
+                file_status = FileStatus::Copied;
+                path = to_path.to_string();
             }
         }
 
@@ -100,17 +245,49 @@ pub fn parse_unified_diff(input: &str) -> Result<Vec<FileDiff>> {
             }
         }
 
+        let previous_deleted_mode = deleted_mode.take();
+        if file_status == FileStatus::Added {
+            let previous = files
+                .last_mut()
+                .filter(|prev| prev.path == path && prev.status == FileStatus::Deleted);
+            let kind_changed = previous_deleted_mode
+                .as_deref()
+                .zip(new_mode.as_deref())
+                .is_some_and(|(old, new)| object_kind(old) != object_kind(new));
+            if let (Some(previous), true) = (previous, kind_changed) {
+                previous.status = FileStatus::TypeChanged;
+                previous.hunks.extend(hunks);
+                previous.lfs = None;
+                previous.new_mode = new_mode;
+                continue;
+            }
+        }
+        if file_status == FileStatus::Deleted {
+            deleted_mode = old_mode.clone();
+        }
+
+        let lfs = lfs_pointer_in_hunks(&hunks);
         files.push(FileDiff {
             path,
             old_path,
             status: file_status,
             hunks,
+            lfs,
+            old_mode,
+            new_mode,
+            similarity,
         });
     }
 
     Ok(files)
 }
 
+/// The object-type bits of a git file mode: `100` for regular files,
+/// `120` for symlinks and `160` for submodules.
+fn object_kind(mode: &str) -> &str {
+    mode.get(..3).unwrap_or(mode)
+}
+
 fn parse_diff_header(line: &str) -> (String, FileStatus) {
     // "diff --git a/path b/path"
     let parts: Vec<&str> = line.splitn(4, ' ').collect();
@@ -141,6 +318,7 @@ fn parse_hunk(lines: &mut std::iter::Peekable<std::str::Lines<'_>>) -> Hunk {
                 old_line_no: None,
                 new_line_no: Some(new_line),
                 change_spans: Vec::new(),
+                no_newline_at_eof: false,
             });
             new_line += 1;
         } else if let Some(content) = line.strip_prefix('-') {
@@ -150,6 +328,7 @@ fn parse_hunk(lines: &mut std::iter::Peekable<std::str::Lines<'_>>) -> Hunk {
                 old_line_no: Some(old_line),
                 new_line_no: None,
                 change_spans: Vec::new(),
+                no_newline_at_eof: false,
             });
             old_line += 1;
         } else if let Some(content) = line.strip_prefix(' ') {
@@ -159,12 +338,15 @@ fn parse_hunk(lines: &mut std::iter::Peekable<std::str::Lines<'_>>) -> Hunk {
                 old_line_no: Some(old_line),
                 new_line_no: Some(new_line),
                 change_spans: Vec::new(),
+                no_newline_at_eof: false,
             });
             old_line += 1;
             new_line += 1;
         } else if line.starts_with('\\') {
-            // "\ No newline at end of file"
-            continue;
+            // "\ No newline at end of file" applies to the preceding line.
+            if let Some(last) = hunk_lines.last_mut() {
+                last.no_newline_at_eof = true;
+            }
         } else {
             hunk_lines.push(DiffLine {
                 origin: LineOrigin::Context,
@@ -172,6 +354,7 @@ fn parse_hunk(lines: &mut std::iter::Peekable<std::str::Lines<'_>>) -> Hunk {
                 old_line_no: Some(old_line),
                 new_line_no: Some(new_line),
                 change_spans: Vec::new(),
+                no_newline_at_eof: false,
             });
             old_line += 1;
             new_line += 1;
@@ -205,7 +388,7 @@ fn parse_hunk_header(header: &str) -> (u32, u32, u32, u32) {
     (old_start, old_count, new_start, new_count)
 }
 
-fn parse_range(range: &str) -> (u32, u32) {
+pub(super) fn parse_range(range: &str) -> (u32, u32) {
     let parts: Vec<&str> = range.split(',').collect();
     let start = parts[0].parse().unwrap_or(0);
     let count = if parts.len() > 1 {
@@ -232,6 +415,22 @@ mod tests {
         assert_eq!((os, oc, ns, nc), (0, 0, 1, 1));
     }
 
+    #[test]
+    fn test_parse_no_newline_marker_flags_preceding_line() {
+        let diff = "diff --git a/file.txt b/file.txt\n\
+--- a/file.txt\n\
++++ b/file.txt\n\
+@@ -1 +1 @@\n\
+-last\n\
+\\ No newline at end of file\n\
++last\n";
+        let files = parse_unified_diff(diff).unwrap();
+        let lines = &files[0].hunks[0].lines;
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].no_newline_at_eof);
+        assert!(!lines[1].no_newline_at_eof);
+    }
+
     #[test]
     fn test_parse_unified_diff() {
         let diff = "diff --git a/file.txt b/file.txt\n\
@@ -296,6 +495,107 @@ index 0000000..abc1234
             .all(|l| l.origin == LineOrigin::Addition));
     }
 
+    #[test]
+    fn test_parse_lfs_pointer_addition() {
+        let diff = "\
+diff --git a/assets/logo.psd b/assets/logo.psd
+new file mode 100644
+index 0000000..5b1c2d3
+--- /dev/null
++++ b/assets/logo.psd
+@@ -0,0 +1,3 @@
++version https://git-lfs.github.com/spec/v1
++oid sha256:4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393
++size 2411724
+";
+        let files = parse_unified_diff(diff).unwrap();
+        let lfs = files[0].lfs.as_ref().expect("LFS pointer detected");
+        assert_eq!(
+            lfs.oid,
+            "sha256:4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393"
+        );
+        assert_eq!(lfs.size, 2411724);
+    }
+
+    #[test]
+    fn test_parse_file_to_symlink_is_a_type_change() {
+        let diff = "\
+diff --git a/link b/link
+deleted file mode 100644
+index 3b18e51..0000000
+--- a/link
++++ /dev/null
+@@ -1 +0,0 @@
+-hello world
+diff --git a/link b/link
+new file mode 120000
+index 0000000..a1f7c8e
+--- /dev/null
++++ b/link
+@@ -0,0 +1 @@
++target.txt
+\\ No newline at end of file
+diff --git a/other.txt b/other.txt
+new file mode 100644
+index 0000000..ce01362
+--- /dev/null
++++ b/other.txt
+@@ -0,0 +1 @@
++hello
+";
+        let files = parse_unified_diff(diff).unwrap();
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].path, "link");
+        assert_eq!(files[0].status, FileStatus::TypeChanged);
+        assert_eq!(files[0].hunks.len(), 2);
+        assert_eq!(files[0].hunks[0].lines[0].origin, LineOrigin::Deletion);
+        assert_eq!(files[0].hunks[1].lines[0].content, "target.txt");
+        assert_eq!(files[0].mode_change(), Some(("100644", "120000")));
+        assert_eq!(files[1].path, "other.txt");
+        assert_eq!(files[1].status, FileStatus::Added);
+        assert_eq!(files[1].hunks.len(), 1);
+        assert_eq!(files[1].mode_change(), None);
+    }
+
+    #[test]
+    fn test_parse_mode_only_change() {
+        let diff = "\
+diff --git a/run.sh b/run.sh
+old mode 100644
+new mode 100755
+";
+        let files = parse_unified_diff(diff).unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].status, FileStatus::Modified);
+        assert!(files[0].hunks.is_empty());
+        assert_eq!(files[0].old_mode.as_deref(), Some("100644"));
+        assert_eq!(files[0].new_mode.as_deref(), Some("100755"));
+        assert_eq!(files[0].mode_change(), Some(("100644", "100755")));
+    }
+
+    #[test]
+    fn test_parse_copy_diff() {
+        let diff = "\
+diff --git a/src/util.rs b/src/util_copy.rs
+similarity index 90%
+copy from src/util.rs
+copy to src/util_copy.rs
+index 3b18e51..a1f7c8e 100644
+--- a/src/util.rs
++++ b/src/util_copy.rs
+@@ -1,2 +1,2 @@
+ fn helper() {}
+-fn old() {}
++fn new() {}
+";
+        let files = parse_unified_diff(diff).unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].status, FileStatus::Copied);
+        assert_eq!(files[0].path, "src/util_copy.rs");
+        assert_eq!(files[0].old_path.as_deref(), Some("src/util.rs"));
+        assert_eq!(files[0].hunks.len(), 1);
+    }
+
     #[test]
     fn test_parse_deleted_file_diff() {
         let diff = "\
@@ -331,15 +631,115 @@ rename to new_name.txt
         assert_eq!(files[0].status, FileStatus::Renamed);
         assert_eq!(files[0].path, "new_name.txt");
         assert_eq!(files[0].old_path.as_deref(), Some("old_name.txt"));
+        assert_eq!(files[0].similarity, Some(100));
+        assert!(files[0].hunks.is_empty());
+    }
+
+    #[test]
+    fn test_parse_changed_binary_rename_keeps_similarity() {
+        let diff = "\
+diff --git a/logo.png b/assets/logo.png
+similarity index 72%
+rename from logo.png
+rename to assets/logo.png
+index abc1234..def5678 100644
+Binary files a/logo.png and b/assets/logo.png differ
+";
+        let files = parse_unified_diff(diff).unwrap();
+        assert_eq!(files[0].status, FileStatus::Renamed);
+        assert_eq!(files[0].path, "assets/logo.png");
+        assert_eq!(files[0].similarity, Some(72));
         assert!(files[0].hunks.is_empty());
     }
 
+    #[test]
+    fn test_parse_case_only_rename() {
+        let diff = "\
+diff --git a/Readme.md b/README.md
+similarity index 100%
+rename from Readme.md
+rename to README.md
+diff --git a/docs/Guide.md b/docs/guide.md
+similarity index 80%
+rename from docs/Guide.md
+rename to docs/guide.md
+index abc1234..def5678 100644
+--- a/docs/Guide.md
++++ b/docs/guide.md
+@@ -1,2 +1,2 @@
+ # Guide
+-Old text
++New text
+";
+        let files = parse_unified_diff(diff).unwrap();
+        assert_eq!(files.len(), 2);
+
+        assert_eq!(files[0].status, FileStatus::Renamed);
+        assert_eq!(files[0].path, "README.md");
+        assert_eq!(files[0].old_path.as_deref(), Some("Readme.md"));
+        assert_ne!(Some(files[0].path.as_str()), files[0].old_path.as_deref());
+
+        assert_eq!(files[1].status, FileStatus::Renamed);
+        assert_eq!(files[1].path, "docs/guide.md");
+        assert_eq!(files[1].old_path.as_deref(), Some("docs/Guide.md"));
+        assert_eq!(files[1].hunks.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_rename_with_spaces_uses_rename_lines() {
+        let diff = "\
+diff --git a/my notes.txt b/My Notes.txt
+similarity index 100%
+rename from my notes.txt
+rename to My Notes.txt
+";
+        let files = parse_unified_diff(diff).unwrap();
+        assert_eq!(files[0].path, "My Notes.txt");
+        assert_eq!(files[0].old_path.as_deref(), Some("my notes.txt"));
+    }
+
     #[test]
     fn test_parse_empty_diff() {
         let files = parse_unified_diff("").unwrap();
         assert!(files.is_empty());
     }
 
+    #[test]
+    fn test_parse_crlf_patch_stream_matches_lf() {
+        let lf = "\
+diff --git a/old.txt b/new.txt
+similarity index 80%
+rename from old.txt
+rename to new.txt
+index abc..def 100644
+--- a/old.txt
++++ b/new.txt
+@@ -1,3 +1,3 @@
+ keep
+-old line
++new line\r
+ tail
+\\ No newline at end of file
+diff --git a/b.txt b/b.txt
+new file mode 100644
+--- /dev/null
++++ b/b.txt
+@@ -0,0 +1 @@
++new b
+";
+        let crlf = lf.replace('\n', "\r\n");
+        let expected = parse_unified_diff(lf).unwrap();
+        let parsed = parse_unified_diff(&crlf).unwrap();
+        assert_eq!(format!("{parsed:?}"), format!("{expected:?}"));
+
+        assert_eq!(parsed[0].path, "new.txt");
+        assert_eq!(parsed[0].old_path.as_deref(), Some("old.txt"));
+        let lines = &parsed[0].hunks[0].lines;
+        assert_eq!(lines[2].content, "new line");
+        assert!(lines[3].no_newline_at_eof);
+        assert_eq!(parsed[1].status, FileStatus::Added);
+    }
+
     #[test]
     fn test_parse_multi_file_diff() {
         let diff = "\
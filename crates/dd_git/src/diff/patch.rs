@@ -0,0 +1,104 @@
+use super::{FileDiff, FileStatus, Hunk, LineOrigin};
+
+/// Render a single hunk of `file` as a standalone patch that `git apply`
+/// accepts.
+pub fn hunk_patch(file: &FileDiff, hunk: &Hunk) -> String {
+    let old_path = file.old_path.as_deref().unwrap_or(&file.path);
+    // A type change's hunks are the old object's removal and the new
+    // one's addition.
+    let old_side = match file.status {
+        FileStatus::Added => "/dev/null".to_string(),
+        FileStatus::TypeChanged if hunk.old_count == 0 => "/dev/null".to_string(),
+        _ => format!("a/{old_path}"),
+    };
+    let new_side = match file.status {
+        FileStatus::Deleted => "/dev/null".to_string(),
+        FileStatus::TypeChanged if hunk.new_count == 0 => "/dev/null".to_string(),
+        _ => format!("b/{}", file.path),
+    };
+
+    let mut patch = format!("diff --git a/{old_path} b/{}\n", file.path);
+    // Without these `git apply` would patch `old_path` in place instead of
+    // moving or copying it.
+    match file.status {
+        FileStatus::Renamed if old_path != file.path => {
+            patch.push_str(&format!(
+                "rename from {old_path}\nrename to {}\n",
+                file.path
+            ));
+        }
+        FileStatus::Copied if old_path != file.path => {
+            patch.push_str(&format!("copy from {old_path}\ncopy to {}\n", file.path));
+        }
+        _ => {}
+    }
+    patch.push_str(&format!(
+        "--- {old_side}\n+++ {new_side}\n{}\n",
+        hunk.header
+    ));
+    for line in &hunk.lines {
+        let prefix = match line.origin {
+            LineOrigin::Context => ' ',
+            LineOrigin::Addition => '+',
+            LineOrigin::Deletion => '-',
+        };
+        patch.push(prefix);
+        patch.push_str(&line.content);
+        patch.push('\n');
+        if line.no_newline_at_eof {
+            patch.push_str("\\ No newline at end of file\n");
+        }
+    }
+    patch
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::parse_unified_diff;
+
+    const DIFF: &str = "\
+diff --git a/src/lib.rs b/src/lib.rs
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,2 +1,2 @@
+ fn a() {}
+-fn b() {}
++fn b() { todo!() }
+@@ -10,1 +10,2 @@ mod tests
+ fn z() {}
++fn y() {}
+";
+
+    #[test]
+    fn test_hunk_patch_contains_only_requested_hunk() {
+        let files = parse_unified_diff(DIFF).unwrap();
+        let patch = hunk_patch(&files[0], &files[0].hunks[1]);
+        assert_eq!(
+            patch,
+            "diff --git a/src/lib.rs b/src/lib.rs\n--- a/src/lib.rs\n+++ b/src/lib.rs\n\
+             @@ -10,1 +10,2 @@ mod tests\n fn z() {}\n+fn y() {}\n"
+        );
+    }
+
+    #[test]
+    fn test_hunk_patch_added_file_uses_dev_null() {
+        let mut files = parse_unified_diff(DIFF).unwrap();
+        files[0].status = FileStatus::Added;
+        let patch = hunk_patch(&files[0], &files[0].hunks[0]);
+        assert!(patch.contains("--- /dev/null\n+++ b/src/lib.rs\n"));
+    }
+
+    #[test]
+    fn test_hunk_patch_renamed_file_has_rename_headers() {
+        let mut files = parse_unified_diff(DIFF).unwrap();
+        files[0].status = FileStatus::Renamed;
+        files[0].old_path = Some("src/old.rs".into());
+        let patch = hunk_patch(&files[0], &files[0].hunks[0]);
+        assert!(patch.starts_with(
+            "diff --git a/src/old.rs b/src/lib.rs\n\
+             rename from src/old.rs\nrename to src/lib.rs\n\
+             --- a/src/old.rs\n+++ b/src/lib.rs\n"
+        ));
+    }
+}
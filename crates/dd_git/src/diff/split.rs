@@ -7,6 +7,10 @@ use super::{DiffLine, LineOrigin};
 /// `left` carries the old-file side (context or deletion),
 /// `right` carries the new-file side (context or addition).
 /// Either side may be `None` when there is no paired line.
+///
+/// Invariant: `left` is never an addition and `right` is never a deletion, so
+/// a present `left` always has `old_line_no` and a present `right` always has
+/// `new_line_no`. Renderers rely on this for the line-number gutter.
 #[derive(Debug, Clone)]
 pub struct SplitRow {
     pub left: Option<Arc<DiffLine>>,
@@ -107,6 +111,7 @@ mod tests {
             old_line_no: Some(old),
             new_line_no: Some(new),
             change_spans: Vec::new(),
+            no_newline_at_eof: false,
         }
     }
 
@@ -117,6 +122,7 @@ mod tests {
             old_line_no: Some(old),
             new_line_no: None,
             change_spans: Vec::new(),
+            no_newline_at_eof: false,
         }
     }
 
@@ -127,6 +133,7 @@ mod tests {
             old_line_no: None,
             new_line_no: Some(new),
             change_spans: Vec::new(),
+            no_newline_at_eof: false,
         }
     }
 
@@ -278,4 +285,27 @@ mod tests {
         assert_eq!(rows[4].left.as_ref().unwrap().content, "gone");
         assert!(rows[4].right.is_none());
     }
+
+    #[test]
+    fn test_sides_respect_origin_invariant() {
+        let lines = vec![
+            del("a", 1),
+            del("b", 2),
+            add("c", 1),
+            ctx("d", 3, 2),
+            add("e", 3),
+            add("f", 4),
+            del("g", 4),
+        ];
+        for row in split_hunk_lines(&lines) {
+            if let Some(left) = &row.left {
+                assert_ne!(left.origin, LineOrigin::Addition);
+                assert!(left.old_line_no.is_some());
+            }
+            if let Some(right) = &row.right {
+                assert_ne!(right.origin, LineOrigin::Deletion);
+                assert!(right.new_line_no.is_some());
+            }
+        }
+    }
 }
@@ -0,0 +1,130 @@
+use anyhow::Result;
+
+use crate::git_command::GitDirs;
+
+use super::parse::run_diff_tree;
+
+/// Per-file line counts for a commit, as reported by `git --numstat`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileStat {
+    pub path: String,
+    /// Added lines; `None` for binary files.
+    pub additions: Option<usize>,
+    /// Deleted lines; `None` for binary files.
+    pub deletions: Option<usize>,
+}
+
+impl FileStat {
+    pub fn is_binary(&self) -> bool {
+        self.additions.is_none() && self.deletions.is_none()
+    }
+}
+
+/// Line counts for every file touched by `oid`, without producing a patch.
+/// Merge commits are compared against their first parent, like
+/// [`super::diff_commit`].
+pub(crate) fn commit_stat(git: &GitDirs, oid: &str) -> Result<Vec<FileStat>> {
+    anyhow::ensure!(
+        oid.bytes().all(|b| b.is_ascii_hexdigit()),
+        "invalid commit OID: {oid}"
+    );
+
+    let stdout = run_diff_tree(git, &["--numstat", "-m", "--first-parent"], oid)?;
+    let stdout = if stdout.trim().is_empty() {
+        run_diff_tree(git, &["--numstat", "--root"], oid)?
+    } else {
+        stdout
+    };
+
+    Ok(parse_numstat(&stdout)
+        .into_iter()
+        .map(|(additions, deletions, path)| FileStat {
+            path,
+            additions,
+            deletions,
+        })
+        .collect())
+}
+
+/// Parse `--numstat` output into `(added, deleted, path)` triples. Binary
+/// files report `-` for both counts, which becomes `None`. Renames are
+/// reported under their new path. Lines that are not numstat records (e.g.
+/// commit headers from `git log`) are skipped.
+pub fn parse_numstat(input: &str) -> Vec<(Option<usize>, Option<usize>, String)> {
+    input.lines().filter_map(parse_numstat_line).collect()
+}
+
+fn parse_numstat_line(line: &str) -> Option<(Option<usize>, Option<usize>, String)> {
+    let mut fields = line.splitn(3, '\t');
+    let additions = parse_count(fields.next()?)?;
+    let deletions = parse_count(fields.next()?)?;
+    let path = fields.next()?;
+    Some((additions, deletions, renamed_path(path)))
+}
+
+/// `Some(None)` for a binary `-`, `None` when the field is not a count.
+fn parse_count(field: &str) -> Option<Option<usize>> {
+    if field == "-" {
+        return Some(None);
+    }
+    field.parse().ok().map(Some)
+}
+
+/// Resolve numstat rename notation to the new path:
+/// `src/{a.rs => b.rs}` becomes `src/b.rs` and `a.rs => b.rs` becomes `b.rs`.
+fn renamed_path(path: &str) -> String {
+    if let (Some(open), Some(close)) = (path.find('{'), path.rfind('}')) {
+        if let Some((_, new)) = path[open + 1..close].split_once(" => ") {
+            let joined = format!("{}{}{}", &path[..open], new, &path[close + 1..]);
+            // An empty side leaves a doubled separator, e.g. `a/{ => b}/c`.
+            return joined.replace("//", "/");
+        }
+    }
+    match path.split_once(" => ") {
+        Some((_, new)) => new.to_string(),
+        None => path.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_numstat_text() {
+        let stats = parse_numstat("3\t1\tsrc/lib.rs\n0\t12\tREADME.md\n");
+        assert_eq!(
+            stats,
+            vec![
+                (Some(3), Some(1), "src/lib.rs".to_string()),
+                (Some(0), Some(12), "README.md".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_numstat_binary() {
+        let stats = parse_numstat("-\t-\tassets/icon.bin\n");
+        assert_eq!(stats, vec![(None, None, "assets/icon.bin".to_string())]);
+    }
+
+    #[test]
+    fn test_parse_numstat_rename() {
+        let stats = parse_numstat(
+            "1\t0\tsrc/{lib.rs => library.rs}\n\
+             0\t0\told.txt => new.txt\n\
+             2\t2\tsrc/{ => nested}/mod.rs\n",
+        );
+        let paths: Vec<_> = stats.iter().map(|(_, _, p)| p.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec!["src/library.rs", "new.txt", "src/nested/mod.rs"]
+        );
+    }
+
+    #[test]
+    fn test_parse_numstat_skips_non_records() {
+        let stats = parse_numstat("\ncommit abc123\n3\t1\ta.rs\nnot\ta\tcount\n");
+        assert_eq!(stats, vec![(Some(3), Some(1), "a.rs".to_string())]);
+    }
+}
@@ -0,0 +1,77 @@
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Why a repository could not be opened or read, in terms the UI can show.
+#[derive(Debug, thiserror::Error)]
+pub enum RepoError {
+    #[error("permission denied: {}", path.display())]
+    PermissionDenied { path: PathBuf },
+    #[error("{} is not a git repository", path.display())]
+    NotARepository { path: PathBuf },
+    #[error("{0}")]
+    Other(String),
+}
+
+impl RepoError {
+    /// Classify a failure for the repository at `path`. gix does not always
+    /// keep the underlying IO error, so an unreadable directory is also
+    /// detected by probing `path` itself.
+    pub fn classify(path: &Path, err: &anyhow::Error) -> Self {
+        let permission_denied = err
+            .chain()
+            .filter_map(|cause| cause.downcast_ref::<io::Error>())
+            .any(|io_err| io_err.kind() == io::ErrorKind::PermissionDenied)
+            || std::fs::read_dir(path)
+                .is_err_and(|io_err| io_err.kind() == io::ErrorKind::PermissionDenied);
+        if permission_denied {
+            return Self::PermissionDenied {
+                path: path.to_path_buf(),
+            };
+        }
+        if err.chain().any(|cause| cause.is::<gix::open::Error>()) {
+            return Self::NotARepository {
+                path: path.to_path_buf(),
+            };
+        }
+        Self::Other(format!("{err:#}"))
+    }
+
+    pub fn is_permission_denied(&self) -> bool {
+        matches!(self, Self::PermissionDenied { .. })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_io_permission_error_is_permission_denied() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let err = anyhow::Error::from(io::Error::from(io::ErrorKind::PermissionDenied))
+            .context("failed to read index");
+        let classified = RepoError::classify(dir.path(), &err);
+        assert!(classified.is_permission_denied());
+        assert_eq!(
+            classified.to_string(),
+            format!("permission denied: {}", dir.path().display())
+        );
+    }
+
+    #[test]
+    fn test_open_failure_is_not_a_repository() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let err = crate::Repository::open(dir.path()).err().unwrap();
+        let classified = RepoError::classify(dir.path(), &err);
+        assert!(matches!(classified, RepoError::NotARepository { .. }));
+        assert!(classified.to_string().ends_with("is not a git repository"));
+    }
+
+    #[test]
+    fn test_other_errors_keep_their_message() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let err = anyhow::anyhow!("git status failed: boom");
+        let classified = RepoError::classify(dir.path(), &err);
+        assert_eq!(classified.to_string(), "git status failed: boom");
+    }
+}
@@ -0,0 +1,27 @@
+use std::path::Path;
+use std::process::Command;
+
+/// Where `git` subprocesses run for a repository. `git_dir` is only set for
+/// repositories opened with an explicit git directory, whose work tree may
+/// not contain a `.git` entry git could discover on its own.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct GitDirs<'a> {
+    pub work_tree: &'a Path,
+    pub git_dir: Option<&'a Path>,
+}
+
+impl GitDirs<'_> {
+    /// A `git` command bound to this repository. It inherits the process
+    /// environment, so git reads the same global and system config, and
+    /// follows the same includes, as it would from a terminal.
+    pub(crate) fn command(&self) -> Command {
+        let mut command = Command::new("git");
+        command.current_dir(self.work_tree);
+        if let Some(git_dir) = self.git_dir {
+            command
+                .env("GIT_DIR", git_dir)
+                .env("GIT_WORK_TREE", self.work_tree);
+        }
+        command
+    }
+}
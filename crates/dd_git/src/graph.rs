@@ -0,0 +1,192 @@
+use crate::commit::CommitInfo;
+
+/// A line drawn through one row of the commit graph, from lane `from` to
+/// lane `to`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GraphEdge {
+    pub from: usize,
+    pub to: usize,
+}
+
+/// The graph cell beside one commit in the list.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LaneRow {
+    /// Lane holding this commit's dot.
+    pub column: usize,
+    /// Lines from the top of the row to the dot's level. Lanes that merely
+    /// pass by run from a lane to itself.
+    pub incoming: Vec<GraphEdge>,
+    /// Lines from the dot's level to the bottom of the row.
+    pub outgoing: Vec<GraphEdge>,
+    /// Number of lanes the row spans.
+    pub width: usize,
+}
+
+/// Lay `commits` (newest first, children before parents) out in lanes,
+/// as in gitk. Each commit takes the lane of the first child that was
+/// waiting for it; further parents of a merge get lanes of their own.
+/// Costs O(commits × lanes).
+pub fn compute_graph_lanes(commits: &[CommitInfo]) -> Vec<LaneRow> {
+    // The commit each lane is waiting to reach, if any.
+    let mut lanes: Vec<Option<&str>> = Vec::new();
+    let mut rows = Vec::with_capacity(commits.len());
+
+    for commit in commits {
+        let oid = commit.oid.as_str();
+        let column = lanes
+            .iter()
+            .position(|lane| *lane == Some(oid))
+            .unwrap_or_else(|| claim_free_lane(&mut lanes));
+
+        let mut incoming = Vec::new();
+        for (i, lane) in lanes.iter().enumerate() {
+            match lane {
+                // Every child waiting on this commit converges on its dot.
+                Some(target) if *target == oid => incoming.push(GraphEdge {
+                    from: i,
+                    to: column,
+                }),
+                Some(_) => incoming.push(GraphEdge { from: i, to: i }),
+                None => {}
+            }
+        }
+        for lane in lanes.iter_mut() {
+            if *lane == Some(oid) {
+                *lane = None;
+            }
+        }
+
+        let mut outgoing = Vec::new();
+        let mut parents = commit.parent_oids.iter().map(String::as_str);
+        if let Some(first) = parents.next() {
+            lanes[column] = Some(first);
+        }
+        for parent in parents {
+            // A parent another lane already waits for is joined, not copied.
+            let lane = match lanes.iter().position(|lane| *lane == Some(parent)) {
+                Some(lane) => lane,
+                None => {
+                    let lane = claim_free_lane(&mut lanes);
+                    lanes[lane] = Some(parent);
+                    lane
+                }
+            };
+            outgoing.push(GraphEdge {
+                from: column,
+                to: lane,
+            });
+        }
+        for (i, lane) in lanes.iter().enumerate() {
+            let branched = outgoing.iter().any(|edge| edge.to == i);
+            if lane.is_some() && !branched {
+                outgoing.push(GraphEdge { from: i, to: i });
+            }
+        }
+        outgoing.sort_by_key(|edge| (edge.to, edge.from));
+
+        while lanes.last() == Some(&None) {
+            lanes.pop();
+        }
+        let width = incoming
+            .iter()
+            .chain(&outgoing)
+            .map(|edge| edge.from.max(edge.to) + 1)
+            .max()
+            .unwrap_or(0)
+            .max(column + 1);
+        rows.push(LaneRow {
+            column,
+            incoming,
+            outgoing,
+            width,
+        });
+    }
+    rows
+}
+
+/// The leftmost lane nobody is waiting on, adding one if all are busy.
+fn claim_free_lane(lanes: &mut Vec<Option<&str>>) -> usize {
+    match lanes.iter().position(Option::is_none) {
+        Some(lane) => lane,
+        None => {
+            lanes.push(None);
+            lanes.len() - 1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commit(oid: &str, parents: &[&str]) -> CommitInfo {
+        CommitInfo {
+            oid: oid.into(),
+            short_oid: oid.into(),
+            tree_oid: String::new(),
+            author_name: String::new(),
+            author_email: String::new(),
+            date: 0,
+            committer_name: String::new(),
+            committer_email: String::new(),
+            committer_date: 0,
+            subject: String::new(),
+            body: String::new(),
+            parent_oids: parents.iter().map(|p| p.to_string()).collect(),
+        }
+    }
+
+    fn edge(from: usize, to: usize) -> GraphEdge {
+        GraphEdge { from, to }
+    }
+
+    #[test]
+    fn test_linear_history_is_one_lane() {
+        let rows =
+            compute_graph_lanes(&[commit("c", &["b"]), commit("b", &["a"]), commit("a", &[])]);
+        assert!(rows.iter().all(|row| row.column == 0 && row.width == 1));
+        assert!(rows[0].incoming.is_empty());
+        assert_eq!(rows[1].incoming, vec![edge(0, 0)]);
+        assert_eq!(rows[1].outgoing, vec![edge(0, 0)]);
+        assert!(rows[2].outgoing.is_empty());
+    }
+
+    #[test]
+    fn test_merge_opens_and_closes_a_lane() {
+        let rows = compute_graph_lanes(&[
+            commit("m", &["a", "b"]),
+            commit("a", &["base"]),
+            commit("b", &["base"]),
+            commit("base", &[]),
+        ]);
+        let columns: Vec<_> = rows.iter().map(|row| row.column).collect();
+        assert_eq!(columns, vec![0, 0, 1, 0]);
+
+        assert_eq!(rows[0].outgoing, vec![edge(0, 0), edge(0, 1)]);
+        assert_eq!(rows[1].incoming, vec![edge(0, 0), edge(1, 1)]);
+        assert_eq!(rows[2].incoming, vec![edge(0, 0), edge(1, 1)]);
+        // Both sides of the merge meet again at the base.
+        assert_eq!(rows[3].incoming, vec![edge(0, 0), edge(1, 0)]);
+        assert_eq!(rows[3].width, 2);
+    }
+
+    #[test]
+    fn test_fork_gives_each_tip_a_lane() {
+        let rows = compute_graph_lanes(&[
+            commit("x", &["p"]),
+            commit("y", &["p"]),
+            commit("p", &["root"]),
+            commit("root", &[]),
+        ]);
+        let columns: Vec<_> = rows.iter().map(|row| row.column).collect();
+        assert_eq!(columns, vec![0, 1, 0, 0]);
+
+        // The second tip starts fresh beside the first lane.
+        assert_eq!(rows[1].incoming, vec![edge(0, 0)]);
+        assert_eq!(rows[1].outgoing, vec![edge(0, 0), edge(1, 1)]);
+        assert_eq!(rows[2].incoming, vec![edge(0, 0), edge(1, 0)]);
+        // The freed lane is not carried below the branch point.
+        assert_eq!(rows[2].outgoing, vec![edge(0, 0)]);
+        assert_eq!(rows[3].width, 1);
+    }
+}
@@ -1,11 +1,29 @@
 pub mod commit;
 pub mod diff;
+pub mod error;
+mod git_command;
+pub mod graph;
+pub mod line_endings;
+pub mod permalink;
 pub mod repository;
 pub mod types;
 
-pub use commit::{CommitInfo, SignatureStatus};
+pub use commit::{
+    contiguous_range, parse_conventional, CommitInfo, ConventionalCommit, SignatureStatus,
+};
 pub use diff::{
-    split_hunk_lines, DiffLine, FileDiff, FileStatus, Hunk, InlineSpan, LineOrigin, SplitRow,
+    expand_hunk_context, full_file_diff, hidden_lines_above, hunk_patch, merge_word_diff,
+    overlay_file_changes, parse_combined_diff, parse_numstat, split_hunk_lines, CombinedFileDiff,
+    CombinedHunk, CombinedLine, DiffLine, ExpandDirection, FileDiff, FileStat, FileStatus, Hunk,
+    ImageFormat, ImageVersions, InlineSpan, LfsPointer, LineOrigin, SplitRow, WhitespaceMode,
+    WordTag,
+};
+pub use error::RepoError;
+pub use graph::{compute_graph_lanes, GraphEdge, LaneRow};
+pub use line_endings::{normalize_line_endings, LineEnding, LineEndingStats};
+pub use permalink::{line_permalink, parse_remote_url, RemoteHost, RemoteWeb};
+pub use repository::{find_git_repos, Repository};
+pub use types::{
+    BranchInfo, OperationInProgress, RemoteInfo, RepoSummary, StashInfo, SubmoduleInfo, TagInfo,
+    TreeEntry,
 };
-pub use repository::Repository;
-pub use types::{BranchInfo, RemoteInfo, StashInfo, TagInfo};
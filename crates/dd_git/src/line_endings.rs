@@ -0,0 +1,114 @@
+/// How many lines of a file end in `\n` versus `\r\n`. A final line without
+/// a terminator counts as neither.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LineEndingStats {
+    pub lf: usize,
+    pub crlf: usize,
+}
+
+impl LineEndingStats {
+    /// Count line terminators in raw file content.
+    pub fn count(content: &[u8]) -> Self {
+        let mut stats = Self::default();
+        for (i, &byte) in content.iter().enumerate() {
+            if byte == b'\n' {
+                if i > 0 && content[i - 1] == b'\r' {
+                    stats.crlf += 1;
+                } else {
+                    stats.lf += 1;
+                }
+            }
+        }
+        stats
+    }
+
+    /// Both styles occur in the same file.
+    pub fn is_mixed(&self) -> bool {
+        self.lf > 0 && self.crlf > 0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineEnding {
+    Lf,
+    CrLf,
+}
+
+impl LineEnding {
+    pub fn label(self) -> &'static str {
+        match self {
+            LineEnding::Lf => "LF",
+            LineEnding::CrLf => "CRLF",
+        }
+    }
+}
+
+/// Rewrite every line terminator in `content` to `ending`. Lone `\r`
+/// characters are left alone.
+pub fn normalize_line_endings(content: &[u8], ending: LineEnding) -> Vec<u8> {
+    let mut out = Vec::with_capacity(content.len());
+    for (i, &byte) in content.iter().enumerate() {
+        if byte == b'\n' {
+            if i > 0 && content[i - 1] == b'\r' {
+                out.pop();
+            }
+            if ending == LineEnding::CrLf {
+                out.push(b'\r');
+            }
+        }
+        out.push(byte);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_lf_only() {
+        let stats = LineEndingStats::count(b"a\nb\nc");
+        assert_eq!(stats, LineEndingStats { lf: 2, crlf: 0 });
+        assert!(!stats.is_mixed());
+    }
+
+    #[test]
+    fn test_count_crlf_only() {
+        let stats = LineEndingStats::count(b"a\r\nb\r\n");
+        assert_eq!(stats, LineEndingStats { lf: 0, crlf: 2 });
+        assert!(!stats.is_mixed());
+    }
+
+    #[test]
+    fn test_count_mixed() {
+        let stats = LineEndingStats::count(b"a\r\nb\nc\r\n\n");
+        assert_eq!(stats, LineEndingStats { lf: 2, crlf: 2 });
+        assert!(stats.is_mixed());
+    }
+
+    #[test]
+    fn test_count_ignores_lone_cr() {
+        let stats = LineEndingStats::count(b"a\rb\n");
+        assert_eq!(stats, LineEndingStats { lf: 1, crlf: 0 });
+    }
+
+    #[test]
+    fn test_count_empty() {
+        assert_eq!(LineEndingStats::count(b""), LineEndingStats::default());
+    }
+
+    #[test]
+    fn test_normalize_to_lf() {
+        assert_eq!(
+            normalize_line_endings(b"a\r\nb\nc\r\n", LineEnding::Lf),
+            b"a\nb\nc\n"
+        );
+    }
+
+    #[test]
+    fn test_normalize_to_crlf() {
+        let out = normalize_line_endings(b"a\r\nb\nc", LineEnding::CrLf);
+        assert_eq!(out, b"a\r\nb\r\nc");
+        assert!(!LineEndingStats::count(&out).is_mixed());
+    }
+}
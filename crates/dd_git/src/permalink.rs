@@ -0,0 +1,199 @@
+/// Hosting services whose web URLs we know how to build.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemoteHost {
+    GitHub,
+    GitLab,
+    Bitbucket,
+}
+
+/// A remote URL reduced to its web location, e.g. `github.com` +
+/// `owner/repo`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteWeb {
+    pub host: RemoteHost,
+    pub domain: String,
+    /// Repository path without a leading slash or trailing `.git`.
+    pub repo_path: String,
+}
+
+/// Parse a git remote URL in any of the usual forms:
+/// `git@host:owner/repo.git`, `ssh://git@host/owner/repo.git` or
+/// `https://host/owner/repo`. Besides the public hosts, self-hosted GitHub
+/// Enterprise and GitLab servers are recognised by a `github.` or `gitlab.`
+/// first label, e.g. `gitlab.company.com`. Returns `None` for unknown hosts.
+pub fn parse_remote_url(url: &str) -> Option<RemoteWeb> {
+    let url = url.trim();
+    let (domain, path) = if let Some((_, rest)) = url.split_once("://") {
+        let rest = rest.rsplit_once('@').map_or(rest, |(_, r)| r);
+        rest.split_once('/')?
+    } else {
+        // scp-like syntax: [user@]host:path
+        let rest = url.rsplit_once('@').map_or(url, |(_, r)| r);
+        rest.split_once(':')?
+    };
+    // Drop any port, e.g. `host:22`.
+    let domain = domain.split(':').next()?.to_ascii_lowercase();
+    let path = path.trim_matches('/');
+    let repo_path = path.strip_suffix(".git").unwrap_or(path);
+    if repo_path.is_empty() {
+        return None;
+    }
+
+    let host = if is_domain(&domain, "github.com") || is_self_hosted(&domain, "github") {
+        RemoteHost::GitHub
+    } else if is_domain(&domain, "gitlab.com") || is_self_hosted(&domain, "gitlab") {
+        RemoteHost::GitLab
+    } else if is_domain(&domain, "bitbucket.org") {
+        RemoteHost::Bitbucket
+    } else {
+        return None;
+    };
+
+    Some(RemoteWeb {
+        host,
+        domain,
+        repo_path: repo_path.to_string(),
+    })
+}
+
+/// Whether `domain` is `base` itself or one of its subdomains.
+fn is_domain(domain: &str, base: &str) -> bool {
+    domain
+        .strip_suffix(base)
+        .is_some_and(|prefix| prefix.is_empty() || prefix.ends_with('.'))
+}
+
+/// Whether `domain` names a self-hosted server of `service` by its first
+/// label, like `github.company.com`. Look-alikes of the public host such
+/// as `github.com.example` are not.
+fn is_self_hosted(domain: &str, service: &str) -> bool {
+    let Some(rest) = domain
+        .strip_prefix(service)
+        .and_then(|rest| rest.strip_prefix('.'))
+    else {
+        return false;
+    };
+    rest.contains('.') && !rest.starts_with("com.")
+}
+
+/// Percent-encode a repository file path for use in a URL, keeping the `/`
+/// separators.
+fn encode_path(path: &str) -> String {
+    let mut encoded = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~/".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
+
+/// Web URL for `line` (1-based) of `path` as of commit `oid` on the remote at
+/// `remote_url`, or `None` when the host is not recognised.
+pub fn line_permalink(remote_url: &str, oid: &str, path: &str, line: u32) -> Option<String> {
+    let web = parse_remote_url(remote_url)?;
+    let base = format!("https://{}/{}", web.domain, web.repo_path);
+    let path = encode_path(path);
+    Some(match web.host {
+        RemoteHost::GitHub => format!("{base}/blob/{oid}/{path}#L{line}"),
+        RemoteHost::GitLab => format!("{base}/-/blob/{oid}/{path}#L{line}"),
+        RemoteHost::Bitbucket => format!("{base}/src/{oid}/{path}#lines-{line}"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_remote_url_forms() {
+        for url in [
+            "git@github.com:owner/repo.git",
+            "https://github.com/owner/repo",
+            "https://github.com/owner/repo.git",
+            "ssh://git@github.com:22/owner/repo.git",
+        ] {
+            let web = parse_remote_url(url).unwrap_or_else(|| panic!("failed on {url}"));
+            assert_eq!(web.host, RemoteHost::GitHub, "{url}");
+            assert_eq!(web.domain, "github.com", "{url}");
+            assert_eq!(web.repo_path, "owner/repo", "{url}");
+        }
+    }
+
+    #[test]
+    fn test_parse_remote_url_rejects_unknown_hosts() {
+        assert!(parse_remote_url("git@example.com:owner/repo.git").is_none());
+        assert!(parse_remote_url("/srv/git/repo.git").is_none());
+        assert!(parse_remote_url("git@github.com.evil.example:owner/repo.git").is_none());
+        assert!(parse_remote_url("git@notgithub.com:owner/repo.git").is_none());
+    }
+
+    #[test]
+    fn test_parse_remote_url_self_hosted() {
+        let web = parse_remote_url("git@gitlab.company.com:team/repo.git").unwrap();
+        assert_eq!(web.host, RemoteHost::GitLab);
+        assert_eq!(web.domain, "gitlab.company.com");
+        let web = parse_remote_url("https://github.corp.example.org/owner/repo").unwrap();
+        assert_eq!(web.host, RemoteHost::GitHub);
+        assert_eq!(web.domain, "github.corp.example.org");
+
+        assert!(parse_remote_url("git@github.internal:owner/repo.git").is_none());
+        assert!(parse_remote_url("git@mygitlab.company.com:team/repo.git").is_none());
+    }
+
+    #[test]
+    fn test_parse_remote_url_strips_one_git_suffix() {
+        let web = parse_remote_url("git@github.com:owner/repo.git.git").unwrap();
+        assert_eq!(web.repo_path, "owner/repo.git");
+    }
+
+    #[test]
+    fn test_permalink_encodes_path() {
+        assert_eq!(
+            line_permalink(
+                "git@github.com:owner/repo.git",
+                "abc123",
+                "docs/my file.md",
+                1
+            )
+            .unwrap(),
+            "https://github.com/owner/repo/blob/abc123/docs/my%20file.md#L1"
+        );
+        assert_eq!(
+            line_permalink("git@github.com:owner/repo.git", "abc123", "src/#1.rs", 2).unwrap(),
+            "https://github.com/owner/repo/blob/abc123/src/%231.rs#L2"
+        );
+    }
+
+    #[test]
+    fn test_github_permalink() {
+        assert_eq!(
+            line_permalink("git@github.com:owner/repo.git", "abc123", "src/lib.rs", 42).unwrap(),
+            "https://github.com/owner/repo/blob/abc123/src/lib.rs#L42"
+        );
+    }
+
+    #[test]
+    fn test_gitlab_permalink_keeps_subgroups() {
+        assert_eq!(
+            line_permalink(
+                "https://gitlab.com/group/sub/repo.git",
+                "abc123",
+                "README.md",
+                3
+            )
+            .unwrap(),
+            "https://gitlab.com/group/sub/repo/-/blob/abc123/README.md#L3"
+        );
+    }
+
+    #[test]
+    fn test_bitbucket_permalink() {
+        assert_eq!(
+            line_permalink("git@bitbucket.org:team/repo.git", "abc123", "a/b.rs", 7).unwrap(),
+            "https://bitbucket.org/team/repo/src/abc123/a/b.rs#lines-7"
+        );
+    }
+}
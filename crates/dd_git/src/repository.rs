@@ -1,22 +1,137 @@
-use std::path::Path;
-use std::process::Command;
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
 
 use anyhow::{Context, Result};
 use gix::bstr::ByteSlice;
 
-use crate::commit::{CommitInfo, SignatureStatus};
-use crate::diff::FileDiff;
-use crate::types::{BranchInfo, RemoteInfo, StashInfo, TagInfo};
+use crate::commit::{split_message, CommitInfo, SignatureStatus};
+use crate::diff::{
+    hunk_patch, overlay_file_changes, parse_numstat, CombinedFileDiff, FileDiff, FileStat,
+    FileStatus, ImageVersions, WhitespaceMode,
+};
+use crate::git_command::GitDirs;
+use crate::line_endings::{normalize_line_endings, LineEnding, LineEndingStats};
+use crate::types::{
+    BranchInfo, OperationInProgress, RemoteInfo, RepoSummary, StashInfo, SubmoduleInfo, TagInfo,
+    TreeEntry,
+};
+
+/// Files bigger than this are not loaded whole for the full-file view.
+pub const MAX_FULL_FILE_SIZE: u64 = 2 * 1024 * 1024;
 
 pub struct Repository {
     inner: gix::Repository,
+    /// Work tree given to [`Repository::open_with`], overriding the one gix
+    /// infers from the git directory.
+    work_tree: Option<PathBuf>,
+    /// Opened from an explicit git directory, so `git` subprocesses must be
+    /// told where it is rather than discovering it from the work tree.
+    explicit_git_dir: bool,
+}
+
+/// Find git repositories beneath `dir`, descending at most `depth` levels.
+///
+/// `dir` itself is never included. A directory that opens as a repository is
+/// returned and not descended into further. Results are sorted by path.
+pub fn find_git_repos(dir: &Path, depth: usize) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    collect_git_repos(dir, depth, &mut found);
+    found.sort();
+    found
+}
+
+fn collect_git_repos(dir: &Path, depth: usize, found: &mut Vec<PathBuf>) {
+    if depth == 0 {
+        return;
+    }
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.is_dir() || entry.file_name() == ".git" {
+            continue;
+        }
+        if Repository::open(&path).is_ok() {
+            found.push(path);
+        } else {
+            collect_git_repos(&path, depth - 1, found);
+        }
+    }
+}
+
+/// `(ahead, behind)` from for-each-ref's `%(upstream:track,nobracket)`,
+/// e.g. `ahead 2, behind 1`. Empty means in sync; `gone` means the
+/// upstream ref was deleted and yields `None`.
+fn parse_upstream_track(track: &str) -> Option<(usize, usize)> {
+    if track == "gone" {
+        return None;
+    }
+    let (mut ahead, mut behind) = (0, 0);
+    for part in track.split(", ").filter(|part| !part.is_empty()) {
+        let (direction, count) = part.split_once(' ')?;
+        let count = count.parse().ok()?;
+        match direction {
+            "ahead" => ahead = count,
+            "behind" => behind = count,
+            _ => return None,
+        }
+    }
+    Some((ahead, behind))
 }
 
 impl Repository {
     pub fn open(path: &Path) -> Result<Self> {
         let inner = gix::open(path)
             .with_context(|| format!("failed to open git repository at {}", path.display()))?;
-        Ok(Self { inner })
+        Ok(Self {
+            inner,
+            work_tree: None,
+            explicit_git_dir: false,
+        })
+    }
+
+    /// Open a repository by its git directory, as `GIT_DIR` does, for
+    /// layouts where it is not `<work tree>/.git`. `work_tree` plays the
+    /// part of `GIT_WORK_TREE`; without it the git directory's own
+    /// `core.worktree`, if any, is used.
+    pub fn open_with(git_dir: &Path, work_tree: Option<&Path>) -> Result<Self> {
+        let git_dir = git_dir
+            .canonicalize()
+            .with_context(|| format!("git directory {} not found", git_dir.display()))?;
+        let inner = gix::open_opts(
+            &git_dir,
+            gix::open::Options::default().open_path_as_is(true),
+        )
+        .with_context(|| format!("failed to open git directory at {}", git_dir.display()))?;
+        let work_tree = work_tree
+            .map(|path| {
+                path.canonicalize()
+                    .with_context(|| format!("work tree {} not found", path.display()))
+            })
+            .transpose()?;
+        Ok(Self {
+            inner,
+            work_tree,
+            explicit_git_dir: true,
+        })
+    }
+
+    /// The repository's working directory, if it is not bare.
+    pub fn workdir(&self) -> Option<&Path> {
+        self.work_tree.as_deref().or_else(|| self.inner.work_dir())
+    }
+
+    /// Where to run `git` subprocesses for this repository.
+    fn git(&self) -> Result<GitDirs<'_>> {
+        Ok(GitDirs {
+            work_tree: self
+                .workdir()
+                .context("repository has no working directory")?,
+            git_dir: self.explicit_git_dir.then(|| self.inner.git_dir()),
+        })
     }
 
     pub fn head_branch(&self) -> Result<String> {
@@ -28,31 +143,218 @@ impl Repository {
         }
     }
 
+    /// False for a freshly initialised repository whose HEAD branch does not
+    /// exist yet.
+    pub fn has_commits(&self) -> Result<bool> {
+        Ok(!self.inner.head()?.is_unborn())
+    }
+
+    /// Detect the repository's default branch.
+    ///
+    /// Prefers the branch `origin/HEAD` points at, then falls back to a local
+    /// `main` or `master` branch.
+    pub fn default_branch(&self) -> Result<String> {
+        if let Some(origin_head) = self.inner.try_find_reference("refs/remotes/origin/HEAD")? {
+            if let Some(target) = origin_head.target().try_name() {
+                let short = target.shorten().to_string();
+                let name = short.strip_prefix("origin/").unwrap_or(&short);
+                return Ok(name.to_string());
+            }
+        }
+        for candidate in ["main", "master"] {
+            if self
+                .inner
+                .try_find_reference(format!("refs/heads/{candidate}").as_str())?
+                .is_some()
+            {
+                return Ok(candidate.to_string());
+            }
+        }
+        anyhow::bail!("could not determine default branch")
+    }
+
     pub fn branches(&self) -> Result<Vec<BranchInfo>> {
         let head_name = self.head_branch().unwrap_or_default();
+        let mut tracking = self.branch_tracking().unwrap_or_default();
         let refs = self.inner.references()?;
         let mut branches = Vec::new();
         for reference in refs.local_branches()?.flatten() {
             let name = reference.name().shorten().to_string();
             let is_head = name == head_name;
-            branches.push(BranchInfo { name, is_head });
+            let (upstream, counts) = tracking.remove(&name).unzip();
+            let (ahead, behind) = counts.flatten().unzip();
+            branches.push(BranchInfo {
+                name,
+                is_head,
+                upstream,
+                ahead,
+                behind,
+            });
+        }
+        // An unborn HEAD names a branch that has no ref yet; list it anyway
+        // so the sidebar shows where the first commit will go.
+        if !self.has_commits()? && !branches.iter().any(|b| b.is_head) {
+            branches.push(BranchInfo {
+                name: head_name,
+                is_head: true,
+                upstream: None,
+                ahead: None,
+                behind: None,
+            });
         }
         branches.sort_by(|a, b| b.is_head.cmp(&a.is_head).then_with(|| a.name.cmp(&b.name)));
         Ok(branches)
     }
 
+    /// Remote-tracking branches of `remote`, named like `origin/main`. The
+    /// remote's `HEAD` pointer is left out.
+    pub fn remote_branches(&self, remote: &str) -> Result<Vec<BranchInfo>> {
+        let prefix = format!("refs/remotes/{remote}/");
+        let head = format!("{prefix}HEAD");
+        let refs = self.inner.references()?;
+        let mut branches = Vec::new();
+        for reference in refs.remote_branches()?.flatten() {
+            let full_name = reference.name().as_bstr();
+            if !full_name.starts_with(prefix.as_bytes()) || full_name == head.as_bytes() {
+                continue;
+            }
+            branches.push(BranchInfo {
+                name: reference.name().shorten().to_string(),
+                is_head: false,
+                upstream: None,
+                ahead: None,
+                behind: None,
+            });
+        }
+        branches.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(branches)
+    }
+
+    /// Map of local branch name to its upstream's short name and the
+    /// `(ahead, behind)` counts against it, for branches that track one.
+    /// The counts are `None` when the upstream ref no longer exists.
+    #[allow(clippy::type_complexity)]
+    fn branch_tracking(&self) -> Result<HashMap<String, (String, Option<(usize, usize)>)>> {
+        let stdout = self.run_git(&[
+            "for-each-ref",
+            "--format=%(refname:short)%09%(upstream:short)%09%(upstream:track,nobracket)",
+            "refs/heads",
+        ])?;
+        Ok(stdout
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.splitn(3, '\t');
+                let branch = fields.next()?;
+                let upstream = fields.next().filter(|u| !u.is_empty())?;
+                let counts = parse_upstream_track(fields.next().unwrap_or_default());
+                Some((branch.to_string(), (upstream.to_string(), counts)))
+            })
+            .collect())
+    }
+
+    /// Make `branch` track `remote/remote_branch`.
+    pub fn set_upstream(&self, branch: &str, remote: &str, remote_branch: &str) -> Result<()> {
+        anyhow::ensure!(
+            !branch.is_empty() && !branch.starts_with('-'),
+            "invalid branch name: {branch:?}"
+        );
+        anyhow::ensure!(
+            !remote.is_empty() && !remote_branch.is_empty(),
+            "upstream needs a remote and a branch, got {remote:?} and {remote_branch:?}"
+        );
+        let target = format!("--set-upstream-to={remote}/{remote_branch}");
+        self.run_git(&["branch", &target, branch])?;
+        Ok(())
+    }
+
+    /// Commits `branch` is ahead of and behind its upstream, in that order.
+    pub fn ahead_behind(&self, branch: &str) -> Result<(usize, usize)> {
+        anyhow::ensure!(
+            !branch.is_empty() && !branch.starts_with('-'),
+            "invalid branch name: {branch:?}"
+        );
+        let range = format!("{branch}...{branch}@{{upstream}}");
+        let stdout = self.run_git(&["rev-list", "--left-right", "--count", &range])?;
+        let mut counts = stdout.split_whitespace().map(str::parse::<usize>);
+        match (counts.next(), counts.next()) {
+            (Some(Ok(ahead)), Some(Ok(behind))) => Ok((ahead, behind)),
+            _ => anyhow::bail!("unexpected rev-list output: {}", stdout.trim()),
+        }
+    }
+
+    /// Remotes as configured when this handle was opened; reopen the
+    /// repository to see changes made since.
     pub fn remotes(&self) -> Result<Vec<RemoteInfo>> {
         let names = self.inner.remote_names();
         let mut remotes: Vec<RemoteInfo> = names
             .iter()
             .map(|name| RemoteInfo {
                 name: name.to_string(),
+                url: self
+                    .inner
+                    .find_remote(name.as_ref())
+                    .ok()
+                    .and_then(|remote| {
+                        remote
+                            .url(gix::remote::Direction::Fetch)
+                            .map(|url| url.to_bstring().to_string())
+                    }),
             })
             .collect();
         remotes.sort_by(|a, b| a.name.cmp(&b.name));
         Ok(remotes)
     }
 
+    /// The effective value of a config key such as `user.name`, resolved
+    /// across every config level and include the way git does. `None` when
+    /// the key is unset.
+    pub fn config_value(&self, key: &str) -> Result<Option<String>> {
+        // Only this lookup needs the config shipped with the `git`
+        // installation, which gix finds by running `git`, so load it on a
+        // handle of its own rather than on every open.
+        let mut permissions = gix::open::Permissions::all();
+        permissions.config.git_binary = true;
+        let options = gix::open::Options::default()
+            .permissions(permissions)
+            .open_path_as_is(true);
+        let repo = gix::open_opts(self.inner.git_dir(), options).with_context(|| {
+            format!(
+                "failed to open git directory at {}",
+                self.inner.git_dir().display()
+            )
+        })?;
+        Ok(repo
+            .config_snapshot()
+            .string(key)
+            .map(|value| value.to_str_lossy().into_owned()))
+    }
+
+    /// The fetch URL configured for remote `name`.
+    pub fn remote_url(&self, name: &str) -> Result<String> {
+        Ok(self
+            .run_git(&["remote", "get-url", "--", name])?
+            .trim()
+            .to_string())
+    }
+
+    /// Add remote `name` fetching from `url`, like `git remote add`.
+    pub fn add_remote(&self, name: &str, url: &str) -> Result<()> {
+        self.run_git(&["remote", "add", "--", name, url])?;
+        Ok(())
+    }
+
+    /// Point remote `remote` at `url`, like `git remote set-url`.
+    pub fn set_remote_url(&self, remote: &str, url: &str) -> Result<()> {
+        self.run_git(&["remote", "set-url", "--", remote, url])?;
+        Ok(())
+    }
+
+    /// Delete remote `name` along with its remote-tracking branches.
+    pub fn remove_remote(&self, name: &str) -> Result<()> {
+        self.run_git(&["remote", "remove", "--", name])?;
+        Ok(())
+    }
+
     pub fn tags(&self) -> Result<Vec<TagInfo>> {
         let refs = self.inner.references()?;
         let mut tags = Vec::new();
@@ -64,6 +366,41 @@ impl Repository {
         Ok(tags)
     }
 
+    /// The message of annotated tag `name`, or `None` for a lightweight
+    /// tag that points straight at a commit.
+    pub fn tag_message(&self, name: &str) -> Result<Option<String>> {
+        let reference = self
+            .inner
+            .find_reference(format!("refs/tags/{name}").as_str())
+            .with_context(|| format!("no tag named {name}"))?;
+        let id = reference
+            .try_id()
+            .with_context(|| format!("tag {name} is a symbolic reference"))?;
+        let Ok(tag) = id.object()?.try_into_tag() else {
+            return Ok(None);
+        };
+        let message = tag.decode()?.message.to_str_lossy().trim_end().to_string();
+        Ok(Some(message))
+    }
+
+    /// Submodules declared in `.gitmodules`, with the commit each one is
+    /// pinned to in the index. Empty when there is no `.gitmodules`.
+    pub fn submodules(&self) -> Result<Vec<SubmoduleInfo>> {
+        let Some(modules) = self.inner.submodules()? else {
+            return Ok(Vec::new());
+        };
+        let mut submodules = Vec::new();
+        for module in modules {
+            submodules.push(SubmoduleInfo {
+                name: module.name().to_string(),
+                path: module.path()?.to_string(),
+                head_oid: module.index_id()?.map(|oid| oid.to_string()),
+            });
+        }
+        submodules.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(submodules)
+    }
+
     pub fn stashes(&self) -> Result<Vec<StashInfo>> {
         let stash_ref = self.inner.try_find_reference("refs/stash")?;
         let Some(stash_ref) = stash_ref else {
@@ -84,10 +421,30 @@ impl Repository {
     }
 
     pub fn commits(&self, limit: usize) -> Result<Vec<CommitInfo>> {
+        if !self.has_commits()? {
+            return Ok(Vec::new());
+        }
         let head_id = self.inner.head_id()?;
+        self.walk_commits(head_id.detach(), limit)
+    }
+
+    /// History of `ref_name` (a branch, tag or any revision), newest first.
+    /// Only the first `limit` commits are read from the object database.
+    pub fn commits_for_ref(&self, ref_name: &str, limit: usize) -> Result<Vec<CommitInfo>> {
+        let id = self
+            .inner
+            .rev_parse_single(ref_name)
+            .with_context(|| format!("failed to resolve {ref_name}"))?
+            .object()?
+            .peel_to_commit()?
+            .id;
+        self.walk_commits(id, limit)
+    }
+
+    fn walk_commits(&self, start: gix::ObjectId, limit: usize) -> Result<Vec<CommitInfo>> {
         let walk = self
             .inner
-            .rev_walk([head_id])
+            .rev_walk([start])
             .sorting(gix::revision::walk::Sorting::ByCommitTime(
                 Default::default(),
             ))
@@ -102,7 +459,7 @@ impl Repository {
             let commit = info.object()?;
             let author = commit.author()?;
             let committer = commit.committer()?;
-            let message = commit.message()?;
+            let (subject, body) = split_message(&commit.message_raw()?.to_str_lossy());
             let tree_oid = commit.tree_id()?.to_hex().to_string();
             let parent_oids: Vec<String> = info
                 .parent_ids
@@ -123,21 +480,31 @@ impl Repository {
                 committer_name: committer.name.to_string(),
                 committer_email: committer.email.to_string(),
                 committer_date: committer.time.seconds,
-                subject: message.title.to_str_lossy().trim().to_string(),
-                body: message
-                    .body
-                    .map(|b| b.to_str_lossy().trim().to_string())
-                    .unwrap_or_default(),
+                subject,
+                body,
                 parent_oids,
             });
         }
         Ok(commits)
     }
 
+    /// Whether the working tree has tracked changes or untracked files.
+    /// Ignore rules are read fresh from the working tree on every call, so
+    /// files matched by a newly added `.gitignore` do not count.
     pub fn is_dirty(&self) -> Result<bool> {
-        // Check tracked changes (staged + unstaged modifications) first via
-        // the fast built-in check which skips the directory walk.
-        if self.inner.is_dirty()? {
+        if self.work_tree.is_some() {
+            // gix only checks the work tree it found itself; ask git about
+            // the one we were given.
+            return Ok(!self.run_git(&["status", "--porcelain"])?.is_empty());
+        }
+        if !self.has_commits()? {
+            // Nothing to compare against yet: anything staged is a change.
+            if !self.inner.index_or_empty()?.entries().is_empty() {
+                return Ok(true);
+            }
+        } else if self.inner.is_dirty()? {
+            // Tracked changes (staged + unstaged modifications) via the fast
+            // built-in check which skips the directory walk.
             return Ok(true);
         }
         // Also check for untracked files via the index-worktree iterator
@@ -158,20 +525,143 @@ impl Repository {
         Ok(false)
     }
 
+    /// True for a shallow clone, whose git directory lists its history
+    /// cut-off points in a `shallow` file. Such history is incomplete, so
+    /// file history and ahead/behind counts can be wrong.
+    pub fn is_shallow(&self) -> Result<bool> {
+        Ok(self.inner.shallow_file().is_file())
+    }
+
+    /// Download the rest of a shallow clone's history, like
+    /// `git fetch --unshallow`.
+    pub fn fetch_unshallow(&self) -> Result<()> {
+        self.run_git(&["fetch", "--unshallow"])?;
+        Ok(())
+    }
+
+    /// The merge, rebase, cherry-pick, revert, `am` or bisect currently in
+    /// progress, read from the state files git leaves in the git directory.
+    /// As in `git status`, a rebase wins over the pick it stopped on.
+    pub fn current_operation(&self) -> Result<Option<OperationInProgress>> {
+        let git_dir = self.inner.git_dir();
+        let exists = |name: &str| {
+            git_dir
+                .join(name)
+                .try_exists()
+                .with_context(|| format!("failed to inspect {name}"))
+        };
+        let operation = if exists("rebase-merge")? {
+            Some(OperationInProgress::Rebase)
+        } else if exists("rebase-apply")? {
+            // `git am` and the apply backend of `git rebase` share the
+            // directory; only `am` leaves the `applying` marker.
+            Some(if exists("rebase-apply/applying")? {
+                OperationInProgress::ApplyMailbox
+            } else {
+                OperationInProgress::Rebase
+            })
+        } else if exists("MERGE_HEAD")? {
+            Some(OperationInProgress::Merge)
+        } else if exists("CHERRY_PICK_HEAD")? {
+            Some(OperationInProgress::CherryPick)
+        } else if exists("REVERT_HEAD")? {
+            Some(OperationInProgress::Revert)
+        } else if exists("BISECT_LOG")? {
+            Some(OperationInProgress::Bisect)
+        } else {
+            None
+        };
+        Ok(operation)
+    }
+
+    /// Give up on `operation` and return to where it started, like
+    /// `git merge --abort`. A bisect is ended with `git bisect reset`.
+    pub fn abort_operation(&self, operation: OperationInProgress) -> Result<()> {
+        let args: &[&str] = match operation {
+            OperationInProgress::Bisect => &["bisect", "reset"],
+            // Labels double as the git subcommand names.
+            _ => &[operation.label(), "--abort"],
+        };
+        self.run_git(args)?;
+        Ok(())
+    }
+
+    /// Resume `operation` once its conflicts are resolved and staged, like
+    /// `git rebase --continue`. Commits keep the message git prepared
+    /// rather than waiting on an editor.
+    pub fn continue_operation(&self, operation: OperationInProgress) -> Result<()> {
+        anyhow::ensure!(
+            operation.can_continue(),
+            "a {} has no continue step",
+            operation.label()
+        );
+        let subcommand = operation.label();
+        let output = self
+            .git()?
+            .command()
+            .env("GIT_EDITOR", "true")
+            .args([subcommand, "--continue"])
+            .output()
+            .with_context(|| format!("failed to run git {subcommand}"))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            // Unresolved conflicts are reported on stdout by some commands.
+            let message = if stderr.trim().is_empty() {
+                stdout
+            } else {
+                stderr
+            };
+            anyhow::bail!("git {subcommand} --continue failed: {}", message.trim());
+        }
+        Ok(())
+    }
+
+    /// Stash only `paths`, leaving other changes in the working tree, like
+    /// `git stash push -m <message> -- <paths>`.
+    pub fn stash_push_paths(&self, paths: &[String], message: Option<&str>) -> Result<()> {
+        anyhow::ensure!(!paths.is_empty(), "no paths to stash");
+        let mut args = vec!["stash", "push"];
+        if let Some(message) = message {
+            args.extend(["-m", message]);
+        }
+        args.push("--");
+        args.extend(paths.iter().map(String::as_str));
+        self.run_git(&args)?;
+        Ok(())
+    }
+
+    /// Aggregate branch, worktree, stash and operation state into one
+    /// summary for display when the repository is opened.
+    pub fn summary(&self) -> Result<RepoSummary> {
+        let commit_count = if self.has_commits()? {
+            self.run_git(&["rev-list", "--count", "HEAD"])?
+                .trim()
+                .parse()
+                .context("unexpected rev-list --count output")?
+        } else {
+            0
+        };
+        Ok(RepoSummary {
+            commit_count,
+            branch: self.head_branch()?,
+            dirty: self.is_dirty()?,
+            stash_count: self.stashes()?.len(),
+            operation: self.current_operation()?,
+            shallow: self.is_shallow()?,
+        })
+    }
+
     pub fn commit_signature_status(&self, oid: &str) -> Result<SignatureStatus> {
         anyhow::ensure!(
             oid.bytes().all(|b| b.is_ascii_hexdigit()),
             "invalid commit OID: {oid}"
         );
 
-        let workdir = self
-            .inner
-            .work_dir()
-            .context("repository has no working directory")?;
-
-        let output = std::process::Command::new("git")
+        let output = self
+            .git()?
+            .command()
             .args(["log", "-1", "--format=%G?", oid])
-            .current_dir(workdir)
             .output()
             .context("failed to run git log for signature status")?;
 
@@ -189,25 +679,483 @@ impl Repository {
     }
 
     pub fn diff_commit(&self, oid: &str) -> Result<Vec<FileDiff>> {
-        let workdir = self
+        self.diff_commit_with_whitespace(oid, WhitespaceMode::Show)
+    }
+
+    /// [`Self::diff_commit`] with whitespace-only changes treated as
+    /// `whitespace` says. Files with nothing but ignored changes are left
+    /// out.
+    pub fn diff_commit_with_whitespace(
+        &self,
+        oid: &str,
+        whitespace: WhitespaceMode,
+    ) -> Result<Vec<FileDiff>> {
+        crate::diff::diff_commit(&self.git()?, oid, whitespace)
+    }
+
+    /// Combined diff of a merge against all of its parents; the way to view
+    /// octopus merges, where a first-parent diff hides most of the story.
+    pub fn diff_combined(&self, oid: &str) -> Result<Vec<CombinedFileDiff>> {
+        self.diff_combined_with_whitespace(oid, WhitespaceMode::Show)
+    }
+
+    /// [`Self::diff_combined`] with whitespace-only changes treated as
+    /// `whitespace` says.
+    pub fn diff_combined_with_whitespace(
+        &self,
+        oid: &str,
+        whitespace: WhitespaceMode,
+    ) -> Result<Vec<CombinedFileDiff>> {
+        crate::diff::diff_combined(&self.git()?, oid, whitespace)
+    }
+
+    /// Changes needed to turn commit `from` into commit `to`.
+    pub fn diff_range(&self, from: &str, to: &str) -> Result<Vec<FileDiff>> {
+        crate::diff::diff_range(&self.git()?, from, to)
+    }
+
+    /// Local branches whose history includes `oid`, sorted by name.
+    pub fn branches_containing(&self, oid: &str) -> Result<Vec<String>> {
+        anyhow::ensure!(
+            oid.bytes().all(|b| b.is_ascii_hexdigit()),
+            "invalid commit OID: {oid}"
+        );
+        let stdout = self.run_git(&["branch", "--contains", oid, "--format=%(refname:short)"])?;
+        let mut branches: Vec<String> = stdout
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect();
+        branches.sort();
+        Ok(branches)
+    }
+
+    /// Whether the index differs from `HEAD`, i.e. an amend would fold
+    /// changes into the commit rather than only reword it.
+    pub fn has_staged_changes(&self) -> Result<bool> {
+        Ok(!self.diff_staged()?.is_empty())
+    }
+
+    /// Resolve a revision (branch, tag, `HEAD~2`, ...) to a full OID.
+    pub fn rev_parse(&self, rev: &str) -> Result<String> {
+        let id = self
             .inner
-            .work_dir()
-            .context("repository has no working directory")?;
-        crate::diff::diff_commit(workdir, oid)
+            .rev_parse_single(rev)
+            .with_context(|| format!("failed to resolve {rev}"))?;
+        Ok(id.to_string())
     }
 
-    pub fn checkout_branch(&self, branch_name: &str) -> Result<()> {
+    /// Changes staged in the index relative to `HEAD`.
+    pub fn diff_staged(&self) -> Result<Vec<FileDiff>> {
+        crate::diff::diff_staged(&self.git()?)
+    }
+
+    /// Unstaged working-tree changes against the index. Untracked files
+    /// show up as added; an empty vec means nothing is left to stage.
+    pub fn diff_workdir(&self) -> Result<Vec<FileDiff>> {
+        crate::diff::diff_workdir(&self.git()?)
+    }
+
+    /// Paths excluded by ignore rules, as `git status --ignored` reports
+    /// them: a fully ignored directory is listed once with a trailing `/`.
+    pub fn ignored_files(&self) -> Result<Vec<String>> {
+        let stdout = self.run_git(&["status", "--porcelain", "-z", "--ignored"])?;
+        Ok(stdout
+            .split('\0')
+            .filter_map(|entry| entry.strip_prefix("!! "))
+            .map(str::to_string)
+            .collect())
+    }
+
+    /// Per-file added/deleted line counts for `oid`. Much cheaper than
+    /// [`Self::diff_commit`] when only the totals are needed.
+    pub fn commit_stat(&self, oid: &str) -> Result<Vec<FileStat>> {
+        crate::diff::commit_stat(&self.git()?, oid)
+    }
+
+    /// Number of files touched by `oid`, counted like [`Self::commit_stat`].
+    pub fn commit_file_count(&self, oid: &str) -> Result<usize> {
+        Ok(self.commit_stat(oid)?.len())
+    }
+
+    /// Total added/deleted lines for each of the newest `limit` commits
+    /// reachable from `rev`, from a single `git log --numstat`. Binary files
+    /// count as zero and merge commits, which report no files, are omitted.
+    pub fn commit_line_counts(
+        &self,
+        rev: &str,
+        limit: usize,
+    ) -> Result<HashMap<String, (usize, usize)>> {
+        let max_count = format!("--max-count={limit}");
+        let stdout = self.run_git(&[
+            "log",
+            &max_count,
+            "--numstat",
+            "-M",
+            "--format=%x1e%H",
+            rev,
+            "--",
+        ])?;
+        let mut counts = HashMap::new();
+        for record in stdout.split('\x1e').filter(|r| !r.trim().is_empty()) {
+            let (oid, numstat) = record.split_once('\n').unwrap_or((record, ""));
+            let stats = parse_numstat(numstat);
+            if stats.is_empty() {
+                continue;
+            }
+            let totals = stats.iter().fold((0, 0), |(added, deleted), (a, d, _)| {
+                (added + a.unwrap_or(0), deleted + d.unwrap_or(0))
+            });
+            counts.insert(oid.trim().to_string(), totals);
+        }
+        Ok(counts)
+    }
+
+    /// Write the commits `oldest..=newest` as a numbered patch series (`git
+    /// format-patch`) into `out_dir` and return the files created, in order.
+    /// The series is based on the parent of `oldest`, or starts at the root
+    /// when `oldest` has no parent.
+    pub fn export_patch_series(
+        &self,
+        oldest: &str,
+        newest: &str,
+        out_dir: &Path,
+    ) -> Result<Vec<PathBuf>> {
+        for oid in [oldest, newest] {
+            anyhow::ensure!(
+                oid.bytes().all(|b| b.is_ascii_hexdigit()),
+                "invalid commit OID: {oid}"
+            );
+        }
+        let out_dir = out_dir
+            .to_str()
+            .context("patch output directory is not valid UTF-8")?;
+
+        // `rev-list --parents` prints the commit followed by its parents.
+        let parents = self.run_git(&["rev-list", "--parents", "-n", "1", oldest])?;
+        let base = parents.split_whitespace().nth(1).map(str::to_string);
+        let range = match &base {
+            Some(base) => format!("{base}..{newest}"),
+            None => newest.to_string(),
+        };
+        let mut args = vec!["format-patch", "-o", out_dir];
+        if base.is_none() {
+            args.push("--root");
+        }
+        args.push(&range);
+
+        let stdout = self.run_git(&args)?;
+        Ok(stdout
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(PathBuf::from)
+            .collect())
+    }
+
+    /// Count LF and CRLF lines in the working-tree copy of `path`.
+    pub fn detect_mixed_line_endings(&self, path: &str) -> Result<LineEndingStats> {
+        let full = self.workdir_path(path)?;
+        let content =
+            std::fs::read(&full).with_context(|| format!("failed to read {}", full.display()))?;
+        Ok(LineEndingStats::count(&content))
+    }
+
+    /// Rewrite the working-tree copy of `path` so every line ends in `ending`.
+    pub fn normalize_line_endings(&self, path: &str, ending: LineEnding) -> Result<()> {
+        let full = self.workdir_path(path)?;
+        let content =
+            std::fs::read(&full).with_context(|| format!("failed to read {}", full.display()))?;
+        std::fs::write(&full, normalize_line_endings(&content, ending))
+            .with_context(|| format!("failed to write {}", full.display()))
+    }
+
+    fn workdir_path(&self, path: &str) -> Result<PathBuf> {
         let workdir = self
-            .inner
-            .work_dir()
+            .workdir()
             .context("repository has no working directory")?;
-        let output = Command::new("git")
+        Ok(workdir.join(path))
+    }
+
+    /// Read the contents of `path` as of commit `oid`. Non-UTF-8 bytes are
+    /// replaced lossily.
+    pub fn file_content_at(&self, oid: &str, path: &str) -> Result<String> {
+        let bytes = self.file_bytes_at(oid, path)?;
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    }
+
+    /// The raw contents of `path` as of commit `oid`.
+    pub fn file_bytes_at(&self, oid: &str, path: &str) -> Result<Vec<u8>> {
+        let blob = self.blob_id_at(oid, path)?.object()?;
+        Ok(blob.detach().data)
+    }
+
+    /// Size in bytes of `path` as of commit `oid`, without reading it.
+    pub fn file_size_at(&self, oid: &str, path: &str) -> Result<u64> {
+        let id = self.blob_id_at(oid, path)?;
+        Ok(self.inner.find_header(id)?.size())
+    }
+
+    /// The entries of directory `dir` (relative to the root, `""` for the
+    /// root itself) in commit `oid`'s tree, directories first, then by name.
+    pub fn tree_entries(&self, oid: &str, dir: &str) -> Result<Vec<TreeEntry>> {
+        anyhow::ensure!(
+            oid.bytes().all(|b| b.is_ascii_hexdigit()),
+            "invalid commit OID: {oid}"
+        );
+        let dir = dir.trim_matches('/');
+        let spec = if dir.is_empty() {
+            oid.to_string()
+        } else {
+            format!("{oid}:{dir}")
+        };
+        let tree = self
+            .inner
+            .rev_parse_single(spec.as_str())
+            .with_context(|| format!("failed to resolve {spec}"))?
+            .object()?
+            .peel_to_tree()
+            .with_context(|| format!("{spec} is not a directory"))?;
+        let mut entries: Vec<TreeEntry> = tree
+            .decode()?
+            .entries
+            .iter()
+            .map(|entry| TreeEntry {
+                name: entry.filename.to_str_lossy().into_owned(),
+                is_dir: entry.mode.is_tree(),
+                oid: entry.oid.to_string(),
+            })
+            .collect();
+        entries.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.cmp(&b.name)));
+        Ok(entries)
+    }
+
+    fn blob_id_at(&self, oid: &str, path: &str) -> Result<gix::Id<'_>> {
+        anyhow::ensure!(
+            oid.bytes().all(|b| b.is_ascii_hexdigit()),
+            "invalid commit OID: {oid}"
+        );
+        let spec = format!("{oid}:{path}");
+        self.inner
+            .rev_parse_single(spec.as_str())
+            .with_context(|| format!("failed to resolve {spec}"))
+    }
+
+    /// `file` before and after commit `oid` (against its first parent). A
+    /// side is `None` when the commit added or deleted the file.
+    #[allow(clippy::type_complexity)]
+    fn file_versions(
+        &self,
+        oid: &str,
+        file: &FileDiff,
+    ) -> Result<(Option<Vec<u8>>, Option<Vec<u8>>)> {
+        anyhow::ensure!(
+            oid.bytes().all(|b| b.is_ascii_hexdigit()),
+            "invalid commit OID: {oid}"
+        );
+        let old = if file.status == FileStatus::Added {
+            None
+        } else {
+            let parent = self.rev_parse(&format!("{oid}^"))?;
+            let old_path = file.old_path.as_deref().unwrap_or(&file.path);
+            Some(self.file_bytes_at(&parent, old_path)?)
+        };
+        let new = if file.status == FileStatus::Deleted {
+            None
+        } else {
+            Some(self.file_bytes_at(oid, &file.path)?)
+        };
+        Ok((old, new))
+    }
+
+    /// Both complete versions of `file` as changed by commit `oid` (against
+    /// its first parent), diffed into one hunk for whole-file comparison.
+    /// Files with a version over [`MAX_FULL_FILE_SIZE`] are refused.
+    pub fn full_file_diff(&self, oid: &str, file: &FileDiff) -> Result<FileDiff> {
+        let (old, new) = self.file_versions(oid, file)?;
+        for side in [&old, &new].into_iter().flatten() {
+            anyhow::ensure!(
+                side.len() as u64 <= MAX_FULL_FILE_SIZE,
+                "{} is too large to compare in full ({} bytes)",
+                file.path,
+                side.len()
+            );
+        }
+        let text = |side: Option<Vec<u8>>| {
+            side.map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+                .unwrap_or_default()
+        };
+        Ok(crate::diff::full_file_diff(file, &text(old), &text(new)))
+    }
+
+    /// The whole of `file` as of commit `oid` with the lines the commit
+    /// added marked, for reading a change in full context. Only the new
+    /// version is read; files over [`MAX_FULL_FILE_SIZE`] are refused.
+    pub fn file_with_changes(&self, oid: &str, file: &FileDiff) -> Result<FileDiff> {
+        anyhow::ensure!(
+            file.status != FileStatus::Deleted,
+            "{} was deleted in {oid}",
+            file.path
+        );
+        let size = self.file_size_at(oid, &file.path)?;
+        anyhow::ensure!(
+            size <= MAX_FULL_FILE_SIZE,
+            "{} is too large to show in full ({size} bytes)",
+            file.path
+        );
+        let content = self.file_content_at(oid, &file.path)?;
+        Ok(overlay_file_changes(file, &content))
+    }
+
+    /// Both blobs of an image changed by commit `oid`, for showing the
+    /// pictures rather than a binary placeholder.
+    pub fn image_versions(&self, oid: &str, file: &FileDiff) -> Result<ImageVersions> {
+        anyhow::ensure!(
+            file.image_format().is_some(),
+            "{} is not an image",
+            file.path
+        );
+        let (old, new) = self.file_versions(oid, file)?;
+        Ok(ImageVersions { old, new })
+    }
+
+    /// OIDs of commits on `HEAD` that are not on its upstream branch.
+    ///
+    /// Returns an empty set when the current branch has no upstream.
+    pub fn unpushed_oids(&self) -> Result<HashSet<String>> {
+        if self
+            .run_git(&["rev-parse", "--symbolic-full-name", "@{upstream}"])
+            .is_err()
+        {
+            return Ok(HashSet::new());
+        }
+        let stdout = self.run_git(&["rev-list", "@{upstream}..HEAD"])?;
+        Ok(stdout.lines().map(|l| l.trim().to_string()).collect())
+    }
+
+    /// Stage one hunk of a working-tree diff into the index.
+    pub fn stage_hunk(&self, file: &FileDiff, hunk_index: usize) -> Result<()> {
+        self.apply_hunk(file, hunk_index, &["--cached"])
+    }
+
+    /// Remove one hunk of a staged diff from the index.
+    pub fn unstage_hunk(&self, file: &FileDiff, hunk_index: usize) -> Result<()> {
+        self.apply_hunk(file, hunk_index, &["--cached", "--reverse"])
+    }
+
+    /// Revert one hunk of a working-tree diff in the working directory.
+    pub fn discard_hunk(&self, file: &FileDiff, hunk_index: usize) -> Result<()> {
+        self.apply_hunk(file, hunk_index, &["--reverse"])
+    }
+
+    fn apply_hunk(&self, file: &FileDiff, hunk_index: usize, args: &[&str]) -> Result<()> {
+        let hunk = file
+            .hunks
+            .get(hunk_index)
+            .with_context(|| format!("no hunk {hunk_index} in {}", file.path))?;
+        let patch = hunk_patch(file, hunk);
+        let mut child = self
+            .git()?
+            .command()
+            .arg("apply")
+            .args(args)
+            .arg("-")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .context("failed to run git apply")?;
+        child
+            .stdin
+            .take()
+            .context("failed to open git apply stdin")?
+            .write_all(patch.as_bytes())?;
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("git apply failed: {}", stderr.trim());
+        }
+        Ok(())
+    }
+
+    /// Run `git` in the working directory and return its stdout.
+    fn run_git(&self, args: &[&str]) -> Result<String> {
+        let output = self
+            .git()?
+            .command()
+            .args(args)
+            .output()
+            .with_context(|| format!("failed to run git {}", args[0]))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("git {} failed: {}", args[0], stderr.trim());
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    /// Those of `paths` with staged, unstaged or untracked changes, i.e.
+    /// local work that [`Self::checkout_paths`] would overwrite.
+    pub fn dirty_paths(&self, paths: &[String]) -> Result<Vec<String>> {
+        if paths.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut args = vec!["status", "--porcelain", "-z", "--"];
+        args.extend(paths.iter().map(String::as_str));
+        let stdout = self.run_git(&args)?;
+        let mut dirty = Vec::new();
+        let mut entries = stdout.split('\0').filter(|entry| !entry.is_empty());
+        while let Some(entry) = entries.next() {
+            let (status, path) = entry.split_at(3.min(entry.len()));
+            dirty.push(path.to_string());
+            // A rename or copy is followed by the path it came from.
+            if status.starts_with(['R', 'C']) {
+                entries.next();
+            }
+        }
+        Ok(dirty)
+    }
+
+    /// Restore `paths` in the working tree (and index) to their contents at
+    /// `oid`, like `git checkout <oid> -- <paths>`. An empty `paths` restores
+    /// every file in the commit's tree.
+    pub fn checkout_paths(&self, oid: &str, paths: &[String]) -> Result<()> {
+        anyhow::ensure!(
+            oid.bytes().all(|b| b.is_ascii_hexdigit()),
+            "invalid commit OID: {oid}"
+        );
+        let mut args = vec!["checkout", oid, "--"];
+        if paths.is_empty() {
+            args.push(".");
+        } else {
+            args.extend(paths.iter().map(String::as_str));
+        }
+        self.run_git(&args)?;
+        Ok(())
+    }
+
+    /// Switch the working tree to `branch_name`, like `git checkout <name>`.
+    /// Fails without touching anything when local changes would be
+    /// overwritten.
+    pub fn checkout_branch(&self, branch_name: &str) -> Result<()> {
+        anyhow::ensure!(
+            !branch_name.is_empty() && !branch_name.starts_with('-'),
+            "invalid branch name: {branch_name:?}"
+        );
+        let output = self
+            .git()?
+            .command()
             .args(["checkout", branch_name])
-            .current_dir(workdir)
             .output()
             .context("failed to run git checkout")?;
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
+            if stderr.contains("would be overwritten") {
+                anyhow::bail!(
+                    "cannot check out {branch_name}: uncommitted changes would be \
+                     overwritten; commit or stash them first\n{}",
+                    stderr.trim()
+                );
+            }
             anyhow::bail!("git checkout failed: {}", stderr.trim());
         }
         Ok(())
@@ -252,6 +1200,37 @@ mod tests {
         let (_dir, _repo) = init_test_repo();
     }
 
+    #[test]
+    fn test_find_git_repos_mixed_tree() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        for name in ["alpha", "beta"] {
+            let repo_dir = root.join(name);
+            std::fs::create_dir(&repo_dir).unwrap();
+            git(&repo_dir, &["init", "-b", "main"]);
+        }
+        std::fs::create_dir(root.join("plain")).unwrap();
+        std::fs::write(root.join("file.txt"), "not a dir").unwrap();
+        let nested = root.join("group").join("gamma");
+        std::fs::create_dir_all(&nested).unwrap();
+        git(&nested, &["init", "-b", "main"]);
+
+        let found = find_git_repos(root, 1);
+        assert_eq!(found, vec![root.join("alpha"), root.join("beta")]);
+
+        let found = find_git_repos(root, 2);
+        assert_eq!(
+            found,
+            vec![root.join("alpha"), root.join("beta"), nested.clone()]
+        );
+    }
+
+    #[test]
+    fn test_find_git_repos_depth_zero_is_empty() {
+        let (dir, _repo) = init_test_repo();
+        assert!(find_git_repos(dir.path(), 0).is_empty());
+    }
+
     #[test]
     fn test_open_non_git_dir_fails() {
         let dir = TempDir::new().unwrap();
@@ -409,6 +1388,27 @@ mod tests {
         assert_eq!(diffs[0].path, "file.txt");
     }
 
+    #[test]
+    fn test_file_content_at() {
+        let (_dir, repo) = init_test_repo_with_commits(2);
+        let commits = repo.commits(2).unwrap();
+        assert_eq!(
+            repo.file_content_at(&commits[0].oid, "file.txt").unwrap(),
+            "content 1"
+        );
+        assert_eq!(
+            repo.file_content_at(&commits[1].oid, "file.txt").unwrap(),
+            "content 0"
+        );
+    }
+
+    #[test]
+    fn test_file_content_at_missing_path_fails() {
+        let (_dir, repo) = init_test_repo_with_commits(1);
+        let commits = repo.commits(1).unwrap();
+        assert!(repo.file_content_at(&commits[0].oid, "nope.txt").is_err());
+    }
+
     #[test]
     fn test_checkout_existing_branch() {
         let (dir, repo) = init_test_repo();
@@ -444,7 +1444,34 @@ mod tests {
         std::fs::write(path.join("file.txt"), "conflicting content").unwrap();
         let repo = Repository::open(path).unwrap();
         let result = repo.checkout_branch("other");
-        assert!(result.is_err());
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains("commit or stash"), "unexpected error: {err}");
+    }
+
+    #[test]
+    fn test_checkout_rejects_option_like_names() {
+        let (_dir, repo) = init_test_repo();
+        let err = repo.checkout_branch("--orphan=x").unwrap_err().to_string();
+        assert!(
+            err.contains("invalid branch name"),
+            "unexpected error: {err}"
+        );
+        assert!(repo.checkout_branch("").is_err());
+    }
+
+    #[test]
+    fn test_unpushed_oids_without_upstream_is_empty() {
+        let (_dir, repo) = init_test_repo_with_commits(2);
+        assert!(repo.unpushed_oids().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_upstream_track() {
+        assert_eq!(parse_upstream_track(""), Some((0, 0)));
+        assert_eq!(parse_upstream_track("ahead 2"), Some((2, 0)));
+        assert_eq!(parse_upstream_track("behind 3"), Some((0, 3)));
+        assert_eq!(parse_upstream_track("ahead 2, behind 1"), Some((2, 1)));
+        assert_eq!(parse_upstream_track("gone"), None);
     }
 
     #[test]
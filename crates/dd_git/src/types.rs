@@ -2,11 +2,20 @@
 pub struct BranchInfo {
     pub name: String,
     pub is_head: bool,
+    /// Short name of the tracked branch, e.g. `origin/main`.
+    pub upstream: Option<String>,
+    /// Commits on this branch that its upstream lacks. `None` without an
+    /// upstream, or when the upstream ref is gone.
+    pub ahead: Option<usize>,
+    /// Commits on the upstream that this branch lacks.
+    pub behind: Option<usize>,
 }
 
 #[derive(Debug, Clone)]
 pub struct RemoteInfo {
     pub name: String,
+    /// Fetch URL, if one is configured.
+    pub url: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -14,7 +23,70 @@ pub struct TagInfo {
     pub name: String,
 }
 
+#[derive(Debug, Clone)]
+pub struct SubmoduleInfo {
+    pub name: String,
+    /// Location in the work tree, relative to its root.
+    pub path: String,
+    /// Commit recorded for the submodule in the index, if staged.
+    pub head_oid: Option<String>,
+}
+
 #[derive(Debug, Clone)]
 pub struct StashInfo {
     pub message: String,
 }
+
+/// One entry of a directory in a commit's tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeEntry {
+    pub name: String,
+    /// Whether the entry is a subdirectory rather than a file.
+    pub is_dir: bool,
+    pub oid: String,
+}
+
+/// A multi-step git operation that has been started but not concluded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperationInProgress {
+    Merge,
+    Rebase,
+    CherryPick,
+    Revert,
+    Bisect,
+    ApplyMailbox,
+}
+
+impl OperationInProgress {
+    /// Display name, which is also the git subcommand driving it.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Merge => "merge",
+            Self::Rebase => "rebase",
+            Self::CherryPick => "cherry-pick",
+            Self::Revert => "revert",
+            Self::Bisect => "bisect",
+            Self::ApplyMailbox => "am",
+        }
+    }
+
+    /// Whether it can be resumed with `--continue`; a bisect is steered by
+    /// marking commits instead.
+    pub fn can_continue(self) -> bool {
+        self != Self::Bisect
+    }
+}
+
+/// At-a-glance state of a repository, computed when it is opened.
+#[derive(Debug, Clone)]
+pub struct RepoSummary {
+    /// Commits reachable from HEAD.
+    pub commit_count: usize,
+    /// Checked-out branch, as reported by `Repository::head_branch`.
+    pub branch: String,
+    pub dirty: bool,
+    pub stash_count: usize,
+    pub operation: Option<OperationInProgress>,
+    /// History is truncated, as in a `--depth` clone.
+    pub shallow: bool,
+}
@@ -5,7 +5,12 @@ use std::sync::LazyLock;
 
 use tempfile::TempDir;
 
-use dd_git::{FileStatus, LineOrigin, Repository};
+use dd_git::diff::parse_unified_diff;
+use dd_git::{
+    contiguous_range, expand_hunk_context, parse_conventional, split_hunk_lines, ExpandDirection,
+    FileStatus, ImageFormat, LineEnding, LineOrigin, OperationInProgress, Repository,
+    SignatureStatus, WhitespaceMode,
+};
 
 // ---------------------------------------------------------------------------
 // Fixture
@@ -43,6 +48,29 @@ fn head_oid(path: &Path) -> String {
     git(path, &["rev-parse", "HEAD"])
 }
 
+/// `git init` on a `main` branch, with a committer identity configured.
+fn init_repo(path: &Path) {
+    git(path, &["init", "-b", "main"]);
+    git(path, &["config", "user.email", "test@example.com"]);
+    git(path, &["config", "user.name", "Test User"]);
+}
+
+/// A throwaway clone of the fixture, for tests that change the repository.
+fn clone_fixture() -> TempDir {
+    let clone = TempDir::new().unwrap();
+    let p = clone.path();
+    git(
+        p,
+        &[
+            "clone",
+            "-q",
+            FIXTURE.path.to_str().unwrap(),
+            p.to_str().unwrap(),
+        ],
+    );
+    clone
+}
+
 static FIXTURE: LazyLock<FixtureRepo> = LazyLock::new(build_fixture);
 
 const LIB_INITIAL: &str = r#"// dd_example library
@@ -79,9 +107,7 @@ fn build_fixture() -> FixtureRepo {
     let dir = TempDir::new().unwrap();
     let p = dir.path().to_path_buf();
 
-    git(&p, &["init", "-b", "main"]);
-    git(&p, &["config", "user.email", "test@example.com"]);
-    git(&p, &["config", "user.name", "Test User"]);
+    init_repo(&p);
 
     // ---- Root commit: initial project ----
     fs::create_dir_all(p.join("src")).unwrap();
@@ -233,6 +259,18 @@ fn tags_include_lightweight_and_annotated() {
     assert!(names.contains(&"v1.0.0"), "missing v1.0.0: {names:?}");
 }
 
+#[test]
+fn tag_message_reads_annotated_tags_only() {
+    let f = &*FIXTURE;
+    let repo = Repository::open(&f.path).unwrap();
+    assert_eq!(
+        repo.tag_message("v1.0.0").unwrap().as_deref(),
+        Some("Release 1.0")
+    );
+    assert_eq!(repo.tag_message("v0.1.0").unwrap(), None);
+    assert!(repo.tag_message("v9.9.9").is_err());
+}
+
 #[test]
 fn remotes_is_empty_for_local_fixture() {
     let f = &*FIXTURE;
@@ -240,6 +278,92 @@ fn remotes_is_empty_for_local_fixture() {
     assert!(repo.remotes().unwrap().is_empty());
 }
 
+#[test]
+fn remotes_can_be_added_edited_and_removed() {
+    let clone = clone_fixture();
+    let p = clone.path();
+    let repo = Repository::open(p).unwrap();
+    repo.add_remote("upstream", "https://example.com/a.git")
+        .unwrap();
+    let remotes = Repository::open(p).unwrap().remotes().unwrap();
+    let names: Vec<_> = remotes.iter().map(|r| r.name.as_str()).collect();
+    assert_eq!(names, vec!["origin", "upstream"]);
+    assert_eq!(remotes[1].url.as_deref(), Some("https://example.com/a.git"));
+
+    repo.set_remote_url("upstream", "https://example.com/b.git")
+        .unwrap();
+    let remotes = Repository::open(p).unwrap().remotes().unwrap();
+    assert_eq!(remotes[1].url.as_deref(), Some("https://example.com/b.git"));
+    assert_eq!(
+        repo.remote_url("upstream").unwrap(),
+        "https://example.com/b.git"
+    );
+
+    repo.remove_remote("upstream").unwrap();
+    let remotes = Repository::open(p).unwrap().remotes().unwrap();
+    assert_eq!(remotes.len(), 1);
+    assert_eq!(remotes[0].name, "origin");
+    assert!(repo.remove_remote("upstream").is_err());
+}
+
+#[test]
+fn submodules_lists_registered_submodule() {
+    let dir = TempDir::new().unwrap();
+    let lib = dir.path().join("lib");
+    let app = dir.path().join("app");
+    for path in [&lib, &app] {
+        fs::create_dir_all(path).unwrap();
+        init_repo(path);
+        fs::write(path.join("README.md"), "# Readme\n").unwrap();
+        git(path, &["add", "."]);
+        git(path, &["commit", "-q", "-m", "initial"]);
+    }
+    git(
+        &app,
+        &[
+            "-c",
+            "protocol.file.allow=always",
+            "submodule",
+            "add",
+            "-q",
+            lib.to_str().unwrap(),
+            "vendor/lib",
+        ],
+    );
+
+    let repo = Repository::open(&app).unwrap();
+    let submodules = repo.submodules().unwrap();
+    assert_eq!(submodules.len(), 1);
+    assert_eq!(submodules[0].name, "vendor/lib");
+    assert_eq!(submodules[0].path, "vendor/lib");
+    assert_eq!(
+        submodules[0].head_oid.as_deref(),
+        Some(head_oid(&lib).as_str())
+    );
+
+    let f = &*FIXTURE;
+    assert!(Repository::open(&f.path)
+        .unwrap()
+        .submodules()
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn remote_branches_lists_tracking_refs_without_head() {
+    let clone = clone_fixture();
+    let p = clone.path();
+    let repo = Repository::open(p).unwrap();
+    let names: Vec<_> = repo
+        .remote_branches("origin")
+        .unwrap()
+        .into_iter()
+        .map(|b| b.name)
+        .collect();
+    assert_eq!(names, vec!["origin/feature/widgets", "origin/main"]);
+    assert!(repo.remote_branches("upstream").unwrap().is_empty());
+}
+
 #[test]
 fn stash_is_present() {
     let f = &*FIXTURE;
@@ -253,6 +377,54 @@ fn stash_is_present() {
     );
 }
 
+#[test]
+fn stash_push_paths_shelves_only_selected_files() {
+    let clone = clone_fixture();
+    let p = clone.path();
+    fs::write(p.join("README.md"), "# Example\n\nShelve me.\n").unwrap();
+    fs::write(p.join("CHANGELOG.md"), "# Changelog\n\n- Keep me\n").unwrap();
+
+    let repo = Repository::open(p).unwrap();
+    repo.stash_push_paths(&["README.md".to_string()], Some("wip: readme only"))
+        .unwrap();
+
+    let stashes = repo.stashes().unwrap();
+    assert_eq!(stashes.len(), 1);
+    assert!(stashes[0].message.contains("wip: readme only"));
+    let remaining = repo.diff_workdir().unwrap();
+    assert_eq!(remaining.len(), 1);
+    assert_eq!(remaining[0].path, "CHANGELOG.md");
+
+    assert!(repo.stash_push_paths(&[], None).is_err());
+}
+
+#[test]
+fn checkout_branch_switches_head() {
+    let clone = clone_fixture();
+    let p = clone.path();
+    let repo = Repository::open(p).unwrap();
+    repo.checkout_branch("feature/widgets").unwrap();
+    assert_eq!(
+        Repository::open(p).unwrap().head_branch().unwrap(),
+        "feature/widgets"
+    );
+
+    repo.checkout_branch("main").unwrap();
+    assert_eq!(Repository::open(p).unwrap().head_branch().unwrap(), "main");
+}
+
+#[test]
+fn checkout_branch_refuses_to_overwrite_local_changes() {
+    let clone = clone_fixture();
+    let p = clone.path();
+    // main edited src/lib.rs after the widget branch forked.
+    fs::write(p.join("src/lib.rs"), "// scratch\n").unwrap();
+    let repo = Repository::open(p).unwrap();
+    let err = repo.checkout_branch("feature/widgets").unwrap_err();
+    assert!(err.to_string().contains("commit or stash"), "{err}");
+    assert_eq!(Repository::open(p).unwrap().head_branch().unwrap(), "main");
+}
+
 #[test]
 fn commits_walk_returns_expected_count() {
     let f = &*FIXTURE;
@@ -266,6 +438,90 @@ fn commits_walk_returns_expected_count() {
     );
 }
 
+#[test]
+fn summary_reflects_fixture_state() {
+    let f = &*FIXTURE;
+    let repo = Repository::open(&f.path).unwrap();
+    let summary = repo.summary().unwrap();
+    assert_eq!(summary.commit_count, 10);
+    assert_eq!(summary.branch, "main");
+    // The stash push leaves the working tree clean.
+    assert!(!summary.dirty);
+    assert_eq!(summary.stash_count, 1);
+    assert_eq!(summary.operation, None);
+}
+
+/// A repo stopped on a conflicting `git merge other`.
+fn conflicted_merge_repo() -> TempDir {
+    let dir = TempDir::new().unwrap();
+    let p = dir.path();
+    init_repo(p);
+    fs::write(p.join("file.txt"), "base\n").unwrap();
+    git(p, &["add", "."]);
+    git(p, &["commit", "-m", "base"]);
+    git(p, &["checkout", "-b", "other"]);
+    fs::write(p.join("file.txt"), "other\n").unwrap();
+    git(p, &["commit", "-am", "other"]);
+    git(p, &["checkout", "main"]);
+    fs::write(p.join("file.txt"), "main\n").unwrap();
+    git(p, &["commit", "-am", "main"]);
+    let merge = Command::new("git")
+        .args(["merge", "other"])
+        .current_dir(p)
+        .output()
+        .unwrap();
+    assert!(!merge.status.success(), "merge was expected to conflict");
+    dir
+}
+
+#[test]
+fn summary_reports_conflicted_merge_in_progress() {
+    let dir = conflicted_merge_repo();
+    let summary = Repository::open(dir.path()).unwrap().summary().unwrap();
+    assert_eq!(summary.operation, Some(OperationInProgress::Merge));
+    assert!(summary.dirty);
+}
+
+#[test]
+fn current_operation_is_none_for_clean_repo() {
+    let f = &*FIXTURE;
+    let repo = Repository::open(&f.path).unwrap();
+    assert_eq!(repo.current_operation().unwrap(), None);
+}
+
+#[test]
+fn current_operation_reports_merge_until_aborted() {
+    let dir = conflicted_merge_repo();
+    let repo = Repository::open(dir.path()).unwrap();
+    assert_eq!(
+        repo.current_operation().unwrap(),
+        Some(OperationInProgress::Merge)
+    );
+
+    repo.abort_operation(OperationInProgress::Merge).unwrap();
+    assert_eq!(repo.current_operation().unwrap(), None);
+    assert!(!repo.is_dirty().unwrap());
+}
+
+#[test]
+fn continue_operation_concludes_resolved_merge() {
+    let dir = conflicted_merge_repo();
+    let p = dir.path();
+    let repo = Repository::open(p).unwrap();
+    assert!(repo.continue_operation(OperationInProgress::Merge).is_err());
+
+    fs::write(p.join("file.txt"), "resolved\n").unwrap();
+    git(p, &["add", "file.txt"]);
+    repo.continue_operation(OperationInProgress::Merge).unwrap();
+
+    assert_eq!(repo.current_operation().unwrap(), None);
+    let head = &repo.commits(1).unwrap()[0];
+    assert_eq!(head.parent_oids.len(), 2);
+    assert!(repo
+        .continue_operation(OperationInProgress::Bisect)
+        .is_err());
+}
+
 #[test]
 fn commits_are_newest_first() {
     let f = &*FIXTURE;
@@ -328,6 +584,123 @@ fn root_commit_has_no_parents() {
     );
 }
 
+#[test]
+fn root_and_merge_commits_carry_tree_and_committer() {
+    let f = &*FIXTURE;
+    let repo = Repository::open(&f.path).unwrap();
+    let commits = repo.commits(100).unwrap();
+    for oid in [&f.root_oid, &f.merge_oid] {
+        let commit = commits.iter().find(|c| &c.oid == oid).unwrap();
+        assert_eq!(commit.tree_oid.len(), 40);
+        assert!(commit.tree_oid.bytes().all(|b| b.is_ascii_hexdigit()));
+        assert_eq!(
+            commit.tree_oid,
+            git(&f.path, &["rev-parse", &format!("{oid}^{{tree}}")])
+        );
+        assert_eq!(commit.committer_name, "Test User");
+        assert_eq!(commit.committer_email, "test@example.com");
+        assert!(commit.committer_date > 0);
+    }
+}
+
+#[test]
+fn committer_differs_from_author_after_amend() {
+    let dir = TempDir::new().unwrap();
+    let p = dir.path();
+    init_repo(p);
+    fs::write(p.join("a.txt"), "a\n").unwrap();
+    git(p, &["add", "."]);
+    git(p, &["commit", "-m", "feat: a"]);
+    git(
+        p,
+        &[
+            "commit",
+            "--amend",
+            "--no-edit",
+            "--author",
+            "Other Author <other@example.com>",
+        ],
+    );
+
+    let repo = Repository::open(p).unwrap();
+    let commit = &repo.commits(1).unwrap()[0];
+    assert_eq!(commit.author_name, "Other Author");
+    assert_eq!(commit.author_email, "other@example.com");
+    assert_eq!(commit.committer_name, "Test User");
+    assert_eq!(commit.committer_email, "test@example.com");
+}
+
+#[test]
+fn commit_signature_status_reports_unsigned_commits() {
+    let dir = TempDir::new().unwrap();
+    let p = dir.path();
+    init_repo(p);
+    fs::write(p.join("a.txt"), "a\n").unwrap();
+    git(p, &["add", "."]);
+    git(p, &["commit", "-m", "feat: unsigned"]);
+
+    let repo = Repository::open(p).unwrap();
+    let unsigned = head_oid(p);
+    assert_eq!(
+        repo.commit_signature_status(&unsigned).unwrap(),
+        SignatureStatus::None
+    );
+    assert!(repo.commit_signature_status("HEAD; rm -rf").is_err());
+}
+
+/// Unix only: gpg is reached through a `/bin/sh` wrapper script.
+#[cfg(unix)]
+#[test]
+fn commit_signature_status_reads_gpg_signatures() {
+    use std::os::unix::fs::PermissionsExt;
+
+    if Command::new("gpg").arg("--version").output().is_err() {
+        return;
+    }
+    let dir = TempDir::new().unwrap();
+    let p = dir.path();
+    init_repo(p);
+    fs::write(p.join("a.txt"), "a\n").unwrap();
+    git(p, &["add", "."]);
+    git(p, &["commit", "-m", "feat: unsigned"]);
+    let repo = Repository::open(p).unwrap();
+
+    // A throwaway keyring, reached through a gpg.program wrapper so the
+    // test leaves the user's GNUPGHOME alone.
+    let gnupg = TempDir::new().unwrap();
+    let home = gnupg.path().display().to_string();
+    let keygen = Command::new("gpg")
+        .args(["--homedir", &home, "--batch", "--passphrase", ""])
+        .args(["--quick-gen-key", "Test User <test@example.com>"])
+        .args(["default", "default", "never"])
+        .output()
+        .unwrap();
+    assert!(keygen.status.success(), "{keygen:?}");
+    let wrapper = gnupg.path().join("gpg.sh");
+    fs::write(
+        &wrapper,
+        format!("#!/bin/sh\nexec gpg --homedir '{home}' \"$@\"\n"),
+    )
+    .unwrap();
+    fs::set_permissions(&wrapper, fs::Permissions::from_mode(0o755)).unwrap();
+    git(
+        p,
+        &["config", "gpg.program", &wrapper.display().to_string()],
+    );
+    git(p, &["config", "user.signingkey", "test@example.com"]);
+    fs::write(p.join("a.txt"), "b\n").unwrap();
+    git(p, &["commit", "-S", "-am", "feat: signed"]);
+
+    let signed = head_oid(p);
+    assert_eq!(
+        repo.commit_signature_status(&signed).unwrap(),
+        SignatureStatus::Good
+    );
+    let _ = Command::new("gpgconf")
+        .args(["--homedir", &home, "--kill", "gpg-agent"])
+        .status();
+}
+
 #[test]
 fn unicode_in_commit_message() {
     let f = &*FIXTURE;
@@ -344,6 +717,43 @@ fn unicode_in_commit_message() {
     );
 }
 
+#[test]
+fn fixture_subjects_parse_as_conventional() {
+    let f = &*FIXTURE;
+    let repo = Repository::open(&f.path).unwrap();
+    let commits = repo.commits(100).unwrap();
+    let kind_of = |oid: &str| {
+        commits
+            .iter()
+            .find(|c| c.oid == oid)
+            .and_then(|c| c.conventional())
+            .map(|cc| cc.kind)
+    };
+    assert_eq!(kind_of(&f.root_oid).as_deref(), Some("feat"));
+    assert_eq!(kind_of(&f.unicode_oid).as_deref(), Some("docs"));
+    assert_eq!(kind_of(&f.rename_oid).as_deref(), Some("refactor"));
+    assert_eq!(kind_of(&f.merge_oid).as_deref(), Some("merge"));
+
+    let unicode = parse_conventional("docs: update Ünïcödé documentation").unwrap();
+    assert_eq!(unicode.description, "update Ünïcödé documentation");
+    assert!(commits.iter().all(|c| c.conventional().is_some()));
+}
+
+#[test]
+fn commits_for_ref_loads_branch_history() {
+    let f = &*FIXTURE;
+    let repo = Repository::open(&f.path).unwrap();
+    let commits = repo.commits_for_ref("feature/widgets", 100).unwrap();
+    let subjects: Vec<_> = commits.iter().map(|c| c.subject.as_str()).collect();
+    assert_eq!(subjects[0], "feat: add widgets module");
+    assert!(subjects.contains(&"feat: initial project setup"));
+    assert!(
+        !subjects.contains(&"docs: add changelog"),
+        "main-only commit leaked into branch history: {subjects:?}"
+    );
+    assert!(repo.commits_for_ref("no/such-branch", 10).is_err());
+}
+
 #[test]
 fn commits_limit_is_respected() {
     let f = &*FIXTURE;
@@ -383,6 +793,151 @@ fn diff_multi_file_commit() {
     assert!(has_modified, "expected at least one Modified file");
 }
 
+#[test]
+fn diff_range_from_branch_tip_to_older_commit() {
+    let f = &*FIXTURE;
+    let repo = Repository::open(&f.path).unwrap();
+    let main_tip = repo.rev_parse("main").unwrap();
+    assert_eq!(main_tip, f.unicode_oid);
+
+    let diffs = repo.diff_range(&main_tip, &f.merge_oid).unwrap();
+    let mut files: Vec<_> = diffs
+        .iter()
+        .map(|d| (d.path.as_str(), d.status.clone()))
+        .collect();
+    files.sort_by_key(|(path, _)| *path);
+    assert_eq!(
+        files,
+        vec![
+            ("README.md", FileStatus::Modified),
+            ("assets/icon.bin", FileStatus::Deleted),
+            ("docs/guide.md", FileStatus::Added),
+            ("src/lib.rs", FileStatus::Renamed),
+        ]
+    );
+}
+
+#[test]
+fn diff_commit_whitespace_modes_hide_whitespace_changes() {
+    let dir = TempDir::new().unwrap();
+    let p = dir.path();
+    init_repo(p);
+    fs::write(p.join("eol.txt"), "one\ntwo\n").unwrap();
+    fs::write(p.join("indent.txt"), "one\ntwo\n").unwrap();
+    git(p, &["add", "."]);
+    git(p, &["commit", "-m", "feat: initial"]);
+    fs::write(p.join("eol.txt"), "one  \ntwo\n").unwrap();
+    fs::write(p.join("indent.txt"), "    one\ntwo\n").unwrap();
+    git(p, &["commit", "-am", "style: whitespace"]);
+
+    let repo = Repository::open(p).unwrap();
+    let oid = head_oid(p);
+    let changed = |mode| -> Vec<String> {
+        repo.diff_commit_with_whitespace(&oid, mode)
+            .unwrap()
+            .into_iter()
+            .map(|file| file.path)
+            .collect()
+    };
+    assert_eq!(changed(WhitespaceMode::Show), ["eol.txt", "indent.txt"]);
+    assert_eq!(changed(WhitespaceMode::IgnoreEol), ["indent.txt"]);
+    assert!(changed(WhitespaceMode::IgnoreAll).is_empty());
+    assert_eq!(WhitespaceMode::IgnoreAll.next(), WhitespaceMode::Show);
+}
+
+#[test]
+fn rev_parse_unknown_rev_is_an_error() {
+    let f = &*FIXTURE;
+    let repo = Repository::open(&f.path).unwrap();
+    assert!(repo.rev_parse("no-such-branch").is_err());
+}
+
+#[test]
+fn branches_containing_widget_commit_after_merge() {
+    let f = &*FIXTURE;
+    let repo = Repository::open(&f.path).unwrap();
+    let widget_oid = repo.rev_parse("feature/widgets").unwrap();
+    let branches = repo.branches_containing(&widget_oid).unwrap();
+    assert!(
+        branches.iter().any(|b| b == "feature/widgets"),
+        "{branches:?}"
+    );
+    assert!(branches.iter().any(|b| b == "main"), "{branches:?}");
+
+    // The newest commit on main is not on the feature branch.
+    let tip = repo.branches_containing(&f.unicode_oid).unwrap();
+    assert!(!tip.iter().any(|b| b == "feature/widgets"), "{tip:?}");
+}
+
+#[test]
+fn commit_stat_multi_file_commit() {
+    let f = &*FIXTURE;
+    let repo = Repository::open(&f.path).unwrap();
+    let mut stats = repo.commit_stat(&f.multi_file_oid).unwrap();
+    stats.sort_by(|a, b| a.path.cmp(&b.path));
+    let counts: Vec<_> = stats
+        .iter()
+        .map(|s| (s.path.as_str(), s.additions, s.deletions))
+        .collect();
+    assert_eq!(
+        counts,
+        vec![
+            ("README.md", Some(2), Some(0)),
+            ("src/lib.rs", Some(1), Some(1)),
+        ]
+    );
+}
+
+#[test]
+fn commit_file_count_multi_file_commit() {
+    let f = &*FIXTURE;
+    let repo = Repository::open(&f.path).unwrap();
+    assert!(repo.commit_file_count(&f.multi_file_oid).unwrap() >= 2);
+    assert_eq!(repo.commit_file_count(&f.binary_oid).unwrap(), 1);
+}
+
+#[test]
+fn commit_stat_binary_file_has_no_counts() {
+    let f = &*FIXTURE;
+    let repo = Repository::open(&f.path).unwrap();
+    let stats = repo.commit_stat(&f.binary_oid).unwrap();
+    assert_eq!(stats.len(), 1);
+    assert_eq!(stats[0].path, "assets/icon.bin");
+    assert!(stats[0].is_binary());
+}
+
+#[test]
+fn commit_line_counts_match_commit_stat() {
+    let f = &*FIXTURE;
+    let repo = Repository::open(&f.path).unwrap();
+    let counts = repo.commit_line_counts("HEAD", 100).unwrap();
+    assert_eq!(counts.get(&f.multi_file_oid), Some(&(3, 1)));
+    assert_eq!(counts.get(&f.binary_oid), Some(&(0, 0)));
+    assert!(!counts.contains_key(&f.merge_oid));
+}
+
+#[test]
+fn export_patch_series_writes_one_file_per_commit() {
+    let f = &*FIXTURE;
+    let repo = Repository::open(&f.path).unwrap();
+    let commits = repo.commits(100).unwrap();
+    let newest = commits
+        .iter()
+        .position(|c| c.oid == f.multi_hunk_oid)
+        .unwrap();
+    let selected = &commits[newest..newest + 2];
+    let (oldest, tip) = contiguous_range(selected).unwrap();
+    assert_eq!(oldest, f.multi_file_oid);
+
+    let out = TempDir::new().unwrap();
+    let patches = repo.export_patch_series(oldest, tip, out.path()).unwrap();
+    assert_eq!(patches.len(), 2);
+    let first = fs::read_to_string(&patches[0]).unwrap();
+    let second = fs::read_to_string(&patches[1]).unwrap();
+    assert!(first.contains("Subject: [PATCH 1/2] feat: update lib and readme"));
+    assert!(second.contains("Subject: [PATCH 2/2] refactor: restructure lib module"));
+}
+
 #[test]
 fn diff_rename_detected() {
     let f = &*FIXTURE;
@@ -399,6 +954,33 @@ fn diff_rename_detected() {
     );
 }
 
+#[test]
+fn diff_copy_detected() {
+    // Built apart from the shared fixture so its commit counts stay put.
+    // Without --find-copies-harder git only looks for copies of files the
+    // same commit modifies, so the source is edited too.
+    let dir = TempDir::new().unwrap();
+    let p = dir.path();
+    init_repo(p);
+    fs::write(p.join("lib.rs"), LIB_INITIAL).unwrap();
+    git(p, &["add", "."]);
+    git(p, &["commit", "-m", "feat: initial"]);
+    fs::copy(p.join("lib.rs"), p.join("lib_copy.rs")).unwrap();
+    fs::write(p.join("lib.rs"), LIB_INITIAL.replace("Hello", "Howdy")).unwrap();
+    git(p, &["add", "."]);
+    git(p, &["commit", "-m", "refactor: copy lib"]);
+
+    let repo = Repository::open(p).unwrap();
+    let diffs = repo.diff_commit(&head_oid(p)).unwrap();
+    let copied = diffs
+        .iter()
+        .find(|d| d.status == FileStatus::Copied)
+        .expect("expected a Copied file in diff");
+    assert_eq!(copied.path, "lib_copy.rs");
+    assert_eq!(copied.old_path.as_deref(), Some("lib.rs"));
+    assert!(copied.hunks.is_empty());
+}
+
 #[test]
 fn diff_delete_detected() {
     let f = &*FIXTURE;
@@ -441,6 +1023,33 @@ fn diff_binary_file_commit() {
     assert_eq!(binary.unwrap().status, FileStatus::Added);
 }
 
+#[test]
+fn image_diff_is_classified_and_both_blobs_load() {
+    const PNG_MAGIC: &[u8] = b"\x89PNG\r\n\x1a\n";
+    let dir = TempDir::new().unwrap();
+    let p = dir.path();
+    init_repo(p);
+    let old_png = [PNG_MAGIC, b"\0\0\0\rIHDR old"].concat();
+    let new_png = [PNG_MAGIC, b"\0\0\0\rIHDR new pixels"].concat();
+    fs::write(p.join("logo.png"), &old_png).unwrap();
+    git(p, &["add", "."]);
+    git(p, &["commit", "-m", "add logo"]);
+    fs::write(p.join("logo.png"), &new_png).unwrap();
+    git(p, &["commit", "-am", "update logo"]);
+
+    let repo = Repository::open(p).unwrap();
+    let oid = repo.rev_parse("HEAD").unwrap();
+    let diffs = repo.diff_commit(&oid).unwrap();
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].image_format(), Some(ImageFormat::Png));
+    assert!(diffs[0].hunks.is_empty());
+
+    let versions = repo.image_versions(&oid, &diffs[0]).unwrap();
+    assert_eq!(versions.old.as_deref(), Some(old_png.as_slice()));
+    assert_eq!(versions.new.as_deref(), Some(new_png.as_slice()));
+    assert_eq!(versions.format(), Some(ImageFormat::Png));
+}
+
 #[test]
 fn diff_hunk_line_origins_are_valid() {
     let f = &*FIXTURE;
@@ -502,6 +1111,811 @@ fn diff_inline_spans_populated_for_word_changes() {
     );
 }
 
+#[test]
+fn diff_expand_context_up_reveals_file_lines() {
+    let f = &*FIXTURE;
+    let repo = Repository::open(&f.path).unwrap();
+    let mut diffs = repo.diff_commit(&f.multi_hunk_oid).unwrap();
+    let content = repo
+        .file_content_at(&f.multi_hunk_oid, &diffs[0].path)
+        .unwrap();
+    let file_lines: Vec<&str> = content.lines().collect();
+
+    let hunks = &mut diffs[0].hunks;
+    let last = hunks.len() - 1;
+    let before_start = hunks[last].new_start;
+    let before_len = hunks[last].lines.len();
+
+    let added = expand_hunk_context(hunks, last, ExpandDirection::Up, 10, &content);
+    assert!(added > 0, "expected context lines to be added");
+
+    let hunk = &hunks[hunks.len() - 1];
+    assert_eq!(hunk.lines.len(), before_len + added as usize);
+    assert_eq!(hunk.new_start, before_start - added);
+    for line in &hunk.lines[..added as usize] {
+        assert_eq!(line.origin, LineOrigin::Context);
+        let new_no = line.new_line_no.unwrap();
+        assert_eq!(line.content, file_lines[new_no as usize - 1]);
+    }
+}
+
+/// Repo with one committed 20-line file whose first and last lines have been
+/// modified in the working tree, producing two hunks.
+fn two_hunk_worktree() -> TempDir {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path();
+    init_repo(path);
+    let lines: Vec<String> = (1..=20).map(|n| format!("line {n}")).collect();
+    fs::write(path.join("file.txt"), lines.join("\n") + "\n").unwrap();
+    git(path, &["add", "."]);
+    git(path, &["commit", "-m", "initial"]);
+
+    let mut changed = lines.clone();
+    changed[0] = "line one".into();
+    changed[19] = "line twenty".into();
+    fs::write(path.join("file.txt"), changed.join("\n") + "\n").unwrap();
+    dir
+}
+
+#[test]
+fn stage_hunk_stages_only_that_hunk() {
+    let dir = two_hunk_worktree();
+    let path = dir.path();
+    let files = parse_unified_diff(&git(path, &["diff"])).unwrap();
+    assert_eq!(files[0].hunks.len(), 2);
+
+    let repo = Repository::open(path).unwrap();
+    repo.stage_hunk(&files[0], 1).unwrap();
+
+    let staged = git(path, &["diff", "--cached"]);
+    assert!(staged.contains("+line twenty"));
+    assert!(!staged.contains("+line one"));
+    let unstaged = git(path, &["diff"]);
+    assert!(unstaged.contains("+line one"));
+    assert!(!unstaged.contains("+line twenty"));
+
+    let staged_files = parse_unified_diff(&staged).unwrap();
+    repo.unstage_hunk(&staged_files[0], 0).unwrap();
+    assert!(git(path, &["diff", "--cached"]).trim().is_empty());
+}
+
+#[test]
+fn unstage_hunk_of_staged_rename_restores_old_path() {
+    let dir = two_hunk_worktree();
+    let path = dir.path();
+    git(path, &["checkout", "--", "file.txt"]);
+    git(path, &["mv", "file.txt", "moved.txt"]);
+    let mut content = fs::read_to_string(path.join("moved.txt")).unwrap();
+    content.push_str("line 21\n");
+    fs::write(path.join("moved.txt"), content).unwrap();
+    git(path, &["add", "moved.txt"]);
+
+    let repo = Repository::open(path).unwrap();
+    let staged = repo.diff_staged().unwrap();
+    assert_eq!(staged.len(), 1);
+    assert_eq!(staged[0].status, FileStatus::Renamed);
+    repo.unstage_hunk(&staged[0], 0).unwrap();
+
+    assert!(git(path, &["diff", "--cached"]).trim().is_empty());
+    assert_eq!(git(path, &["ls-files"]).trim(), "file.txt");
+}
+
+#[test]
+fn discard_hunk_reverts_working_tree() {
+    let dir = two_hunk_worktree();
+    let path = dir.path();
+    let files = parse_unified_diff(&git(path, &["diff"])).unwrap();
+
+    let repo = Repository::open(path).unwrap();
+    repo.discard_hunk(&files[0], 0).unwrap();
+
+    let content = fs::read_to_string(path.join("file.txt")).unwrap();
+    assert!(content.starts_with("line 1\n"));
+    assert!(content.ends_with("line twenty\n"));
+    assert!(repo.stage_hunk(&files[0], 5).is_err());
+}
+
+#[test]
+fn is_dirty_respects_gitignore_added_later() {
+    let dir = TempDir::new().unwrap();
+    let p = dir.path();
+    init_repo(p);
+    fs::write(p.join("file.txt"), "base\n").unwrap();
+    git(p, &["add", "."]);
+    git(p, &["commit", "-m", "base"]);
+
+    fs::write(p.join("scratch.log"), "noise\n").unwrap();
+    assert!(Repository::open(p).unwrap().is_dirty().unwrap());
+
+    fs::write(p.join(".gitignore"), "*.log\n").unwrap();
+    git(p, &["add", ".gitignore"]);
+    git(p, &["commit", "-m", "ignore logs"]);
+    assert!(!Repository::open(p).unwrap().is_dirty().unwrap());
+}
+
+#[test]
+fn ignored_files_lists_only_excluded_paths() {
+    let dir = TempDir::new().unwrap();
+    let p = dir.path();
+    init_repo(p);
+    fs::write(p.join(".gitignore"), "*.log\nbuild/\n").unwrap();
+    git(p, &["add", "."]);
+    git(p, &["commit", "-m", "ignore logs"]);
+
+    fs::write(p.join("scratch.log"), "noise\n").unwrap();
+    fs::create_dir(p.join("build")).unwrap();
+    fs::write(p.join("build/out.o"), "obj\n").unwrap();
+    let repo = Repository::open(p).unwrap();
+    let mut ignored = repo.ignored_files().unwrap();
+    ignored.sort();
+    assert_eq!(ignored, vec!["build/", "scratch.log"]);
+    assert!(!repo.is_dirty().unwrap());
+    assert!(repo.diff_workdir().unwrap().is_empty());
+
+    fs::write(p.join("notes.txt"), "keep\n").unwrap();
+    assert_eq!(repo.ignored_files().unwrap().len(), 2);
+    let untracked = repo.diff_workdir().unwrap();
+    assert_eq!(untracked.len(), 1);
+    assert_eq!(untracked[0].path, "notes.txt");
+}
+
+#[test]
+fn unpushed_oids_lists_commits_ahead_of_upstream() {
+    let upstream = TempDir::new().unwrap();
+    let up = upstream.path();
+    init_repo(up);
+    fs::write(up.join("file.txt"), "base\n").unwrap();
+    git(up, &["add", "."]);
+    git(up, &["commit", "-m", "base"]);
+
+    let clone = TempDir::new().unwrap();
+    let p = clone.path();
+    git(p, &["clone", up.to_str().unwrap(), p.to_str().unwrap()]);
+    git(p, &["config", "user.email", "test@example.com"]);
+    git(p, &["config", "user.name", "Test User"]);
+    let mut ahead = Vec::new();
+    for i in 0..2 {
+        fs::write(p.join("file.txt"), format!("local {i}\n")).unwrap();
+        git(p, &["commit", "-am", &format!("local {i}")]);
+        ahead.push(head_oid(p));
+    }
+
+    let repo = Repository::open(p).unwrap();
+    let unpushed = repo.unpushed_oids().unwrap();
+    assert_eq!(unpushed.len(), 2, "unexpected unpushed set: {unpushed:?}");
+    for oid in &ahead {
+        assert!(unpushed.contains(oid), "missing {oid} in {unpushed:?}");
+    }
+    let base = git(up, &["rev-parse", "HEAD"]);
+    assert!(!unpushed.contains(&base));
+}
+
+#[test]
+fn set_upstream_enables_ahead_behind() {
+    let upstream = TempDir::new().unwrap();
+    let up = upstream.path();
+    init_repo(up);
+    fs::write(up.join("file.txt"), "base\n").unwrap();
+    git(up, &["add", "."]);
+    git(up, &["commit", "-m", "base"]);
+
+    let clone = TempDir::new().unwrap();
+    let p = clone.path();
+    git(p, &["clone", up.to_str().unwrap(), p.to_str().unwrap()]);
+    git(p, &["config", "user.email", "test@example.com"]);
+    git(p, &["config", "user.name", "Test User"]);
+    git(p, &["checkout", "-b", "topic"]);
+    git(p, &["push", "origin", "topic"]);
+
+    let repo = Repository::open(p).unwrap();
+    assert!(repo.ahead_behind("topic").is_err());
+    assert!(repo.ahead_behind("--all").is_err());
+    assert!(repo.set_upstream("-d", "origin", "topic").is_err());
+    let topic = repo.branches().unwrap();
+    let topic = topic.iter().find(|b| b.name == "topic").unwrap();
+    assert_eq!(topic.upstream, None);
+
+    repo.set_upstream("topic", "origin", "topic").unwrap();
+    fs::write(p.join("file.txt"), "local\n").unwrap();
+    git(p, &["commit", "-am", "local"]);
+
+    let repo = Repository::open(p).unwrap();
+    assert_eq!(repo.ahead_behind("topic").unwrap(), (1, 0));
+    let branches = repo.branches().unwrap();
+    let topic = branches.iter().find(|b| b.name == "topic").unwrap();
+    assert_eq!(topic.upstream.as_deref(), Some("origin/topic"));
+    assert_eq!((topic.ahead, topic.behind), (Some(1), Some(0)));
+
+    assert!(repo.set_upstream("topic", "", "topic").is_err());
+    assert!(repo.set_upstream("topic", "origin", "").is_err());
+}
+
+#[test]
+fn branches_report_ahead_and_behind_counts() {
+    let upstream = TempDir::new().unwrap();
+    let up = upstream.path();
+    init_repo(up);
+    fs::write(up.join("file.txt"), "base\n").unwrap();
+    git(up, &["add", "."]);
+    git(up, &["commit", "-m", "base"]);
+
+    let clone = TempDir::new().unwrap();
+    let p = clone.path();
+    git(p, &["clone", up.to_str().unwrap(), p.to_str().unwrap()]);
+    git(p, &["config", "user.email", "test@example.com"]);
+    git(p, &["config", "user.name", "Test User"]);
+    git(p, &["branch", "scratch"]);
+    for i in 0..2 {
+        fs::write(p.join("local.txt"), format!("local {i}\n")).unwrap();
+        git(p, &["add", "."]);
+        git(p, &["commit", "-m", &format!("local {i}")]);
+    }
+    fs::write(up.join("file.txt"), "remote\n").unwrap();
+    git(up, &["commit", "-am", "remote"]);
+    git(p, &["fetch", "origin"]);
+
+    let repo = Repository::open(p).unwrap();
+    let branches = repo.branches().unwrap();
+    let main = branches.iter().find(|b| b.name == "main").unwrap();
+    assert_eq!(main.upstream.as_deref(), Some("origin/main"));
+    assert_eq!((main.ahead, main.behind), (Some(2), Some(1)));
+    assert_eq!(repo.ahead_behind("main").unwrap(), (2, 1));
+    let scratch = branches.iter().find(|b| b.name == "scratch").unwrap();
+    assert_eq!((scratch.ahead, scratch.behind), (None, None));
+}
+
+#[test]
+fn remote_url_reads_configured_origin() {
+    let dir = TempDir::new().unwrap();
+    let p = dir.path();
+    init_repo(p);
+    git(
+        p,
+        &["remote", "add", "origin", "git@github.com:owner/repo.git"],
+    );
+    let repo = Repository::open(p).unwrap();
+    assert_eq!(
+        repo.remote_url("origin").unwrap(),
+        "git@github.com:owner/repo.git"
+    );
+    assert!(repo.remote_url("missing").is_err());
+}
+
+#[test]
+fn config_value_reads_repo_level_user_name() {
+    let repo = Repository::open(&FIXTURE.path).unwrap();
+    assert_eq!(
+        repo.config_value("user.name").unwrap().as_deref(),
+        Some("Test User")
+    );
+    assert_eq!(repo.config_value("dd-merge.unset-key").unwrap(), None);
+}
+
+#[test]
+fn config_value_follows_include_path() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path();
+    init_repo(path);
+    let included = path.join("extra.gitconfig");
+    fs::write(&included, "[diff]\n\trenameLimit = 321\n").unwrap();
+    git(
+        path,
+        &["config", "include.path", included.to_str().unwrap()],
+    );
+
+    let repo = Repository::open(path).unwrap();
+    assert_eq!(
+        repo.config_value("diff.renameLimit").unwrap().as_deref(),
+        Some("321")
+    );
+}
+
+#[test]
+fn unborn_head_reports_branch_without_commits() {
+    let dir = TempDir::new().unwrap();
+    init_repo(dir.path());
+    fs::write(dir.path().join("notes.txt"), "draft\n").unwrap();
+
+    let repo = Repository::open(dir.path()).unwrap();
+    assert_eq!(repo.head_branch().unwrap(), "main");
+    assert!(!repo.has_commits().unwrap());
+    assert!(repo.commits(10).unwrap().is_empty());
+
+    let branches = repo.branches().unwrap();
+    assert_eq!(branches.len(), 1);
+    assert_eq!(branches[0].name, "main");
+    assert!(branches[0].is_head);
+
+    let summary = repo.summary().unwrap();
+    assert_eq!(summary.branch, "main");
+    assert_eq!(summary.commit_count, 0);
+    assert!(summary.dirty);
+}
+
+#[test]
+fn checkout_paths_restores_deleted_file_from_old_commit() {
+    let f = &*FIXTURE;
+    let clone = clone_fixture();
+    let p = clone.path();
+    let guide = p.join("docs/guide.md");
+    assert!(!guide.exists());
+
+    let repo = Repository::open(p).unwrap();
+    repo.checkout_paths(&f.root_oid, &["docs/guide.md".to_string()])
+        .unwrap();
+    assert_eq!(
+        fs::read_to_string(&guide).unwrap(),
+        "# Guide\n\nGetting started.\n"
+    );
+    assert!(repo.has_staged_changes().unwrap());
+
+    assert!(repo
+        .checkout_paths(&f.root_oid, &["no/such/file".to_string()])
+        .is_err());
+}
+
+#[test]
+fn dirty_paths_reports_local_changes_among_given_paths() {
+    let clone = clone_fixture();
+    let p = clone.path();
+    let repo = Repository::open(p).unwrap();
+    let paths = vec![
+        "README.md".to_string(),
+        "src/lib.rs".to_string(),
+        "TODO.md".to_string(),
+    ];
+    assert!(repo.dirty_paths(&paths).unwrap().is_empty());
+
+    fs::write(p.join("README.md"), "# Edited\n").unwrap();
+    fs::write(p.join("TODO.md"), "- new\n").unwrap();
+    fs::write(p.join("CHANGELOG.md"), "# Not asked about\n").unwrap();
+    git(p, &["add", "README.md"]);
+    assert_eq!(
+        repo.dirty_paths(&paths).unwrap(),
+        vec!["README.md".to_string(), "TODO.md".to_string()]
+    );
+    assert!(repo.dirty_paths(&[]).unwrap().is_empty());
+}
+
+#[test]
+fn has_staged_changes_tracks_the_index() {
+    let dir = TempDir::new().unwrap();
+    let p = dir.path();
+    init_repo(p);
+    fs::write(p.join("notes.txt"), "one\n").unwrap();
+    git(p, &["add", "notes.txt"]);
+    git(p, &["commit", "-m", "first"]);
+
+    let repo = Repository::open(p).unwrap();
+    assert!(!repo.has_staged_changes().unwrap());
+
+    // Unstaged edits would not be part of an amend.
+    fs::write(p.join("notes.txt"), "one\ntwo\n").unwrap();
+    assert!(!repo.has_staged_changes().unwrap());
+
+    git(p, &["add", "notes.txt"]);
+    assert!(repo.has_staged_changes().unwrap());
+    let staged = repo.diff_staged().unwrap();
+    assert_eq!(staged.len(), 1);
+    assert_eq!(staged[0].path, "notes.txt");
+}
+
+#[test]
+fn diff_workdir_reports_unstaged_changes() {
+    let clone = clone_fixture();
+    let p = clone.path();
+    let repo = Repository::open(p).unwrap();
+    assert!(repo.diff_workdir().unwrap().is_empty());
+
+    fs::write(p.join("CHANGELOG.md"), "# Changelog\n\n- Unreleased\n").unwrap();
+    let diffs = repo.diff_workdir().unwrap();
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].path, "CHANGELOG.md");
+    assert_eq!(diffs[0].status, FileStatus::Modified);
+    assert!(diffs[0].hunks[0]
+        .lines
+        .iter()
+        .any(|l| l.origin == LineOrigin::Addition && l.content == "- Unreleased"));
+
+    // Staged edits are not part of the working-tree diff.
+    git(p, &["add", "CHANGELOG.md"]);
+    assert!(repo.diff_workdir().unwrap().is_empty());
+}
+
+#[test]
+fn diff_workdir_flags_untracked_and_deleted_files() {
+    let clone = clone_fixture();
+    let p = clone.path();
+    fs::write(p.join("TODO.md"), "- write docs\n- ship\n").unwrap();
+    fs::write(p.join("blob.bin"), b"\0\x01\x02").unwrap();
+    fs::write(p.join(".git/info/exclude"), "scratch.txt\n").unwrap();
+    fs::write(p.join("scratch.txt"), "ignored\n").unwrap();
+    fs::remove_file(p.join("CHANGELOG.md")).unwrap();
+
+    let repo = Repository::open(p).unwrap();
+    let diffs = repo.diff_workdir().unwrap();
+    let status_of = |path: &str| {
+        diffs
+            .iter()
+            .find(|d| d.path == path)
+            .map(|d| d.status.clone())
+    };
+    assert_eq!(diffs.len(), 3);
+    assert_eq!(status_of("CHANGELOG.md"), Some(FileStatus::Deleted));
+    assert_eq!(status_of("TODO.md"), Some(FileStatus::Added));
+    assert_eq!(status_of("blob.bin"), Some(FileStatus::Added));
+    assert_eq!(status_of("scratch.txt"), None);
+
+    // Untracked files read as one hunk of additions, binary ones as none.
+    let todo = diffs.iter().find(|d| d.path == "TODO.md").unwrap();
+    assert_eq!(todo.hunks.len(), 1);
+    assert_eq!(todo.hunks[0].header, "@@ -0,0 +1,2 @@");
+    let added: Vec<_> = todo.hunks[0]
+        .lines
+        .iter()
+        .map(|l| (l.origin.clone(), l.new_line_no, l.content.as_str()))
+        .collect();
+    assert_eq!(
+        added,
+        vec![
+            (LineOrigin::Addition, Some(1), "- write docs"),
+            (LineOrigin::Addition, Some(2), "- ship"),
+        ]
+    );
+    let blob = diffs.iter().find(|d| d.path == "blob.bin").unwrap();
+    assert!(blob.hunks.is_empty());
+}
+
+#[test]
+fn diff_staged_splits_from_workdir_diff() {
+    let clone = clone_fixture();
+    let p = clone.path();
+    let repo = Repository::open(p).unwrap();
+    assert!(repo.diff_staged().unwrap().is_empty());
+
+    fs::write(p.join("README.md"), "# Example\n\nStaged edit.\n").unwrap();
+    fs::write(p.join("TODO.md"), "- write docs\n").unwrap();
+    git(p, &["add", "README.md", "TODO.md"]);
+    git(p, &["rm", "-q", "CHANGELOG.md"]);
+
+    let staged = repo.diff_staged().unwrap();
+    let status_of = |path: &str| {
+        staged
+            .iter()
+            .find(|d| d.path == path)
+            .map(|d| d.status.clone())
+    };
+    assert_eq!(staged.len(), 3);
+    assert_eq!(status_of("README.md"), Some(FileStatus::Modified));
+    assert_eq!(status_of("TODO.md"), Some(FileStatus::Added));
+    assert_eq!(status_of("CHANGELOG.md"), Some(FileStatus::Deleted));
+
+    assert!(repo.diff_workdir().unwrap().is_empty());
+}
+
+#[test]
+fn is_shallow_only_for_depth_limited_clone() {
+    let f = &*FIXTURE;
+    let source = format!("file://{}", f.path.display());
+
+    let shallow = TempDir::new().unwrap();
+    git(
+        shallow.path(),
+        &["clone", "-q", "--depth", "1", &source, "."],
+    );
+    let repo = Repository::open(shallow.path()).unwrap();
+    assert!(repo.is_shallow().unwrap());
+    assert!(repo.summary().unwrap().shallow);
+
+    let full = TempDir::new().unwrap();
+    git(full.path(), &["clone", "-q", &source, "."]);
+    assert!(!Repository::open(full.path()).unwrap().is_shallow().unwrap());
+}
+
+#[test]
+fn full_file_diff_splits_into_aligned_rows() {
+    let f = &*FIXTURE;
+    let repo = Repository::open(&f.path).unwrap();
+    let diffs = repo.diff_commit(&f.multi_file_oid).unwrap();
+    let lib = diffs.iter().find(|d| d.path == "src/lib.rs").unwrap();
+
+    let full = repo.full_file_diff(&f.multi_file_oid, lib).unwrap();
+    assert_eq!(full.hunks.len(), 1);
+    let rows = split_hunk_lines(&full.hunks[0].lines);
+
+    let lib_multi = LIB_INITIAL.replace("Hello, {}!", "Hi, {}!");
+    let left = rows.iter().filter(|r| r.left.is_some()).count();
+    let right = rows.iter().filter(|r| r.right.is_some()).count();
+    assert_eq!(left, LIB_INITIAL.lines().count());
+    assert_eq!(right, lib_multi.lines().count());
+    // One line changed, so every row holds a line on both sides.
+    assert_eq!(rows.len(), left);
+    assert!(rows
+        .iter()
+        .all(|r| r.left.as_ref().unwrap().old_line_no.is_some()
+            && r.right.as_ref().unwrap().new_line_no.is_some()));
+}
+
+#[test]
+fn full_file_diff_refuses_oversized_files() {
+    let dir = TempDir::new().unwrap();
+    let p = dir.path();
+    init_repo(p);
+    let line = "x".repeat(99) + "\n";
+    fs::write(p.join("big.txt"), "small\n").unwrap();
+    git(p, &["add", "."]);
+    git(p, &["commit", "-q", "-m", "small"]);
+    let size = dd_git::repository::MAX_FULL_FILE_SIZE as usize + line.len();
+    fs::write(p.join("big.txt"), line.repeat(size / line.len())).unwrap();
+    git(p, &["commit", "-q", "-am", "big"]);
+
+    let repo = Repository::open(p).unwrap();
+    let head = repo.rev_parse("HEAD").unwrap();
+    let diffs = repo.diff_commit(&head).unwrap();
+    let err = repo.full_file_diff(&head, &diffs[0]).unwrap_err();
+    assert!(err.to_string().contains("too large"), "{err}");
+}
+
+#[test]
+fn file_with_changes_shows_whole_file_with_additions_marked() {
+    let f = &*FIXTURE;
+    let repo = Repository::open(&f.path).unwrap();
+    let diffs = repo.diff_commit(&f.multi_file_oid).unwrap();
+    let lib = diffs.iter().find(|d| d.path == "src/lib.rs").unwrap();
+
+    let full = repo.file_with_changes(&f.multi_file_oid, lib).unwrap();
+    let lib_multi = LIB_INITIAL.replace("Hello, {}!", "Hi, {}!");
+    let lines = &full.hunks[0].lines;
+    assert_eq!(lines.len(), lib_multi.lines().count());
+
+    let added: Vec<_> = lines
+        .iter()
+        .filter(|l| l.origin == LineOrigin::Addition)
+        .collect();
+    assert_eq!(added.len(), 1);
+    assert!(added[0].content.contains("Hi, {}!"));
+    assert!(lines
+        .iter()
+        .all(|l| l.origin == LineOrigin::Addition || l.old_line_no == l.new_line_no));
+}
+
+#[test]
+fn tree_entries_lists_directories_then_files() {
+    let f = &*FIXTURE;
+    let repo = Repository::open(&f.path).unwrap();
+
+    let root = repo.tree_entries(&f.root_oid, "").unwrap();
+    let names: Vec<_> = root.iter().map(|e| (e.name.as_str(), e.is_dir)).collect();
+    assert_eq!(
+        names,
+        vec![("docs", true), ("src", true), ("README.md", false)]
+    );
+
+    let src = repo.tree_entries(&f.root_oid, "src").unwrap();
+    assert_eq!(src.len(), 1);
+    assert_eq!(src[0].name, "lib.rs");
+    assert!(!src[0].is_dir);
+    assert!(repo.tree_entries(&f.root_oid, "README.md").is_err());
+}
+
+#[test]
+fn crlf_commit_message_is_normalized() {
+    let dir = TempDir::new().unwrap();
+    let p = dir.path();
+    init_repo(p);
+    fs::write(p.join("notes.txt"), "one\n").unwrap();
+    git(p, &["add", "notes.txt"]);
+    let message = p.join("message.txt");
+    fs::write(
+        &message,
+        "fix: windows line endings \r\n\r\nFirst paragraph,  \r\nwrapped.\r\n\r\nSecond paragraph.\r\n",
+    )
+    .unwrap();
+    // verbatim: git's default cleanup would strip the CRs itself.
+    git(
+        p,
+        &[
+            "commit",
+            "--cleanup=verbatim",
+            "-F",
+            message.to_str().unwrap(),
+        ],
+    );
+
+    let commits = Repository::open(p).unwrap().commits(1).unwrap();
+    assert_eq!(commits[0].subject, "fix: windows line endings");
+    assert_eq!(
+        commits[0].body,
+        "First paragraph,\nwrapped.\n\nSecond paragraph."
+    );
+}
+
+#[test]
+fn open_with_separate_git_dir_and_work_tree() {
+    let dir = TempDir::new().unwrap();
+    let git_dir = dir.path().join("meta.git");
+    let work_tree = dir.path().join("checkout");
+    fs::create_dir(&work_tree).unwrap();
+    let (git_dir_arg, work_tree_arg) = (
+        format!("--git-dir={}", git_dir.display()),
+        format!("--work-tree={}", work_tree.display()),
+    );
+    let separated = |args: &[&str]| {
+        let mut full = vec![git_dir_arg.as_str(), work_tree_arg.as_str()];
+        full.extend_from_slice(args);
+        git(dir.path(), &full)
+    };
+    separated(&["init", "-b", "main"]);
+    separated(&["config", "user.email", "test@test.com"]);
+    separated(&["config", "user.name", "Test"]);
+    fs::write(work_tree.join("notes.txt"), "one\n").unwrap();
+    separated(&["add", "notes.txt"]);
+    separated(&["commit", "-m", "first"]);
+    fs::write(work_tree.join("notes.txt"), "one\ntwo\n").unwrap();
+    separated(&["commit", "-am", "second"]);
+    // Leave nothing in the git dir pointing at the work tree, so only the
+    // explicit pairing connects them.
+    git(
+        dir.path(),
+        &[git_dir_arg.as_str(), "config", "--unset", "core.worktree"],
+    );
+
+    let repo = Repository::open_with(&git_dir, Some(&work_tree)).unwrap();
+    assert_eq!(
+        repo.workdir(),
+        Some(work_tree.canonicalize().unwrap().as_path())
+    );
+
+    let commits = repo.commits(10).unwrap();
+    assert_eq!(commits.len(), 2);
+    assert_eq!(commits[0].subject, "second");
+    let diffs = repo.diff_commit(&commits[0].oid).unwrap();
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].path, "notes.txt");
+    assert_eq!(repo.commit_file_count(&commits[0].oid).unwrap(), 1);
+
+    assert!(!repo.is_dirty().unwrap());
+    fs::write(work_tree.join("notes.txt"), "changed\n").unwrap();
+    assert!(repo.is_dirty().unwrap());
+}
+
+#[test]
+fn normalize_line_endings_rewrites_working_file() {
+    let dir = TempDir::new().unwrap();
+    init_repo(dir.path());
+    fs::write(dir.path().join("mixed.txt"), "a\r\nb\nc\r\n").unwrap();
+
+    let repo = Repository::open(dir.path()).unwrap();
+    let stats = repo.detect_mixed_line_endings("mixed.txt").unwrap();
+    assert_eq!((stats.lf, stats.crlf), (1, 2));
+    assert!(stats.is_mixed());
+
+    repo.normalize_line_endings("mixed.txt", LineEnding::Lf)
+        .unwrap();
+    assert_eq!(
+        fs::read_to_string(dir.path().join("mixed.txt")).unwrap(),
+        "a\nb\nc\n"
+    );
+    assert!(!repo
+        .detect_mixed_line_endings("mixed.txt")
+        .unwrap()
+        .is_mixed());
+}
+
+#[test]
+fn octopus_merge_combined_diff_has_three_parent_columns() {
+    let dir = TempDir::new().unwrap();
+    let p = dir.path();
+    init_repo(p);
+    fs::write(p.join("notes.txt"), "base\n").unwrap();
+    git(p, &["add", "."]);
+    git(p, &["commit", "-m", "base"]);
+    for branch in ["a", "b", "c"] {
+        git(p, &["checkout", "-q", "-b", branch, "main"]);
+        fs::write(p.join(format!("{branch}.txt")), "x\n").unwrap();
+        git(p, &["add", "."]);
+        git(p, &["commit", "-m", branch]);
+    }
+    git(p, &["checkout", "-q", "a"]);
+    git(p, &["merge", "-q", "b", "c", "-m", "octopus"]);
+    // Resolve by hand so the merge result differs from every parent.
+    fs::write(p.join("notes.txt"), "base\nmerged\n").unwrap();
+    git(p, &["commit", "-q", "-a", "--amend", "--no-edit"]);
+    let oid = head_oid(p);
+
+    let repo = Repository::open(p).unwrap();
+    let files = repo.diff_combined(&oid).unwrap();
+    let notes = files.iter().find(|f| f.path == "notes.txt").unwrap();
+    assert_eq!(notes.hunks[0].parent_ranges.len(), 3);
+    let simplified = notes.simplified();
+    let added: Vec<_> = simplified.hunks[0]
+        .lines
+        .iter()
+        .filter(|l| l.origin == LineOrigin::Addition)
+        .map(|l| l.content.as_str())
+        .collect();
+    assert_eq!(added, vec!["merged"]);
+}
+
+#[test]
+fn octopus_merge_combined_diff_ignores_whitespace() {
+    let dir = TempDir::new().unwrap();
+    let p = dir.path();
+    init_repo(p);
+    fs::write(p.join("notes.txt"), "base\n").unwrap();
+    git(p, &["add", "."]);
+    git(p, &["commit", "-m", "base"]);
+    for branch in ["a", "b", "c"] {
+        git(p, &["checkout", "-q", "-b", branch, "main"]);
+        fs::write(p.join(format!("{branch}.txt")), "x\n").unwrap();
+        git(p, &["add", "."]);
+        git(p, &["commit", "-m", branch]);
+    }
+    git(p, &["checkout", "-q", "a"]);
+    git(p, &["merge", "-q", "b", "c", "-m", "octopus"]);
+    // The hand resolution only adds trailing whitespace.
+    fs::write(p.join("notes.txt"), "base  \n").unwrap();
+    git(p, &["commit", "-q", "-a", "--amend", "--no-edit"]);
+    let oid = head_oid(p);
+
+    let repo = Repository::open(p).unwrap();
+    let shown = repo.diff_combined(&oid).unwrap();
+    assert!(shown
+        .iter()
+        .any(|f| f.path == "notes.txt" && !f.hunks.is_empty()));
+    let ignored = repo
+        .diff_combined_with_whitespace(&oid, WhitespaceMode::IgnoreAll)
+        .unwrap();
+    assert!(ignored
+        .iter()
+        .filter(|f| f.path == "notes.txt")
+        .all(|f| f.hunks.is_empty()));
+}
+
+#[test]
+fn default_branch_falls_back_to_local_main() {
+    let f = &*FIXTURE;
+    let repo = Repository::open(&f.path).unwrap();
+    assert_eq!(repo.default_branch().unwrap(), "main");
+}
+
+#[test]
+fn default_branch_prefers_origin_head() {
+    let dir = TempDir::new().unwrap();
+    let p = dir.path();
+    init_repo(p);
+    fs::write(p.join("file.txt"), "hello\n").unwrap();
+    git(p, &["add", "."]);
+    git(p, &["commit", "-m", "initial"]);
+    git(p, &["update-ref", "refs/remotes/origin/develop", "HEAD"]);
+    git(
+        p,
+        &[
+            "symbolic-ref",
+            "refs/remotes/origin/HEAD",
+            "refs/remotes/origin/develop",
+        ],
+    );
+
+    let repo = Repository::open(p).unwrap();
+    assert_eq!(repo.default_branch().unwrap(), "develop");
+}
+
+#[test]
+fn default_branch_errors_without_candidates() {
+    let dir = TempDir::new().unwrap();
+    let p = dir.path();
+    git(p, &["init", "-b", "trunk"]);
+    git(p, &["config", "user.email", "test@example.com"]);
+    git(p, &["config", "user.name", "Test User"]);
+    fs::write(p.join("file.txt"), "hello\n").unwrap();
+    git(p, &["add", "."]);
+    git(p, &["commit", "-m", "initial"]);
+
+    let repo = Repository::open(p).unwrap();
+    assert!(repo.default_branch().is_err());
+}
+
 // ---------------------------------------------------------------------------
 // Smoke tests against dd_merge repo
 // ---------------------------------------------------------------------------
@@ -2,8 +2,47 @@ use gpui::*;
 use gpui_component::Root;
 use gpui_component_assets::Assets;
 
-use dd_core::Session;
-use dd_ui::app_view::{CloseTab, NextTab, OpenRepository, PreviousTab, Quit};
+use dd_core::{AppState, Session};
+use dd_ui::app_view::{
+    CheckoutFilesFromCommit, CloseTab, ExportPatchSeries, ExportSession, ImportSession, NextTab,
+    OpenRepositoriesInFolder, OpenRepository, PreviousTab, Quit, RefreshStatus, ShowStagedChanges,
+    ShowUnstagedChanges, ToggleCommitGraph, ToggleDiffFocus, ToggleSidebar, ToggleTheme,
+};
+use dd_ui::commit_list::{CheckoutFiles, SelectFirst, SelectLast, SelectNext, SelectPrevious};
+use dd_ui::diff_view::{
+    CycleViewMode, CycleWhitespaceMode, DiscardHunk, NextHunk, PreviousHunk, StageHunk,
+    ToggleFilePalette, UnstageHunk, WorkingChanges,
+};
+
+/// Where to open the main window: the bounds saved at the last quit, pulled
+/// back onto the display they overlap, or centered when there are none or
+/// their display is no longer connected.
+fn initial_window_bounds(state: &AppState, cx: &App) -> Bounds<Pixels> {
+    let centered = Bounds::centered(None, size(px(1200.0), px(800.0)), cx);
+    let Some((x, y, width, height)) = state.window_bounds else {
+        return centered;
+    };
+    let saved = Bounds::new(point(px(x), px(y)), size(px(width), px(height)));
+    let Some(display) = cx
+        .displays()
+        .into_iter()
+        .map(|display| display.bounds())
+        .find(|display| display.intersects(&saved))
+    else {
+        return centered;
+    };
+
+    let (left, top) = (f32::from(display.origin.x), f32::from(display.origin.y));
+    let width = width.min(display.size.width.into());
+    let height = height.min(display.size.height.into());
+    let x = x.clamp(left, f32::from(display.right()) - width);
+    let y = y.clamp(top, f32::from(display.bottom()) - height);
+    Bounds::new(point(px(x), px(y)), size(px(width), px(height)))
+}
+
+/// Where the single-letter diff shortcuts apply: not while the file palette
+/// or a review note takes typing.
+const SINGLE_KEY_CONTEXT: &str = "DiffView && !palette && !note_editing";
 
 fn main() {
     let app = Application::new().with_assets(Assets);
@@ -18,6 +57,25 @@ fn main() {
             KeyBinding::new("cmd-w", CloseTab, None),
             KeyBinding::new("cmd-}", NextTab, None),
             KeyBinding::new("cmd-{", PreviousTab, None),
+            KeyBinding::new("cmd-shift-enter", ToggleDiffFocus, None),
+            KeyBinding::new("cmd-r", RefreshStatus, None),
+            KeyBinding::new("cmd-b", ToggleSidebar, None),
+            KeyBinding::new("cmd-shift-g", ToggleCommitGraph, None),
+            KeyBinding::new("cmd-shift-l", ToggleTheme, None),
+            KeyBinding::new("s", StageHunk, Some(SINGLE_KEY_CONTEXT)),
+            KeyBinding::new("u", UnstageHunk, Some(SINGLE_KEY_CONTEXT)),
+            KeyBinding::new("x", DiscardHunk, Some(SINGLE_KEY_CONTEXT)),
+            KeyBinding::new("j", NextHunk, Some(SINGLE_KEY_CONTEXT)),
+            KeyBinding::new("k", PreviousHunk, Some(SINGLE_KEY_CONTEXT)),
+            KeyBinding::new("v", CycleViewMode, Some(SINGLE_KEY_CONTEXT)),
+            KeyBinding::new("w", CycleWhitespaceMode, Some(SINGLE_KEY_CONTEXT)),
+            KeyBinding::new("cmd-p", ToggleFilePalette, Some("DiffView")),
+            KeyBinding::new("escape", ToggleFilePalette, Some("DiffView && palette")),
+            KeyBinding::new("up", SelectPrevious, Some("CommitList")),
+            KeyBinding::new("down", SelectNext, Some("CommitList")),
+            KeyBinding::new("home", SelectFirst, Some("CommitList")),
+            KeyBinding::new("end", SelectLast, Some("CommitList")),
+            KeyBinding::new("cmd-shift-o", CheckoutFiles, Some("CommitList")),
         ]);
 
         cx.on_action(|_action: &Quit, cx: &mut App| {
@@ -31,13 +89,40 @@ fn main() {
             },
             Menu {
                 name: "File".into(),
-                items: vec![MenuItem::action("Open Repository...", OpenRepository)],
+                items: vec![
+                    MenuItem::action("Open Repository...", OpenRepository),
+                    MenuItem::action(
+                        "Open All Repositories in Folder...",
+                        OpenRepositoriesInFolder,
+                    ),
+                    MenuItem::separator(),
+                    MenuItem::action("Export Patch Series...", ExportPatchSeries),
+                    MenuItem::action("Check Out Files from Commit", CheckoutFilesFromCommit),
+                    MenuItem::separator(),
+                    MenuItem::action("Export Session...", ExportSession),
+                    MenuItem::action("Import Session...", ImportSession),
+                ],
+            },
+            Menu {
+                name: "View".into(),
+                items: vec![
+                    MenuItem::action("Focus Diff", ToggleDiffFocus),
+                    MenuItem::action("Toggle Sidebar", ToggleSidebar),
+                    MenuItem::action("Toggle Commit Graph", ToggleCommitGraph),
+                    MenuItem::action("Unstaged Changes", ShowUnstagedChanges),
+                    MenuItem::action("Staged Changes", ShowStagedChanges),
+                    MenuItem::action("Cycle Diff Layout", CycleViewMode),
+                    MenuItem::action("Cycle Whitespace Mode", CycleWhitespaceMode),
+                    MenuItem::action("Toggle Light/Dark Theme", ToggleTheme),
+                    MenuItem::action("Refresh", RefreshStatus),
+                ],
             },
         ]);
 
         cx.activate(true);
 
-        let bounds = Bounds::centered(None, size(px(1200.0), px(800.0)), cx);
+        let saved_state = Session::load().ok().flatten().unwrap_or_default();
+        let bounds = initial_window_bounds(&saved_state, cx);
         let _window_handle = cx
             .open_window(
                 WindowOptions {
@@ -56,10 +141,24 @@ fn main() {
                 |window, cx| {
                     let app_view = cx.new(|cx| dd_ui::AppView::new(window, cx));
                     let app_view_for_menu = app_view.downgrade();
+                    let app_view_for_folder = app_view.downgrade();
                     let app_view_for_close = app_view.downgrade();
                     let app_view_for_next = app_view.downgrade();
                     let app_view_for_prev = app_view.downgrade();
+                    let app_view_for_focus = app_view.downgrade();
+                    let app_view_for_refresh = app_view.downgrade();
+                    let app_view_for_sidebar = app_view.downgrade();
+                    let app_view_for_graph = app_view.downgrade();
+                    let app_view_for_theme = app_view.downgrade();
+                    let app_view_for_export = app_view.downgrade();
+                    let app_view_for_checkout_files = app_view.downgrade();
+                    let window_for_checkout_files = window.window_handle();
+                    let app_view_for_unstaged = app_view.downgrade();
+                    let app_view_for_staged = app_view.downgrade();
+                    let app_view_for_export_session = app_view.downgrade();
+                    let app_view_for_import_session = app_view.downgrade();
                     let app_view_for_quit = app_view.downgrade();
+                    let window_for_quit = window.window_handle();
 
                     // Handle File > Open Repository menu action
                     cx.on_action(move |_action: &OpenRepository, cx: &mut App| {
@@ -70,6 +169,14 @@ fn main() {
                         }
                     });
 
+                    cx.on_action(move |_action: &OpenRepositoriesInFolder, cx: &mut App| {
+                        if let Some(app_view) = app_view_for_folder.upgrade() {
+                            app_view.update(cx, |view, cx| {
+                                view.open_folder_dialog(cx);
+                            });
+                        }
+                    });
+
                     cx.on_action(move |_action: &CloseTab, cx: &mut App| {
                         if let Some(app_view) = app_view_for_close.upgrade() {
                             app_view.update(cx, |view, cx| {
@@ -94,10 +201,111 @@ fn main() {
                         }
                     });
 
-                    // Save session state on quit
+                    cx.on_action(move |_action: &ToggleDiffFocus, cx: &mut App| {
+                        if let Some(app_view) = app_view_for_focus.upgrade() {
+                            app_view.update(cx, |view, cx| {
+                                view.toggle_diff_focus(cx);
+                            });
+                        }
+                    });
+
+                    cx.on_action(move |_action: &ToggleSidebar, cx: &mut App| {
+                        if let Some(app_view) = app_view_for_sidebar.upgrade() {
+                            app_view.update(cx, |view, cx| {
+                                view.toggle_sidebar(cx);
+                            });
+                        }
+                    });
+
+                    cx.on_action(move |_action: &ToggleCommitGraph, cx: &mut App| {
+                        if let Some(app_view) = app_view_for_graph.upgrade() {
+                            app_view.update(cx, |view, cx| {
+                                view.toggle_commit_graph(cx);
+                            });
+                        }
+                    });
+
+                    cx.on_action(move |_action: &ToggleTheme, cx: &mut App| {
+                        if let Some(app_view) = app_view_for_theme.upgrade() {
+                            app_view.update(cx, |view, cx| {
+                                view.toggle_theme(cx);
+                            });
+                        }
+                    });
+
+                    cx.on_action(move |_action: &RefreshStatus, cx: &mut App| {
+                        if let Some(app_view) = app_view_for_refresh.upgrade() {
+                            app_view.update(cx, |view, cx| {
+                                view.refresh_status(cx);
+                            });
+                        }
+                    });
+
+                    cx.on_action(move |_action: &ExportPatchSeries, cx: &mut App| {
+                        if let Some(app_view) = app_view_for_export.upgrade() {
+                            app_view.update(cx, |view, cx| {
+                                view.export_patch_series(cx);
+                            });
+                        }
+                    });
+
+                    cx.on_action(move |_action: &CheckoutFilesFromCommit, cx: &mut App| {
+                        if let Some(app_view) = app_view_for_checkout_files.upgrade() {
+                            // The window shows the overwrite prompt.
+                            let _ = window_for_checkout_files.update(cx, |_, window, cx| {
+                                app_view.update(cx, |view, cx| {
+                                    view.checkout_files_from_commit(window, cx);
+                                });
+                            });
+                        }
+                    });
+
+                    cx.on_action(move |_action: &ShowUnstagedChanges, cx: &mut App| {
+                        if let Some(app_view) = app_view_for_unstaged.upgrade() {
+                            app_view.update(cx, |view, cx| {
+                                view.show_working_changes(WorkingChanges::Unstaged, cx);
+                            });
+                        }
+                    });
+
+                    cx.on_action(move |_action: &ShowStagedChanges, cx: &mut App| {
+                        if let Some(app_view) = app_view_for_staged.upgrade() {
+                            app_view.update(cx, |view, cx| {
+                                view.show_working_changes(WorkingChanges::Staged, cx);
+                            });
+                        }
+                    });
+
+                    cx.on_action(move |_action: &ExportSession, cx: &mut App| {
+                        if let Some(app_view) = app_view_for_export_session.upgrade() {
+                            app_view.update(cx, |view, cx| {
+                                view.export_session_dialog(cx);
+                            });
+                        }
+                    });
+
+                    cx.on_action(move |_action: &ImportSession, cx: &mut App| {
+                        if let Some(app_view) = app_view_for_import_session.upgrade() {
+                            app_view.update(cx, |view, cx| {
+                                view.import_session_dialog(cx);
+                            });
+                        }
+                    });
+
+                    // Save session state and window bounds on quit
                     let _ = cx.on_app_quit(move |cx| {
                         if let Some(app_view) = app_view_for_quit.upgrade() {
-                            let state = app_view.read(cx).state().clone();
+                            let mut state = app_view.read(cx).session_state();
+                            if let Ok(bounds) = window_for_quit
+                                .update(cx, |_, window, _| window.window_bounds().get_bounds())
+                            {
+                                state.window_bounds = Some((
+                                    bounds.origin.x.into(),
+                                    bounds.origin.y.into(),
+                                    bounds.size.width.into(),
+                                    bounds.size.height.into(),
+                                ));
+                            }
                             let _ = Session::save(&state);
                         }
                         async {}
@@ -1,62 +1,212 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use gpui::prelude::*;
-use gpui::{actions, Context, Entity, PathPromptOptions, Window};
-use gpui_component::{button::Button, v_flex, ActiveTheme};
+use gpui::{actions, px, Context, Entity, PathPromptOptions, Subscription, Window};
+use gpui_component::{button::Button, h_flex, v_flex, ActiveTheme};
 
-use dd_core::{AppState, Session};
+use dd_core::state::canonical_repo_path;
+use dd_core::{AppState, Preferences, RepoTab, ReviewNotes, Session, SidebarGroup};
+use dd_git::RepoError;
 
+use crate::diff_view::WorkingChanges;
 use crate::repo_view::RepoView;
 use crate::tab_bar::{TabBar, TabInfo};
+use crate::theme::repo_accent_color;
 
 actions!(
     dd_merge,
-    [OpenRepository, Quit, CloseTab, NextTab, PreviousTab]
+    [
+        OpenRepository,
+        OpenRepositoriesInFolder,
+        Quit,
+        CloseTab,
+        NextTab,
+        PreviousTab,
+        ToggleDiffFocus,
+        RefreshStatus,
+        ExportPatchSeries,
+        CheckoutFilesFromCommit,
+        ShowUnstagedChanges,
+        ShowStagedChanges,
+        ExportSession,
+        ImportSession,
+        ToggleSidebar,
+        ToggleCommitGraph,
+        ToggleTheme
+    ]
 );
 
+/// How many directory levels "Open all repos in folder" scans.
+const FOLDER_SCAN_DEPTH: usize = 1;
+
+/// File name "Export Session" suggests in its save dialog.
+const SESSION_EXPORT_FILE: &str = "dd_merge-session.json";
+
 pub struct AppView {
     state: AppState,
     repo_views: Vec<Entity<RepoView>>,
+    /// Unpinned tabs left closed by `open_pinned_only`, still saved with
+    /// the session.
+    unopened: Vec<RepoTab>,
     tab_bar: Entity<TabBar>,
     error_message: Option<String>,
+    _subscriptions: Vec<Subscription>,
 }
 
 impl AppView {
-    pub fn new(_window: &mut Window, cx: &mut Context<Self>) -> Self {
+    pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
         let mut state = Session::load().ok().flatten().unwrap_or_default();
 
-        // Filter out repos that no longer exist or aren't valid git repos
+        // Filter out repos that no longer exist or aren't valid git repos,
+        // keeping the previously active repo selected when it survives.
+        state.retain_repos(|tab| dd_git::Repository::open(&tab.path).is_ok());
         state
-            .repos
-            .retain(|tab| dd_git::Repository::open(&tab.path).is_ok());
-        state.active_tab = state.active_tab.min(state.repos.len().saturating_sub(1));
+            .recent
+            .retain(|path| dd_git::Repository::open(path).is_ok());
+        let unopened = if state.preferences.open_pinned_only {
+            state.split_off_unpinned()
+        } else {
+            Vec::new()
+        };
+        state.sort_pinned_first();
 
         let repo_views: Vec<_> = state
             .repos
             .iter()
-            .map(|tab| {
-                let path = tab.path.clone();
-                cx.new(|cx| RepoView::new(path, cx))
-            })
+            .map(|tab| Self::new_repo_view(tab.path.clone(), Some(tab), &state, cx))
             .collect();
 
+        crate::theme::setup_theme(state.appearance, cx);
         let tab_bar = cx.new(|_cx| TabBar::new());
 
+        // Files may have changed (or become ignored) while the app was in
+        // the background; recompute dirty state when the window regains focus.
+        let activation = cx.observe_window_activation(window, |view, window, cx| {
+            if window.is_window_active() {
+                view.refresh_status(cx);
+            }
+        });
+
         let mut view = Self {
             state,
             repo_views,
+            unopened,
             tab_bar,
             error_message: None,
+            _subscriptions: vec![activation],
         };
         view.setup_tab_bar(cx);
         view.sync_tab_bar(cx);
         view
     }
 
+    /// A view for the repo at `path`, restoring what `tab` saved for it.
+    fn new_repo_view(
+        path: PathBuf,
+        tab: Option<&RepoTab>,
+        state: &AppState,
+        cx: &mut Context<Self>,
+    ) -> Entity<RepoView> {
+        let this = cx.entity().downgrade();
+        let canonical = canonical_repo_path(&path);
+        cx.new(|cx| {
+            let mut view = RepoView::new(path, cx);
+            view.apply_preferences(&state.preferences, cx);
+            view.set_diff_view_mode(tab.and_then(|t| t.diff_view_mode.as_deref()), cx);
+            let select_path = canonical.clone();
+            let collapse_path = canonical.clone();
+            let mode_path = canonical.clone();
+            let this_mode = this.clone();
+            let notes = tab.map(|t| t.review_notes.clone()).unwrap_or_default();
+            let this_notes = this.clone();
+            view.diff_view().update(cx, |diff_view, cx| {
+                diff_view.set_review_notes(notes, cx);
+                diff_view.on_review_notes_change(move |notes, _window, cx| {
+                    // Deferred: the diff view is still being updated.
+                    let notes = notes.clone();
+                    let path = canonical.clone();
+                    let this = this_notes.clone();
+                    cx.defer(move |cx| {
+                        let _ = this
+                            .update(cx, |view, cx| view.set_repo_review_notes(&path, notes, cx));
+                    });
+                });
+                diff_view.on_preferred_mode_change(move |mode, _window, cx| {
+                    // Deferred: the diff view is still being updated.
+                    let path = mode_path.clone();
+                    let this = this_mode.clone();
+                    cx.defer(move |cx| {
+                        let _ = this.update(cx, |view, cx| {
+                            let index = view.state.repos.iter().position(|tab| tab.path == path);
+                            if let Some(index) = index {
+                                let mode = Some(mode.as_str().to_string());
+                                view.set_repo_diff_view_mode(index, mode, cx);
+                            }
+                        });
+                    });
+                });
+            });
+            if let Some(tab) = tab {
+                view.restore_ui_state(
+                    tab.selected_oid.clone(),
+                    &tab.collapsed_groups,
+                    &tab.collapsed_folders,
+                    cx,
+                );
+            }
+            let this_select = this.clone();
+            view.on_commit_select(move |oid, _window, cx| {
+                // Deferred: the selecting RepoView is still being updated.
+                let oid = oid.to_string();
+                let path = select_path.clone();
+                let this = this_select.clone();
+                cx.defer(move |cx| {
+                    let _ = this.update(cx, |view, cx| view.set_repo_selected_oid(&path, oid, cx));
+                });
+            });
+            let this_collapse = this.clone();
+            view.on_sidebar_collapse_change(move |groups, folders, _window, cx| {
+                // Deferred: the RepoView is still being updated.
+                let groups = groups.to_vec();
+                let folders = folders.to_vec();
+                let path = collapse_path.clone();
+                let this = this_collapse.clone();
+                cx.defer(move |cx| {
+                    let _ = this.update(cx, |view, cx| {
+                        view.set_repo_sidebar_collapse(&path, groups, folders, cx)
+                    });
+                });
+            });
+            view.set_sidebar_collapsed(state.sidebar_collapsed, cx);
+            view.on_sidebar_toggle(move |collapsed, _window, cx| {
+                // Deferred: the toggling RepoView is still being updated.
+                let this = this.clone();
+                cx.defer(move |cx| {
+                    let _ = this.update(cx, |view, cx| view.set_sidebar_collapsed(collapsed, cx));
+                });
+            });
+            view
+        })
+    }
+
     pub fn state(&self) -> &AppState {
         &self.state
     }
 
+    /// The state to save: the open tabs, then those `open_pinned_only`
+    /// left closed this run unless they were opened again since.
+    pub fn session_state(&self) -> AppState {
+        let mut state = self.state.clone();
+        let unopened: Vec<_> = self
+            .unopened
+            .iter()
+            .filter(|tab| !state.repos.iter().any(|r| r.path == tab.path))
+            .cloned()
+            .collect();
+        state.repos.extend(unopened);
+        state
+    }
+
     pub fn error_message(&self) -> Option<&str> {
         self.error_message.as_deref()
     }
@@ -96,6 +246,13 @@ impl AppView {
                 });
             });
 
+            let this_pin = this.clone();
+            bar.on_toggle_pin(move |index, _window, cx| {
+                let _ = this_pin.update(cx, |view, cx| {
+                    view.toggle_repo_pinned(index, cx);
+                });
+            });
+
             bar.on_close(move |index, _window, cx| {
                 let _ = this.update(cx, |view, cx| {
                     view.remove_repo(index, cx);
@@ -105,28 +262,54 @@ impl AppView {
     }
 
     fn sync_tab_bar(&mut self, cx: &mut Context<Self>) {
-        let tabs: Vec<TabInfo> = self
-            .state
-            .repos
-            .iter()
-            .enumerate()
-            .map(|(i, tab)| {
-                let is_dirty = dd_git::Repository::open(&tab.path)
-                    .map(|r| r.is_dirty().unwrap_or(false))
-                    .unwrap_or(false);
-                TabInfo {
-                    name: tab.name.clone(),
-                    is_active: i == self.state.active_tab,
-                    is_dirty,
+        let mut probe_error = None;
+        let mut tabs = Vec::with_capacity(self.state.repos.len());
+        for (i, tab) in self.state.repos.iter().enumerate() {
+            let probe = match self.repo_views.get(i) {
+                Some(repo_view) => repo_view.update(cx, |view, _cx| view.is_dirty()),
+                None => dd_git::Repository::open(&tab.path).and_then(|r| r.is_dirty()),
+            };
+            let is_dirty = probe.unwrap_or_else(|e| {
+                // A tab that can no longer be read is not "clean"; say why
+                // instead of hiding the failure.
+                let error = RepoError::classify(&tab.path, &e);
+                if error.is_permission_denied() {
+                    probe_error.get_or_insert(error.to_string());
                 }
-            })
-            .collect();
+                false
+            });
+            tabs.push(TabInfo {
+                name: tab.name.clone(),
+                is_active: i == self.state.active_tab,
+                is_dirty,
+                pinned: tab.pinned,
+                color: tab
+                    .color
+                    .map(|c| gpui::rgb(c).into())
+                    .unwrap_or_else(|| repo_accent_color(&tab.path)),
+            });
+        }
 
         self.tab_bar.update(cx, |bar: &mut TabBar, cx| {
             bar.set_tabs(tabs, cx);
         });
+        if let Some(message) = probe_error {
+            self.error_message = Some(message);
+            cx.notify();
+        }
     }
 
+    /// Where save dialogs start browsing: the last opened repository's
+    /// parent, else the configured default directory.
+    pub fn open_dialog_start_dir(&self) -> Option<PathBuf> {
+        self.state
+            .preferences
+            .open_dialog_start_dir()
+            .map(PathBuf::from)
+    }
+
+    // gpui's `PathPromptOptions` has no initial-directory field, so unlike
+    // the save dialogs this one can't start in `open_dialog_start_dir`.
     pub fn open_repository_dialog(&mut self, cx: &mut Context<Self>) {
         let receiver = cx.prompt_for_paths(PathPromptOptions {
             files: false,
@@ -149,35 +332,146 @@ impl AppView {
         .detach();
     }
 
+    pub fn open_folder_dialog(&mut self, cx: &mut Context<Self>) {
+        let receiver = cx.prompt_for_paths(PathPromptOptions {
+            files: false,
+            directories: true,
+            multiple: false,
+            prompt: Some("Open All Repositories in Folder".into()),
+        });
+
+        cx.spawn(async move |this, cx| {
+            if let Ok(Ok(Some(paths))) = receiver.await {
+                if let Some(path) = paths.into_iter().next() {
+                    let _ = cx.update(|cx| {
+                        this.update(cx, |view, cx| {
+                            view.add_repos_from_folder(path, cx);
+                        })
+                    });
+                }
+            }
+        })
+        .detach();
+    }
+
+    /// Ask where to save the open repos as a session file. The platform's
+    /// save dialog confirms before replacing an existing file.
+    pub fn export_session_dialog(&mut self, cx: &mut Context<Self>) {
+        let dir = self
+            .open_dialog_start_dir()
+            .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
+        let receiver = cx.prompt_for_new_path(&dir, Some(SESSION_EXPORT_FILE));
+        let state = self.session_state();
+
+        cx.spawn(async move |this, cx| {
+            if let Ok(Ok(Some(path))) = receiver.await {
+                if let Err(e) = Session::export_to(&path, &state) {
+                    let _ = cx.update(|cx| {
+                        this.update(cx, |view, cx| {
+                            view.error_message = Some(format!("{e:#}"));
+                            cx.notify();
+                        })
+                    });
+                }
+            }
+        })
+        .detach();
+    }
+
+    pub fn import_session_dialog(&mut self, cx: &mut Context<Self>) {
+        let receiver = cx.prompt_for_paths(PathPromptOptions {
+            files: true,
+            directories: false,
+            multiple: false,
+            prompt: Some("Import Session".into()),
+        });
+
+        cx.spawn(async move |this, cx| {
+            if let Ok(Ok(Some(paths))) = receiver.await {
+                if let Some(path) = paths.into_iter().next() {
+                    let _ = cx.update(|cx| {
+                        this.update(cx, |view, cx| {
+                            view.import_session(path, cx);
+                        })
+                    });
+                }
+            }
+        })
+        .detach();
+    }
+
+    /// Replace the open repos with those in an exported session file. Repos
+    /// that are missing or no longer git repositories are skipped; local
+    /// preferences are kept.
+    pub fn import_session(&mut self, path: PathBuf, cx: &mut Context<Self>) {
+        let mut imported = match Session::import_from(&path) {
+            Ok(imported) => imported,
+            Err(e) => {
+                self.error_message = Some(format!("{e:#}"));
+                cx.notify();
+                return;
+            }
+        };
+        imported.retain_repos(|tab| dd_git::Repository::open(&tab.path).is_ok());
+
+        self.repo_views = imported
+            .repos
+            .iter()
+            .map(|tab| Self::new_repo_view(tab.path.clone(), Some(tab), &self.state, cx))
+            .collect();
+        self.state.repos = imported.repos;
+        self.state.active_tab = imported.active_tab;
+        self.unopened.clear();
+        self.error_message = None;
+        self.sync_tab_bar(cx);
+        cx.notify();
+    }
+
+    /// Add every git repository found directly inside `dir` as a tab.
+    pub fn add_repos_from_folder(&mut self, dir: PathBuf, cx: &mut Context<Self>) {
+        let repos = dd_git::find_git_repos(&dir, FOLDER_SCAN_DEPTH);
+        if repos.is_empty() {
+            self.error_message = Some(format!("No git repositories found in {}", dir.display()));
+            cx.notify();
+            return;
+        }
+        for path in repos {
+            self.try_add_repo(path, cx);
+        }
+    }
+
     pub fn try_add_repo(&mut self, path: PathBuf, cx: &mut Context<Self>) {
-        if self.state.repos.iter().any(|r| r.path == path) {
+        let canonical = canonical_repo_path(&path);
+        if self.state.repos.iter().any(|r| r.path == canonical) {
             return;
         }
 
         match dd_git::Repository::open(&path) {
             Ok(_) => {
                 self.error_message = None;
+                self.state.preferences.last_opened_dir = path.parent().map(PathBuf::from);
                 self.state.add_repo(path.clone());
-                let repo_view = cx.new(|cx| RepoView::new(path, cx));
+                let repo_view = Self::new_repo_view(path, None, &self.state, cx);
                 self.repo_views.push(repo_view);
                 self.sync_tab_bar(cx);
                 cx.notify();
             }
-            Err(_) => {
-                self.error_message = Some(format!("{} is not a git repository", path.display()));
+            Err(e) => {
+                self.error_message = Some(RepoError::classify(&path, &e).to_string());
                 cx.notify();
             }
         }
     }
 
     pub fn reorder_repo(&mut self, from: usize, to: usize, cx: &mut Context<Self>) {
-        let len = self.repo_views.len();
-        if from == to || from >= len || to >= len {
+        if from >= self.repo_views.len() {
             return;
         }
+        let Some(to) = self.state.reorder_repos(from, to) else {
+            return;
+        };
         let view = self.repo_views.remove(from);
         self.repo_views.insert(to, view);
-        self.state.reorder_repos(from, to);
         cx.notify();
         let entity = cx.entity().downgrade();
         cx.defer(move |cx| {
@@ -205,17 +499,32 @@ impl AppView {
 
     fn render_welcome(&mut self, cx: &mut Context<Self>) -> impl IntoElement {
         let error = self.error_message.clone();
+        let recent = self.render_recent_repos(cx);
+        let branding = &self.state.preferences.branding;
 
         v_flex()
             .size_full()
             .items_center()
             .justify_center()
             .gap_4()
-            .child(gpui::div().text_xl().child("DD Merge"))
+            .when_some(branding.logo.clone(), |el, logo| {
+                el.child(
+                    gpui::img(logo)
+                        .debug_selector(|| "welcome-logo".into())
+                        .size(px(96.0)),
+                )
+            })
             .child(
                 gpui::div()
+                    .text_xl()
+                    .debug_selector(|| "welcome-title".into())
+                    .child(branding.title().to_string()),
+            )
+            .child(
+                gpui::div()
+                    .debug_selector(|| "welcome-message".into())
                     .text_color(cx.theme().muted_foreground)
-                    .child("Open a git repository to get started"),
+                    .child(branding.welcome_message().to_string()),
             )
             .child(
                 Button::new("open-repo")
@@ -224,9 +533,53 @@ impl AppView {
                         view.open_repository_dialog(cx);
                     })),
             )
+            .children(recent)
             .children(error.map(|msg| gpui::div().text_color(gpui::red()).child(msg)))
     }
 
+    /// Clickable list of recently used repositories, if there are any.
+    fn render_recent_repos(&self, cx: &Context<Self>) -> Option<impl IntoElement> {
+        if self.state.recent.is_empty() {
+            return None;
+        }
+        let theme = cx.theme();
+        let rows = self.state.recent.iter().enumerate().map(|(i, path)| {
+            let name = path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| path.display().to_string());
+            let open_path = path.clone();
+            h_flex()
+                .id(("recent-repo", i))
+                .debug_selector(move || format!("recent-repo-{i}"))
+                .gap_2()
+                .cursor_pointer()
+                .child(gpui::div().text_color(theme.link).child(name))
+                .child(
+                    gpui::div()
+                        .text_xs()
+                        .text_color(theme.muted_foreground)
+                        .child(path.display().to_string()),
+                )
+                .on_click(cx.listener(move |view, _event, _window, cx| {
+                    view.try_add_repo(open_path.clone(), cx);
+                }))
+        });
+        Some(
+            v_flex()
+                .debug_selector(|| "welcome-recent".into())
+                .items_center()
+                .gap_1()
+                .child(
+                    gpui::div()
+                        .text_sm()
+                        .text_color(theme.muted_foreground)
+                        .child("Recent"),
+                )
+                .children(rows),
+        )
+    }
+
     pub fn set_active_tab(&mut self, index: usize, cx: &mut Context<Self>) {
         if index < self.state.repos.len() {
             self.state.active_tab = index;
@@ -235,6 +588,101 @@ impl AppView {
         }
     }
 
+    /// Replace the preferences and push them down to every open repo.
+    pub fn set_preferences(&mut self, preferences: Preferences, cx: &mut Context<Self>) {
+        self.state.preferences = preferences;
+        for repo_view in &self.repo_views {
+            repo_view.update(cx, |view, cx| {
+                view.apply_preferences(&self.state.preferences, cx);
+            });
+        }
+        cx.notify();
+    }
+
+    /// Override the accent color of a tab (`0xRRGGBB`), or reset it to the
+    /// path-derived default with `None`. Persisted with the session.
+    pub fn set_repo_color(&mut self, index: usize, color: Option<u32>, cx: &mut Context<Self>) {
+        if index < self.state.repos.len() {
+            self.state.set_repo_color(index, color);
+            self.sync_tab_bar(cx);
+            cx.notify();
+        }
+    }
+
+    /// Remember a repo's diff layout (`"unified"`/`"split"`, or `None` to
+    /// follow the window width) and apply it. Persisted with the session.
+    pub fn set_repo_diff_view_mode(
+        &mut self,
+        index: usize,
+        mode: Option<String>,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(repo_view) = self.repo_views.get(index) else {
+            return;
+        };
+        repo_view.update(cx, |view, cx| view.set_diff_view_mode(mode.as_deref(), cx));
+        self.state.set_repo_diff_view_mode(index, mode);
+        cx.notify();
+    }
+
+    /// Store the review notes of the repo at `path`. Persisted with the
+    /// session.
+    pub fn set_repo_review_notes(
+        &mut self,
+        path: &Path,
+        notes: ReviewNotes,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some(tab) = self.state.repos.iter_mut().find(|tab| tab.path == path) {
+            tab.review_notes = notes;
+            cx.notify();
+        }
+    }
+
+    /// Remember the commit selected in the repo at `path`. Persisted with
+    /// the session.
+    pub fn set_repo_selected_oid(&mut self, path: &Path, oid: String, cx: &mut Context<Self>) {
+        if let Some(tab) = self.state.repos.iter_mut().find(|tab| tab.path == path) {
+            tab.selected_oid = Some(oid);
+            cx.notify();
+        }
+    }
+
+    /// Remember which sidebar groups and branch folders are folded in the
+    /// repo at `path`. Persisted with the session.
+    pub fn set_repo_sidebar_collapse(
+        &mut self,
+        path: &Path,
+        groups: Vec<SidebarGroup>,
+        folders: Vec<String>,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some(tab) = self.state.repos.iter_mut().find(|tab| tab.path == path) {
+            tab.collapsed_groups = groups;
+            tab.collapsed_folders = folders;
+            cx.notify();
+        }
+    }
+
+    /// Flip a tab's pinned flag and move pinned tabs to the front.
+    /// Persisted with the session.
+    pub fn toggle_repo_pinned(&mut self, index: usize, cx: &mut Context<Self>) {
+        let Some(pinned) = self.state.repos.get(index).map(|r| !r.pinned) else {
+            return;
+        };
+        let order = self.state.set_repo_pinned(index, pinned);
+        let mut views: Vec<_> = self.repo_views.drain(..).map(Some).collect();
+        self.repo_views = order.iter().filter_map(|&i| views[i].take()).collect();
+        cx.notify();
+        // Deferred: this may run from a TabBar callback.
+        let entity = cx.entity().downgrade();
+        cx.defer(move |cx| {
+            let _ = entity.update(cx, |view, cx| {
+                view.sync_tab_bar(cx);
+            });
+        });
+    }
+
     pub fn close_active_tab(&mut self, cx: &mut Context<Self>) {
         if !self.state.repos.is_empty() {
             let index = self.state.active_tab.min(self.state.repos.len() - 1);
@@ -251,6 +699,71 @@ impl AppView {
         }
     }
 
+    /// Recompute dirty state for every tab and the active repo's summary.
+    pub fn refresh_status(&mut self, cx: &mut Context<Self>) {
+        for repo_view in &self.repo_views {
+            repo_view.update(cx, |view, _cx| view.expire_dirty_state());
+        }
+        let active = self.state.active_tab;
+        if let Some(repo_view) = self.repo_views.get(active) {
+            repo_view.update(cx, |view, cx| view.refresh_status(cx));
+        }
+        self.sync_tab_bar(cx);
+        cx.notify();
+    }
+
+    /// Export the active repo's selected commits as a patch series.
+    pub fn export_patch_series(&mut self, cx: &mut Context<Self>) {
+        let active = self.state.active_tab;
+        if let Some(repo_view) = self.repo_views.get(active) {
+            repo_view.update(cx, |view, cx| view.export_patch_series(cx));
+        }
+    }
+
+    pub fn checkout_files_from_commit(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let active = self.state.active_tab;
+        if let Some(repo_view) = self.repo_views.get(active) {
+            repo_view.update(cx, |view, cx| view.checkout_files_from_commit(window, cx));
+        }
+    }
+
+    /// Collapse or restore the sidebar in every tab; persisted with the
+    /// session.
+    pub fn set_sidebar_collapsed(&mut self, collapsed: bool, cx: &mut Context<Self>) {
+        self.state.sidebar_collapsed = collapsed;
+        for repo_view in &self.repo_views {
+            repo_view.update(cx, |view, cx| view.set_sidebar_collapsed(collapsed, cx));
+        }
+        cx.notify();
+    }
+
+    pub fn toggle_sidebar(&mut self, cx: &mut Context<Self>) {
+        self.set_sidebar_collapsed(!self.state.sidebar_collapsed, cx);
+    }
+
+    /// Switch between the dark and light theme. Persisted with the session.
+    pub fn toggle_theme(&mut self, cx: &mut Context<Self>) {
+        self.state.appearance = self.state.appearance.toggled();
+        crate::theme::setup_theme(self.state.appearance, cx);
+        cx.notify();
+    }
+
+    /// Show or hide the commit graph column in every repo. Persisted with
+    /// the preferences.
+    pub fn toggle_commit_graph(&mut self, cx: &mut Context<Self>) {
+        let mut preferences = self.state.preferences.clone();
+        preferences.show_commit_graph = !preferences.show_commit_graph;
+        self.set_preferences(preferences, cx);
+    }
+
+    /// Toggle full-width diff mode in the active repo.
+    pub fn toggle_diff_focus(&mut self, cx: &mut Context<Self>) {
+        let active = self.state.active_tab;
+        if let Some(repo_view) = self.repo_views.get(active) {
+            repo_view.update(cx, |view, cx| view.toggle_diff_focus(cx));
+        }
+    }
+
     pub fn previous_tab(&mut self, cx: &mut Context<Self>) {
         let len = self.state.repos.len();
         if len > 1 {
@@ -259,6 +772,14 @@ impl AppView {
             cx.notify();
         }
     }
+
+    /// Show the active repo's unstaged or staged changes in its diff pane.
+    pub fn show_working_changes(&mut self, changes: WorkingChanges, cx: &mut Context<Self>) {
+        let active = self.state.active_tab;
+        if let Some(repo_view) = self.repo_views.get(active) {
+            repo_view.update(cx, |view, cx| view.show_working_changes(changes, cx));
+        }
+    }
 }
 
 impl Render for AppView {
@@ -296,8 +817,12 @@ impl Render for AppView {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::test_helpers::{init_test_repo, init_test_theme};
-    use gpui::TestAppContext;
+    use crate::diff_view::DiffViewMode;
+    use crate::test_helpers::{
+        init_test_repo, init_test_repo_at, init_test_repo_with_changes, init_test_theme,
+    };
+    use crate::theme::DiffTheme;
+    use gpui::{rems, Rems, TestAppContext, VisualTestContext};
 
     #[gpui::test]
     fn test_fresh_start_has_no_repos(cx: &mut TestAppContext) {
@@ -313,6 +838,74 @@ mod tests {
             .unwrap();
     }
 
+    #[gpui::test]
+    fn test_configured_welcome_branding_renders(cx: &mut TestAppContext) {
+        cx.update(|cx| init_test_theme(cx));
+        let (view, cx) = cx.add_window_view(|window, cx| AppView::new(window, cx));
+        cx.run_until_parked();
+        assert!(cx.debug_bounds("welcome-message").is_some());
+        assert!(cx.debug_bounds("welcome-logo").is_none());
+
+        let mut preferences = Preferences::default();
+        preferences.branding.title = Some("Acme Review".into());
+        preferences.branding.welcome_message = Some("Pick a repo to review".into());
+        preferences.branding.logo = Some(PathBuf::from("/nonexistent/logo.png"));
+        view.update(cx, |view, cx| view.set_preferences(preferences, cx));
+        cx.run_until_parked();
+        assert!(cx.debug_bounds("welcome-logo").is_some());
+
+        // The labels shrink to their text, so a label shows the configured
+        // string when it is as wide as that string shaped in its font size.
+        let shows =
+            |selector: &'static str, text: &'static str, size: Rems, cx: &mut VisualTestContext| {
+                let width = cx.debug_bounds(selector).unwrap().size.width;
+                let shaped = cx.update(|window, _cx| {
+                    let style = window.text_style();
+                    let size = size.to_pixels(window.rem_size());
+                    window
+                        .text_system()
+                        .shape_line(text.into(), size, &[style.to_run(text.len())], None)
+                        .width
+                });
+                (width - shaped).abs() < px(1.0)
+            };
+        assert!(shows("welcome-title", "Acme Review", rems(1.25), cx));
+        assert!(!shows("welcome-title", "DD Merge", rems(1.25), cx));
+        assert!(shows(
+            "welcome-message",
+            "Pick a repo to review",
+            rems(1.0),
+            cx
+        ));
+    }
+
+    #[gpui::test]
+    fn test_closed_repo_can_be_reopened_from_welcome(cx: &mut TestAppContext) {
+        cx.update(|cx| init_test_theme(cx));
+        let dir = init_test_repo();
+        let (view, cx) = cx.add_window_view(|window, cx| AppView::new(window, cx));
+
+        view.update(cx, |view, cx| {
+            view.try_add_repo(dir.path().to_path_buf(), cx);
+            view.remove_repo(0, cx);
+        });
+        cx.run_until_parked();
+        assert!(cx.debug_bounds("welcome-recent").is_some());
+
+        let row = cx
+            .debug_bounds("recent-repo-0")
+            .expect("recent repo listed");
+        cx.simulate_click(row.center(), gpui::Modifiers::none());
+        cx.run_until_parked();
+
+        view.read_with(cx, |view, _cx| {
+            assert_eq!(view.state().repos.len(), 1);
+            assert_eq!(view.repo_view_count(), 1);
+            let canonical = canonical_repo_path(dir.path());
+            assert_eq!(view.state().recent.first(), Some(&canonical));
+        });
+    }
+
     #[gpui::test]
     fn test_add_valid_repo(cx: &mut TestAppContext) {
         cx.update(|cx| init_test_theme(cx));
@@ -334,6 +927,278 @@ mod tests {
             .unwrap();
     }
 
+    #[gpui::test]
+    fn test_toggle_commit_graph_hides_graph_column(cx: &mut TestAppContext) {
+        cx.update(|cx| init_test_theme(cx));
+        let dir = init_test_repo();
+        let (view, cx) = cx.add_window_view(|window, cx| AppView::new(window, cx));
+
+        view.update(cx, |view, cx| {
+            view.try_add_repo(dir.path().to_path_buf(), cx)
+        });
+        cx.run_until_parked();
+        assert!(cx.debug_bounds("commit-graph-0").is_some());
+
+        // Debug bounds outlive the frame that drew them, so the hidden
+        // column is checked on the list the rows are built from.
+        let graph_shown = |view: &AppView, cx: &gpui::App| {
+            let list = view.repo_views[0].read(cx).commit_list().read(cx);
+            list.graph_row(0).is_some()
+        };
+        view.update(cx, |view, cx| view.toggle_commit_graph(cx));
+        cx.run_until_parked();
+        view.read_with(cx, |view, cx| {
+            assert!(!view.state().preferences.show_commit_graph);
+            assert!(!graph_shown(view, cx));
+        });
+
+        view.update(cx, |view, cx| view.toggle_commit_graph(cx));
+        cx.run_until_parked();
+        view.read_with(cx, |view, cx| assert!(graph_shown(view, cx)));
+        assert!(cx.debug_bounds("commit-graph-0").is_some());
+    }
+
+    #[gpui::test]
+    fn test_toggle_theme_switches_diff_colors_to_light(cx: &mut TestAppContext) {
+        cx.update(|cx| init_test_theme(cx));
+        let window = cx.add_window(|window, cx| AppView::new(window, cx));
+        let palette = dd_core::DiffPalette::default();
+
+        window
+            .update(cx, |view, _window, cx| {
+                view.state.appearance = dd_core::Appearance::Dark;
+                crate::theme::setup_dark_theme(cx);
+                assert!(DiffTheme::from_cx(cx, &palette).add_bg.l < 0.5);
+
+                view.toggle_theme(cx);
+                assert_eq!(view.state().appearance, dd_core::Appearance::Light);
+                let diff_theme = DiffTheme::from_cx(cx, &palette);
+                assert!(diff_theme.add_bg.l > 0.5);
+                assert!(diff_theme.del_bg.l > 0.5);
+                assert!(cx.theme().background.l > 0.5);
+
+                view.toggle_theme(cx);
+                assert!(DiffTheme::from_cx(cx, &palette).add_bg.l < 0.5);
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    fn test_import_session_replaces_open_repos(cx: &mut TestAppContext) {
+        cx.update(|cx| init_test_theme(cx));
+        let repo = init_test_repo();
+        let not_a_repo = tempfile::TempDir::new().unwrap();
+        let export_dir = tempfile::TempDir::new().unwrap();
+        let session_file = export_dir.path().join(SESSION_EXPORT_FILE);
+
+        let mut exported = AppState::default();
+        exported.add_repo(not_a_repo.path().to_path_buf());
+        exported.add_repo(repo.path().to_path_buf());
+        Session::export_to(&session_file, &exported).unwrap();
+
+        let window = cx.add_window(|window, cx| AppView::new(window, cx));
+        window
+            .update(cx, |view, _window, cx| {
+                view.import_session(session_file, cx);
+            })
+            .unwrap();
+
+        window
+            .read_with(cx, |view, _cx| {
+                assert!(view.error_message().is_none());
+                assert_eq!(view.state().repos.len(), 1);
+                assert_eq!(view.repo_view_count(), 1);
+                assert_eq!(view.state().active_tab, 0);
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    fn test_export_session_writes_chosen_file(cx: &mut TestAppContext) {
+        cx.update(|cx| init_test_theme(cx));
+        let parent = tempfile::TempDir::new().unwrap();
+        let repo_path = parent.path().join("project");
+        init_test_repo_at(&repo_path);
+        let export_dir = tempfile::TempDir::new().unwrap();
+        let session_file = export_dir.path().join("team.json");
+        let window = cx.add_window(|window, cx| AppView::new(window, cx));
+
+        window
+            .update(cx, |view, _window, cx| {
+                view.try_add_repo(repo_path, cx);
+                view.export_session_dialog(cx);
+            })
+            .unwrap();
+        cx.simulate_new_path_selection(|dir| {
+            assert_eq!(dir, parent.path());
+            Some(session_file.clone())
+        });
+        cx.run_until_parked();
+
+        let exported = Session::import_from(&session_file).unwrap();
+        assert_eq!(exported.repos.len(), 1);
+        assert_eq!(exported.repos[0].name, "project");
+    }
+
+    #[gpui::test]
+    fn test_imported_diff_view_mode_is_restored(cx: &mut TestAppContext) {
+        cx.update(|cx| init_test_theme(cx));
+        let repo = init_test_repo();
+        let export_dir = tempfile::TempDir::new().unwrap();
+        let session_file = export_dir.path().join(SESSION_EXPORT_FILE);
+
+        let mut exported = AppState::default();
+        exported.add_repo(repo.path().to_path_buf());
+        exported.set_repo_diff_view_mode(0, Some("split".into()));
+        Session::export_to(&session_file, &exported).unwrap();
+
+        let window = cx.add_window(|window, cx| AppView::new(window, cx));
+        window
+            .update(cx, |view, _window, cx| {
+                view.import_session(session_file, cx);
+                let diff_view = view.repo_views[0].read(cx).diff_view().read(cx);
+                assert_eq!(diff_view.preferred_mode(), Some(DiffViewMode::Split));
+                assert_eq!(diff_view.mode(), DiffViewMode::Split);
+
+                view.set_repo_diff_view_mode(0, None, cx);
+                assert_eq!(view.state().repos[0].diff_view_mode, None);
+                let diff_view = view.repo_views[0].read(cx).diff_view().read(cx);
+                assert_eq!(diff_view.preferred_mode(), None);
+            })
+            .unwrap();
+
+        // Cycling the layout in the diff view is saved for the repo.
+        window
+            .update(cx, |view, window, cx| {
+                let diff_view = view.repo_views[0].read(cx).diff_view().clone();
+                diff_view.update(cx, |diff_view, cx| {
+                    diff_view.set_preferred_mode(Some(DiffViewMode::Unified), cx);
+                    diff_view.cycle_view_mode(window, cx);
+                });
+            })
+            .unwrap();
+        cx.run_until_parked();
+        window
+            .read_with(cx, |view, _cx| {
+                let mode = view.state().repos[0].diff_view_mode.as_deref();
+                assert_eq!(mode, Some("split"));
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    fn test_repo_ui_state_is_restored_and_saved(cx: &mut TestAppContext) {
+        cx.update(|cx| init_test_theme(cx));
+        let repo = init_test_repo_with_changes();
+        let export_dir = tempfile::TempDir::new().unwrap();
+        let session_file = export_dir.path().join(SESSION_EXPORT_FILE);
+        let commits = dd_git::Repository::open(repo.path())
+            .unwrap()
+            .commits(10)
+            .unwrap();
+        let older = commits[1].oid.clone();
+
+        let mut exported = AppState::default();
+        exported.add_repo(repo.path().to_path_buf());
+        exported.repos[0].selected_oid = Some(older.clone());
+        exported.repos[0].collapsed_groups = vec![SidebarGroup::Tags];
+        exported.repos[0].collapsed_folders = vec!["feat".into()];
+        Session::export_to(&session_file, &exported).unwrap();
+
+        let window = cx.add_window(|window, cx| AppView::new(window, cx));
+        window
+            .update(cx, |view, _window, cx| {
+                view.import_session(session_file, cx)
+            })
+            .unwrap();
+        cx.run_until_parked();
+
+        window
+            .read_with(cx, |view, cx| {
+                let repo_view = view.repo_views[0].read(cx);
+                let list = repo_view.commit_list().read(cx);
+                let selected = list.selected_index().map(|i| list.commits()[i].oid.clone());
+                assert_eq!(selected, Some(older.clone()));
+                let sidebar = repo_view.sidebar().read(cx);
+                assert!(sidebar.is_collapsed(SidebarGroup::Tags));
+                assert!(!sidebar.is_collapsed(SidebarGroup::Branches));
+                assert!(sidebar.is_folder_collapsed("feat"));
+            })
+            .unwrap();
+
+        // Selecting another commit is pushed back into the session state.
+        window
+            .update(cx, |view, window, cx| {
+                let list = view.repo_views[0].read(cx).commit_list().clone();
+                list.update(cx, |list, cx| list.select_commit(0, window, cx));
+            })
+            .unwrap();
+        cx.run_until_parked();
+        window
+            .read_with(cx, |view, _cx| {
+                let tab = &view.state().repos[0];
+                assert_eq!(tab.selected_oid.as_deref(), Some(commits[0].oid.as_str()));
+                assert_eq!(tab.collapsed_groups, vec![SidebarGroup::Tags]);
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    fn test_review_note_is_saved_to_session_state(cx: &mut TestAppContext) {
+        cx.update(|cx| init_test_theme(cx));
+        let dir = init_test_repo();
+        let window = cx.add_window(|window, cx| AppView::new(window, cx));
+
+        window
+            .update(cx, |view, window, cx| {
+                view.try_add_repo(dir.path().to_path_buf(), cx);
+                let diff_view = view.repo_views[0].read(cx).diff_view().clone();
+                diff_view.update(cx, |diff_view, cx| {
+                    diff_view.set_review_note(
+                        "abc123",
+                        "file.txt",
+                        1,
+                        Some("double-check this".into()),
+                        window,
+                        cx,
+                    );
+                });
+            })
+            .unwrap();
+        cx.run_until_parked();
+
+        window
+            .read_with(cx, |view, _cx| {
+                let notes = &view.state().repos[0].review_notes;
+                assert_eq!(
+                    notes.get("abc123", "file.txt", 1),
+                    Some("double-check this")
+                );
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    fn test_add_repo_remembers_parent_directory(cx: &mut TestAppContext) {
+        cx.update(|cx| init_test_theme(cx));
+        let parent = tempfile::TempDir::new().unwrap();
+        let repo_path = parent.path().join("project");
+        init_test_repo_at(&repo_path);
+        let window = cx.add_window(|window, cx| AppView::new(window, cx));
+
+        window
+            .update(cx, |view, _window, cx| {
+                view.try_add_repo(repo_path, cx);
+            })
+            .unwrap();
+
+        window
+            .read_with(cx, |view, _cx| {
+                assert_eq!(view.open_dialog_start_dir().as_deref(), Some(parent.path()));
+            })
+            .unwrap();
+    }
+
     #[gpui::test]
     fn test_add_invalid_path_shows_error(cx: &mut TestAppContext) {
         cx.update(|cx| init_test_theme(cx));
@@ -355,6 +1220,36 @@ mod tests {
             .unwrap();
     }
 
+    #[cfg(unix)]
+    #[gpui::test]
+    fn test_symlink_and_target_are_same_repo(cx: &mut TestAppContext) {
+        cx.update(|cx| init_test_theme(cx));
+        let dir = init_test_repo();
+        let links = tempfile::TempDir::new().unwrap();
+        let link = links.path().join("linked");
+        std::os::unix::fs::symlink(dir.path(), &link).unwrap();
+        let window = cx.add_window(|window, cx| AppView::new(window, cx));
+
+        window
+            .update(cx, |view, _window, cx| {
+                view.try_add_repo(link.clone(), cx);
+                view.try_add_repo(dir.path().to_path_buf(), cx);
+            })
+            .unwrap();
+
+        window
+            .read_with(cx, |view, _cx| {
+                assert_eq!(view.state().repos.len(), 1);
+                assert_eq!(view.repo_view_count(), 1);
+                assert_eq!(view.state().repos[0].name, "linked");
+                assert_eq!(
+                    view.state().repos[0].path,
+                    dir.path().canonicalize().unwrap()
+                );
+            })
+            .unwrap();
+    }
+
     #[gpui::test]
     fn test_add_multiple_repos(cx: &mut TestAppContext) {
         cx.update(|cx| init_test_theme(cx));
@@ -378,6 +1273,102 @@ mod tests {
             .unwrap();
     }
 
+    #[gpui::test]
+    fn test_add_repos_from_folder(cx: &mut TestAppContext) {
+        cx.update(|cx| init_test_theme(cx));
+        let parent = tempfile::TempDir::new().unwrap();
+        init_test_repo_at(&parent.path().join("one"));
+        init_test_repo_at(&parent.path().join("two"));
+        std::fs::create_dir(parent.path().join("not-a-repo")).unwrap();
+        let window = cx.add_window(|window, cx| AppView::new(window, cx));
+
+        window
+            .update(cx, |view, _window, cx| {
+                view.add_repos_from_folder(parent.path().to_path_buf(), cx);
+            })
+            .unwrap();
+
+        window
+            .read_with(cx, |view, _cx| {
+                let names: Vec<&str> = view.state().repos.iter().map(|r| r.name.as_str()).collect();
+                assert_eq!(names, ["one", "two"]);
+                assert_eq!(view.repo_view_count(), 2);
+                assert!(view.error_message().is_none());
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    fn test_add_repos_from_empty_folder_shows_error(cx: &mut TestAppContext) {
+        cx.update(|cx| init_test_theme(cx));
+        let parent = tempfile::TempDir::new().unwrap();
+        let window = cx.add_window(|window, cx| AppView::new(window, cx));
+
+        window
+            .update(cx, |view, _window, cx| {
+                view.add_repos_from_folder(parent.path().to_path_buf(), cx);
+            })
+            .unwrap();
+
+        window
+            .read_with(cx, |view, _cx| {
+                assert!(view.state().repos.is_empty());
+                assert!(view.error_message().is_some());
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    fn test_refresh_status_recomputes_dirty_after_gitignore_change(cx: &mut TestAppContext) {
+        cx.update(|cx| init_test_theme(cx));
+        let dir = init_test_repo();
+        let window = cx.add_window(|window, cx| AppView::new(window, cx));
+
+        window
+            .update(cx, |view, _window, cx| {
+                view.try_add_repo(dir.path().to_path_buf(), cx);
+            })
+            .unwrap();
+
+        let tab_dirty = |cx: &mut TestAppContext| {
+            window
+                .read_with(cx, |view, cx| view.tab_bar().read(cx).tabs()[0].is_dirty)
+                .unwrap()
+        };
+
+        std::fs::write(dir.path().join("scratch.log"), "noise").unwrap();
+        window
+            .update(cx, |view, _window, cx| view.refresh_status(cx))
+            .unwrap();
+        assert!(tab_dirty(cx));
+
+        std::fs::write(dir.path().join(".gitignore"), "*.log\n.gitignore\n").unwrap();
+        window
+            .update(cx, |view, _window, cx| view.refresh_status(cx))
+            .unwrap();
+        assert!(!tab_dirty(cx));
+    }
+
+    #[gpui::test]
+    fn test_sync_tab_bar_reuses_repository_handle(cx: &mut TestAppContext) {
+        cx.update(|cx| init_test_theme(cx));
+        let dir = init_test_repo();
+        let window = cx.add_window(|window, cx| AppView::new(window, cx));
+
+        window
+            .update(cx, |view, _window, cx| {
+                view.try_add_repo(dir.path().to_path_buf(), cx);
+                for _ in 0..5 {
+                    view.sync_tab_bar(cx);
+                }
+                // Refreshing walks the work tree again, on the same handle.
+                view.refresh_status(cx);
+                let opens = view.repo_views[0].read(cx).repo_opens;
+                assert_eq!(opens, 1);
+            })
+            .unwrap();
+    }
+
     #[gpui::test]
     fn test_remove_repo(cx: &mut TestAppContext) {
         cx.update(|cx| init_test_theme(cx));
@@ -436,6 +1427,26 @@ mod tests {
             .unwrap();
     }
 
+    #[gpui::test]
+    fn test_set_repo_color(cx: &mut TestAppContext) {
+        cx.update(|cx| init_test_theme(cx));
+        let dir = init_test_repo();
+        let window = cx.add_window(|window, cx| AppView::new(window, cx));
+
+        window
+            .update(cx, |view, _window, cx| {
+                view.try_add_repo(dir.path().to_path_buf(), cx);
+                view.set_repo_color(0, Some(0x00ff00), cx);
+            })
+            .unwrap();
+
+        window
+            .read_with(cx, |view, _cx| {
+                assert_eq!(view.state().repos[0].color, Some(0x00ff00));
+            })
+            .unwrap();
+    }
+
     #[gpui::test]
     fn test_add_duplicate_repo_is_ignored(cx: &mut TestAppContext) {
         cx.update(|cx| init_test_theme(cx));
@@ -553,6 +1564,77 @@ mod tests {
             .unwrap();
     }
 
+    #[gpui::test]
+    fn test_tab_bar_pin_moves_repo_first(cx: &mut TestAppContext) {
+        cx.update(|cx| init_test_theme(cx));
+        let dir1 = init_test_repo();
+        let dir2 = init_test_repo();
+        let dir3 = init_test_repo();
+        let window = cx.add_window(|window, cx| AppView::new(window, cx));
+
+        window
+            .update(cx, |view, _window, cx| {
+                view.try_add_repo(dir1.path().to_path_buf(), cx);
+                view.try_add_repo(dir2.path().to_path_buf(), cx);
+                view.try_add_repo(dir3.path().to_path_buf(), cx);
+            })
+            .unwrap();
+
+        let tab_bar = window
+            .read_with(cx, |view, _cx| view.tab_bar().clone())
+            .unwrap();
+
+        let any_handle = window.into();
+        cx.update_window(any_handle, |_root, window, app| {
+            tab_bar.update(app, |bar, cx| {
+                bar.toggle_pin(2, window, cx);
+            });
+        })
+        .unwrap();
+
+        cx.run_until_parked();
+
+        let name3 = dir3.path().file_name().unwrap().to_str().unwrap();
+        window
+            .read_with(cx, |view, cx| {
+                assert_eq!(view.state().repos[0].name, name3);
+                assert!(view.state().repos[0].pinned);
+                assert_eq!(view.repo_view_count(), 3);
+                // The active tab (repo3) moved along with it.
+                assert_eq!(view.state().active_tab, 0);
+                let tabs = view.tab_bar().read(cx).tabs();
+                assert!(tabs[0].pinned && tabs[0].is_active);
+                assert!(!tabs[1].pinned);
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    fn test_session_state_keeps_tabs_left_unopened(cx: &mut TestAppContext) {
+        cx.update(|cx| init_test_theme(cx));
+        let open = init_test_repo();
+        let closed = init_test_repo();
+        let window = cx.add_window(|window, cx| AppView::new(window, cx));
+
+        window
+            .update(cx, |view, _window, cx| {
+                view.try_add_repo(open.path().to_path_buf(), cx);
+                let mut skipped = AppState::default();
+                skipped.add_repo(closed.path().to_path_buf());
+                view.unopened = skipped.repos;
+
+                let saved = view.session_state();
+                assert_eq!(view.repo_view_count(), 1);
+                assert_eq!(saved.repos.len(), 2);
+                assert_eq!(saved.repos[1].path, view.unopened[0].path);
+
+                // Reopening a skipped repo doesn't save it twice.
+                view.try_add_repo(closed.path().to_path_buf(), cx);
+                assert_eq!(view.session_state().repos.len(), 2);
+            })
+            .unwrap();
+    }
+
     #[gpui::test]
     fn test_tab_bar_reorder_does_not_crash(cx: &mut TestAppContext) {
         cx.update(|cx| init_test_theme(cx));
@@ -1,39 +1,581 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::sync::Arc;
+
 use gpui::prelude::*;
-use gpui::{Context, MouseButton, MouseDownEvent, Window};
-use gpui_component::{scroll::ScrollableElement, v_flex, ActiveTheme};
+use gpui::{
+    actions, canvas, point, px, size, App, Bounds, Context, FocusHandle, Focusable, Hsla,
+    MouseButton, MouseDownEvent, PathBuilder, Pixels, ScrollHandle, SharedString, Window,
+};
+use gpui_component::{scroll::ScrollableElement, tooltip::Tooltip, v_flex, ActiveTheme};
+use unicode_segmentation::UnicodeSegmentation;
+
+use dd_core::RowDensity;
+use dd_git::{compute_graph_lanes, CommitInfo, ConventionalCommit, FileStat, LaneRow};
+
+use crate::diff_view::format_person;
+use crate::theme::{conventional_type_color, graph_lane_color};
 
-use dd_git::CommitInfo;
+actions!(
+    commit_list,
+    [
+        SelectPrevious,
+        SelectNext,
+        SelectFirst,
+        SelectLast,
+        CheckoutFiles
+    ]
+);
+
+/// Horizontal space given to each lane of the commit graph.
+const GRAPH_LANE_WIDTH: f32 = 12.0;
+const GRAPH_DOT_SIZE: f32 = 7.0;
+
+/// Paint one row's lane lines and commit dot into `bounds`. `colors[i]`
+/// is the color of lane `i`.
+fn paint_graph_row(row: &LaneRow, colors: &[Hsla], bounds: Bounds<Pixels>, window: &mut Window) {
+    let x = |lane: usize| bounds.left() + px(GRAPH_LANE_WIDTH * (lane as f32 + 0.5));
+    let (top, middle, bottom) = (bounds.top(), bounds.center().y, bounds.bottom());
+    let color = |lane: usize| colors[lane % colors.len()];
+    let segments = row
+        .incoming
+        .iter()
+        .map(|edge| (edge, top, middle))
+        .chain(row.outgoing.iter().map(|edge| (edge, middle, bottom)));
+    for (edge, y1, y2) in segments {
+        let mut line = PathBuilder::stroke(px(1.5));
+        line.move_to(point(x(edge.from), y1));
+        line.line_to(point(x(edge.to), y2));
+        if let Ok(path) = line.build() {
+            // Lines into a merge's side lane take that lane's color.
+            window.paint_path(path, color(edge.from.max(edge.to)));
+        }
+    }
+    let dot = Bounds::centered_at(
+        point(x(row.column), middle),
+        size(px(GRAPH_DOT_SIZE), px(GRAPH_DOT_SIZE)),
+    );
+    window.paint_quad(gpui::fill(dot, color(row.column)).corner_radii(px(GRAPH_DOT_SIZE / 2.0)));
+}
+
+/// Colored `type` chip followed by the scope, e.g. `[feat] ui`.
+pub(crate) fn render_conventional_chip(cc: &ConventionalCommit, cx: &App) -> impl IntoElement {
+    let theme = cx.theme();
+    let color = conventional_type_color(&cc.kind, cc.breaking, theme);
+    let label = if cc.breaking && cc.kind != "BREAKING CHANGE" {
+        format!("{}!", cc.kind)
+    } else {
+        cc.kind.clone()
+    };
+
+    gpui::div()
+        .flex()
+        .flex_none()
+        .items_center()
+        .gap_1()
+        .child(
+            gpui::div()
+                .px_1()
+                .rounded_sm()
+                .text_xs()
+                .bg(color.opacity(0.2))
+                .text_color(color)
+                .child(label),
+        )
+        .when_some(cc.scope.clone(), |el, scope| {
+            el.child(
+                gpui::div()
+                    .text_xs()
+                    .text_color(theme.muted_foreground)
+                    .child(scope),
+            )
+        })
+}
+
+/// `git show --stat`-style summary: one `path | N +-` line per file and a
+/// totals line.
+pub(crate) fn format_stat(stats: &[FileStat]) -> String {
+    let width = stats.iter().map(|s| s.path.len()).max().unwrap_or(0);
+    let mut out = String::new();
+    let (mut added, mut deleted) = (0, 0);
+    for stat in stats {
+        let counts = match (stat.additions, stat.deletions) {
+            (Some(a), Some(d)) => {
+                added += a;
+                deleted += d;
+                format!(
+                    "{} {}{}",
+                    a + d,
+                    "+".repeat(a.min(20)),
+                    "-".repeat(d.min(20))
+                )
+            }
+            _ => "Bin".to_string(),
+        };
+        out.push_str(&format!("{:width$} | {counts}\n", stat.path));
+    }
+    let files = stats.len();
+    out.push_str(&format!(
+        "{files} file{} changed, {added} insertion{}(+), {deleted} deletion{}(-)",
+        if files == 1 { "" } else { "s" },
+        if added == 1 { "" } else { "s" },
+        if deleted == 1 { "" } else { "s" },
+    ));
+    out
+}
+
+const DEFAULT_SUBJECT_MAX_LEN: usize = 72;
+
+/// Shorten `s` to at most `max` grapheme clusters, replacing the tail with
+/// `…` when it does not fit. Never splits a character or emoji sequence.
+pub fn truncate_end(s: &str, max: usize) -> String {
+    if s.graphemes(true).nth(max).is_none() {
+        return s.to_string();
+    }
+    if max == 0 {
+        return String::new();
+    }
+    let mut out: String = s.graphemes(true).take(max - 1).collect();
+    out.push('\u{2026}');
+    out
+}
+
+/// Horizontal and vertical padding of a commit row. The one-line layout
+/// halves the vertical padding of the chosen density.
+fn row_padding(density: RowDensity, compact: bool) -> (Pixels, Pixels) {
+    let (x, y) = match density {
+        RowDensity::Compact => (8.0, 2.0),
+        RowDensity::Comfortable => (12.0, 4.0),
+        RowDensity::Spacious => (16.0, 8.0),
+    };
+    (px(x), px(if compact { y / 2.0 } else { y }))
+}
 
 pub struct CommitList {
     commits: Vec<CommitInfo>,
     selected_index: Option<usize>,
+    /// Rows in the multi-selection, including `selected_index`.
+    multi_selected: BTreeSet<usize>,
+    /// OIDs of commits not yet pushed to the branch's upstream.
+    unpushed: HashSet<String>,
+    /// Single-line rows showing only subject and short OID.
+    compact: bool,
+    density: RowDensity,
+    /// Subjects are truncated to this many characters; the full subject
+    /// is shown on hover.
+    subject_max_len: usize,
+    /// Added/deleted line totals per commit OID, shown as a stat badge.
+    line_counts: HashMap<String, (usize, usize)>,
+    /// Changed-file count per commit OID, loaded as rows become visible.
+    /// `None` marks a count still loading or one that failed, so neither
+    /// is requested again every frame.
+    file_counts: HashMap<String, Option<usize>>,
+    /// Counts a commit's files. Runs on the background executor.
+    #[allow(clippy::type_complexity)]
+    file_count_loader: Option<Arc<dyn Fn(&str) -> anyhow::Result<usize> + Send + Sync + 'static>>,
+    /// Shown in place of the rows when there are no commits.
+    empty_message: Option<String>,
+    /// Commits are being read; shown while the list is still empty.
+    loading: bool,
+    /// Loads per-file line counts for the hover tooltip. Runs on the
+    /// background executor.
+    #[allow(clippy::type_complexity)]
+    stat_loader: Option<Arc<dyn Fn(&str) -> anyhow::Result<Vec<FileStat>> + Send + Sync + 'static>>,
+    /// Tooltip stat text by OID; `None` while it is still loading.
+    stats: HashMap<String, Option<String>>,
     #[allow(clippy::type_complexity)]
     on_select: Option<Box<dyn Fn(&CommitInfo, &mut Window, &mut Context<Self>) + 'static>>,
+    #[allow(clippy::type_complexity)]
+    on_checkout_files: Option<Box<dyn Fn(&mut Window, &mut Context<Self>) + 'static>>,
+    /// Draw branch lanes beside the rows.
+    show_graph: bool,
+    /// Graph cell for each commit, by index into `commits`.
+    graph: Vec<LaneRow>,
+    /// Lanes in the widest graph row, so subjects line up.
+    graph_width: usize,
+    /// Lowercased filter query; empty shows every commit.
+    filter: String,
+    /// Indices into `commits` of the rows shown under the current filter.
+    visible: Vec<usize>,
+    focus_handle: FocusHandle,
+    /// Keeps the keyboard-selected row scrolled into view.
+    scroll_handle: ScrollHandle,
 }
 
 impl CommitList {
-    pub fn new_empty() -> Self {
+    pub fn new_empty(cx: &mut Context<Self>) -> Self {
         Self {
             commits: Vec::new(),
             selected_index: None,
+            multi_selected: BTreeSet::new(),
+            unpushed: HashSet::new(),
+            compact: false,
+            density: RowDensity::default(),
+            subject_max_len: DEFAULT_SUBJECT_MAX_LEN,
+            line_counts: HashMap::new(),
+            file_counts: HashMap::new(),
+            file_count_loader: None,
+            empty_message: None,
+            loading: false,
+            stat_loader: None,
+            stats: HashMap::new(),
             on_select: None,
+            on_checkout_files: None,
+            show_graph: false,
+            graph: Vec::new(),
+            graph_width: 0,
+            filter: String::new(),
+            visible: Vec::new(),
+            focus_handle: cx.focus_handle(),
+            scroll_handle: ScrollHandle::new(),
         }
     }
 
     pub fn set_commits(&mut self, commits: Vec<CommitInfo>, cx: &mut Context<Self>) {
         self.commits = commits;
         self.selected_index = None;
+        self.multi_selected.clear();
+        self.rebuild_rows();
+        cx.notify();
+    }
+
+    /// Replace the commits with a refreshed history, keeping the same
+    /// commits selected. The selected row stays where it was on screen
+    /// even if commits were added above it; a selection that is no longer
+    /// listed is dropped.
+    pub fn update_commits(&mut self, commits: Vec<CommitInfo>, cx: &mut Context<Self>) {
+        let oid_at = |i: &usize| self.commits.get(*i).map(|c| c.oid.clone());
+        let selected_oid = self.selected_index.as_ref().and_then(oid_at);
+        let multi_oids: HashSet<String> = self.multi_selected.iter().filter_map(oid_at).collect();
+        let old_row = self.selected_index.and_then(|i| self.visible_row(i));
+
+        self.commits = commits;
+        self.rebuild_rows();
+        self.selected_index =
+            selected_oid.and_then(|oid| self.commits.iter().position(|c| c.oid == oid));
+        self.multi_selected = self
+            .commits
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| multi_oids.contains(&c.oid))
+            .map(|(i, _)| i)
+            .collect();
+
+        let new_row = self.selected_index.and_then(|i| self.visible_row(i));
+        if let (Some(old_row), Some(new_row)) = (old_row, new_row) {
+            self.keep_row_in_place(old_row, new_row);
+        }
         cx.notify();
     }
 
+    /// Add older commits below the loaded ones, e.g. as history pages in.
+    /// Selection and scroll position are untouched.
+    pub fn append_commits(&mut self, commits: Vec<CommitInfo>, cx: &mut Context<Self>) {
+        if commits.is_empty() {
+            return;
+        }
+        self.commits.extend(commits);
+        self.rebuild_rows();
+        cx.notify();
+    }
+
+    /// Recompute the graph and the filtered rows after `commits` changed.
+    fn rebuild_rows(&mut self) {
+        self.graph = compute_graph_lanes(&self.commits);
+        self.graph_width = self.graph.iter().map(|row| row.width).max().unwrap_or(0);
+        self.update_visible();
+    }
+
+    /// Scroll by the rows inserted above the selection, so the row that
+    /// was at `old_row` stays put at `new_row`. Rows are assumed to share
+    /// the pitch measured above `old_row` in the last layout, which is
+    /// fractional once rows snap to the pixel grid.
+    fn keep_row_in_place(&self, old_row: usize, new_row: usize) {
+        if old_row == new_row {
+            return;
+        }
+        let Some(bounds) = self.scroll_handle.bounds_for_item(old_row) else {
+            self.scroll_handle.scroll_to_item(new_row);
+            return;
+        };
+        let pitch = match self.scroll_handle.bounds_for_item(0) {
+            Some(first) if old_row > 0 => (bounds.top() - first.top()) / old_row as f32,
+            _ => bounds.size.height,
+        };
+        let offset = self.scroll_handle.offset();
+        let shift = pitch * (new_row as f32 - old_row as f32);
+        self.scroll_handle
+            .set_offset(point(offset.x, offset.y - shift));
+    }
+
+    /// Show only commits whose subject, author or short OID contains
+    /// `query`, ignoring case. `commits()` still returns the full list.
+    pub fn set_filter(&mut self, query: String, cx: &mut Context<Self>) {
+        let query = query.trim().to_lowercase();
+        if query == self.filter {
+            return;
+        }
+        self.filter = query;
+        self.update_visible();
+        let visible: HashSet<usize> = self.visible.iter().copied().collect();
+        self.multi_selected.retain(|i| visible.contains(i));
+        cx.notify();
+    }
+
+    pub fn filter(&self) -> &str {
+        &self.filter
+    }
+
+    /// Number of rows shown under the current filter.
+    pub fn visible_count(&self) -> usize {
+        self.visible.len()
+    }
+
+    /// The commit shown in row `row` of the filtered list.
+    pub fn visible_commit(&self, row: usize) -> Option<&CommitInfo> {
+        self.visible.get(row).and_then(|&i| self.commits.get(i))
+    }
+
+    fn update_visible(&mut self) {
+        let query = &self.filter;
+        self.visible = self
+            .commits
+            .iter()
+            .enumerate()
+            .filter(|(_, commit)| {
+                query.is_empty()
+                    || [&commit.subject, &commit.author_name, &commit.short_oid]
+                        .iter()
+                        .any(|field| field.to_lowercase().contains(query.as_str()))
+            })
+            .map(|(i, _)| i)
+            .collect();
+    }
+
+    /// Row of `index` in the filtered list, if it is shown.
+    fn visible_row(&self, index: usize) -> Option<usize> {
+        self.visible.iter().position(|&i| i == index)
+    }
+
     pub fn commits(&self) -> &[CommitInfo] {
         &self.commits
     }
 
+    pub fn set_unpushed(&mut self, unpushed: HashSet<String>, cx: &mut Context<Self>) {
+        self.unpushed = unpushed;
+        cx.notify();
+    }
+
+    pub fn is_unpushed(&self, oid: &str) -> bool {
+        self.unpushed.contains(oid)
+    }
+
+    pub fn set_compact(&mut self, compact: bool, cx: &mut Context<Self>) {
+        self.compact = compact;
+        cx.notify();
+    }
+
+    pub fn set_show_graph(&mut self, show_graph: bool, cx: &mut Context<Self>) {
+        self.show_graph = show_graph;
+        cx.notify();
+    }
+
+    /// Graph cell of commit `index`, when the graph is shown. Filtering
+    /// breaks the lanes' continuity, so the graph is hidden meanwhile.
+    pub fn graph_row(&self, index: usize) -> Option<&LaneRow> {
+        if !self.show_graph || !self.filter.is_empty() {
+            return None;
+        }
+        self.graph.get(index)
+    }
+
+    pub fn set_density(&mut self, density: RowDensity, cx: &mut Context<Self>) {
+        self.density = density;
+        cx.notify();
+    }
+
+    pub fn set_subject_max_len(&mut self, max_len: usize, cx: &mut Context<Self>) {
+        self.subject_max_len = max_len;
+        cx.notify();
+    }
+
+    pub fn set_line_counts(
+        &mut self,
+        line_counts: HashMap<String, (usize, usize)>,
+        cx: &mut Context<Self>,
+    ) {
+        self.line_counts = line_counts;
+        cx.notify();
+    }
+
+    pub fn line_counts(&self, oid: &str) -> Option<(usize, usize)> {
+        self.line_counts.get(oid).copied()
+    }
+
+    /// Install the function that counts a commit's changed files. Counts
+    /// are only requested for rows on screen and cached by OID.
+    pub fn set_file_count_loader(
+        &mut self,
+        loader: impl Fn(&str) -> anyhow::Result<usize> + Send + Sync + 'static,
+    ) {
+        self.file_count_loader = Some(Arc::new(loader));
+    }
+
+    pub fn file_count(&self, oid: &str) -> Option<usize> {
+        self.file_counts.get(oid).copied().flatten()
+    }
+
+    /// Count `oid`'s files off the UI thread, since this is asked for
+    /// while rows paint.
+    fn load_file_count(&mut self, oid: &str, cx: &mut Context<Self>) {
+        if self.file_counts.contains_key(oid) {
+            return;
+        }
+        let Some(loader) = self.file_count_loader.clone() else {
+            return;
+        };
+        self.file_counts.insert(oid.to_string(), None);
+        let oid = oid.to_string();
+        cx.spawn(async move |list, cx| {
+            let task_oid = oid.clone();
+            let result = cx
+                .background_executor()
+                .spawn(async move { loader(&task_oid) })
+                .await;
+            let count = match result {
+                Ok(count) => Some(count),
+                Err(e) => {
+                    eprintln!("failed to count files in {oid}: {e}");
+                    None
+                }
+            };
+            let _ = list.update(cx, |list, cx| {
+                list.file_counts.insert(oid, count);
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    pub fn set_loading(&mut self, loading: bool, cx: &mut Context<Self>) {
+        self.loading = loading;
+        cx.notify();
+    }
+
+    pub fn is_loading(&self) -> bool {
+        self.loading
+    }
+
+    pub fn empty_message(&self) -> Option<&str> {
+        self.empty_message.as_deref()
+    }
+
+    pub fn set_empty_message(&mut self, message: Option<String>, cx: &mut Context<Self>) {
+        self.empty_message = message;
+        cx.notify();
+    }
+
+    pub fn set_stat_loader(
+        &mut self,
+        loader: impl Fn(&str) -> anyhow::Result<Vec<FileStat>> + Send + Sync + 'static,
+    ) {
+        self.stat_loader = Some(Arc::new(loader));
+    }
+
+    /// Tooltip stat for `oid`, or `None` if it has not loaded yet.
+    pub fn stat_text(&self, oid: &str) -> Option<&str> {
+        self.stats.get(oid).and_then(|text| text.as_deref())
+    }
+
+    /// Compute `oid`'s stat off the UI thread; the tooltip shows a
+    /// placeholder until it arrives.
+    fn load_stat(&mut self, oid: &str, cx: &mut Context<Self>) {
+        if self.stats.contains_key(oid) {
+            return;
+        }
+        let Some(loader) = self.stat_loader.clone() else {
+            return;
+        };
+        self.stats.insert(oid.to_string(), None);
+        let oid = oid.to_string();
+        cx.spawn(async move |list, cx| {
+            let task_oid = oid.clone();
+            let result = cx
+                .background_executor()
+                .spawn(async move { loader(&task_oid) })
+                .await;
+            let text = match result {
+                Ok(stats) => format_stat(&stats),
+                Err(e) => format!("Failed to load stat: {e}"),
+            };
+            let _ = list.update(cx, |list, cx| {
+                list.stats.insert(oid, Some(text));
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    /// The commits directly after and before `oid` in the list.
+    pub fn adjacent_commits(&self, oid: &str) -> Vec<CommitInfo> {
+        let Some(index) = self.commits.iter().position(|c| c.oid == oid) else {
+            return Vec::new();
+        };
+        [index.checked_add(1), index.checked_sub(1)]
+            .into_iter()
+            .flatten()
+            .filter_map(|i| self.commits.get(i).cloned())
+            .collect()
+    }
+
     pub fn selected_index(&self) -> Option<usize> {
         self.selected_index
     }
 
+    pub fn is_selected(&self, index: usize) -> bool {
+        self.selected_index == Some(index) || self.multi_selected.contains(&index)
+    }
+
+    /// Selected commits in list order (newest first).
+    pub fn selected_commits(&self) -> Vec<CommitInfo> {
+        let mut indices = self.multi_selected.clone();
+        indices.extend(self.selected_index);
+        indices
+            .into_iter()
+            .filter_map(|i| self.commits.get(i).cloned())
+            .collect()
+    }
+
+    /// Add or remove `index` from the multi-selection without changing the
+    /// commit shown in the diff.
+    pub fn toggle_in_selection(&mut self, index: usize, cx: &mut Context<Self>) {
+        if index >= self.commits.len() {
+            return;
+        }
+        if !self.multi_selected.remove(&index) {
+            self.multi_selected.insert(index);
+        }
+        cx.notify();
+    }
+
+    /// Select every row between the current selection and `index`.
+    pub fn extend_selection(&mut self, index: usize, cx: &mut Context<Self>) {
+        if index >= self.commits.len() {
+            return;
+        }
+        let Some(row) = self.visible_row(index) else {
+            return;
+        };
+        let anchor = self
+            .selected_index
+            .and_then(|i| self.visible_row(i))
+            .unwrap_or(row);
+        self.multi_selected = self.visible[anchor.min(row)..=anchor.max(row)]
+            .iter()
+            .copied()
+            .collect();
+        cx.notify();
+    }
+
     pub fn on_select(
         &mut self,
         callback: impl Fn(&CommitInfo, &mut Window, &mut Context<Self>) + 'static,
@@ -41,7 +583,28 @@ impl CommitList {
         self.on_select = Some(Box::new(callback));
     }
 
+    pub fn on_checkout_files(
+        &mut self,
+        callback: impl Fn(&mut Window, &mut Context<Self>) + 'static,
+    ) {
+        self.on_checkout_files = Some(Box::new(callback));
+    }
+
+    /// Ask to restore the files the selected commit touched.
+    pub fn checkout_files(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.selected_index.is_none() {
+            return;
+        }
+        if let Some(ref on_checkout_files) = self.on_checkout_files {
+            on_checkout_files(window, cx);
+        }
+    }
+
     pub fn select_commit(&mut self, index: usize, window: &mut Window, cx: &mut Context<Self>) {
+        if index < self.commits.len() && !self.multi_selected.is_empty() {
+            self.multi_selected.clear();
+            cx.notify();
+        }
         if self.selected_index == Some(index) {
             return;
         }
@@ -54,6 +617,59 @@ impl CommitList {
         cx.notify();
     }
 
+    /// Report the current selection to the select callback again, e.g. so
+    /// its diff is recomputed with different options.
+    pub fn reload_selection(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let selected = self.selected_index.and_then(|i| self.commits.get(i));
+        if let (Some(commit), Some(on_select)) = (selected, self.on_select.as_ref()) {
+            on_select(commit, window, cx);
+        }
+    }
+
+    /// Select the commit with `oid`, scrolling to its row when the filter
+    /// shows it. Unknown OIDs are ignored.
+    pub fn select_oid(&mut self, oid: &str, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(index) = self.commits.iter().position(|c| c.oid == oid) else {
+            return;
+        };
+        match self.visible_row(index) {
+            Some(row) => self.select_visible(row, window, cx),
+            None => self.select_commit(index, window, cx),
+        }
+    }
+
+    /// Select the commit in row `row` of the filtered list.
+    pub fn select_visible(&mut self, row: usize, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(&index) = self.visible.get(row) {
+            self.select_commit(index, window, cx);
+            self.scroll_handle.scroll_to_item(row);
+        }
+    }
+
+    /// Move the selection by `delta` rows, stopping at the first and last
+    /// visible commit. With no visible selection, any move selects the
+    /// first row.
+    pub fn move_selection(&mut self, delta: isize, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(last) = self.visible.len().checked_sub(1) else {
+            return;
+        };
+        let row = match self.selected_index.and_then(|i| self.visible_row(i)) {
+            Some(current) => current.saturating_add_signed(delta).min(last),
+            None => 0,
+        };
+        self.select_visible(row, window, cx);
+    }
+
+    pub fn select_first(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.select_visible(0, window, cx);
+    }
+
+    pub fn select_last(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(last) = self.visible.len().checked_sub(1) {
+            self.select_visible(last, window, cx);
+        }
+    }
+
     fn format_date(timestamp: i64) -> String {
         use chrono::{DateTime, Utc};
         let dt = DateTime::<Utc>::from_timestamp(timestamp, 0);
@@ -69,17 +685,59 @@ impl CommitList {
         commit: &CommitInfo,
         cx: &Context<Self>,
     ) -> impl IntoElement {
-        let is_selected = self.selected_index == Some(index);
-        let subject = commit.subject.clone();
-        let author = commit.author_name.clone();
+        let is_selected = self.is_selected(index);
+        let conventional = commit.conventional();
+        let full_subject = conventional
+            .as_ref()
+            .map(|cc| cc.description.clone())
+            .unwrap_or_else(|| commit.subject.clone());
+        let subject = truncate_end(&full_subject, self.subject_max_len);
+        let truncated_subject = (subject != full_subject).then_some(full_subject);
+        let author = if commit.author_name.is_empty() {
+            format_person("", &commit.author_email)
+        } else {
+            commit.author_name.clone()
+        };
         let date = Self::format_date(commit.date);
         let short_oid = commit.short_oid.clone();
+        let is_unpushed = self.is_unpushed(&commit.oid);
+        let compact = self.compact;
+        let (pad_x, pad_y) = row_padding(self.density, compact);
+        let line_counts = self.line_counts(&commit.oid);
+        let file_count = self.file_count(&commit.oid);
+        // Rows report themselves once painted inside the visible area, so
+        // counts are only loaded for commits the user can see.
+        let needs_file_count = !compact
+            && self.file_count_loader.is_some()
+            && !self.file_counts.contains_key(&commit.oid);
+        let weak = cx.entity().downgrade();
+        let count_oid = commit.oid.clone();
+        let has_stat = self.stat_loader.is_some();
+        let stat_oid = commit.oid.clone();
+        let graph = self.graph_row(index).cloned().map(|row| {
+            let colors: Vec<Hsla> = (0..self.graph_width.max(1))
+                .map(|lane| graph_lane_color(lane, cx.theme()))
+                .collect();
+            (row, colors)
+        });
+        let has_graph = graph.is_some();
+        let graph_px = px(GRAPH_LANE_WIDTH * self.graph_width as f32);
+        let theme = cx.theme();
+        let unpushed_marker = || {
+            gpui::div()
+                .flex_none()
+                .text_xs()
+                .text_color(theme.warning)
+                .child("\u{2191}")
+        };
 
         gpui::div()
             .id(gpui::ElementId::Integer(index as u64))
+            .debug_selector(move || format!("commit-row-{index}"))
+            .relative()
             .w_full()
-            .px_3()
-            .py_1()
+            .px(pad_x)
+            .py(pad_y)
             .cursor_pointer()
             .when(is_selected, |el| el.bg(cx.theme().accent))
             .hover(|el| {
@@ -91,57 +749,252 @@ impl CommitList {
             })
             .on_mouse_down(
                 MouseButton::Left,
-                cx.listener(move |view, _event: &MouseDownEvent, window, cx| {
-                    view.select_commit(index, window, cx);
+                cx.listener(move |view, event: &MouseDownEvent, window, cx| {
+                    window.focus(&view.focus_handle);
+                    if event.modifiers.shift {
+                        view.extend_selection(index, cx);
+                    } else if event.modifiers.secondary() {
+                        view.toggle_in_selection(index, cx);
+                    } else {
+                        view.select_commit(index, window, cx);
+                    }
                 }),
             )
+            .when(has_stat || truncated_subject.is_some(), |el| {
+                let weak = weak.clone();
+                // The stat is only requested once the tooltip is shown, and
+                // the tooltip re-reads it each frame until it has loaded.
+                el.tooltip(move |window, cx| {
+                    if has_stat {
+                        let _ = weak.update(cx, |list, cx| list.load_stat(&stat_oid, cx));
+                    }
+                    let weak = weak.clone();
+                    let subject = truncated_subject.clone();
+                    let oid = stat_oid.clone();
+                    Tooltip::element(move |_window, cx| {
+                        let stat = has_stat.then(|| {
+                            weak.upgrade()
+                                .and_then(|list| list.read(cx).stat_text(&oid).map(str::to_string))
+                                .unwrap_or_else(|| "Loading\u{2026}".to_string())
+                        });
+                        SharedString::from(
+                            [subject.clone(), stat]
+                                .into_iter()
+                                .flatten()
+                                .collect::<Vec<_>>()
+                                .join("\n\n"),
+                        )
+                    })
+                    .build(window, cx)
+                })
+            })
+            .when(needs_file_count, |el| {
+                el.child(
+                    canvas(
+                        move |bounds: Bounds<Pixels>, window: &mut Window, app: &mut App| {
+                            if window.content_mask().bounds.intersects(&bounds) {
+                                let _ = weak.update(app, |list: &mut CommitList, cx| {
+                                    list.load_file_count(&count_oid, cx);
+                                });
+                            }
+                        },
+                        |_, _, _, _| {},
+                    )
+                    .absolute()
+                    .size_full(),
+                )
+            })
+            .when_some(graph, |el, (row, colors)| {
+                el.child(
+                    gpui::div()
+                        .debug_selector(move || format!("commit-graph-{index}"))
+                        .absolute()
+                        .top_0()
+                        .bottom_0()
+                        .left(pad_x)
+                        .w(graph_px)
+                        .child(
+                            canvas(
+                                |_, _, _| {},
+                                move |bounds, _, window, _| {
+                                    paint_graph_row(&row, &colors, bounds, window)
+                                },
+                            )
+                            .size_full(),
+                        ),
+                )
+            })
             .child(
                 v_flex()
                     .gap_0p5()
+                    .when(has_graph, |el| el.pl(graph_px))
                     .child(
                         gpui::div()
-                            .text_sm()
-                            .text_color(if is_selected {
-                                cx.theme().accent_foreground
-                            } else {
-                                cx.theme().foreground
+                            .flex()
+                            .items_center()
+                            .gap_1()
+                            .when_some(conventional.as_ref(), |el, cc| {
+                                el.child(render_conventional_chip(cc, cx))
                             })
-                            .child(subject),
+                            .child(
+                                gpui::div()
+                                    .flex_1()
+                                    .min_w_0()
+                                    .truncate()
+                                    .text_sm()
+                                    .text_color(if is_selected {
+                                        cx.theme().accent_foreground
+                                    } else {
+                                        cx.theme().foreground
+                                    })
+                                    .child(subject),
+                            )
+                            .when(compact, |el| {
+                                el.when(is_unpushed, |el| el.child(unpushed_marker()))
+                                    .child(
+                                        gpui::div()
+                                            .flex_none()
+                                            .text_xs()
+                                            .text_color(cx.theme().muted_foreground)
+                                            .child(short_oid.clone()),
+                                    )
+                            }),
                     )
-                    .child(
-                        gpui::div()
-                            .flex()
-                            .gap_2()
-                            .text_xs()
-                            .text_color(cx.theme().muted_foreground)
-                            .child(short_oid)
-                            .child(author)
-                            .child(date),
-                    ),
+                    .when(!compact, |el| {
+                        el.child(
+                            gpui::div()
+                                .debug_selector(move || format!("commit-meta-{index}"))
+                                .flex()
+                                .gap_2()
+                                .text_xs()
+                                .text_color(cx.theme().muted_foreground)
+                                .when(is_unpushed, |el| el.child(unpushed_marker()))
+                                .child(short_oid)
+                                .child(author)
+                                .child(date)
+                                .when_some(file_count, |el, files| {
+                                    el.child(
+                                        gpui::div()
+                                            .debug_selector(move || format!("commit-files-{index}"))
+                                            .px_1()
+                                            .rounded_sm()
+                                            .bg(cx.theme().muted)
+                                            .child(format!(
+                                                "{files} file{}",
+                                                if files == 1 { "" } else { "s" }
+                                            )),
+                                    )
+                                })
+                                .when_some(line_counts, |el, (added, deleted)| {
+                                    el.child(
+                                        gpui::div()
+                                            .debug_selector(move || format!("commit-stat-{index}"))
+                                            .flex()
+                                            .gap_1()
+                                            .child(
+                                                gpui::div()
+                                                    .text_color(cx.theme().success)
+                                                    .child(format!("+{added}")),
+                                            )
+                                            .child(
+                                                gpui::div()
+                                                    .text_color(cx.theme().danger)
+                                                    .child(format!("-{deleted}")),
+                                            ),
+                                    )
+                                }),
+                        )
+                    }),
             )
     }
 }
 
 impl Render for CommitList {
     fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        if self.commits.is_empty() && self.loading {
+            return v_flex()
+                .size_full()
+                .items_center()
+                .justify_center()
+                .debug_selector(|| "commit-list-loading".into())
+                .text_sm()
+                .text_color(cx.theme().muted_foreground)
+                .child("Loading commits\u{2026}")
+                .into_any_element();
+        }
+        if self.commits.is_empty() {
+            if let Some(message) = self.empty_message.clone() {
+                return v_flex()
+                    .size_full()
+                    .items_center()
+                    .justify_center()
+                    .debug_selector(|| "commit-list-empty".into())
+                    .text_sm()
+                    .text_color(cx.theme().muted_foreground)
+                    .child(message)
+                    .into_any_element();
+            }
+        }
+
+        if self.visible.is_empty() && !self.commits.is_empty() {
+            return v_flex()
+                .size_full()
+                .items_center()
+                .justify_center()
+                .debug_selector(|| "commit-list-no-matches".into())
+                .text_sm()
+                .text_color(cx.theme().muted_foreground)
+                .child("No commits match the filter")
+                .into_any_element();
+        }
+
         let rows: Vec<_> = self
-            .commits
+            .visible
             .iter()
-            .enumerate()
-            .map(|(i, commit)| self.render_commit_row(i, commit, cx))
+            .map(|&i| self.render_commit_row(i, &self.commits[i], cx))
             .collect();
 
         v_flex()
+            .id("commit-list")
+            .debug_selector(|| "commit-list".into())
             .h_full()
             .w_full()
-            .overflow_y_scrollbar()
+            .track_focus(&self.focus_handle)
+            .key_context("CommitList")
+            .on_action(cx.listener(|view, _: &SelectPrevious, window, cx| {
+                view.move_selection(-1, window, cx);
+            }))
+            .on_action(cx.listener(|view, _: &SelectNext, window, cx| {
+                view.move_selection(1, window, cx);
+            }))
+            .on_action(cx.listener(|view, _: &SelectFirst, window, cx| {
+                view.select_first(window, cx);
+            }))
+            .on_action(cx.listener(|view, _: &SelectLast, window, cx| {
+                view.select_last(window, cx);
+            }))
+            .on_action(cx.listener(|view, _: &CheckoutFiles, window, cx| {
+                view.checkout_files(window, cx);
+            }))
+            .overflow_y_scroll()
+            .track_scroll(&self.scroll_handle)
             .children(rows)
+            // Last, so child indices match commit rows for `scroll_to_item`.
+            .vertical_scrollbar(&self.scroll_handle)
+            .into_any_element()
+    }
+}
+
+impl Focusable for CommitList {
+    fn focus_handle(&self, _cx: &App) -> FocusHandle {
+        self.focus_handle.clone()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::rc::Rc;
 
     fn mock_commits() -> Vec<CommitInfo> {
         vec![
@@ -184,6 +1037,59 @@ mod tests {
         assert_eq!(commits[1].author_name, "Bob");
     }
 
+    #[test]
+    fn test_format_stat() {
+        let stats = vec![
+            FileStat {
+                path: "README.md".into(),
+                additions: Some(2),
+                deletions: Some(0),
+            },
+            FileStat {
+                path: "src/lib.rs".into(),
+                additions: Some(1),
+                deletions: Some(1),
+            },
+            FileStat {
+                path: "icon.bin".into(),
+                additions: None,
+                deletions: None,
+            },
+        ];
+        assert_eq!(
+            format_stat(&stats),
+            "README.md  | 2 ++\n\
+             src/lib.rs | 2 +-\n\
+             icon.bin   | Bin\n\
+             3 files changed, 3 insertions(+), 1 deletion(-)"
+        );
+    }
+
+    #[test]
+    fn test_truncate_end_ascii() {
+        assert_eq!(truncate_end("fix: typo", 20), "fix: typo");
+        assert_eq!(truncate_end("fix: typo", 9), "fix: typo");
+        assert_eq!(truncate_end("fix: typo", 5), "fix:\u{2026}");
+        assert_eq!(truncate_end("fix: typo", 0), "");
+    }
+
+    #[test]
+    fn test_truncate_end_multibyte() {
+        assert_eq!(truncate_end("Ünïcödé docs", 4), "Ünï\u{2026}");
+        assert_eq!(truncate_end("日本語のコミット", 3), "日本\u{2026}");
+    }
+
+    #[test]
+    fn test_truncate_end_keeps_emoji_sequences_whole() {
+        // Family emoji (ZWJ sequence) and a flag are single graphemes.
+        let subject = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}\u{1F1EF}\u{1F1F5} release party";
+        assert_eq!(
+            truncate_end(subject, 3),
+            "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}\u{1F1EF}\u{1F1F5}\u{2026}"
+        );
+        assert_eq!(truncate_end("\u{1F389}\u{1F389}", 1), "\u{2026}");
+    }
+
     #[test]
     fn test_format_date() {
         let formatted = CommitList::format_date(1700000000);
@@ -203,7 +1109,7 @@ mod tests {
         let selected_oid = std::rc::Rc::new(std::cell::Cell::new(String::new()));
         let selected_oid_clone = selected_oid.clone();
 
-        let window = cx.add_window(|_window, _cx| CommitList::new_empty());
+        let window = cx.add_window(|_window, cx| CommitList::new_empty(cx));
 
         window
             .update(cx, |list, _window, cx| {
@@ -229,11 +1135,76 @@ mod tests {
             .unwrap();
     }
 
+    #[gpui::test]
+    fn test_set_unpushed_marks_commits(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| crate::test_helpers::init_test_theme(cx));
+
+        let window = cx.add_window(|_window, cx| CommitList::new_empty(cx));
+
+        window
+            .update(cx, |list, _window, cx| {
+                list.set_commits(mock_commits(), cx);
+                list.set_unpushed(HashSet::from(["abc123def456".to_string()]), cx);
+            })
+            .unwrap();
+
+        window
+            .read_with(cx, |list, _cx| {
+                assert!(list.is_unpushed("abc123def456"));
+                assert!(!list.is_unpushed("def456abc789"));
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    fn test_line_counts_render_stat_badge(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| crate::test_helpers::init_test_theme(cx));
+        let (list, cx) = cx.add_window_view(|_window, cx| CommitList::new_empty(cx));
+
+        list.update(cx, |list, cx| {
+            list.set_commits(mock_commits(), cx);
+            list.set_line_counts(HashMap::from([("abc123def456".to_string(), (3, 1))]), cx);
+        });
+        cx.run_until_parked();
+
+        assert!(cx.debug_bounds("commit-stat-0").is_some());
+        assert!(cx.debug_bounds("commit-stat-1").is_none());
+    }
+
+    #[gpui::test]
+    fn test_file_count_badge_loads_lazily(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| crate::test_helpers::init_test_theme(cx));
+        let (list, cx) = cx.add_window_view(|_window, cx| CommitList::new_empty(cx));
+
+        let requested = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let requested_clone = requested.clone();
+        list.update(cx, |list, cx| {
+            list.set_file_count_loader(move |oid| {
+                requested_clone.lock().unwrap().push(oid.to_string());
+                Ok(if oid == "abc123def456" { 3 } else { 1 })
+            });
+            list.set_commits(mock_commits(), cx);
+        });
+        cx.run_until_parked();
+
+        list.read_with(cx, |list, _cx| {
+            assert_eq!(list.file_count("abc123def456"), Some(3));
+            assert_eq!(list.file_count("def456abc789"), Some(1));
+        });
+        assert!(cx.debug_bounds("commit-files-0").is_some());
+        assert!(cx.debug_bounds("commit-files-1").is_some());
+
+        // Cached counts are not loaded again on later frames.
+        list.update(cx, |_list, cx| cx.notify());
+        cx.run_until_parked();
+        assert_eq!(requested.lock().unwrap().len(), 2);
+    }
+
     #[gpui::test]
     fn test_select_commit_out_of_bounds_leaves_none(cx: &mut gpui::TestAppContext) {
         cx.update(|cx| crate::test_helpers::init_test_theme(cx));
 
-        let window = cx.add_window(|_window, _cx| CommitList::new_empty());
+        let window = cx.add_window(|_window, cx| CommitList::new_empty(cx));
 
         window
             .update(cx, |list, _window, cx| {
@@ -253,4 +1224,283 @@ mod tests {
             })
             .unwrap();
     }
+
+    #[gpui::test]
+    fn test_multi_selection(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| crate::test_helpers::init_test_theme(cx));
+
+        let window = cx.add_window(|_window, cx| CommitList::new_empty(cx));
+
+        window
+            .update(cx, |list, window, cx| {
+                list.set_commits(mock_commits(), cx);
+                list.select_commit(0, window, cx);
+                list.extend_selection(1, cx);
+            })
+            .unwrap();
+
+        window
+            .read_with(cx, |list, _cx| {
+                let oids: Vec<_> = list.selected_commits().into_iter().map(|c| c.oid).collect();
+                assert_eq!(oids, vec!["abc123def456", "def456abc789"]);
+                assert!(list.is_selected(1));
+            })
+            .unwrap();
+
+        window
+            .update(cx, |list, window, cx| {
+                list.toggle_in_selection(1, cx);
+                assert_eq!(list.selected_commits().len(), 1);
+                list.toggle_in_selection(1, cx);
+                list.select_commit(1, window, cx);
+            })
+            .unwrap();
+
+        window
+            .read_with(cx, |list, _cx| {
+                assert_eq!(list.selected_commits().len(), 1);
+                assert!(!list.is_selected(0));
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    fn test_compact_rows_omit_metadata(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| crate::test_helpers::init_test_theme(cx));
+        let (list, cx) = cx.add_window_view(|_window, cx| CommitList::new_empty(cx));
+
+        list.update(cx, |list, cx| list.set_commits(mock_commits(), cx));
+        cx.run_until_parked();
+        assert!(cx.debug_bounds("commit-meta-0").is_some());
+
+        list.update(cx, |list, cx| list.set_compact(true, cx));
+        cx.run_until_parked();
+        assert!(cx.debug_bounds("commit-meta-0").is_none());
+    }
+
+    #[gpui::test]
+    fn test_stat_loads_in_background_once(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| crate::test_helpers::init_test_theme(cx));
+        let (list, cx) = cx.add_window_view(|_window, cx| CommitList::new_empty(cx));
+
+        let calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let calls_clone = calls.clone();
+        list.update(cx, |list, cx| {
+            list.set_stat_loader(move |_oid| {
+                calls_clone.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                Ok(vec![FileStat {
+                    path: "src/main.rs".into(),
+                    additions: Some(2),
+                    deletions: Some(1),
+                }])
+            });
+            list.set_commits(mock_commits(), cx);
+            list.load_stat("abc123def456", cx);
+            // Still loading; the tooltip shows a placeholder meanwhile.
+            assert_eq!(list.stat_text("abc123def456"), None);
+            list.load_stat("abc123def456", cx);
+        });
+        cx.run_until_parked();
+
+        list.read_with(cx, |list, _cx| {
+            assert!(list
+                .stat_text("abc123def456")
+                .is_some_and(|text| text.contains("src/main.rs")));
+        });
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    #[gpui::test]
+    fn test_row_padding_follows_density(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| crate::test_helpers::init_test_theme(cx));
+        let (list, cx) = cx.add_window_view(|_window, cx| CommitList::new_empty(cx));
+        list.update(cx, |list, cx| list.set_commits(mock_commits(), cx));
+
+        for (density, pad_x, pad_y) in [
+            (RowDensity::Compact, 8.0, 2.0),
+            (RowDensity::Comfortable, 12.0, 4.0),
+            (RowDensity::Spacious, 16.0, 8.0),
+        ] {
+            list.update(cx, |list, cx| list.set_density(density, cx));
+            cx.run_until_parked();
+            let row = cx.debug_bounds("commit-row-0").unwrap();
+            let meta = cx.debug_bounds("commit-meta-0").unwrap();
+            assert_eq!(meta.origin.x - row.origin.x, px(pad_x), "{density:?}");
+            assert_eq!(row.bottom() - meta.bottom(), px(pad_y), "{density:?}");
+        }
+    }
+
+    #[gpui::test]
+    fn test_graph_lanes_render_beside_rows(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| crate::test_helpers::init_test_theme(cx));
+        let (list, cx) = cx.add_window_view(|_window, cx| CommitList::new_empty(cx));
+        list.update(cx, |list, cx| {
+            list.set_commits(mock_commits(), cx);
+            list.set_show_graph(true, cx);
+        });
+        cx.run_until_parked();
+
+        let row = cx.debug_bounds("commit-row-0").unwrap();
+        let graph = cx.debug_bounds("commit-graph-0").unwrap();
+        let meta = cx.debug_bounds("commit-meta-0").unwrap();
+        assert_eq!(graph.size.width, px(GRAPH_LANE_WIDTH));
+        assert_eq!(graph.size.height, row.size.height);
+        assert_eq!(meta.origin.x, graph.right());
+
+        list.update(cx, |list, cx| list.set_filter("typo".into(), cx));
+        cx.run_until_parked();
+        assert!(cx.debug_bounds("commit-graph-1").is_none());
+    }
+
+    /// `count` linear commits with OIDs `{prefix}{i}`, newest first.
+    fn numbered_commits(prefix: &str, count: usize) -> Vec<CommitInfo> {
+        (0..count)
+            .map(|i| CommitInfo {
+                oid: format!("{prefix}{i}"),
+                short_oid: format!("{prefix}{i}"),
+                subject: format!("commit {prefix}{i}"),
+                parent_oids: vec![format!("{prefix}{}", i + 1)],
+                ..mock_commits()[0].clone()
+            })
+            .collect()
+    }
+
+    #[gpui::test]
+    fn test_update_commits_keeps_selected_row_in_view(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| crate::test_helpers::init_test_theme(cx));
+        let (list, cx) = cx.add_window_view(|_window, cx| CommitList::new_empty(cx));
+        list.update_in(cx, |list, window, cx| {
+            list.set_commits(numbered_commits("old", 300), cx);
+            list.select_visible(150, window, cx);
+        });
+        cx.run_until_parked();
+        let viewport = cx.debug_bounds("commit-list").unwrap();
+        let before = cx.debug_bounds("commit-row-150").unwrap();
+        assert!(viewport.intersects(&before));
+
+        // A refresh brings in newer commits above the selection.
+        let mut refreshed = numbered_commits("new", 50);
+        refreshed.extend(numbered_commits("old", 300));
+        list.update(cx, |list, cx| list.update_commits(refreshed, cx));
+        cx.run_until_parked();
+
+        list.read_with(cx, |list, _| {
+            assert_eq!(list.selected_index(), Some(200));
+            assert_eq!(list.commits()[200].oid, "old150");
+        });
+        let after = cx.debug_bounds("commit-row-200").unwrap();
+        assert_eq!(after.origin.y, before.origin.y);
+
+        list.update(cx, |list, cx| {
+            list.append_commits(numbered_commits("older", 10), cx)
+        });
+        cx.run_until_parked();
+        list.read_with(cx, |list, _| {
+            assert_eq!(list.commits().len(), 360);
+            assert_eq!(list.selected_index(), Some(200));
+        });
+        assert_eq!(cx.debug_bounds("commit-row-200").unwrap(), after);
+    }
+
+    #[gpui::test]
+    fn test_arrow_keys_move_selection(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| {
+            crate::test_helpers::init_test_theme(cx);
+            cx.bind_keys([
+                gpui::KeyBinding::new("up", SelectPrevious, Some("CommitList")),
+                gpui::KeyBinding::new("down", SelectNext, Some("CommitList")),
+                gpui::KeyBinding::new("home", SelectFirst, Some("CommitList")),
+                gpui::KeyBinding::new("end", SelectLast, Some("CommitList")),
+            ]);
+        });
+
+        let selected_oids = Rc::new(std::cell::RefCell::new(Vec::new()));
+        let selected_oids_clone = selected_oids.clone();
+        let (list, cx) = cx.add_window_view(|_window, cx| CommitList::new_empty(cx));
+        list.update_in(cx, |list, window, cx| {
+            list.set_commits(mock_commits(), cx);
+            list.on_select(move |commit, _window, _cx| {
+                selected_oids_clone.borrow_mut().push(commit.oid.clone());
+            });
+            list.select_commit(0, window, cx);
+            window.focus(&list.focus_handle);
+        });
+
+        cx.simulate_keystrokes("down");
+        list.read_with(cx, |list, _| assert_eq!(list.selected_index(), Some(1)));
+        assert_eq!(
+            *selected_oids.borrow(),
+            vec!["abc123def456".to_string(), "def456abc789".to_string()]
+        );
+
+        // Selection stops at the ends instead of wrapping.
+        cx.simulate_keystrokes("down");
+        list.read_with(cx, |list, _| assert_eq!(list.selected_index(), Some(1)));
+        cx.simulate_keystrokes("home");
+        list.read_with(cx, |list, _| assert_eq!(list.selected_index(), Some(0)));
+        cx.simulate_keystrokes("up");
+        list.read_with(cx, |list, _| assert_eq!(list.selected_index(), Some(0)));
+        cx.simulate_keystrokes("end");
+        list.read_with(cx, |list, _| assert_eq!(list.selected_index(), Some(1)));
+        assert_eq!(selected_oids.borrow().len(), 4);
+    }
+
+    #[gpui::test]
+    fn test_filter_matches_subject_author_and_short_oid(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| crate::test_helpers::init_test_theme(cx));
+
+        let (list, cx) = cx.add_window_view(|_window, cx| CommitList::new_empty(cx));
+        list.update(cx, |list, cx| {
+            list.set_commits(mock_commits(), cx);
+            assert_eq!(list.visible_count(), 2);
+
+            list.set_filter("TYPO".into(), cx);
+            assert_eq!(list.visible_count(), 1);
+            list.set_filter("alice".into(), cx);
+            assert_eq!(list.visible_count(), 1);
+            list.set_filter("def456a".into(), cx);
+            assert_eq!(list.visible_count(), 1);
+            list.set_filter("nothing".into(), cx);
+            assert_eq!(list.visible_count(), 0);
+            assert_eq!(list.commits().len(), 2);
+        });
+        cx.run_until_parked();
+        assert!(cx.debug_bounds("commit-list-no-matches").is_some());
+
+        list.update(cx, |list, cx| list.set_filter(String::new(), cx));
+        cx.run_until_parked();
+        assert!(cx.debug_bounds("commit-row-1").is_some());
+    }
+
+    #[gpui::test]
+    fn test_selection_within_filter_maps_to_commit(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| crate::test_helpers::init_test_theme(cx));
+
+        let selected_oid = Rc::new(std::cell::RefCell::new(String::new()));
+        let selected_oid_clone = selected_oid.clone();
+        let (list, cx) = cx.add_window_view(|_window, cx| CommitList::new_empty(cx));
+        list.update_in(cx, |list, window, cx| {
+            list.set_commits(mock_commits(), cx);
+            list.on_select(move |commit, _window, _cx| {
+                *selected_oid_clone.borrow_mut() = commit.oid.clone();
+            });
+            list.set_filter("bob".into(), cx);
+            assert_eq!(
+                list.visible_commit(0).map(|c| c.oid.as_str()),
+                Some("def456abc789")
+            );
+
+            list.select_visible(0, window, cx);
+            assert_eq!(list.selected_index(), Some(1));
+
+            // Keyboard moves stay inside the filtered rows.
+            list.move_selection(-1, window, cx);
+            assert_eq!(list.selected_index(), Some(1));
+        });
+        assert_eq!(*selected_oid.borrow(), "def456abc789");
+
+        cx.run_until_parked();
+        assert!(cx.debug_bounds("commit-row-1").is_some());
+        assert!(cx.debug_bounds("commit-row-0").is_none());
+    }
 }
@@ -0,0 +1,460 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::rc::Rc;
+
+use gpui::{HighlightStyle, Hsla, SharedString};
+
+use dd_git::{DiffLine, LineOrigin};
+
+use crate::syntax;
+
+pub type Highlight = (Range<usize>, HighlightStyle);
+
+/// A gutter mark for one line, supplied from outside the diff (coverage,
+/// CODEOWNERS, lint results).
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineAnnotation {
+    /// Short text drawn in the gutter, usually a single character.
+    pub glyph: SharedString,
+    pub color: Hsla,
+    /// Shown when hovering the glyph.
+    pub tooltip: SharedString,
+}
+
+/// Everything a decorator may need to style one diff line.
+pub struct LineContext<'a> {
+    pub line: &'a DiffLine,
+    pub file_path: &'a str,
+    /// Default foreground for the line's origin.
+    pub fg: Hsla,
+    /// Background for word-level change spans.
+    pub change_bg: Hsla,
+    pub search_bg: Hsla,
+    pub whitespace_bg: Hsla,
+    pub is_dark: bool,
+    /// Current search text; empty when not searching.
+    pub search_query: &'a str,
+    pub show_whitespace: bool,
+}
+
+/// Contributes highlight spans for a diff line. Each decorator returns its
+/// own layer; [`compose_highlights`] merges the layers so decorators never
+/// need to know about each other.
+pub trait LineDecorator {
+    fn decorate(&self, cx: &LineContext) -> Vec<Highlight>;
+
+    /// Drop any memoized output, e.g. because the theme it was colored for
+    /// changed.
+    fn clear_cache(&self) {}
+}
+
+impl<T: LineDecorator + ?Sized> LineDecorator for Rc<T> {
+    fn decorate(&self, cx: &LineContext) -> Vec<Highlight> {
+        (**self).decorate(cx)
+    }
+
+    fn clear_cache(&self) {
+        (**self).clear_cache()
+    }
+}
+
+/// Identifies one highlighted line: the same text can color differently
+/// depending on the lines around it.
+#[derive(PartialEq, Eq, Hash)]
+struct SyntaxKey {
+    file_path: String,
+    /// Whether the line is read from the old side (a deletion).
+    old_side: bool,
+    /// Line number on that side.
+    line_no: Option<u32>,
+    content: String,
+    /// Bits of the fallback color.
+    fg: [u32; 4],
+}
+
+impl SyntaxKey {
+    fn new(file_path: &str, line: &DiffLine, fg: Hsla) -> Self {
+        let old_side = line.origin == LineOrigin::Deletion;
+        Self {
+            file_path: file_path.to_string(),
+            old_side,
+            line_no: if old_side {
+                line.old_line_no
+            } else {
+                line.new_line_no
+            },
+            content: line.content.clone(),
+            fg: [fg.h, fg.s, fg.l, fg.a].map(f32::to_bits),
+        }
+    }
+}
+
+/// Syntax-aware foreground colors, memoized per line because syntect is
+/// too slow to rerun every frame. Lines of a hunk primed with
+/// [`Self::prime_hunk`] are colored in context; others one at a time.
+#[derive(Default)]
+pub struct SyntaxDecorator {
+    cache: RefCell<HashMap<SyntaxKey, Vec<Highlight>>>,
+    #[cfg(test)]
+    pub(crate) computations: std::cell::Cell<usize>,
+}
+
+impl SyntaxDecorator {
+    /// Number of lines with memoized highlights.
+    pub fn cached_lines(&self) -> usize {
+        self.cache.borrow().len()
+    }
+
+    /// Highlight a hunk's lines with the parse state carried through each
+    /// side, so a block comment opened on one line colors the lines after
+    /// it. Deletions take their colors from the old side, other lines from
+    /// the new side. `fg` gives each origin's fallback color.
+    pub fn prime_hunk(
+        &self,
+        file_path: &str,
+        lines: &[DiffLine],
+        fg: impl Fn(&LineOrigin) -> Hsla,
+        is_dark: bool,
+    ) {
+        let keys: Vec<SyntaxKey> = lines
+            .iter()
+            .map(|line| SyntaxKey::new(file_path, line, fg(&line.origin)))
+            .collect();
+        let mut cache = self.cache.borrow_mut();
+        if keys.iter().all(|key| cache.contains_key(key)) {
+            return;
+        }
+
+        let fallback = fg(&LineOrigin::Context);
+        let mut keys: Vec<Option<SyntaxKey>> = keys.into_iter().map(Some).collect();
+        for old_side in [true, false] {
+            let side: Vec<usize> = (0..lines.len())
+                .filter(|&i| match lines[i].origin {
+                    LineOrigin::Context => true,
+                    LineOrigin::Deletion => old_side,
+                    LineOrigin::Addition => !old_side,
+                })
+                .collect();
+            if !side
+                .iter()
+                .any(|&i| (lines[i].origin == LineOrigin::Deletion) == old_side)
+            {
+                continue;
+            }
+            let texts: Vec<&str> = side.iter().map(|&i| lines[i].content.as_str()).collect();
+            let highlights = syntax::highlight_hunk(file_path, &texts, fallback, is_dark);
+            for (&i, line_highlights) in side.iter().zip(highlights) {
+                // Context lines are shown with their new-side colors.
+                if (lines[i].origin == LineOrigin::Deletion) != old_side {
+                    continue;
+                }
+                let Some(key) = keys[i].take() else {
+                    continue;
+                };
+                #[cfg(test)]
+                if !cache.contains_key(&key) {
+                    self.computations.set(self.computations.get() + 1);
+                }
+                cache.insert(
+                    key,
+                    line_highlights
+                        .into_iter()
+                        .map(|sh| (sh.range, color_style(sh.color)))
+                        .collect(),
+                );
+            }
+        }
+    }
+}
+
+impl LineDecorator for SyntaxDecorator {
+    fn decorate(&self, cx: &LineContext) -> Vec<Highlight> {
+        let key = SyntaxKey::new(cx.file_path, cx.line, cx.fg);
+        self.cache
+            .borrow_mut()
+            .entry(key)
+            .or_insert_with(|| {
+                #[cfg(test)]
+                self.computations.set(self.computations.get() + 1);
+                syntax::highlight_line(cx.file_path, &cx.line.content, cx.fg, cx.is_dark)
+                    .into_iter()
+                    .map(|sh| (sh.range, color_style(sh.color)))
+                    .collect()
+            })
+            .clone()
+    }
+
+    fn clear_cache(&self) {
+        self.cache.borrow_mut().clear();
+    }
+}
+
+/// Word-level change backgrounds from `DiffLine::change_spans`.
+pub struct ChangeSpanDecorator;
+
+impl LineDecorator for ChangeSpanDecorator {
+    fn decorate(&self, cx: &LineContext) -> Vec<Highlight> {
+        cx.line
+            .change_spans
+            .iter()
+            .map(|cs| (cs.start..cs.end, background_style(cx.change_bg)))
+            .collect()
+    }
+}
+
+/// Case-insensitive matches of the active search query.
+pub struct SearchDecorator;
+
+impl LineDecorator for SearchDecorator {
+    fn decorate(&self, cx: &LineContext) -> Vec<Highlight> {
+        if cx.search_query.is_empty() {
+            return Vec::new();
+        }
+        let haystack = cx.line.content.to_lowercase();
+        let needle = cx.search_query.to_lowercase();
+        // Lowercasing can change byte lengths for some scripts; only trust
+        // offsets when it did not.
+        if haystack.len() != cx.line.content.len() {
+            return Vec::new();
+        }
+        haystack
+            .match_indices(&needle)
+            .map(|(start, m)| (start..start + m.len(), background_style(cx.search_bg)))
+            .collect()
+    }
+}
+
+/// Marks trailing whitespace when whitespace display is enabled.
+pub struct WhitespaceDecorator;
+
+impl LineDecorator for WhitespaceDecorator {
+    fn decorate(&self, cx: &LineContext) -> Vec<Highlight> {
+        if !cx.show_whitespace {
+            return Vec::new();
+        }
+        let content = &cx.line.content;
+        let trimmed = content.trim_end().len();
+        if trimmed == content.len() {
+            return Vec::new();
+        }
+        vec![(trimmed..content.len(), background_style(cx.whitespace_bg))]
+    }
+}
+
+/// The decorators every diff view starts with, in application order.
+/// `syntax` is shared so the view can inspect and clear its cache.
+pub fn builtin_decorators(syntax: Rc<SyntaxDecorator>) -> Vec<Box<dyn LineDecorator>> {
+    vec![
+        Box::new(syntax),
+        Box::new(ChangeSpanDecorator),
+        Box::new(SearchDecorator),
+        Box::new(WhitespaceDecorator),
+    ]
+}
+
+/// Run `decorators` over a line and merge their layers.
+pub fn decorate_line(decorators: &[Box<dyn LineDecorator>], cx: &LineContext) -> Vec<Highlight> {
+    let layers: Vec<Vec<Highlight>> = decorators.iter().map(|d| d.decorate(cx)).collect();
+    compose_highlights(&layers, cx.line.content.len())
+}
+
+/// Merge highlight layers into sorted, non-overlapping ranges. Where layers
+/// overlap, later layers' set properties win over earlier ones.
+pub fn compose_highlights(layers: &[Vec<Highlight>], len: usize) -> Vec<Highlight> {
+    let mut bounds: Vec<usize> = layers
+        .iter()
+        .flatten()
+        .flat_map(|(range, _)| [range.start.min(len), range.end.min(len)])
+        .collect();
+    bounds.sort_unstable();
+    bounds.dedup();
+
+    let mut result: Vec<Highlight> = Vec::new();
+    for window in bounds.windows(2) {
+        let (start, end) = (window[0], window[1]);
+        let mut style: Option<HighlightStyle> = None;
+        for (range, layer_style) in layers.iter().flatten() {
+            if range.start <= start && end <= range.end {
+                merge_style(
+                    style.get_or_insert_with(HighlightStyle::default),
+                    *layer_style,
+                );
+            }
+        }
+        let Some(style) = style else {
+            continue;
+        };
+        match result.last_mut() {
+            Some((prev, prev_style)) if prev.end == start && *prev_style == style => {
+                prev.end = end;
+            }
+            _ => result.push((start..end, style)),
+        }
+    }
+    result
+}
+
+/// Overlay `over` onto `base`: properties `over` sets replace those in
+/// `base`, the rest are kept.
+fn merge_style(base: &mut HighlightStyle, over: HighlightStyle) {
+    base.color = over.color.or(base.color);
+    base.font_weight = over.font_weight.or(base.font_weight);
+    base.font_style = over.font_style.or(base.font_style);
+    base.background_color = over.background_color.or(base.background_color);
+    base.underline = over.underline.or(base.underline);
+    base.strikethrough = over.strikethrough.or(base.strikethrough);
+    base.fade_out = over.fade_out.or(base.fade_out);
+}
+
+fn color_style(color: Hsla) -> HighlightStyle {
+    HighlightStyle {
+        color: Some(color),
+        ..Default::default()
+    }
+}
+
+fn background_style(color: Hsla) -> HighlightStyle {
+    HighlightStyle {
+        background_color: Some(color),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dd_git::{InlineSpan, LineOrigin};
+
+    fn line(content: &str, spans: &[(usize, usize)]) -> DiffLine {
+        DiffLine {
+            origin: LineOrigin::Addition,
+            content: content.to_string(),
+            old_line_no: None,
+            new_line_no: Some(1),
+            change_spans: spans
+                .iter()
+                .map(|&(start, end)| InlineSpan { start, end })
+                .collect(),
+            no_newline_at_eof: false,
+        }
+    }
+
+    fn context<'a>(line: &'a DiffLine, query: &'a str) -> LineContext<'a> {
+        LineContext {
+            line,
+            file_path: "notes.txt",
+            fg: gpui::white(),
+            change_bg: gpui::green(),
+            search_bg: gpui::yellow(),
+            whitespace_bg: gpui::red(),
+            is_dark: true,
+            search_query: query,
+            show_whitespace: true,
+        }
+    }
+
+    #[test]
+    fn test_whitespace_marks_trailing_only() {
+        let l = line("let x = 1;  ", &[]);
+        let spans = WhitespaceDecorator.decorate(&context(&l, ""));
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].0, 10..12);
+    }
+
+    #[test]
+    fn test_search_is_case_insensitive() {
+        let l = line("Foo foo FOO", &[]);
+        let ranges: Vec<_> = SearchDecorator
+            .decorate(&context(&l, "foo"))
+            .into_iter()
+            .map(|(r, _)| r)
+            .collect();
+        assert_eq!(ranges, vec![0..3, 4..7, 8..11]);
+    }
+
+    #[test]
+    fn test_change_spans_and_search_compose_without_overlap() {
+        let l = line("hello world", &[(0, 8)]);
+        let cx = context(&l, "world");
+        let decorators: Vec<Box<dyn LineDecorator>> =
+            vec![Box::new(ChangeSpanDecorator), Box::new(SearchDecorator)];
+        let merged = decorate_line(&decorators, &cx);
+
+        // Search covers the change span's tail, so 6..8 and 8..11 end up
+        // with the same style and merge.
+        let ranges: Vec<_> = merged.iter().map(|(r, _)| r.clone()).collect();
+        assert_eq!(ranges, vec![0..6, 6..11]);
+        for pair in merged.windows(2) {
+            assert!(pair[0].0.end <= pair[1].0.start);
+        }
+        // The later layer (search) wins where both apply.
+        assert_eq!(merged[1].1.background_color, Some(gpui::yellow()));
+        assert_eq!(merged[0].1.background_color, Some(gpui::green()));
+    }
+
+    #[test]
+    fn test_syntax_highlights_are_memoized() {
+        let syntax = SyntaxDecorator::default();
+        let l = line("let x = 1;", &[]);
+        let first = syntax.decorate(&context(&l, ""));
+        let second = syntax.decorate(&context(&l, ""));
+        assert_eq!(first, second);
+        assert_eq!(syntax.computations.get(), 1);
+
+        syntax.clear_cache();
+        assert_eq!(syntax.cached_lines(), 0);
+        syntax.decorate(&context(&l, ""));
+        assert_eq!(syntax.computations.get(), 2);
+    }
+
+    #[test]
+    fn test_primed_hunk_colors_lines_in_context() {
+        let hunk_line = |origin, old, new, content: &str| DiffLine {
+            origin,
+            content: content.to_string(),
+            old_line_no: old,
+            new_line_no: new,
+            change_spans: Vec::new(),
+            no_newline_at_eof: false,
+        };
+        let lines = vec![
+            hunk_line(LineOrigin::Context, Some(1), Some(1), "/* note"),
+            hunk_line(LineOrigin::Deletion, Some(2), None, "let a = 1;"),
+            hunk_line(LineOrigin::Addition, None, Some(2), "let b = 2;"),
+            hunk_line(LineOrigin::Context, Some(3), Some(3), "*/"),
+        ];
+        let syntax = SyntaxDecorator::default();
+        syntax.prime_hunk("lib.rs", &lines, |_| gpui::white(), true);
+        assert_eq!(syntax.cached_lines(), 4);
+
+        let colors = |line: &DiffLine| {
+            let mut cx = context(line, "");
+            cx.file_path = "lib.rs";
+            let mut colors: Vec<_> = syntax
+                .decorate(&cx)
+                .into_iter()
+                .filter_map(|(_, style)| style.color)
+                .collect();
+            colors.dedup();
+            colors
+        };
+        // Both sides are still inside the comment.
+        assert_eq!(colors(&lines[1]).len(), 1);
+        assert_eq!(colors(&lines[2]), colors(&lines[1]));
+        assert_eq!(syntax.computations.get(), 4);
+    }
+
+    #[test]
+    fn test_compose_keeps_properties_from_both_layers() {
+        let color = vec![(0..5, color_style(gpui::blue()))];
+        let background = vec![(2..4, background_style(gpui::red()))];
+        let merged = compose_highlights(&[color, background], 5);
+
+        assert_eq!(merged.len(), 3);
+        assert_eq!(merged[1].0, 2..4);
+        assert_eq!(merged[1].1.color, Some(gpui::blue()));
+        assert_eq!(merged[1].1.background_color, Some(gpui::red()));
+        assert_eq!(merged[2].1.background_color, None);
+    }
+}
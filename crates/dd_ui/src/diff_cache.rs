@@ -0,0 +1,111 @@
+use std::collections::VecDeque;
+
+use dd_git::FileDiff;
+
+/// Recently loaded commit diffs keyed by OID, most recently used first.
+/// Holds at most `capacity` commits; the least recently used is evicted.
+pub struct DiffCache {
+    capacity: usize,
+    /// Bumped by [`Self::clear`], so a load started before it can tell
+    /// that its diff is stale.
+    generation: u64,
+    entries: VecDeque<(String, Vec<FileDiff>)>,
+}
+
+impl DiffCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            generation: 0,
+            entries: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn contains(&self, oid: &str) -> bool {
+        self.entries.iter().any(|(key, _)| key == oid)
+    }
+
+    /// Look up a commit's diff and mark it as most recently used.
+    pub fn get(&mut self, oid: &str) -> Option<Vec<FileDiff>> {
+        let index = self.entries.iter().position(|(key, _)| key == oid)?;
+        let entry = self.entries.remove(index)?;
+        let diffs = entry.1.clone();
+        self.entries.push_front(entry);
+        Some(diffs)
+    }
+
+    pub fn insert(&mut self, oid: String, diffs: Vec<FileDiff>) {
+        if self.capacity == 0 {
+            return;
+        }
+        self.entries.retain(|(key, _)| *key != oid);
+        self.entries.push_front((oid, diffs));
+        self.entries.truncate(self.capacity);
+    }
+
+    /// Insert a diff loaded while the cache was at `generation`. Diffs
+    /// from before the last [`Self::clear`] are dropped.
+    pub fn insert_at(&mut self, generation: u64, oid: String, diffs: Vec<FileDiff>) {
+        if generation == self.generation {
+            self.insert(oid, diffs);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.generation += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let mut cache = DiffCache::new(2);
+        cache.insert("a".into(), Vec::new());
+        cache.insert("b".into(), Vec::new());
+        assert!(cache.get("a").is_some());
+        cache.insert("c".into(), Vec::new());
+
+        assert_eq!(cache.len(), 2);
+        assert!(cache.contains("a"));
+        assert!(!cache.contains("b"));
+        assert!(cache.contains("c"));
+    }
+
+    #[test]
+    fn test_reinserting_does_not_duplicate() {
+        let mut cache = DiffCache::new(4);
+        cache.insert("a".into(), Vec::new());
+        cache.insert("a".into(), Vec::new());
+        assert_eq!(cache.len(), 1);
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_insert_from_before_clear_is_dropped() {
+        let mut cache = DiffCache::new(4);
+        let generation = cache.generation();
+        cache.clear();
+        cache.insert_at(generation, "a".into(), Vec::new());
+        assert!(cache.is_empty());
+
+        cache.insert_at(cache.generation(), "a".into(), Vec::new());
+        assert!(cache.contains("a"));
+    }
+}
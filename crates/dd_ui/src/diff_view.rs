@@ -1,21 +1,60 @@
-use std::ops::Range;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::Arc;
 
 use gpui::prelude::*;
 use gpui::{
-    canvas, px, App, Bounds, Context, HighlightStyle, Hsla, Pixels, SharedString, StyledText,
-    Window,
+    actions, anchored, canvas, deferred, point, px, App, Bounds, ClickEvent, ClipboardItem,
+    Context, Entity, FocusHandle, Focusable, HighlightStyle, Hsla, KeyContext, MouseButton,
+    MouseDownEvent, Pixels, Point, ScrollHandle, SharedString, StrikethroughStyle, StyledText,
+    Subscription, Task, Window,
 };
-use gpui_component::{scroll::ScrollableElement, v_flex, ActiveTheme};
+use gpui_component::input::{Input, InputEvent, InputState};
+use gpui_component::{h_flex, scroll::ScrollableElement, tooltip::Tooltip, v_flex, ActiveTheme};
 
+use dd_core::{
+    is_generated, Branding, ChangeMarkerStyle, DiffPalette, GeneratedFileMode, Preferences,
+    ReviewNotes, SplitGutter,
+};
 use dd_git::{
-    split_hunk_lines, CommitInfo, DiffLine, FileDiff, Hunk, LineOrigin, SignatureStatus, SplitRow,
+    expand_hunk_context, hidden_lines_above, line_permalink, merge_word_diff, split_hunk_lines,
+    CommitInfo, DiffLine, ExpandDirection, FileDiff, FileStatus, Hunk, ImageFormat, ImageVersions,
+    LfsPointer, LineEnding, LineEndingStats, LineOrigin, SignatureStatus, SplitRow, WhitespaceMode,
+    WordTag,
+};
+
+use crate::commit_list::{render_conventional_chip, truncate_end};
+use crate::decorators::{
+    builtin_decorators, decorate_line, LineAnnotation, LineContext, LineDecorator, SyntaxDecorator,
 };
+use crate::fuzzy::fuzzy_rank;
+use crate::scroll_anchor::{find_anchor, offset_for_anchor, Anchor, LaidOutRow};
+use crate::theme::{theme_generation, DiffTheme};
+
+actions!(
+    diff_view,
+    [
+        StageHunk,
+        UnstageHunk,
+        DiscardHunk,
+        NextHunk,
+        PreviousHunk,
+        ToggleFilePalette,
+        CycleViewMode,
+        CycleWhitespaceMode
+    ]
+);
 
-use crate::syntax;
-use crate::theme::DiffTheme;
+/// Most matches listed under the file palette's query box.
+const FILE_PALETTE_MAX_ROWS: usize = 12;
 
 const SPLIT_VIEW_MIN_WIDTH: f32 = 1000.0;
 
+/// Number of context lines revealed per "expand" click.
+const CONTEXT_EXPAND_STEP: u32 = 10;
+
 fn fallback_color(
     origin: &LineOrigin,
     diff_theme: &DiffTheme,
@@ -27,903 +66,5291 @@ fn fallback_color(
     }
 }
 
+/// Width of the column holding [`LineAnnotation`] glyphs.
+const ANNOTATION_GUTTER_WIDTH: f32 = 16.0;
+
+/// Gutter glyph for a line with a review note.
+const REVIEW_NOTE_GLYPH: &str = "\u{270E}";
+
+/// Width of the left-edge bar in `ChangeMarkerStyle::Bar` mode.
+const CHANGE_BAR_WIDTH: f32 = 3.0;
+
+/// Color of a line's change bar; `None` for unchanged lines.
+fn change_bar_color(line: &DiffLine, diff_theme: &DiffTheme) -> Option<Hsla> {
+    match line.origin {
+        LineOrigin::Addition => Some(diff_theme.add_highlight_bg),
+        LineOrigin::Deletion => Some(diff_theme.del_highlight_bg),
+        LineOrigin::Context => None,
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum DiffViewMode {
+pub enum DiffViewMode {
     Unified,
     Split,
 }
 
+impl DiffViewMode {
+    /// Name stored in `RepoTab::diff_view_mode`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DiffViewMode::Unified => "unified",
+            DiffViewMode::Split => "split",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "unified" => Some(DiffViewMode::Unified),
+            "split" => Some(DiffViewMode::Split),
+            _ => None,
+        }
+    }
+}
+
+/// How the two versions of a changed image are compared.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ImageCompareMode {
+    SideBySide,
+    /// The new version drawn over the old one at `image_overlay_opacity`.
+    Overlay,
+}
+
+/// Step for the overlay opacity controls.
+const IMAGE_OPACITY_STEP: f32 = 0.25;
+
+/// Both sides of an image change, ready to draw.
+struct ImagePair {
+    old: Option<Arc<gpui::Image>>,
+    new: Option<Arc<gpui::Image>>,
+}
+
+impl ImagePair {
+    fn new(versions: ImageVersions) -> Option<Self> {
+        let format = match versions.format()? {
+            ImageFormat::Png => gpui::ImageFormat::Png,
+            ImageFormat::Jpeg => gpui::ImageFormat::Jpeg,
+            ImageFormat::Gif => gpui::ImageFormat::Gif,
+        };
+        let image = |bytes: Vec<u8>| Arc::new(gpui::Image::from_bytes(format, bytes));
+        Some(Self {
+            old: versions.old.map(image),
+            new: versions.new.map(image),
+        })
+    }
+}
+
+/// Paired side-by-side rows by `(file_index, hunk_index)`.
+type SplitRowCache = RefCell<HashMap<(usize, usize), Rc<Vec<SplitRow>>>>;
+
+/// Reads a file in full at a commit (`oid`, file) for a [`FullFileView`].
+/// Runs on the background executor.
+type FullFileLoader = Arc<dyn Fn(&str, &FileDiff) -> anyhow::Result<FileDiff> + Send + Sync>;
+
+/// A file shown in full instead of as hunks. A file has at most one.
+enum FullFileView {
+    /// Both complete versions side by side.
+    BothVersions(Rc<Vec<SplitRow>>),
+    /// The new version with the changes inline.
+    Whole(Rc<FileDiff>),
+}
+
+/// The inline editor open under one line of the current commit.
+struct NoteEditor {
+    path: String,
+    line: u32,
+    input: Entity<InputState>,
+    _subscription: Subscription,
+}
+
+/// Quick-jump box over the changed files of the current diff.
+struct FilePalette {
+    input: Entity<InputState>,
+    /// Indices into `diffs` matching the query, best match first.
+    matches: Vec<usize>,
+    _subscription: Subscription,
+}
+
+/// Row shown in place of git's `\ No newline at end of file` marker.
+const NO_NEWLINE_LABEL: &str = "\u{21B5} No newline at end of file";
+const PURE_RENAME_LABEL: &str = "File renamed, no content changes";
+const PURE_COPY_LABEL: &str = "File copied, no content changes";
+
+/// A rename or copy that left the content as it was. Binary files have no
+/// hunks even when they changed, so only git's 100% similarity counts.
+fn is_pure_rename(file: &FileDiff) -> bool {
+    matches!(file.status, FileStatus::Renamed | FileStatus::Copied)
+        && file.hunks.is_empty()
+        && file.similarity == Some(100)
+}
+
+/// Human-readable byte count with one decimal, e.g. `2.3 MB`.
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{size:.1} {}", UNITS[unit])
+}
+
+/// One visual row of a unified hunk.
+#[derive(Debug)]
+enum UnifiedRow<'a> {
+    Line(&'a DiffLine),
+    /// A deletion and the addition it was paired with, shown as one line.
+    WordDiff(&'a DiffLine, &'a DiffLine),
+    NoNewline,
+}
+
+impl UnifiedRow<'_> {
+    /// The new-side line the row shows, which scroll anchors pin.
+    fn new_line_no(&self) -> Option<u32> {
+        match self {
+            UnifiedRow::Line(line) => line.new_line_no,
+            UnifiedRow::WordDiff(_, add) => add.new_line_no,
+            UnifiedRow::NoNewline => None,
+        }
+    }
+}
+
+/// Rows for a unified hunk. With `word_diff`, each run of deletions is
+/// paired 1:1 with the additions that follow it; unpaired lines are kept
+/// as they are.
+fn unified_rows(lines: &[DiffLine], word_diff: bool) -> Vec<UnifiedRow<'_>> {
+    fn push_line<'a>(rows: &mut Vec<UnifiedRow<'a>>, line: &'a DiffLine) {
+        rows.push(UnifiedRow::Line(line));
+        if line.no_newline_at_eof {
+            rows.push(UnifiedRow::NoNewline);
+        }
+    }
+
+    let mut rows = Vec::with_capacity(lines.len());
+    let mut i = 0;
+    while i < lines.len() {
+        if !word_diff || lines[i].origin != LineOrigin::Deletion {
+            push_line(&mut rows, &lines[i]);
+            i += 1;
+            continue;
+        }
+        let dels_end = i + lines[i..]
+            .iter()
+            .take_while(|l| l.origin == LineOrigin::Deletion)
+            .count();
+        let adds_end = dels_end
+            + lines[dels_end..]
+                .iter()
+                .take_while(|l| l.origin == LineOrigin::Addition)
+                .count();
+        let (dels, adds) = (&lines[i..dels_end], &lines[dels_end..adds_end]);
+        let paired = dels.len().min(adds.len());
+        for (del, add) in dels.iter().zip(adds) {
+            rows.push(UnifiedRow::WordDiff(del, add));
+            if del.no_newline_at_eof || add.no_newline_at_eof {
+                rows.push(UnifiedRow::NoNewline);
+            }
+        }
+        for line in dels[paired..].iter().chain(&adds[paired..]) {
+            push_line(&mut rows, line);
+        }
+        i = adds_end;
+    }
+    rows
+}
+
+/// One visual row of a split hunk. `NoNewline` flags which sides carry the
+/// marker for the row above it.
+#[derive(Debug)]
+enum SplitItem<'a> {
+    Row(&'a SplitRow),
+    NoNewline { left: bool, right: bool },
+}
+
+impl SplitItem<'_> {
+    fn new_line_no(&self) -> Option<u32> {
+        match self {
+            SplitItem::Row(row) => row.right.as_ref().and_then(|line| line.new_line_no),
+            SplitItem::NoNewline { .. } => None,
+        }
+    }
+}
+
+fn split_items(rows: &[SplitRow]) -> Vec<SplitItem<'_>> {
+    let flagged = |line: &Option<std::sync::Arc<DiffLine>>| {
+        line.as_ref().is_some_and(|l| l.no_newline_at_eof)
+    };
+    let mut items = Vec::with_capacity(rows.len());
+    for row in rows {
+        items.push(SplitItem::Row(row));
+        let (left, right) = (flagged(&row.left), flagged(&row.right));
+        if left || right {
+            items.push(SplitItem::NoNewline { left, right });
+        }
+    }
+    items
+}
+
 #[derive(Debug, Clone, Copy)]
 enum SplitSide {
     Left,
     Right,
 }
 
+/// Width of one line-number column in the diff gutter.
+const GUTTER_COLUMN_WIDTH: f32 = 48.0;
+
+/// Line-number columns for one side of a split row.
+///
+/// `SplitRow` guarantees the left side never holds an addition and the right
+/// never a deletion, so the side's own number is always present.
+fn split_gutter_columns(line: &DiffLine, side: SplitSide, gutter: SplitGutter) -> Vec<String> {
+    let fmt = |n: Option<u32>| {
+        n.map(|n| format!("{:>4}", n))
+            .unwrap_or_else(|| "    ".to_string())
+    };
+    match (gutter, side) {
+        (SplitGutter::SideOnly, SplitSide::Left) => vec![fmt(line.old_line_no)],
+        (SplitGutter::SideOnly, SplitSide::Right) => vec![fmt(line.new_line_no)],
+        (SplitGutter::Both, _) => vec![fmt(line.old_line_no), fmt(line.new_line_no)],
+    }
+}
+
+/// Old and new line numbers of a hunk's first change. Unlike its index or
+/// start, this stays put when context is revealed around the hunk.
+type HunkKey = (Option<u32>, Option<u32>);
+
+fn hunk_key(hunk: &Hunk) -> HunkKey {
+    hunk.lines
+        .iter()
+        .find(|line| line.origin != LineOrigin::Context)
+        .map(|line| (line.old_line_no, line.new_line_no))
+        .unwrap_or((Some(hunk.old_start), Some(hunk.new_start)))
+}
+
+/// A line's context menu, shown where it was right-clicked.
+struct LineMenu {
+    file_index: usize,
+    line: DiffLine,
+    position: Point<Pixels>,
+}
+
+/// Operation requested on the focused hunk of a working-changes diff.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HunkAction {
+    Stage,
+    Unstage,
+    Discard,
+}
+
+impl HunkAction {
+    /// Staging and discarding work on unstaged hunks; unstaging on staged
+    /// ones.
+    fn applies_to(self, changes: WorkingChanges) -> bool {
+        match self {
+            HunkAction::Stage | HunkAction::Discard => changes == WorkingChanges::Unstaged,
+            HunkAction::Unstage => changes == WorkingChanges::Staged,
+        }
+    }
+}
+
+/// Which uncommitted changes a diff shows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkingChanges {
+    /// The work tree against the index.
+    Unstaged,
+    /// The index against `HEAD`.
+    Staged,
+}
+
 pub struct DiffView {
     diffs: Vec<FileDiff>,
     commit_info: Option<CommitInfo>,
     signature_status: Option<SignatureStatus>,
+    /// Local branches that include the displayed commit.
+    containing_branches: Vec<String>,
+    /// Subjects of a merge's parents, in `parent_oids` order.
+    parent_subjects: Vec<String>,
+    /// Called with a parent's OID when its chip in a merge header is clicked.
+    #[allow(clippy::type_complexity)]
+    on_navigate_parent: Option<Box<dyn Fn(&str, &mut Window, &mut Context<Self>) + 'static>>,
     error_message: Option<String>,
+    /// A diff is being computed; shown until data or an error arrives.
+    loading: bool,
     mode: DiffViewMode,
+    /// Layout chosen by the user; when set, the width no longer decides.
+    preferred_mode: Option<DiffViewMode>,
+    /// Files whose trailing context has been expanded to end-of-file.
+    expanded_to_eof: HashSet<usize>,
+    /// Working directory used to build absolute file paths.
+    repo_root: Option<PathBuf>,
+    #[allow(clippy::type_complexity)]
+    file_loader: Option<Box<dyn Fn(&str, &str) -> anyhow::Result<String> + 'static>>,
+    /// Contents read by `file_loader`, by (`oid`, `path`), so repeated
+    /// expansions of one file read it once.
+    file_contents: HashMap<(String, String), Rc<str>>,
+    /// Builds a whole-file comparison of a file at a commit.
+    full_file_loader: Option<FullFileLoader>,
+    /// Reads a file's new version with the commit's additions marked.
+    whole_file_loader: Option<FullFileLoader>,
+    /// Files shown in full rather than as hunks, by file index.
+    full_files: HashMap<usize, FullFileView>,
+    /// Full-file reads still in flight, by file index. Dropping one
+    /// cancels it.
+    full_file_loads: HashMap<usize, Task<()>>,
+    /// Images in the current diff, by file index.
+    images: HashMap<usize, ImagePair>,
+    image_compare: ImageCompareMode,
+    image_overlay_opacity: f32,
+    /// Rewrites a working-tree file to a single line ending.
+    #[allow(clippy::type_complexity)]
+    line_ending_normalizer: Option<Box<dyn Fn(&str, LineEnding) -> anyhow::Result<()> + 'static>>,
+    /// Files in the current commit that mix LF and CRLF.
+    mixed_line_endings: HashMap<String, LineEndingStats>,
+    /// URL of the `origin` remote, used to build line permalinks.
+    remote_url: Option<String>,
+    /// Opens permalinks; defaults to the system browser.
+    #[allow(clippy::type_complexity)]
+    url_opener: Option<Box<dyn Fn(&str, &mut App) + 'static>>,
+    /// Context menu opened by right-clicking a line.
+    line_menu: Option<LineMenu>,
+    /// Globs from `Preferences` identifying generated files.
+    generated_patterns: Vec<String>,
+    generated_mode: GeneratedFileMode,
+    split_gutter: SplitGutter,
+    diff_palette: DiffPalette,
+    /// Highlight passes applied to every line's content, in order.
+    decorators: Vec<Box<dyn LineDecorator>>,
+    /// Gutter marks per file path, keyed by new-side line number.
+    line_annotations: HashMap<String, HashMap<u32, LineAnnotation>>,
+    /// Local review notes of the repository, across all commits.
+    review_notes: ReviewNotes,
+    note_editor: Option<NoteEditor>,
+    file_palette: Option<FilePalette>,
+    /// Called with every note after one is added, edited or removed.
+    #[allow(clippy::type_complexity)]
+    on_review_notes_change:
+        Option<Box<dyn Fn(&ReviewNotes, &mut Window, &mut Context<Self>) + 'static>>,
+    /// Called with the layout the user pinned by cycling through them.
+    #[allow(clippy::type_complexity)]
+    on_preferred_mode_change:
+        Option<Box<dyn Fn(DiffViewMode, &mut Window, &mut Context<Self>) + 'static>>,
+    /// The built-in syntax decorator, also held in `decorators`.
+    syntax: Rc<SyntaxDecorator>,
+    /// [`theme_generation`] the render caches were built for.
+    theme_generation: u64,
+    search_query: String,
+    show_whitespace: bool,
+    /// Show the old/new line-number gutters beside each line.
+    show_line_numbers: bool,
+    /// Shown when there is no diff to display.
+    placeholder: String,
+    change_marker_style: ChangeMarkerStyle,
+    /// Merge paired deletions/additions into single lines in unified mode.
+    word_diff: bool,
+    /// How whitespace-only changes were treated when the diff was computed.
+    whitespace_mode: WhitespaceMode,
+    /// Called when the user picks another whitespace mode; the owner
+    /// recomputes the diff with it.
+    #[allow(clippy::type_complexity)]
+    on_whitespace_mode_change:
+        Option<Box<dyn Fn(WhitespaceMode, &mut Window, &mut Context<Self>) + 'static>>,
+    /// Collapsed generated files the user chose to show anyway.
+    revealed_generated: HashSet<usize>,
+    /// Files rendered before a "Show remaining" row stands in for the rest.
+    max_files: usize,
+    /// Whether the user expanded the files past `max_files`.
+    show_all_files: bool,
+    focus_handle: FocusHandle,
+    /// `(file_index, hunk_index)` targeted by the hunk keyboard shortcuts.
+    focused_hunk: Option<(usize, usize)>,
+    /// Scroll position of the file list, for jumping to a file.
+    scroll_handle: ScrollHandle,
+    /// File last brought into view by [`Self::scroll_to_file`].
+    scrolled_file: Option<usize>,
+    /// Rows recorded so far while the current frame is prepainted.
+    frame_rows: Rc<RefCell<Vec<LaidOutRow>>>,
+    /// Rows of the last frame drawn, for anchoring the scroll position.
+    laid_out_rows: Rc<RefCell<Vec<LaidOutRow>>>,
+    /// Row to scroll back to once the reshaped diff has been laid out.
+    pending_anchor: Rc<RefCell<Option<Anchor>>>,
+    /// Side-by-side rows per `(file_index, hunk_index)`, built on first
+    /// render so large hunks are not re-paired every frame.
+    split_rows: SplitRowCache,
+    /// Hunks shown side-by-side while the rest of the view is unified, by
+    /// file path and [`HunkKey`].
+    split_hunks: HashSet<(String, HunkKey)>,
+    #[cfg(test)]
+    split_computations: std::cell::Cell<usize>,
+    #[allow(clippy::type_complexity)]
+    on_hunk_action: Option<
+        Box<dyn Fn(HunkAction, &FileDiff, usize, &mut Window, &mut Context<Self>) + 'static>,
+    >,
+    /// Set while the diffs are uncommitted changes rather than a commit.
+    working_changes: Option<WorkingChanges>,
+    /// Files of a working-changes diff picked for stashing, by file index.
+    selected_files: HashSet<usize>,
+    #[allow(clippy::type_complexity)]
+    on_stash_files: Option<Box<dyn Fn(Vec<String>, &mut Window, &mut Context<Self>) + 'static>>,
+    /// Whether unstaged changes also list the files ignore rules exclude.
+    /// Kept across reloads.
+    show_ignored: bool,
+    /// Ignored paths listed under unstaged changes while `show_ignored`.
+    ignored_files: Vec<String>,
+    #[allow(clippy::type_complexity)]
+    on_show_ignored_change: Option<Box<dyn Fn(bool, &mut Window, &mut Context<Self>) + 'static>>,
 }
 
 impl DiffView {
-    pub fn new_empty() -> Self {
+    pub fn new_empty(cx: &mut App) -> Self {
+        let syntax = Rc::new(SyntaxDecorator::default());
         Self {
             diffs: Vec::new(),
             commit_info: None,
             signature_status: None,
+            containing_branches: Vec::new(),
+            parent_subjects: Vec::new(),
+            on_navigate_parent: None,
             error_message: None,
+            loading: false,
             mode: DiffViewMode::Unified,
+            preferred_mode: None,
+            expanded_to_eof: HashSet::new(),
+            repo_root: None,
+            file_loader: None,
+            file_contents: HashMap::new(),
+            full_file_loader: None,
+            whole_file_loader: None,
+            full_files: HashMap::new(),
+            full_file_loads: HashMap::new(),
+            images: HashMap::new(),
+            image_compare: ImageCompareMode::SideBySide,
+            image_overlay_opacity: 0.5,
+            line_ending_normalizer: None,
+            mixed_line_endings: HashMap::new(),
+            remote_url: None,
+            url_opener: None,
+            line_menu: None,
+            generated_patterns: Vec::new(),
+            generated_mode: GeneratedFileMode::default(),
+            split_gutter: SplitGutter::default(),
+            diff_palette: DiffPalette::default(),
+            decorators: builtin_decorators(syntax.clone()),
+            line_annotations: HashMap::new(),
+            review_notes: ReviewNotes::default(),
+            note_editor: None,
+            file_palette: None,
+            on_review_notes_change: None,
+            on_preferred_mode_change: None,
+            syntax,
+            theme_generation: theme_generation(cx),
+            search_query: String::new(),
+            show_whitespace: false,
+            show_line_numbers: true,
+            placeholder: Branding::default().empty_diff_message().to_string(),
+            change_marker_style: ChangeMarkerStyle::default(),
+            word_diff: false,
+            whitespace_mode: WhitespaceMode::default(),
+            on_whitespace_mode_change: None,
+            revealed_generated: HashSet::new(),
+            max_files: Preferences::default().max_diff_files,
+            show_all_files: false,
+            focus_handle: cx.focus_handle(),
+            focused_hunk: None,
+            scroll_handle: ScrollHandle::new(),
+            scrolled_file: None,
+            frame_rows: Rc::default(),
+            laid_out_rows: Rc::default(),
+            pending_anchor: Rc::default(),
+            split_rows: RefCell::default(),
+            split_hunks: HashSet::new(),
+            #[cfg(test)]
+            split_computations: std::cell::Cell::new(0),
+            on_hunk_action: None,
+            working_changes: None,
+            selected_files: HashSet::new(),
+            on_stash_files: None,
+            show_ignored: false,
+            ignored_files: Vec::new(),
+            on_show_ignored_change: None,
         }
     }
 
-    pub fn diffs(&self) -> &[FileDiff] {
-        &self.diffs
+    pub fn set_repo_root(&mut self, root: PathBuf) {
+        self.repo_root = Some(root);
     }
 
-    pub fn commit_info(&self) -> Option<&CommitInfo> {
-        self.commit_info.as_ref()
+    /// Copy a file's path to the clipboard. `absolute` joins it onto the
+    /// repository root; otherwise the repo-relative path is copied.
+    pub fn copy_file_path(&self, file_index: usize, absolute: bool, cx: &mut Context<Self>) {
+        let Some(file) = self.diffs.get(file_index) else {
+            return;
+        };
+        let text = match (&self.repo_root, absolute) {
+            (Some(root), true) => root.join(&file.path).display().to_string(),
+            _ => file.path.clone(),
+        };
+        cx.write_to_clipboard(ClipboardItem::new_string(text));
     }
 
-    pub fn error_message(&self) -> Option<&str> {
-        self.error_message.as_deref()
+    /// Install the function used to read a file at a commit (`oid`, `path`)
+    /// when expanding folded context.
+    pub fn set_file_loader(
+        &mut self,
+        loader: impl Fn(&str, &str) -> anyhow::Result<String> + 'static,
+    ) {
+        self.file_loader = Some(Box::new(loader));
     }
 
-    pub fn set_diffs(&mut self, diffs: Vec<FileDiff>, cx: &mut Context<Self>) {
-        self.diffs = diffs;
-        self.commit_info = None;
-        self.signature_status = None;
-        self.error_message = None;
-        cx.notify();
+    /// Install the function that diffs both complete versions of a file at
+    /// a commit, for the whole-file comparison.
+    pub fn set_full_file_loader(
+        &mut self,
+        loader: impl Fn(&str, &FileDiff) -> anyhow::Result<FileDiff> + Send + Sync + 'static,
+    ) {
+        self.full_file_loader = Some(Arc::new(loader));
     }
 
-    pub fn set_commit_data(
+    /// Whether a file is shown as its complete old and new versions.
+    pub fn is_full_file(&self, file_index: usize) -> bool {
+        matches!(
+            self.full_files.get(&file_index),
+            Some(FullFileView::BothVersions(_))
+        )
+    }
+
+    /// Switch a file between its hunks and a side-by-side view of both
+    /// complete versions, which are read in the background.
+    pub fn toggle_full_file(&mut self, file_index: usize, cx: &mut Context<Self>) {
+        if self.is_full_file(file_index) {
+            self.full_files.remove(&file_index);
+            cx.notify();
+            return;
+        }
+        let loader = self.full_file_loader.clone();
+        self.load_full_file(file_index, loader, cx, |full| {
+            let lines: Vec<_> = full.hunks.iter().flat_map(|h| h.lines.clone()).collect();
+            FullFileView::BothVersions(Rc::new(split_hunk_lines(&lines)))
+        });
+    }
+
+    /// Read `file_index` with `loader` off the UI thread and show it as
+    /// `view` builds it. Replaces a read already pending for the file.
+    fn load_full_file(
         &mut self,
-        commit: CommitInfo,
-        signature: SignatureStatus,
-        diffs: Vec<FileDiff>,
+        file_index: usize,
+        loader: Option<FullFileLoader>,
         cx: &mut Context<Self>,
+        view: impl FnOnce(FileDiff) -> FullFileView + 'static,
     ) {
-        self.commit_info = Some(commit);
-        self.signature_status = Some(signature);
-        self.diffs = diffs;
-        self.error_message = None;
-        cx.notify();
+        let (Some(commit), Some(loader)) = (self.commit_info.as_ref(), loader) else {
+            return;
+        };
+        let Some(file) = self.diffs.get(file_index).cloned() else {
+            return;
+        };
+        let oid = commit.oid.clone();
+        let load = cx.spawn(async move |this, cx| {
+            let path = file.path.clone();
+            let result = cx
+                .background_executor()
+                .spawn(async move { loader(&oid, &file) })
+                .await;
+            let _ = this.update(cx, |this, cx| {
+                this.full_file_loads.remove(&file_index);
+                match result {
+                    Ok(full) => {
+                        this.full_files.insert(file_index, view(full));
+                    }
+                    Err(e) => eprintln!("failed to load {path} in full: {e}"),
+                }
+                cx.notify();
+            });
+        });
+        self.full_file_loads.insert(file_index, load);
     }
 
-    pub fn set_error(&mut self, message: String, cx: &mut Context<Self>) {
-        self.error_message = Some(message);
-        self.diffs.clear();
-        self.commit_info = None;
-        self.signature_status = None;
-        cx.notify();
+    /// Install the function that reads a file's new version with the
+    /// commit's additions marked, for the whole-file view.
+    pub fn set_whole_file_loader(
+        &mut self,
+        loader: impl Fn(&str, &FileDiff) -> anyhow::Result<FileDiff> + Send + Sync + 'static,
+    ) {
+        self.whole_file_loader = Some(Arc::new(loader));
     }
 
-    // -- Shared helpers ---------------------------------------------------
+    /// Whether a file is shown in full with its changes inline.
+    pub fn is_whole_file(&self, file_index: usize) -> bool {
+        matches!(
+            self.full_files.get(&file_index),
+            Some(FullFileView::Whole(_))
+        )
+    }
 
-    fn render_file_header(&self, file: &FileDiff, cx: &Context<Self>) -> gpui::Div {
-        let status_label = match file.status {
-            dd_git::FileStatus::Added => "A",
-            dd_git::FileStatus::Deleted => "D",
-            dd_git::FileStatus::Modified => "M",
-            dd_git::FileStatus::Renamed => "R",
-        };
+    /// Switch a file between its hunks and its complete new version with
+    /// the added lines marked. The file is read in the background, once
+    /// per toggle.
+    pub fn toggle_whole_file(&mut self, file_index: usize, cx: &mut Context<Self>) {
+        if self.is_whole_file(file_index) {
+            self.full_files.remove(&file_index);
+            cx.notify();
+            return;
+        }
+        let loader = self.whole_file_loader.clone();
+        self.load_full_file(file_index, loader, cx, |whole| {
+            FullFileView::Whole(Rc::new(whole))
+        });
+    }
 
-        let path_display = if let Some(ref old) = file.old_path {
-            format!("{} {} \u{2192} {}", status_label, old, file.path)
-        } else {
-            format!("{} {}", status_label, file.path)
-        };
+    /// Install the function that rewrites a working-tree file to a single
+    /// line ending.
+    pub fn set_line_ending_normalizer(
+        &mut self,
+        normalizer: impl Fn(&str, LineEnding) -> anyhow::Result<()> + 'static,
+    ) {
+        self.line_ending_normalizer = Some(Box::new(normalizer));
+    }
 
-        gpui::div()
-            .px_3()
-            .py_1()
-            .bg(cx.theme().muted)
-            .text_sm()
-            .font_weight(gpui::FontWeight::BOLD)
-            .child(path_display)
+    /// Warn about the files in `stats`, counted off the UI thread from their
+    /// working copies. Only mixed files are kept, and only while unstaged
+    /// changes are shown, since those are the files normalizing rewrites.
+    pub fn set_mixed_line_endings(
+        &mut self,
+        stats: HashMap<String, LineEndingStats>,
+        cx: &mut Context<Self>,
+    ) {
+        if self.working_changes != Some(WorkingChanges::Unstaged) {
+            self.mixed_line_endings.clear();
+            return;
+        }
+        self.mixed_line_endings = stats
+            .into_iter()
+            .filter(|(_, stats)| stats.is_mixed())
+            .collect();
+        cx.notify();
     }
 
-    fn render_content(
-        &self,
-        line: &DiffLine,
-        file_path: &str,
-        diff_theme: &DiffTheme,
-        cx: &Context<Self>,
-    ) -> StyledText {
-        let theme = cx.theme();
-        let content = &line.content;
+    pub fn mixed_line_endings(&self, path: &str) -> Option<LineEndingStats> {
+        self.mixed_line_endings.get(path).copied()
+    }
 
-        let fg = fallback_color(&line.origin, diff_theme, theme);
-        let is_dark = theme.background.l < 0.5;
+    /// Rewrite a file's working copy to use `ending` throughout.
+    pub fn normalize_line_endings(
+        &mut self,
+        file_index: usize,
+        ending: LineEnding,
+        cx: &mut Context<Self>,
+    ) {
+        let (Some(normalizer), Some(file)) =
+            (&self.line_ending_normalizer, self.diffs.get(file_index))
+        else {
+            return;
+        };
+        let path = file.path.clone();
+        match normalizer(&path, ending) {
+            Ok(()) => {
+                self.mixed_line_endings.remove(&path);
+            }
+            Err(e) => eprintln!("failed to normalize line endings in {path}: {e}"),
+        }
+        cx.notify();
+    }
 
-        let highlight_bg = match line.origin {
-            LineOrigin::Addition => diff_theme.add_highlight_bg,
-            LineOrigin::Deletion => diff_theme.del_highlight_bg,
-            LineOrigin::Context => diff_theme.ctx_bg,
+    /// Show both versions of the current commit's images, read off the UI
+    /// thread, by file index. Git reports images only as binary changes.
+    pub fn set_images(&mut self, images: HashMap<usize, ImageVersions>, cx: &mut Context<Self>) {
+        self.images = images
+            .into_iter()
+            .filter_map(|(index, versions)| Some((index, ImagePair::new(versions)?)))
+            .collect();
+        cx.notify();
+    }
+
+    /// Switch images between side-by-side and overlay comparison.
+    pub fn toggle_image_compare(&mut self, cx: &mut Context<Self>) {
+        self.image_compare = match self.image_compare {
+            ImageCompareMode::SideBySide => ImageCompareMode::Overlay,
+            ImageCompareMode::Overlay => ImageCompareMode::SideBySide,
         };
+        cx.notify();
+    }
+
+    /// Fade the new version in the overlay comparison in or out by `delta`.
+    pub fn adjust_image_opacity(&mut self, delta: f32, cx: &mut Context<Self>) {
+        self.image_overlay_opacity = (self.image_overlay_opacity + delta).clamp(0.0, 1.0);
+        cx.notify();
+    }
 
-        let mut highlights: Vec<(Range<usize>, HighlightStyle)> = Vec::new();
+    pub fn set_generated_filter(
+        &mut self,
+        patterns: Vec<String>,
+        mode: GeneratedFileMode,
+        cx: &mut Context<Self>,
+    ) {
+        self.generated_patterns = patterns;
+        self.generated_mode = mode;
+        cx.notify();
+    }
 
-        // Syntax foreground colors
-        let syntax_highlights = syntax::highlight_line(file_path, content, fg, is_dark);
-        for sh in &syntax_highlights {
-            highlights.push((
-                sh.range.clone(),
-                HighlightStyle {
-                    color: Some(sh.color),
-                    ..Default::default()
-                },
-            ));
+    /// Drop memoized rendering: split rows and decorator output.
+    fn clear_render_caches(&mut self) {
+        self.split_rows.get_mut().clear();
+        for decorator in &self.decorators {
+            decorator.clear_cache();
         }
+    }
 
-        // Change-span background colors
-        for cs in &line.change_spans {
-            highlights.push((
-                cs.start..cs.end,
-                HighlightStyle {
-                    background_color: Some(highlight_bg),
-                    ..Default::default()
-                },
-            ));
+    /// Clear the render caches if the theme changed since they were built,
+    /// since their colors depend on it.
+    fn sync_theme_generation(&mut self, cx: &App) {
+        let generation = theme_generation(cx);
+        if self.theme_generation != generation {
+            self.theme_generation = generation;
+            self.clear_render_caches();
         }
+    }
 
-        StyledText::new(SharedString::from(content.clone())).with_highlights(highlights)
+    /// Append a decorator that runs after the built-in ones.
+    pub fn add_decorator(
+        &mut self,
+        decorator: impl LineDecorator + 'static,
+        cx: &mut Context<Self>,
+    ) {
+        self.decorators.push(Box::new(decorator));
+        cx.notify();
     }
 
-    // -- Unified rendering ------------------------------------------------
+    /// Replace the gutter marks for `file`. Keys are new-side line numbers,
+    /// so deleted lines are never annotated; an empty map clears the file.
+    pub fn set_line_annotations(
+        &mut self,
+        file: &str,
+        annotations: HashMap<u32, LineAnnotation>,
+        cx: &mut Context<Self>,
+    ) {
+        if annotations.is_empty() {
+            self.line_annotations.remove(file);
+        } else {
+            self.line_annotations.insert(file.to_string(), annotations);
+        }
+        cx.notify();
+    }
 
-    fn render_unified(&self, cx: &Context<Self>) -> gpui::AnyElement {
-        let file_elements: Vec<_> = self
-            .diffs
-            .iter()
-            .map(|file| self.render_file_diff(file, cx))
-            .collect();
+    pub fn review_notes(&self) -> &ReviewNotes {
+        &self.review_notes
+    }
 
-        v_flex()
-            .flex_1()
-            .min_h_0()
-            .w_full()
-            .overflow_y_scrollbar()
-            .gap_2()
-            .children(file_elements)
-            .into_any_element()
+    pub fn set_review_notes(&mut self, notes: ReviewNotes, cx: &mut Context<Self>) {
+        self.review_notes = notes;
+        cx.notify();
     }
 
-    fn render_file_diff(&self, file: &FileDiff, cx: &Context<Self>) -> impl IntoElement {
-        let hunk_elements: Vec<_> = file
-            .hunks
-            .iter()
-            .map(|hunk| self.render_hunk(hunk, &file.path, cx))
-            .collect();
+    /// Called whenever the user changes a review note, so the notes can be
+    /// saved with the session. Lines only offer notes once this is set.
+    pub fn on_review_notes_change(
+        &mut self,
+        callback: impl Fn(&ReviewNotes, &mut Window, &mut Context<Self>) + 'static,
+    ) {
+        self.on_review_notes_change = Some(Box::new(callback));
+    }
 
-        v_flex()
-            .w_full()
-            .gap_1()
-            .child(self.render_file_header(file, cx))
-            .children(hunk_elements)
+    /// Attach `text` to a line of commit `oid`; blank or `None` text
+    /// deletes the line's note.
+    pub fn set_review_note(
+        &mut self,
+        oid: &str,
+        path: &str,
+        line: u32,
+        text: Option<String>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        match text.filter(|text| !text.trim().is_empty()) {
+            Some(text) => self.review_notes.add(oid, path, line, text),
+            None => {
+                self.review_notes.remove(oid, path, line);
+            }
+        }
+        if let Some(ref on_change) = self.on_review_notes_change {
+            on_change(&self.review_notes, window, cx);
+        }
+        cx.notify();
     }
 
-    fn render_hunk(&self, hunk: &Hunk, file_path: &str, cx: &Context<Self>) -> impl IntoElement {
-        let diff_theme = DiffTheme::from_cx(cx);
-        let theme = cx.theme();
+    /// Open the note editor under a line of the current commit, prefilled
+    /// with its existing note.
+    pub fn open_note_editor(
+        &mut self,
+        path: &str,
+        line: u32,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(oid) = self.commit_info.as_ref().map(|c| c.oid.clone()) else {
+            return;
+        };
+        let existing = self
+            .review_notes
+            .get(&oid, path, line)
+            .unwrap_or_default()
+            .to_string();
+        let input = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("Add a review note")
+                .default_value(existing)
+        });
+        let subscription = cx.subscribe_in(&input, window, |view, _input, event, window, cx| {
+            if let InputEvent::PressEnter { .. } = event {
+                view.save_note_editor(window, cx);
+            }
+        });
+        input.update(cx, |input, cx| input.focus(window, cx));
+        self.note_editor = Some(NoteEditor {
+            path: path.to_string(),
+            line,
+            input,
+            _subscription: subscription,
+        });
+        cx.notify();
+    }
 
-        let line_elements: Vec<_> = hunk
-            .lines
-            .iter()
-            .map(|line| self.render_diff_line(line, file_path, &diff_theme, cx))
-            .collect();
+    pub fn close_note_editor(&mut self, cx: &mut Context<Self>) {
+        self.note_editor = None;
+        cx.notify();
+    }
 
-        v_flex()
-            .w_full()
-            .child(
-                gpui::div()
-                    .px_3()
-                    .py_0p5()
-                    .text_xs()
-                    .text_color(theme.muted_foreground)
-                    .bg(theme.muted)
-                    .child(hunk.header.clone()),
-            )
-            .children(line_elements)
+    fn save_note_editor(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(editor) = self.note_editor.take() else {
+            return;
+        };
+        let Some(oid) = self.commit_info.as_ref().map(|c| c.oid.clone()) else {
+            return;
+        };
+        let text = editor.input.read(cx).value().to_string();
+        self.set_review_note(&oid, &editor.path, editor.line, Some(text), window, cx);
     }
 
-    fn render_diff_line(
-        &self,
-        line: &DiffLine,
-        file_path: &str,
-        diff_theme: &DiffTheme,
-        cx: &Context<Self>,
-    ) -> impl IntoElement {
-        let theme = cx.theme();
+    /// `palette` and `note_editing` let the single-key hunk bindings stand
+    /// down while a text box takes typing.
+    fn key_context(&self) -> KeyContext {
+        let mut context = KeyContext::new_with_defaults();
+        context.add("DiffView");
+        if self.file_palette.is_some() {
+            context.add("palette");
+        }
+        if self.note_editor.is_some() {
+            context.add("note_editing");
+        }
+        context
+    }
 
-        let (prefix, bg_color) = match line.origin {
-            LineOrigin::Addition => ("+", diff_theme.add_bg),
-            LineOrigin::Deletion => ("-", diff_theme.del_bg),
-            LineOrigin::Context => (" ", diff_theme.ctx_bg),
+    /// Scroll the diff so `file_index` is in view. Hidden files are skipped.
+    pub fn scroll_to_file(&mut self, file_index: usize, cx: &mut Context<Self>) {
+        let Some(row) = (0..self.diffs.len())
+            .filter(|i| !self.is_file_hidden(*i))
+            .position(|i| i == file_index)
+        else {
+            return;
         };
+        if row >= self.max_files {
+            self.show_all_files = true;
+        }
+        // To the top: files taller than the view would otherwise be
+        // scrolled only until their last line shows.
+        self.scroll_handle.scroll_to_top_of_item(row);
+        self.scrolled_file = Some(file_index);
+        cx.notify();
+    }
 
-        let fg = fallback_color(&line.origin, diff_theme, theme);
-
-        let old_str = line
-            .old_line_no
-            .map(|n| format!("{:>4}", n))
-            .unwrap_or_else(|| "    ".to_string());
-        let new_str = line
-            .new_line_no
-            .map(|n| format!("{:>4}", n))
-            .unwrap_or_else(|| "    ".to_string());
+    pub fn scrolled_file(&self) -> Option<usize> {
+        self.scrolled_file
+    }
 
-        gpui::div()
-            .w_full()
-            .flex()
-            .overflow_x_hidden()
-            .bg(bg_color)
-            .text_xs()
-            .line_height(gpui::rems(1.0))
-            .font_family(theme.font_family.clone())
-            .child(
-                gpui::div()
-                    .w(gpui::px(48.0))
-                    .flex_shrink_0()
-                    .text_color(diff_theme.line_number_fg)
-                    .text_right()
-                    .px_1()
-                    .child(old_str),
-            )
-            .child(
-                gpui::div()
-                    .w(gpui::px(48.0))
-                    .flex_shrink_0()
-                    .text_color(diff_theme.line_number_fg)
-                    .text_right()
-                    .px_1()
-                    .child(new_str),
-            )
-            .child(
-                gpui::div()
-                    .flex_shrink_0()
-                    .text_color(fg)
-                    .child(prefix.to_string()),
-            )
-            .child(
-                gpui::div()
-                    .px_1()
-                    .overflow_x_hidden()
-                    .child(self.render_content(line, file_path, diff_theme, cx)),
-            )
+    /// The row at the top of the viewport as last drawn, so
+    /// [`Self::restore_scroll_anchor`] can follow it after the diff changes
+    /// shape.
+    fn scroll_anchor(&self) -> Option<Anchor> {
+        find_anchor(
+            &self.laid_out_rows.borrow(),
+            -f32::from(self.scroll_handle.offset().y),
+        )
     }
 
-    // -- Commit header -----------------------------------------------------
-}
+    /// Scroll `anchor` back to the top of the viewport once the diff has
+    /// been laid out again; `None` drops an anchor still waiting.
+    fn restore_scroll_anchor(&self, anchor: Option<Anchor>) {
+        *self.pending_anchor.borrow_mut() = anchor;
+    }
 
-fn compute_stats(diffs: &[FileDiff]) -> (usize, usize, usize) {
-    let files = diffs.len();
-    let mut additions = 0usize;
-    let mut deletions = 0usize;
-    for file in diffs {
-        for hunk in &file.hunks {
-            for line in &hunk.lines {
-                match line.origin {
-                    LineOrigin::Addition => additions += 1,
-                    LineOrigin::Deletion => deletions += 1,
-                    LineOrigin::Context => {}
+    /// Record where the rows of a hunk were laid out. `lines` holds the
+    /// new-side line of each child, `None` for children that are not
+    /// anchors such as the hunk header or deleted lines.
+    fn record_rows(
+        &self,
+        path: &str,
+        lines: Vec<Option<u32>>,
+    ) -> impl Fn(Vec<Bounds<Pixels>>, &mut Window, &mut App) + 'static {
+        let frame_rows = self.frame_rows.clone();
+        let scroll_handle = self.scroll_handle.clone();
+        let path = path.to_string();
+        move |bounds, _window, _cx| {
+            // Rows are prepainted already shifted by the scroll offset.
+            let content_top = scroll_handle.bounds().top() + scroll_handle.offset().y;
+            let mut rows = frame_rows.borrow_mut();
+            for (line, bounds) in lines.iter().zip(bounds) {
+                if let Some(line) = line {
+                    rows.push(LaidOutRow {
+                        path: path.clone(),
+                        new_line_no: Some(*line),
+                        top: f32::from(bounds.top() - content_top),
+                    });
                 }
             }
         }
     }
-    (files, additions, deletions)
-}
 
-fn format_commit_date(timestamp: i64) -> String {
-    use chrono::{DateTime, Local, TimeZone};
-    match Local.timestamp_opt(timestamp, 0) {
-        chrono::LocalResult::Single(dt) => dt.format("%a, %b %-d, %Y, %-I:%M %p").to_string(),
-        _ => match DateTime::from_timestamp(timestamp, 0) {
-            Some(dt) => dt.format("%a, %b %-d, %Y, %-I:%M %p UTC").to_string(),
-            None => "unknown".to_string(),
-        },
+    /// Record the file headers once the file list has been prepainted,
+    /// which finishes the frame's layout, then scrolls a pending anchor
+    /// back into place. `paths` holds the file of each child in order.
+    fn record_files(
+        &self,
+        paths: Vec<String>,
+        cx: &Context<Self>,
+    ) -> impl Fn(Vec<Bounds<Pixels>>, &mut Window, &mut App) + 'static {
+        let frame_rows = self.frame_rows.clone();
+        let laid_out_rows = self.laid_out_rows.clone();
+        let pending_anchor = self.pending_anchor.clone();
+        let scroll_handle = self.scroll_handle.clone();
+        let view = cx.entity().downgrade();
+        move |bounds, _window, cx| {
+            // Unlike the rows, these bounds are not yet scrolled.
+            let content_top = scroll_handle.bounds().top();
+            let mut rows = frame_rows.take();
+            rows.extend(paths.iter().zip(bounds).map(|(path, bounds)| LaidOutRow {
+                path: path.clone(),
+                new_line_no: None,
+                top: f32::from(bounds.top() - content_top),
+            }));
+            let anchor = pending_anchor.borrow_mut().take();
+            let top = anchor.and_then(|anchor| offset_for_anchor(&rows, &anchor));
+            let offset = scroll_handle.offset();
+            if let Some(top) = top.filter(|top| px(-top) != offset.y) {
+                scroll_handle.set_offset(point(offset.x, px(-top)));
+                // This frame was laid out at the old offset.
+                let view = view.clone();
+                cx.defer(move |cx| {
+                    let _ = view.update(cx, |_view, cx| cx.notify());
+                });
+            }
+            *laid_out_rows.borrow_mut() = rows;
+        }
     }
-}
 
-const LABEL_WIDTH: f32 = 100.0;
+    /// Open the changed-file palette, or close it when already open.
+    pub fn toggle_file_palette(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.file_palette.take().is_some() {
+            window.focus(&self.focus_handle);
+            cx.notify();
+            return;
+        }
+        if self.diffs.is_empty() {
+            return;
+        }
+        let input = cx.new(|cx| InputState::new(window, cx).placeholder("Jump to a changed file"));
+        let subscription = cx.subscribe_in(&input, window, |view, input, event, window, cx| {
+            if let InputEvent::Change = event {
+                let query = input.read(cx).value().to_string();
+                view.update_file_palette(&query, cx);
+            } else if let InputEvent::PressEnter { .. } = event {
+                view.confirm_file_palette(0, window, cx);
+            }
+        });
+        input.update(cx, |input, cx| input.focus(window, cx));
+        self.file_palette = Some(FilePalette {
+            input,
+            matches: Vec::new(),
+            _subscription: subscription,
+        });
+        self.update_file_palette("", cx);
+    }
 
-impl DiffView {
-    fn render_commit_header(&self, cx: &Context<Self>) -> impl IntoElement {
-        let theme = cx.theme();
-        let commit = self.commit_info.as_ref().unwrap();
-        let signature = self.signature_status.unwrap_or(SignatureStatus::None);
+    pub fn is_file_palette_open(&self) -> bool {
+        self.file_palette.is_some()
+    }
 
-        let parents_str = if commit.parent_oids.is_empty() {
-            "(root commit)".to_string()
-        } else {
-            commit
-                .parent_oids
-                .iter()
-                .map(|p| &p[..7.min(p.len())])
-                .collect::<Vec<_>>()
-                .join(", ")
-        };
+    /// Files listed by the open palette, best match first.
+    pub fn file_palette_matches(&self) -> &[usize] {
+        self.file_palette
+            .as_ref()
+            .map(|palette| palette.matches.as_slice())
+            .unwrap_or(&[])
+    }
 
-        let (files, additions, deletions) = compute_stats(&self.diffs);
-        let stats_str = format!(
-            "{} file{}, +{} addition{}, -{} deletion{}",
-            files,
-            if files == 1 { "" } else { "s" },
-            additions,
-            if additions == 1 { "" } else { "s" },
-            deletions,
-            if deletions == 1 { "" } else { "s" },
-        );
+    fn update_file_palette(&mut self, query: &str, cx: &mut Context<Self>) {
+        let visible: Vec<usize> = (0..self.diffs.len())
+            .filter(|i| !self.is_file_hidden(*i))
+            .collect();
+        let ranked = fuzzy_rank(query, visible.iter().map(|i| self.diffs[*i].path.as_str()));
+        if let Some(palette) = self.file_palette.as_mut() {
+            palette.matches = ranked.into_iter().map(|r| visible[r]).collect();
+        }
+        cx.notify();
+    }
 
-        let sig_color = match signature {
-            SignatureStatus::Good => theme.success,
-            SignatureStatus::Bad => theme.danger,
-            _ => theme.muted_foreground,
+    /// Jump to the palette's `rank`-th match and close it.
+    fn confirm_file_palette(&mut self, rank: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(palette) = self.file_palette.take() else {
+            return;
         };
+        if let Some(&file_index) = palette.matches.get(rank) {
+            self.scroll_to_file(file_index, cx);
+        }
+        window.focus(&self.focus_handle);
+        cx.notify();
+    }
 
-        let mut header = v_flex().w_full().px_3().py_2().gap_0p5();
+    pub fn set_search_query(&mut self, query: String, cx: &mut Context<Self>) {
+        self.search_query = query;
+        cx.notify();
+    }
 
-        let rows: Vec<(&str, String, Option<Hsla>)> = vec![
-            ("Commit", commit.oid.clone(), None),
-            ("Tree", commit.tree_oid.clone(), None),
-            (
-                "Author",
-                format!("{} <{}>", commit.author_name, commit.author_email),
-                None,
-            ),
-            (
-                "Committer",
-                format!("{} <{}>", commit.committer_name, commit.committer_email),
-                None,
-            ),
-            ("Date", format_commit_date(commit.date), None),
-            ("Parents", parents_str, None),
-            ("Signature", signature.label().to_string(), Some(sig_color)),
-            ("Stats", stats_str, None),
-        ];
+    pub fn set_show_whitespace(&mut self, show: bool, cx: &mut Context<Self>) {
+        self.show_whitespace = show;
+        cx.notify();
+    }
 
-        for (label, value, color) in rows {
-            header = header.child(
-                gpui::div()
-                    .flex()
-                    .w_full()
-                    .text_xs()
-                    .font_family(theme.font_family.clone())
-                    .child(
-                        gpui::div()
-                            .w(gpui::px(LABEL_WIDTH))
-                            .flex_shrink_0()
-                            .text_right()
-                            .pr_2()
-                            .text_color(theme.muted_foreground)
-                            .child(format!("{}:", label)),
-                    )
-                    .child(
-                        gpui::div()
-                            .text_color(color.unwrap_or(theme.foreground))
-                            .child(value),
-                    ),
-            );
-        }
+    pub fn set_show_line_numbers(&mut self, show: bool, cx: &mut Context<Self>) {
+        self.show_line_numbers = show;
+        cx.notify();
+    }
 
-        header = header.child(
-            v_flex()
-                .mt_2()
-                .px_1()
-                .gap_0p5()
-                .child(
-                    gpui::div()
-                        .text_sm()
-                        .font_weight(gpui::FontWeight::BOLD)
-                        .text_color(theme.foreground)
-                        .child(commit.subject.clone()),
-                )
-                .when(!commit.body.is_empty(), |el| {
-                    el.child(
-                        gpui::div()
-                            .text_xs()
-                            .text_color(theme.muted_foreground)
-                            .child(commit.body.clone()),
-                    )
-                }),
-        );
+    pub fn set_placeholder(&mut self, placeholder: String, cx: &mut Context<Self>) {
+        self.placeholder = placeholder;
+        cx.notify();
+    }
 
-        header = header.child(
-            gpui::div()
-                .mt_2()
-                .w_full()
-                .h(gpui::px(1.0))
-                .bg(theme.border),
-        );
+    pub fn set_change_marker_style(&mut self, style: ChangeMarkerStyle, cx: &mut Context<Self>) {
+        self.change_marker_style = style;
+        cx.notify();
+    }
 
-        header
+    pub fn set_word_diff(&mut self, word_diff: bool, cx: &mut Context<Self>) {
+        self.word_diff = word_diff;
+        cx.notify();
     }
 
-    // -- Split rendering --------------------------------------------------
+    pub fn word_diff(&self) -> bool {
+        self.word_diff
+    }
 
-    fn render_split(&self, cx: &Context<Self>) -> gpui::AnyElement {
-        let file_elements: Vec<_> = self
-            .diffs
-            .iter()
-            .map(|file| self.render_file_diff_split(file, cx))
-            .collect();
+    pub fn mode(&self) -> DiffViewMode {
+        self.mode
+    }
 
-        v_flex()
-            .flex_1()
-            .min_h_0()
-            .w_full()
-            .overflow_y_scrollbar()
-            .gap_2()
-            .children(file_elements)
-            .into_any_element()
+    pub fn preferred_mode(&self) -> Option<DiffViewMode> {
+        self.preferred_mode
     }
 
-    fn render_file_diff_split(&self, file: &FileDiff, cx: &Context<Self>) -> impl IntoElement {
-        let hunk_elements: Vec<_> = file
-            .hunks
-            .iter()
-            .map(|hunk| self.render_hunk_split(hunk, &file.path, cx))
-            .collect();
+    /// Pin the layout to `mode`, or with `None` go back to choosing it from
+    /// the available width.
+    pub fn set_preferred_mode(&mut self, mode: Option<DiffViewMode>, cx: &mut Context<Self>) {
+        self.preferred_mode = mode;
+        if let Some(mode) = mode {
+            self.mode = mode;
+        }
+        cx.notify();
+    }
 
-        v_flex()
-            .w_full()
-            .gap_1()
-            .child(self.render_file_header(file, cx))
-            .children(hunk_elements)
+    /// Called when the user pins a layout with [`Self::cycle_view_mode`], so
+    /// it can be saved for the repo.
+    pub fn on_preferred_mode_change(
+        &mut self,
+        callback: impl Fn(DiffViewMode, &mut Window, &mut Context<Self>) + 'static,
+    ) {
+        self.on_preferred_mode_change = Some(Box::new(callback));
     }
 
-    fn render_hunk_split(
-        &self,
-        hunk: &Hunk,
-        file_path: &str,
-        cx: &Context<Self>,
-    ) -> impl IntoElement {
-        let diff_theme = DiffTheme::from_cx(cx);
-        let theme = cx.theme();
-        let rows = split_hunk_lines(&hunk.lines);
+    pub fn set_max_files(&mut self, max_files: usize, cx: &mut Context<Self>) {
+        self.max_files = max_files;
+        cx.notify();
+    }
 
-        let row_elements: Vec<_> = rows
-            .iter()
-            .map(|row| self.render_split_row(row, file_path, &diff_theme, cx))
+    /// Render the files held back by [`Self::set_max_files`].
+    pub fn show_remaining_files(&mut self, cx: &mut Context<Self>) {
+        self.show_all_files = true;
+        cx.notify();
+    }
+
+    /// Indices of the files to render, in order, and how many visible files
+    /// are held back behind the "Show remaining" row.
+    fn rendered_files(&self) -> (Vec<usize>, usize) {
+        let visible: Vec<usize> = (0..self.diffs.len())
+            .filter(|i| !self.is_file_hidden(*i))
             .collect();
+        if self.show_all_files || visible.len() <= self.max_files {
+            return (visible, 0);
+        }
+        let remaining = visible.len() - self.max_files;
+        (visible[..self.max_files].to_vec(), remaining)
+    }
 
-        v_flex()
-            .w_full()
-            .child(
-                gpui::div()
-                    .px_3()
-                    .py_0p5()
-                    .text_xs()
-                    .text_color(theme.muted_foreground)
-                    .bg(theme.muted)
-                    .child(hunk.header.clone()),
-            )
-            .children(row_elements)
-    }
-
-    fn render_split_row(
-        &self,
-        row: &SplitRow,
-        file_path: &str,
-        diff_theme: &DiffTheme,
-        cx: &Context<Self>,
-    ) -> impl IntoElement {
+    fn render_show_remaining(&self, remaining: usize, cx: &Context<Self>) -> impl IntoElement {
         let theme = cx.theme();
-
+        let label = if remaining == 1 {
+            "Show remaining 1 file".to_string()
+        } else {
+            format!("Show remaining {remaining} files")
+        };
         gpui::div()
+            .id("show-remaining-files")
+            .debug_selector(|| "show-remaining-files".into())
             .w_full()
+            .py_2()
             .flex()
-            .text_xs()
-            .line_height(gpui::rems(1.0))
-            .font_family(theme.font_family.clone())
-            .child(self.render_split_half(
-                row.left.as_deref(),
-                SplitSide::Left,
-                file_path,
-                diff_theme,
-                cx,
-            ))
-            .child(gpui::div().w(px(1.0)).flex_shrink_0().bg(theme.border))
-            .child(self.render_split_half(
-                row.right.as_deref(),
-                SplitSide::Right,
-                file_path,
-                diff_theme,
-                cx,
-            ))
+            .justify_center()
+            .text_sm()
+            .text_color(theme.muted_foreground)
+            .border_1()
+            .border_color(theme.border)
+            .rounded_md()
+            .hover(|el| el.text_color(theme.foreground))
+            .on_click(cx.listener(|view, _, _, cx| view.show_remaining_files(cx)))
+            .child(label)
     }
 
-    fn render_split_half(
-        &self,
-        line: Option<&DiffLine>,
-        side: SplitSide,
-        file_path: &str,
-        diff_theme: &DiffTheme,
-        cx: &Context<Self>,
-    ) -> gpui::Div {
-        let theme = cx.theme();
-
-        let Some(line) = line else {
-            return gpui::div()
-                .flex_1()
-                .flex()
-                .overflow_x_hidden()
-                .bg(theme.background);
+    /// Step the layout through unified, split and unified word diff, then
+    /// back to unified. The chosen layout is pinned like
+    /// [`Self::set_preferred_mode`].
+    pub fn cycle_view_mode(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let (mode, word_diff) = match (self.mode, self.word_diff) {
+            (DiffViewMode::Unified, false) => (DiffViewMode::Split, false),
+            (DiffViewMode::Split, _) => (DiffViewMode::Unified, true),
+            (DiffViewMode::Unified, true) => (DiffViewMode::Unified, false),
         };
+        self.word_diff = word_diff;
+        self.set_preferred_mode(Some(mode), cx);
+        if let Some(ref on_change) = self.on_preferred_mode_change {
+            on_change(mode, window, cx);
+        }
+    }
 
-        let bg_color = match line.origin {
-            LineOrigin::Addition => diff_theme.add_bg,
-            LineOrigin::Deletion => diff_theme.del_bg,
-            LineOrigin::Context => diff_theme.ctx_bg,
+    pub fn whitespace_mode(&self) -> WhitespaceMode {
+        self.whitespace_mode
+    }
+
+    /// Record the mode the shown diff was computed with.
+    pub fn set_whitespace_mode(&mut self, mode: WhitespaceMode, cx: &mut Context<Self>) {
+        self.whitespace_mode = mode;
+        cx.notify();
+    }
+
+    /// Called with the new mode when the user cycles the whitespace mode.
+    pub fn on_whitespace_mode_change(
+        &mut self,
+        callback: impl Fn(WhitespaceMode, &mut Window, &mut Context<Self>) + 'static,
+    ) {
+        self.on_whitespace_mode_change = Some(Box::new(callback));
+    }
+
+    /// Move to the next [`WhitespaceMode`] and ask the owner to re-diff.
+    pub fn cycle_whitespace_mode(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.whitespace_mode = self.whitespace_mode.next();
+        if let Some(ref on_change) = self.on_whitespace_mode_change {
+            on_change(self.whitespace_mode, window, cx);
+        }
+        cx.notify();
+    }
+
+    pub fn set_remote_url(&mut self, url: Option<String>) {
+        self.remote_url = url;
+    }
+
+    /// Replace how permalinks are opened (the system browser by default).
+    pub fn set_url_opener(&mut self, opener: impl Fn(&str, &mut App) + 'static) {
+        self.url_opener = Some(Box::new(opener));
+    }
+
+    /// Web URL for `line` of file `file_index` on the remote. Deleted lines
+    /// link to the old path in the commit's first parent, where they exist.
+    pub fn line_permalink(&self, file_index: usize, line: &DiffLine) -> Option<String> {
+        let remote_url = self.remote_url.as_deref()?;
+        let commit = self.commit_info.as_ref()?;
+        let file = self.diffs.get(file_index)?;
+        let (oid, path, line_no) = match line.origin {
+            LineOrigin::Deletion => (
+                commit.parent_oids.first()?,
+                file.old_path.as_deref().unwrap_or(&file.path),
+                line.old_line_no?,
+            ),
+            _ => (&commit.oid, file.path.as_str(), line.new_line_no?),
         };
+        line_permalink(remote_url, oid, path, line_no)
+    }
+
+    /// Show the context menu for `line` at `position`. Lines without a
+    /// permalink have nothing to offer, so no menu opens for them.
+    pub fn open_line_menu(
+        &mut self,
+        file_index: usize,
+        line: &DiffLine,
+        position: Point<Pixels>,
+        cx: &mut Context<Self>,
+    ) {
+        if self.line_permalink(file_index, line).is_none() {
+            return;
+        }
+        self.line_menu = Some(LineMenu {
+            file_index,
+            line: line.clone(),
+            position,
+        });
+        cx.notify();
+    }
+
+    pub fn is_line_menu_open(&self) -> bool {
+        self.line_menu.is_some()
+    }
 
-        let line_no_str = match side {
-            SplitSide::Left => line
-                .old_line_no
-                .map(|n| format!("{:>4}", n))
-                .unwrap_or_else(|| "    ".to_string()),
-            SplitSide::Right => line
-                .new_line_no
-                .map(|n| format!("{:>4}", n))
-                .unwrap_or_else(|| "    ".to_string()),
+    fn close_line_menu(&mut self, cx: &mut Context<Self>) {
+        if self.line_menu.take().is_some() {
+            cx.notify();
+        }
+    }
+
+    pub fn open_line_on_remote(&self, file_index: usize, line: &DiffLine, cx: &mut Context<Self>) {
+        let Some(url) = self.line_permalink(file_index, line) else {
+            return;
         };
+        match &self.url_opener {
+            Some(opener) => opener(&url, cx),
+            None => cx.open_url(&url),
+        }
+    }
 
-        gpui::div()
-            .flex_1()
-            .flex()
-            .overflow_x_hidden()
-            .bg(bg_color)
-            .child(
-                gpui::div()
-                    .w(px(48.0))
-                    .flex_shrink_0()
-                    .text_color(diff_theme.line_number_fg)
-                    .text_right()
-                    .px_1()
-                    .child(line_no_str),
-            )
-            .child(
-                gpui::div()
-                    .px_1()
-                    .overflow_x_hidden()
-                    .whitespace_nowrap()
-                    .child(self.render_content(line, file_path, diff_theme, cx)),
-            )
+    pub fn set_diff_palette(&mut self, palette: DiffPalette, cx: &mut Context<Self>) {
+        self.diff_palette = palette;
+        cx.notify();
     }
-}
 
-impl Render for DiffView {
-    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
-        if let Some(ref error) = self.error_message {
-            return v_flex()
-                .size_full()
-                .items_center()
-                .justify_center()
-                .child(
-                    gpui::div()
-                        .text_sm()
-                        .text_color(gpui::red())
-                        .child(error.clone()),
-                )
-                .into_any_element();
+    pub fn set_split_gutter(&mut self, gutter: SplitGutter, cx: &mut Context<Self>) {
+        self.split_gutter = gutter;
+        cx.notify();
+    }
+
+    pub fn is_file_generated(&self, file_index: usize) -> bool {
+        self.diffs
+            .get(file_index)
+            .is_some_and(|file| is_generated(&file.path, &self.generated_patterns))
+    }
+
+    /// Whether a file's hunks are currently folded behind a placeholder.
+    pub fn is_file_collapsed(&self, file_index: usize) -> bool {
+        self.generated_mode == GeneratedFileMode::Collapse
+            && self.is_file_generated(file_index)
+            && !self.revealed_generated.contains(&file_index)
+    }
+
+    fn is_file_hidden(&self, file_index: usize) -> bool {
+        self.generated_mode == GeneratedFileMode::Hide && self.is_file_generated(file_index)
+    }
+
+    pub fn toggle_generated(&mut self, file_index: usize, cx: &mut Context<Self>) {
+        if !self.revealed_generated.remove(&file_index) {
+            self.revealed_generated.insert(file_index);
         }
+        cx.notify();
+    }
 
-        if self.diffs.is_empty() {
-            return v_flex()
-                .size_full()
-                .items_center()
-                .justify_center()
-                .child(
-                    gpui::div()
-                        .text_sm()
-                        .text_color(cx.theme().muted_foreground)
-                        .child("Select a commit to view its diff"),
-                )
-                .into_any_element();
+    /// Register the handler for stage/unstage/discard shortcuts. They only
+    /// fire on diffs shown with [`Self::set_working_changes`].
+    pub fn on_hunk_action(
+        &mut self,
+        callback: impl Fn(HunkAction, &FileDiff, usize, &mut Window, &mut Context<Self>) + 'static,
+    ) {
+        self.on_hunk_action = Some(Box::new(callback));
+    }
+
+    /// Register the handler for "Stash selected", called with the paths of
+    /// the files picked in a working-changes diff.
+    pub fn on_stash_files(
+        &mut self,
+        callback: impl Fn(Vec<String>, &mut Window, &mut Context<Self>) + 'static,
+    ) {
+        self.on_stash_files = Some(Box::new(callback));
+    }
+
+    /// Pick or unpick a file of a working-changes diff for stashing.
+    pub fn toggle_file_selected(&mut self, file_index: usize, cx: &mut Context<Self>) {
+        if self.working_changes.is_none() || file_index >= self.diffs.len() {
+            return;
+        }
+        if !self.selected_files.remove(&file_index) {
+            self.selected_files.insert(file_index);
         }
+        cx.notify();
+    }
 
-        let weak = cx.entity().downgrade();
+    /// Paths of the picked files, in diff order.
+    pub fn selected_paths(&self) -> Vec<String> {
+        self.diffs
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| self.selected_files.contains(i))
+            .map(|(_, file)| file.path.clone())
+            .collect()
+    }
 
-        let content = match self.mode {
-            DiffViewMode::Unified => self.render_unified(cx),
-            DiffViewMode::Split => self.render_split(cx),
+    /// Hand the picked files to the stash handler.
+    pub fn stash_selected_files(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let paths = self.selected_paths();
+        if paths.is_empty() {
+            return;
+        }
+        if let Some(callback) = &self.on_stash_files {
+            callback(paths, window, cx);
+        }
+    }
+
+    /// Register the handler told when "Show ignored" is switched, so the
+    /// ignored files can be loaded or dropped.
+    pub fn on_show_ignored_change(
+        &mut self,
+        callback: impl Fn(bool, &mut Window, &mut Context<Self>) + 'static,
+    ) {
+        self.on_show_ignored_change = Some(Box::new(callback));
+    }
+
+    pub fn show_ignored(&self) -> bool {
+        self.show_ignored
+    }
+
+    /// Switch listing ignored files under unstaged changes.
+    pub fn toggle_show_ignored(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.show_ignored = !self.show_ignored;
+        if !self.show_ignored {
+            self.ignored_files.clear();
+        }
+        if let Some(callback) = &self.on_show_ignored_change {
+            callback(self.show_ignored, window, cx);
+        }
+        cx.notify();
+    }
+
+    /// List the paths ignore rules exclude, while unstaged changes are
+    /// shown with "Show ignored" on.
+    pub fn set_ignored_files(&mut self, files: Vec<String>, cx: &mut Context<Self>) {
+        if self.working_changes != Some(WorkingChanges::Unstaged) || !self.show_ignored {
+            return;
+        }
+        self.ignored_files = files;
+        cx.notify();
+    }
+
+    pub fn ignored_files(&self) -> &[String] {
+        &self.ignored_files
+    }
+
+    pub fn focused_hunk(&self) -> Option<(usize, usize)> {
+        self.focused_hunk
+    }
+
+    pub fn focus_hunk(&mut self, file_index: usize, hunk_index: usize, cx: &mut Context<Self>) {
+        self.focused_hunk = Some((file_index, hunk_index));
+        cx.notify();
+    }
+
+    /// Show one hunk side-by-side in the unified view, or back to unified.
+    pub fn toggle_hunk_split(
+        &mut self,
+        file_index: usize,
+        hunk_index: usize,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(file) = self.diffs.get(file_index) else {
+            return;
+        };
+        let Some(hunk) = file.hunks.get(hunk_index) else {
+            return;
         };
+        let key = (file.path.clone(), hunk_key(hunk));
+        if !self.split_hunks.remove(&key) {
+            self.split_hunks.insert(key);
+        }
+        cx.notify();
+    }
 
-        // Measure available width during layout and update mode for the next
-        // frame. The content uses the previous frame's mode (defaults to Unified);
-        // this is a standard one-frame-behind pattern in GPUI that converges
-        // after a single extra render cycle. Oscillation cannot occur because
-        // the diff view's width is determined by its parent resizable panel,
-        // not by the diff content.
-        v_flex()
-            .size_full()
-            .child(
-                canvas(
-                    move |bounds: Bounds<Pixels>, _window: &mut Window, app: &mut App| {
-                        let new_mode = if bounds.size.width >= px(SPLIT_VIEW_MIN_WIDTH) {
-                            DiffViewMode::Split
-                        } else {
-                            DiffViewMode::Unified
-                        };
-                        let _ = weak.update(app, |view: &mut DiffView, cx| {
-                            if view.mode != new_mode {
-                                view.mode = new_mode;
-                                cx.notify();
-                            }
-                        });
-                    },
-                    |_, _, _, _| {},
-                )
-                .w_full()
-                .h(px(0.)),
-            )
-            .when(self.commit_info.is_some(), |el| {
-                el.child(self.render_commit_header(cx))
+    pub fn is_hunk_split(&self, file_index: usize, hunk_index: usize) -> bool {
+        self.diffs
+            .get(file_index)
+            .and_then(|file| Some((file, file.hunks.get(hunk_index)?)))
+            .is_some_and(|(file, hunk)| {
+                self.split_hunks
+                    .contains(&(file.path.clone(), hunk_key(hunk)))
             })
-            .child(content)
-            .into_any_element()
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use dd_git::{FileStatus, Hunk, SignatureStatus};
+    /// Hunks in display order, skipping files that are hidden or collapsed.
+    fn visible_hunks(&self) -> Vec<(usize, usize)> {
+        self.diffs
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !self.is_file_hidden(*i) && !self.is_file_collapsed(*i))
+            .flat_map(|(i, file)| (0..file.hunks.len()).map(move |h| (i, h)))
+            .collect()
+    }
+
+    pub fn focus_next_hunk(&mut self, cx: &mut Context<Self>) {
+        let hunks = self.visible_hunks();
+        let next = match self
+            .focused_hunk
+            .and_then(|f| hunks.iter().position(|h| *h == f))
+        {
+            Some(pos) => hunks.get(pos + 1).or(hunks.last()),
+            None => hunks.first(),
+        };
+        self.focused_hunk = next.copied();
+        cx.notify();
+    }
+
+    pub fn focus_previous_hunk(&mut self, cx: &mut Context<Self>) {
+        let hunks = self.visible_hunks();
+        let previous = match self
+            .focused_hunk
+            .and_then(|f| hunks.iter().position(|h| *h == f))
+        {
+            Some(pos) => hunks.get(pos.saturating_sub(1)),
+            None => hunks.last(),
+        };
+        self.focused_hunk = previous.copied();
+        cx.notify();
+    }
+
+    /// Run `action` on the focused hunk through the registered handler.
+    pub fn apply_to_focused_hunk(
+        &mut self,
+        action: HunkAction,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some((file_index, hunk_index)) = self.focused_hunk else {
+            return;
+        };
+        if !self
+            .working_changes
+            .is_some_and(|changes| action.applies_to(changes))
+        {
+            return;
+        }
+        let (Some(callback), Some(file)) = (&self.on_hunk_action, self.diffs.get(file_index))
+        else {
+            return;
+        };
+        if hunk_index < file.hunks.len() {
+            callback(action, file, hunk_index, window, cx);
+        }
+    }
+
+    pub fn diffs(&self) -> &[FileDiff] {
+        &self.diffs
+    }
+
+    pub fn commit_info(&self) -> Option<&CommitInfo> {
+        self.commit_info.as_ref()
+    }
+
+    pub fn signature_status(&self) -> Option<SignatureStatus> {
+        self.signature_status
+    }
+
+    pub fn error_message(&self) -> Option<&str> {
+        self.error_message.as_deref()
+    }
+
+    /// Drop everything tied to the files currently shown: caches, expanded
+    /// context, per-file warnings and open editors, plus any error or
+    /// loading state.
+    fn reset_file_state(&mut self) {
+        self.mixed_line_endings.clear();
+        self.clear_render_caches();
+        self.split_hunks.clear();
+        self.expanded_to_eof.clear();
+        self.revealed_generated.clear();
+        self.full_files.clear();
+        self.full_file_loads.clear();
+        self.file_contents.clear();
+        self.images.clear();
+        self.note_editor = None;
+        self.file_palette = None;
+        self.line_menu = None;
+        self.scrolled_file = None;
+        self.focused_hunk = None;
+        self.working_changes = None;
+        self.selected_files.clear();
+        self.ignored_files.clear();
+        self.error_message = None;
+        self.loading = false;
+    }
+
+    pub fn set_diffs(&mut self, diffs: Vec<FileDiff>, cx: &mut Context<Self>) {
+        self.diffs = diffs;
+        self.reset_file_state();
+        self.show_all_files = false;
+        self.commit_info = None;
+        self.signature_status = None;
+        self.containing_branches.clear();
+        self.parent_subjects.clear();
+        cx.notify();
+    }
+
+    /// Show a loading state until the next diff or error is set.
+    pub fn set_loading(&mut self, cx: &mut Context<Self>) {
+        self.loading = true;
+        cx.notify();
+    }
+
+    pub fn is_loading(&self) -> bool {
+        self.loading
+    }
+
+    pub fn set_commit_data(
+        &mut self,
+        commit: CommitInfo,
+        signature: SignatureStatus,
+        diffs: Vec<FileDiff>,
+        cx: &mut Context<Self>,
+    ) {
+        // Re-fetching the shown commit keeps the reader on the same line.
+        let anchor = self
+            .commit_info
+            .as_ref()
+            .filter(|current| current.oid == commit.oid)
+            .and_then(|_| self.scroll_anchor());
+        if anchor.is_none() {
+            self.show_all_files = false;
+        }
+        self.commit_info = Some(commit);
+        self.signature_status = Some(signature);
+        self.containing_branches.clear();
+        self.parent_subjects.clear();
+        self.diffs = diffs;
+        self.restore_scroll_anchor(anchor);
+        self.reset_file_state();
+        cx.notify();
+    }
+
+    /// Show uncommitted changes, which the hunk shortcuts can act on.
+    /// Reloading the same kind keeps the hunk cursor where it was, so the
+    /// next hunk moves under it once the focused one is staged.
+    pub fn set_working_changes(
+        &mut self,
+        changes: WorkingChanges,
+        diffs: Vec<FileDiff>,
+        cx: &mut Context<Self>,
+    ) {
+        let focused = self
+            .focused_hunk
+            .filter(|_| self.working_changes == Some(changes));
+        self.set_diffs(diffs, cx);
+        self.working_changes = Some(changes);
+        self.focused_hunk = focused.and_then(|focused| {
+            let hunks = self.visible_hunks();
+            hunks
+                .iter()
+                .find(|hunk| **hunk >= focused)
+                .or(hunks.last())
+                .copied()
+        });
+    }
+
+    pub fn working_changes(&self) -> Option<WorkingChanges> {
+        self.working_changes
+    }
+
+    /// Show which local branches include the current commit.
+    pub fn set_containing_branches(&mut self, branches: Vec<String>, cx: &mut Context<Self>) {
+        self.containing_branches = branches;
+        cx.notify();
+    }
+
+    pub fn containing_branches(&self) -> &[String] {
+        &self.containing_branches
+    }
+
+    /// Label a merge's parent chips with their subjects.
+    pub fn set_parent_subjects(&mut self, subjects: Vec<String>, cx: &mut Context<Self>) {
+        self.parent_subjects = subjects;
+        cx.notify();
+    }
+
+    pub fn on_navigate_parent(
+        &mut self,
+        callback: impl Fn(&str, &mut Window, &mut Context<Self>) + 'static,
+    ) {
+        self.on_navigate_parent = Some(Box::new(callback));
+    }
+
+    /// Jump to the current commit's `index`th parent.
+    pub fn navigate_to_parent(
+        &mut self,
+        index: usize,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(oid) = self
+            .commit_info
+            .as_ref()
+            .and_then(|c| c.parent_oids.get(index))
+            .cloned()
+        else {
+            return;
+        };
+        if let Some(ref on_navigate) = self.on_navigate_parent {
+            on_navigate(&oid, window, cx);
+        }
+    }
+
+    pub fn set_error(&mut self, message: String, cx: &mut Context<Self>) {
+        self.diffs.clear();
+        self.reset_file_state();
+        self.error_message = Some(message);
+        self.show_all_files = false;
+        self.commit_info = None;
+        self.signature_status = None;
+        self.containing_branches.clear();
+        self.parent_subjects.clear();
+        cx.notify();
+    }
+
+    /// Reveal folded context above or below a hunk using the file contents
+    /// at the current commit.
+    pub fn expand_context(
+        &mut self,
+        file_index: usize,
+        hunk_index: usize,
+        direction: ExpandDirection,
+        cx: &mut Context<Self>,
+    ) {
+        let anchor = self.scroll_anchor();
+        let (Some(commit), Some(loader)) = (self.commit_info.as_ref(), self.file_loader.as_ref())
+        else {
+            return;
+        };
+        let Some(file) = self.diffs.get_mut(file_index) else {
+            return;
+        };
+        let key = (commit.oid.clone(), file.path.clone());
+        let content = match self.file_contents.get(&key) {
+            Some(content) => content.clone(),
+            None => match loader(&commit.oid, &file.path) {
+                Ok(content) => {
+                    let content: Rc<str> = content.into();
+                    self.file_contents.insert(key, content.clone());
+                    content
+                }
+                Err(e) => {
+                    eprintln!("failed to load {} for context expansion: {e}", file.path);
+                    return;
+                }
+            },
+        };
+        let is_last = hunk_index + 1 == file.hunks.len();
+        let added = expand_hunk_context(
+            &mut file.hunks,
+            hunk_index,
+            direction,
+            CONTEXT_EXPAND_STEP,
+            &content,
+        );
+        if direction == ExpandDirection::Down && is_last && added < CONTEXT_EXPAND_STEP {
+            self.expanded_to_eof.insert(file_index);
+        }
+        // Expansion can merge neighbouring hunks, shifting every later index.
+        self.split_rows
+            .get_mut()
+            .retain(|&(file, _), _| file != file_index);
+        self.restore_scroll_anchor(anchor);
+        cx.notify();
+    }
+
+    // -- Shared helpers ---------------------------------------------------
+
+    /// Controls shown in the folded gap above `hunk_index`, or below the last
+    /// hunk when `hunk_index == file.hunks.len()`.
+    fn render_expand_row(
+        &self,
+        file_index: usize,
+        file: &FileDiff,
+        hunk_index: usize,
+        cx: &Context<Self>,
+    ) -> Option<gpui::AnyElement> {
+        if self.commit_info.is_none()
+            || self.file_loader.is_none()
+            || file.status == FileStatus::Deleted
+        {
+            return None;
+        }
+
+        let is_tail = hunk_index == file.hunks.len();
+        let (can_up, can_down) = if is_tail {
+            (false, !self.expanded_to_eof.contains(&file_index))
+        } else {
+            let hidden = hidden_lines_above(&file.hunks, hunk_index) > 0;
+            (hidden, hidden && hunk_index > 0)
+        };
+        if !can_up && !can_down {
+            return None;
+        }
+
+        let theme = cx.theme();
+        let control = |id: String, label: &str, hunk: usize, direction: ExpandDirection| {
+            gpui::div()
+                .id(gpui::ElementId::Name(id.into()))
+                .px_2()
+                .cursor_pointer()
+                .hover(|el| el.text_color(theme.foreground))
+                .on_click(cx.listener(move |view, _event, _window, cx| {
+                    view.expand_context(file_index, hunk, direction, cx);
+                }))
+                .child(format!("{label} Expand {CONTEXT_EXPAND_STEP} lines"))
+        };
+
+        Some(
+            gpui::div()
+                .w_full()
+                .flex()
+                .gap_2()
+                .px_3()
+                .text_xs()
+                .text_color(theme.muted_foreground)
+                .bg(theme.muted.opacity(0.5))
+                .when(can_down, |el| {
+                    el.child(control(
+                        format!("expand-down-{file_index}-{hunk_index}"),
+                        "\u{2193}",
+                        hunk_index.saturating_sub(1),
+                        ExpandDirection::Down,
+                    ))
+                })
+                .when(can_up, |el| {
+                    el.child(control(
+                        format!("expand-up-{file_index}-{hunk_index}"),
+                        "\u{2191}",
+                        hunk_index,
+                        ExpandDirection::Up,
+                    ))
+                })
+                .into_any_element(),
+        )
+    }
+
+    /// The `@@` line of a hunk. Clicking it moves the hunk cursor there.
+    fn render_hunk_header(
+        &self,
+        file_index: usize,
+        hunk_index: usize,
+        hunk: &Hunk,
+        cx: &Context<Self>,
+    ) -> impl IntoElement {
+        let theme = cx.theme();
+        let focused = self.focused_hunk == Some((file_index, hunk_index));
+        gpui::div()
+            .id(gpui::ElementId::Name(
+                format!("hunk-header-{file_index}-{hunk_index}").into(),
+            ))
+            .px_3()
+            .py_0p5()
+            .text_xs()
+            .text_color(theme.muted_foreground)
+            .bg(theme.muted)
+            .border_l_2()
+            .border_color(if focused {
+                theme.primary
+            } else {
+                gpui::transparent_black()
+            })
+            .flex()
+            .justify_between()
+            .on_click(cx.listener(move |view, _event, _window, cx| {
+                view.focus_hunk(file_index, hunk_index, cx);
+            }))
+            .child(hunk.header.clone())
+            .when(self.mode == DiffViewMode::Unified, |el| {
+                let is_split = self.is_hunk_split(file_index, hunk_index);
+                el.child(
+                    gpui::div()
+                        .id(gpui::ElementId::Name(
+                            format!("hunk-layout-toggle-{file_index}-{hunk_index}").into(),
+                        ))
+                        .cursor_pointer()
+                        .hover(|el| el.text_color(theme.foreground))
+                        .on_click(cx.listener(move |view, _event, _window, cx| {
+                            cx.stop_propagation();
+                            view.toggle_hunk_split(file_index, hunk_index, cx);
+                        }))
+                        .child(if is_split { "Unified" } else { "Split" }),
+                )
+            })
+    }
+
+    /// Body for a rename or copy without content changes, which has no
+    /// hunks and would otherwise look like a header with a missing diff.
+    fn render_pure_rename(
+        &self,
+        file_index: usize,
+        file: &FileDiff,
+        cx: &Context<Self>,
+    ) -> gpui::AnyElement {
+        let theme = cx.theme();
+        let body = gpui::div()
+            .debug_selector(|| format!("pure-rename-{file_index}"))
+            .px_3()
+            .py_1()
+            .text_xs()
+            .italic()
+            .text_color(theme.muted_foreground)
+            .bg(theme.muted.opacity(0.5))
+            .child(if file.status == FileStatus::Copied {
+                PURE_COPY_LABEL
+            } else {
+                PURE_RENAME_LABEL
+            });
+
+        v_flex()
+            .w_full()
+            .gap_1()
+            .child(self.render_file_header(file_index, file, cx))
+            .child(body)
+            .into_any_element()
+    }
+
+    /// Body for a Git LFS pointer: the tracked object's size instead of the
+    /// pointer text, which says nothing about the actual change.
+    fn render_lfs_object(
+        &self,
+        file_index: usize,
+        file: &FileDiff,
+        lfs: &LfsPointer,
+        cx: &Context<Self>,
+    ) -> gpui::AnyElement {
+        let theme = cx.theme();
+        let body = gpui::div()
+            .debug_selector(|| format!("lfs-object-{file_index}"))
+            .px_3()
+            .py_1()
+            .text_xs()
+            .italic()
+            .text_color(theme.muted_foreground)
+            .bg(theme.muted.opacity(0.5))
+            .child(format!("LFS object ({})", format_size(lfs.size)));
+
+        v_flex()
+            .w_full()
+            .gap_1()
+            .child(self.render_file_header(file_index, file, cx))
+            .child(body)
+            .into_any_element()
+    }
+
+    /// Both complete versions of a file side by side, in place of its hunks.
+    fn render_full_file(
+        &self,
+        file_index: usize,
+        file: &FileDiff,
+        rows: &[SplitRow],
+        cx: &Context<Self>,
+    ) -> gpui::AnyElement {
+        let diff_theme = DiffTheme::from_cx(cx, &self.diff_palette);
+        v_flex()
+            .w_full()
+            .gap_1()
+            .child(self.render_file_header(file_index, file, cx))
+            .child(
+                v_flex()
+                    .w_full()
+                    .debug_selector(|| format!("full-file-{file_index}"))
+                    .children(self.render_split_rows(
+                        file_index,
+                        rows,
+                        &file.path,
+                        &diff_theme,
+                        cx,
+                    )),
+            )
+            .into_any_element()
+    }
+
+    /// The complete new version of a file with added lines marked, in
+    /// place of its hunks.
+    fn render_whole_file(
+        &self,
+        file_index: usize,
+        file: &FileDiff,
+        whole: &FileDiff,
+        cx: &Context<Self>,
+    ) -> gpui::AnyElement {
+        let diff_theme = DiffTheme::from_cx(cx, &self.diff_palette);
+        for hunk in &whole.hunks {
+            self.prime_syntax(&hunk.lines, &file.path, &diff_theme, cx);
+        }
+        let lines = whole.hunks.iter().flat_map(|hunk| &hunk.lines).map(|line| {
+            self.render_diff_line(file_index, line, &file.path, &diff_theme, cx)
+                .into_any_element()
+        });
+        v_flex()
+            .w_full()
+            .gap_1()
+            .child(self.render_file_header(file_index, file, cx))
+            .child(
+                v_flex()
+                    .w_full()
+                    .debug_selector(|| format!("whole-file-{file_index}"))
+                    .children(lines),
+            )
+            .into_any_element()
+    }
+
+    /// The old and new image next to each other, or the new one laid over
+    /// the old with adjustable opacity.
+    fn render_image_diff(
+        &self,
+        file_index: usize,
+        file: &FileDiff,
+        images: &ImagePair,
+        cx: &Context<Self>,
+    ) -> gpui::AnyElement {
+        let theme = cx.theme();
+        let control = |id: &str, label: String| {
+            gpui::div()
+                .id(gpui::ElementId::Name(format!("{id}-{file_index}").into()))
+                .cursor_pointer()
+                .text_color(theme.muted_foreground)
+                .hover(|el| el.text_color(theme.foreground))
+                .child(label)
+        };
+        let overlay = self.image_compare == ImageCompareMode::Overlay;
+        let controls = gpui::div()
+            .px_3()
+            .flex()
+            .gap_3()
+            .text_xs()
+            .child(
+                control(
+                    "image-compare-toggle",
+                    if overlay { "Side by side" } else { "Overlay" }.into(),
+                )
+                .on_click(cx.listener(|view, _event, _window, cx| {
+                    view.toggle_image_compare(cx);
+                })),
+            )
+            .when(overlay, |el| {
+                el.child(
+                    control("image-fade-out", "\u{2212}".into()).on_click(cx.listener(
+                        |view, _event, _window, cx| {
+                            view.adjust_image_opacity(-IMAGE_OPACITY_STEP, cx);
+                        },
+                    )),
+                )
+                .child(format!("New at {:.0}%", self.image_overlay_opacity * 100.0))
+                .child(control("image-fade-in", "+".into()).on_click(cx.listener(
+                    |view, _event, _window, cx| {
+                        view.adjust_image_opacity(IMAGE_OPACITY_STEP, cx);
+                    },
+                )))
+            });
+
+        let pane = |label: &'static str, image: &Option<Arc<gpui::Image>>| {
+            v_flex()
+                .flex_1()
+                .min_w_0()
+                .gap_1()
+                .child(
+                    gpui::div()
+                        .text_xs()
+                        .text_color(theme.muted_foreground)
+                        .child(label),
+                )
+                .child(match image {
+                    Some(image) => gpui::img(image.clone()).max_w_full().into_any_element(),
+                    None => gpui::div()
+                        .text_xs()
+                        .italic()
+                        .text_color(theme.muted_foreground)
+                        .child("(none)")
+                        .into_any_element(),
+                })
+        };
+        let body = if overlay {
+            gpui::div()
+                .relative()
+                .children(images.old.clone().map(|old| gpui::img(old).max_w_full()))
+                .children(images.new.clone().map(|new| {
+                    gpui::img(new)
+                        .absolute()
+                        .top_0()
+                        .left_0()
+                        .max_w_full()
+                        .opacity(self.image_overlay_opacity)
+                }))
+                .into_any_element()
+        } else {
+            gpui::div()
+                .flex()
+                .gap_3()
+                .child(pane("Before", &images.old))
+                .child(pane("After", &images.new))
+                .into_any_element()
+        };
+
+        v_flex()
+            .w_full()
+            .gap_1()
+            .child(self.render_file_header(file_index, file, cx))
+            .child(controls)
+            .child(
+                gpui::div()
+                    .debug_selector(|| format!("image-diff-{file_index}"))
+                    .px_3()
+                    .py_1()
+                    .child(body),
+            )
+            .into_any_element()
+    }
+
+    /// Header plus a placeholder row standing in for a generated file's hunks.
+    fn render_collapsed_file(
+        &self,
+        file_index: usize,
+        file: &FileDiff,
+        cx: &Context<Self>,
+    ) -> gpui::AnyElement {
+        let theme = cx.theme();
+        let placeholder = gpui::div()
+            .id(gpui::ElementId::Name(
+                format!("show-generated-{file_index}").into(),
+            ))
+            .px_3()
+            .py_1()
+            .text_xs()
+            .text_color(theme.muted_foreground)
+            .bg(theme.muted.opacity(0.5))
+            .cursor_pointer()
+            .hover(|el| el.text_color(theme.foreground))
+            .on_click(cx.listener(move |view, _event, _window, cx| {
+                view.toggle_generated(file_index, cx);
+            }))
+            .child("Generated file \u{2014} click to show diff");
+
+        v_flex()
+            .w_full()
+            .gap_1()
+            .child(self.render_file_header(file_index, file, cx))
+            .child(placeholder)
+            .into_any_element()
+    }
+
+    /// Warning row under a file header when its working copy mixes LF and
+    /// CRLF, with actions to normalize it either way.
+    fn render_line_ending_warning(
+        &self,
+        file_index: usize,
+        file: &FileDiff,
+        cx: &Context<Self>,
+    ) -> Option<gpui::AnyElement> {
+        let stats = self.mixed_line_endings(&file.path)?;
+        let theme = cx.theme();
+        let action = |ending: LineEnding| {
+            gpui::div()
+                .id(gpui::ElementId::Name(
+                    format!("normalize-{}-{file_index}", ending.label()).into(),
+                ))
+                .cursor_pointer()
+                .text_color(theme.primary)
+                .hover(|el| el.opacity(0.8))
+                .on_click(cx.listener(move |view, _event, _window, cx| {
+                    view.normalize_line_endings(file_index, ending, cx);
+                }))
+                .child(format!("Normalize to {}", ending.label()))
+        };
+        Some(
+            gpui::div()
+                .debug_selector(|| format!("line-ending-warning-{file_index}"))
+                .px_3()
+                .py_0p5()
+                .flex()
+                .gap_3()
+                .text_xs()
+                .text_color(theme.warning)
+                .child(format!(
+                    "Working copy mixes line endings ({} LF, {} CRLF)",
+                    stats.lf, stats.crlf
+                ))
+                .child(action(LineEnding::Lf))
+                .child(action(LineEnding::CrLf))
+                .into_any_element(),
+        )
+    }
+
+    /// Clicking the path copies it repo-relative; alt-click copies the
+    /// absolute path.
+    fn render_file_header(
+        &self,
+        file_index: usize,
+        file: &FileDiff,
+        cx: &Context<Self>,
+    ) -> impl IntoElement {
+        let status_label = match file.status {
+            dd_git::FileStatus::Added => "A",
+            dd_git::FileStatus::Deleted => "D",
+            dd_git::FileStatus::Modified => "M",
+            dd_git::FileStatus::Renamed => "R",
+            dd_git::FileStatus::Copied => "C",
+            dd_git::FileStatus::TypeChanged => "T",
+        };
+
+        let path_display = if let Some(ref old) = file.old_path {
+            format!("{} {} \u{2192} {}", status_label, old, file.path)
+        } else {
+            format!("{} {}", status_label, file.path)
+        };
+
+        gpui::div()
+            .id(gpui::ElementId::Name(
+                format!("file-header-{file_index}").into(),
+            ))
+            .debug_selector(move || format!("file-header-{file_index}"))
+            .px_3()
+            .py_1()
+            .bg(cx.theme().muted)
+            .text_sm()
+            .font_weight(gpui::FontWeight::BOLD)
+            .cursor_pointer()
+            .flex()
+            .justify_between()
+            .on_click(cx.listener(move |view, event: &ClickEvent, _window, cx| {
+                view.copy_file_path(file_index, event.modifiers().alt, cx);
+            }))
+            .child(
+                h_flex()
+                    .gap_2()
+                    .when(
+                        self.working_changes.is_some() && self.on_stash_files.is_some(),
+                        |el| {
+                            let selected = self.selected_files.contains(&file_index);
+                            el.child(
+                                gpui::div()
+                                    .id(gpui::ElementId::Name(
+                                        format!("select-file-{file_index}").into(),
+                                    ))
+                                    .debug_selector(move || format!("select-file-{file_index}"))
+                                    .font_weight(gpui::FontWeight::NORMAL)
+                                    .on_click(cx.listener(move |view, _event, _window, cx| {
+                                        cx.stop_propagation();
+                                        view.toggle_file_selected(file_index, cx);
+                                    }))
+                                    .child(if selected { "\u{2611}" } else { "\u{2610}" }),
+                            )
+                        },
+                    )
+                    .child(path_display)
+                    .when_some(file.mode_change(), |el, (old, new)| {
+                        el.child(
+                            gpui::div()
+                                .font_weight(gpui::FontWeight::NORMAL)
+                                .text_xs()
+                                .text_color(cx.theme().muted_foreground)
+                                .debug_selector(|| format!("mode-change-{file_index}"))
+                                .child(format!("mode changed {old} \u{2192} {new}")),
+                        )
+                    }),
+            )
+            .child(
+                h_flex()
+                    .gap_3()
+                    .when(self.can_show_whole_file(file), |el| {
+                        let theme = cx.theme();
+                        el.child(
+                            gpui::div()
+                                .id(gpui::ElementId::Name(
+                                    format!("whole-file-toggle-{file_index}").into(),
+                                ))
+                                .font_weight(gpui::FontWeight::NORMAL)
+                                .text_xs()
+                                .text_color(theme.muted_foreground)
+                                .hover(|el| el.text_color(theme.foreground))
+                                .on_click(cx.listener(move |view, _event, _window, cx| {
+                                    cx.stop_propagation();
+                                    view.toggle_whole_file(file_index, cx);
+                                }))
+                                .child(if self.is_whole_file(file_index) {
+                                    "Hunks"
+                                } else {
+                                    "Whole file"
+                                }),
+                        )
+                    })
+                    .when(self.can_show_full_file(file), |el| {
+                        let theme = cx.theme();
+                        el.child(
+                            gpui::div()
+                                .id(gpui::ElementId::Name(
+                                    format!("full-file-toggle-{file_index}").into(),
+                                ))
+                                .font_weight(gpui::FontWeight::NORMAL)
+                                .text_xs()
+                                .text_color(theme.muted_foreground)
+                                .hover(|el| el.text_color(theme.foreground))
+                                .on_click(cx.listener(move |view, _event, _window, cx| {
+                                    cx.stop_propagation();
+                                    view.toggle_full_file(file_index, cx);
+                                }))
+                                .child(if self.is_full_file(file_index) {
+                                    "Hunks"
+                                } else {
+                                    "Full file"
+                                }),
+                        )
+                    }),
+            )
+    }
+
+    /// Whole-file comparison is offered for files with both an old and a
+    /// new version.
+    fn can_show_full_file(&self, file: &FileDiff) -> bool {
+        self.commit_info.is_some()
+            && self.full_file_loader.is_some()
+            && matches!(
+                file.status,
+                FileStatus::Modified | FileStatus::Renamed | FileStatus::Copied
+            )
+            && file.lfs.is_none()
+    }
+
+    /// The whole-file view needs a new version to read.
+    fn can_show_whole_file(&self, file: &FileDiff) -> bool {
+        self.commit_info.is_some()
+            && self.whole_file_loader.is_some()
+            && file.status != FileStatus::Deleted
+            && file.lfs.is_none()
+            && file.image_format().is_none()
+    }
+
+    /// Syntax-highlight a hunk as a whole, so lines inside multi-line
+    /// constructs are colored in context when they render.
+    fn prime_syntax(
+        &self,
+        lines: &[DiffLine],
+        file_path: &str,
+        diff_theme: &DiffTheme,
+        cx: &Context<Self>,
+    ) {
+        let theme = cx.theme();
+        let is_dark = theme.background.l < 0.5;
+        self.syntax.prime_hunk(
+            file_path,
+            lines,
+            |origin| fallback_color(origin, diff_theme, theme),
+            is_dark,
+        );
+    }
+
+    fn render_content(
+        &self,
+        line: &DiffLine,
+        file_path: &str,
+        diff_theme: &DiffTheme,
+        cx: &Context<Self>,
+    ) -> StyledText {
+        let theme = cx.theme();
+        let content = &line.content;
+
+        let fg = fallback_color(&line.origin, diff_theme, theme);
+        let is_dark = theme.background.l < 0.5;
+
+        let highlight_bg = match line.origin {
+            LineOrigin::Addition => diff_theme.add_highlight_bg,
+            LineOrigin::Deletion => diff_theme.del_highlight_bg,
+            LineOrigin::Context => diff_theme.ctx_bg,
+        };
+
+        let line_cx = LineContext {
+            line,
+            file_path,
+            fg,
+            change_bg: highlight_bg,
+            search_bg: theme.warning.opacity(0.35),
+            whitespace_bg: theme.danger.opacity(0.3),
+            is_dark,
+            search_query: &self.search_query,
+            show_whitespace: self.show_whitespace,
+        };
+        let highlights = decorate_line(&self.decorators, &line_cx);
+
+        StyledText::new(SharedString::from(content.clone())).with_highlights(highlights)
+    }
+
+    // -- Unified rendering ------------------------------------------------
+
+    fn render_unified(&self, cx: &Context<Self>) -> gpui::AnyElement {
+        let (files, remaining) = self.rendered_files();
+        let paths: Vec<_> = files.iter().map(|&i| self.diffs[i].path.clone()).collect();
+        let file_elements: Vec<_> = files
+            .into_iter()
+            .map(|i| self.render_file_diff(i, &self.diffs[i], cx))
+            .collect();
+
+        v_flex()
+            .on_children_prepainted(self.record_files(paths, cx))
+            .id("diff-files")
+            .flex_1()
+            .min_h_0()
+            .w_full()
+            .overflow_y_scroll()
+            .track_scroll(&self.scroll_handle)
+            .gap_2()
+            .children(file_elements)
+            .when(remaining > 0, |el| {
+                el.child(self.render_show_remaining(remaining, cx))
+            })
+            // Last, so child indices match file rows for `scroll_to_item`.
+            .vertical_scrollbar(&self.scroll_handle)
+            .into_any_element()
+    }
+
+    fn render_file_diff(
+        &self,
+        file_index: usize,
+        file: &FileDiff,
+        cx: &Context<Self>,
+    ) -> gpui::AnyElement {
+        if self.is_file_collapsed(file_index) {
+            return self.render_collapsed_file(file_index, file, cx);
+        }
+        if is_pure_rename(file) {
+            return self.render_pure_rename(file_index, file, cx);
+        }
+        if let Some(lfs) = &file.lfs {
+            return self.render_lfs_object(file_index, file, lfs, cx);
+        }
+        match self.full_files.get(&file_index) {
+            Some(FullFileView::BothVersions(rows)) => {
+                return self.render_full_file(file_index, file, rows, cx);
+            }
+            Some(FullFileView::Whole(whole)) => {
+                return self.render_whole_file(file_index, file, whole, cx);
+            }
+            None => {}
+        }
+        if let Some(images) = self.images.get(&file_index) {
+            return self.render_image_diff(file_index, file, images, cx);
+        }
+        let mut hunk_elements = Vec::new();
+        for (i, hunk) in file.hunks.iter().enumerate() {
+            hunk_elements.extend(self.render_expand_row(file_index, file, i, cx));
+            let element = if self
+                .split_hunks
+                .contains(&(file.path.clone(), hunk_key(hunk)))
+            {
+                self.render_hunk_split(file_index, i, hunk, &file.path, cx)
+                    .into_any_element()
+            } else {
+                self.render_hunk(file_index, i, hunk, &file.path, cx)
+                    .into_any_element()
+            };
+            hunk_elements.push(element);
+        }
+        hunk_elements.extend(self.render_expand_row(file_index, file, file.hunks.len(), cx));
+
+        v_flex()
+            .w_full()
+            .gap_1()
+            .child(self.render_file_header(file_index, file, cx))
+            .children(self.render_line_ending_warning(file_index, file, cx))
+            .children(hunk_elements)
+            .into_any_element()
+    }
+
+    fn render_hunk(
+        &self,
+        file_index: usize,
+        hunk_index: usize,
+        hunk: &Hunk,
+        file_path: &str,
+        cx: &Context<Self>,
+    ) -> impl IntoElement {
+        let diff_theme = DiffTheme::from_cx(cx, &self.diff_palette);
+        self.prime_syntax(&hunk.lines, file_path, &diff_theme, cx);
+
+        let rows = unified_rows(&hunk.lines, self.word_diff);
+        // The hunk header comes first.
+        let lines = std::iter::once(None)
+            .chain(rows.iter().map(UnifiedRow::new_line_no))
+            .collect();
+        let line_elements: Vec<_> = rows
+            .into_iter()
+            .map(|row| match row {
+                UnifiedRow::Line(line) => {
+                    let element = self
+                        .render_diff_line(file_index, line, file_path, &diff_theme, cx)
+                        .into_any_element();
+                    self.with_note_editor(element, file_path, line.new_line_no, cx)
+                }
+                UnifiedRow::WordDiff(del, add) => {
+                    let element = self
+                        .render_word_diff_line(file_index, del, add, file_path, &diff_theme, cx)
+                        .into_any_element();
+                    self.with_note_editor(element, file_path, add.new_line_no, cx)
+                }
+                UnifiedRow::NoNewline => gpui::div()
+                    .w_full()
+                    .pl(px(if self.show_line_numbers { 104.0 } else { 8.0 }))
+                    .child(self.render_no_newline_marker(cx))
+                    .into_any_element(),
+            })
+            .collect();
+
+        v_flex()
+            .on_children_prepainted(self.record_rows(file_path, lines))
+            .w_full()
+            .debug_selector(|| format!("hunk-unified-{file_index}-{hunk_index}"))
+            .child(self.render_hunk_header(file_index, hunk_index, hunk, cx))
+            .children(line_elements)
+    }
+
+    fn render_diff_line(
+        &self,
+        file_index: usize,
+        line: &DiffLine,
+        file_path: &str,
+        diff_theme: &DiffTheme,
+        cx: &Context<Self>,
+    ) -> impl IntoElement {
+        let theme = cx.theme();
+
+        let (prefix, bg_color) = match line.origin {
+            LineOrigin::Addition => ("+", diff_theme.add_bg),
+            LineOrigin::Deletion => ("-", diff_theme.del_bg),
+            LineOrigin::Context => (" ", diff_theme.ctx_bg),
+        };
+        let marker = self.render_change_marker(prefix, change_bar_color(line, diff_theme));
+
+        let fg = fallback_color(&line.origin, diff_theme, theme);
+
+        let old_str = line
+            .old_line_no
+            .map(|n| format!("{:>4}", n))
+            .unwrap_or_else(|| "    ".to_string());
+        let new_str = line
+            .new_line_no
+            .map(|n| format!("{:>4}", n))
+            .unwrap_or_else(|| "    ".to_string());
+        let show_line_numbers = self.show_line_numbers;
+
+        gpui::div()
+            .w_full()
+            .flex()
+            .overflow_x_hidden()
+            .bg(bg_color)
+            .on_mouse_down(
+                MouseButton::Right,
+                self.line_menu_listener(file_index, line, cx),
+            )
+            .text_xs()
+            .line_height(gpui::rems(1.0))
+            .font_family(theme.font_family.clone())
+            .when(show_line_numbers, |el| {
+                el.child(
+                    gpui::div()
+                        .w(gpui::px(48.0))
+                        .flex_shrink_0()
+                        .debug_selector(|| "line-number-gutter".into())
+                        .text_color(diff_theme.line_number_fg)
+                        .text_right()
+                        .px_1()
+                        .child(old_str),
+                )
+            })
+            .when(show_line_numbers, |el| {
+                el.child(
+                    gpui::div()
+                        .w(gpui::px(48.0))
+                        .flex_shrink_0()
+                        .debug_selector(|| "line-number-gutter".into())
+                        .text_color(diff_theme.line_number_fg)
+                        .text_right()
+                        .px_1()
+                        .child(new_str),
+                )
+            })
+            .children(self.render_annotation_gutter(file_index, file_path, line.new_line_no))
+            .children(self.render_note_gutter(file_index, file_path, line.new_line_no, cx))
+            .child(marker.text_color(fg))
+            .child(
+                gpui::div()
+                    .px_1()
+                    .overflow_x_hidden()
+                    .child(self.render_content(line, file_path, diff_theme, cx)),
+            )
+    }
+
+    /// A paired deletion/addition on one line: removed words struck through
+    /// on the deletion background, added words on the addition background.
+    fn render_word_diff_line(
+        &self,
+        file_index: usize,
+        del: &DiffLine,
+        add: &DiffLine,
+        file_path: &str,
+        diff_theme: &DiffTheme,
+        cx: &Context<Self>,
+    ) -> impl IntoElement {
+        let theme = cx.theme();
+
+        let mut content = String::new();
+        let mut highlights = Vec::new();
+        for (range, tag) in merge_word_diff(del, add) {
+            let start = content.len();
+            let style = match tag {
+                WordTag::Equal => {
+                    content.push_str(&add.content[range]);
+                    continue;
+                }
+                WordTag::Removed => {
+                    content.push_str(&del.content[range]);
+                    HighlightStyle {
+                        background_color: Some(diff_theme.del_highlight_bg),
+                        strikethrough: Some(StrikethroughStyle {
+                            thickness: px(1.0),
+                            color: None,
+                        }),
+                        ..Default::default()
+                    }
+                }
+                WordTag::Added => {
+                    content.push_str(&add.content[range]);
+                    HighlightStyle {
+                        background_color: Some(diff_theme.add_highlight_bg),
+                        ..Default::default()
+                    }
+                }
+            };
+            highlights.push((start..content.len(), style));
+        }
+
+        let number = |n: Option<u32>| {
+            n.map(|n| format!("{:>4}", n))
+                .unwrap_or_else(|| "    ".to_string())
+        };
+        let new_line_no = add.new_line_no;
+        let show_line_numbers = self.show_line_numbers;
+
+        gpui::div()
+            .w_full()
+            .flex()
+            .overflow_x_hidden()
+            .bg(diff_theme.ctx_bg)
+            .debug_selector(move || {
+                format!("word-diff-{file_index}-{}", new_line_no.unwrap_or_default())
+            })
+            .on_mouse_down(
+                MouseButton::Right,
+                self.line_menu_listener(file_index, add, cx),
+            )
+            .text_xs()
+            .line_height(gpui::rems(1.0))
+            .font_family(theme.font_family.clone())
+            .when(show_line_numbers, |el| {
+                el.child(
+                    gpui::div()
+                        .w(gpui::px(48.0))
+                        .flex_shrink_0()
+                        .debug_selector(|| "line-number-gutter".into())
+                        .text_color(diff_theme.line_number_fg)
+                        .text_right()
+                        .px_1()
+                        .child(number(del.old_line_no)),
+                )
+            })
+            .when(show_line_numbers, |el| {
+                el.child(
+                    gpui::div()
+                        .w(gpui::px(48.0))
+                        .flex_shrink_0()
+                        .debug_selector(|| "line-number-gutter".into())
+                        .text_color(diff_theme.line_number_fg)
+                        .text_right()
+                        .px_1()
+                        .child(number(new_line_no)),
+                )
+            })
+            .children(self.render_annotation_gutter(file_index, file_path, new_line_no))
+            .children(self.render_note_gutter(file_index, file_path, new_line_no, cx))
+            .child(
+                self.render_change_marker("~", Some(theme.warning))
+                    .text_color(theme.foreground),
+            )
+            .child(
+                gpui::div()
+                    .px_1()
+                    .overflow_x_hidden()
+                    .text_color(theme.foreground)
+                    .child(
+                        StyledText::new(SharedString::from(content)).with_highlights(highlights),
+                    ),
+            )
+    }
+
+    /// The `+`/`-`/`~` column in prefix mode, or in bar mode a thin strip
+    /// painted `bar_color` (left blank for unchanged lines).
+    fn render_change_marker(&self, prefix: &'static str, bar_color: Option<Hsla>) -> gpui::Div {
+        match self.change_marker_style {
+            ChangeMarkerStyle::Prefix => gpui::div()
+                .flex_shrink_0()
+                .debug_selector(|| "change-prefix".into())
+                .child(prefix),
+            ChangeMarkerStyle::Bar => gpui::div()
+                .w(px(CHANGE_BAR_WIDTH))
+                .flex_shrink_0()
+                .when_some(bar_color, |el, color| {
+                    el.bg(color).debug_selector(|| "change-bar".into())
+                }),
+        }
+    }
+
+    /// The annotation column for a line, present only in files that have
+    /// annotations so other diffs keep their layout.
+    fn render_annotation_gutter(
+        &self,
+        file_index: usize,
+        file_path: &str,
+        new_line_no: Option<u32>,
+    ) -> Option<gpui::AnyElement> {
+        let annotations = self.line_annotations.get(file_path)?;
+        let gutter = gpui::div()
+            .w(px(ANNOTATION_GUTTER_WIDTH))
+            .flex_shrink_0()
+            .flex()
+            .justify_center();
+        let Some((line_no, annotation)) =
+            new_line_no.and_then(|n| Some((n, annotations.get(&n)?.clone())))
+        else {
+            return Some(gutter.into_any_element());
+        };
+        let tooltip = annotation.tooltip.clone();
+        Some(
+            gutter
+                .child(
+                    gpui::div()
+                        .id(SharedString::from(format!(
+                            "line-annotation-{file_index}-{line_no}"
+                        )))
+                        .debug_selector(move || format!("line-annotation-{file_index}-{line_no}"))
+                        .text_color(annotation.color)
+                        .child(annotation.glyph)
+                        .when(!tooltip.is_empty(), |el| {
+                            el.tooltip(move |window, cx| {
+                                Tooltip::new(tooltip.clone()).build(window, cx)
+                            })
+                        }),
+                )
+                .into_any_element(),
+        )
+    }
+
+    /// Column for review notes: the note glyph on annotated lines, and a
+    /// click target on every new-side line to add or edit one.
+    fn render_note_gutter(
+        &self,
+        file_index: usize,
+        file_path: &str,
+        new_line_no: Option<u32>,
+        cx: &Context<Self>,
+    ) -> Option<gpui::AnyElement> {
+        self.on_review_notes_change.as_ref()?;
+        let oid = &self.commit_info.as_ref()?.oid;
+        let gutter = gpui::div()
+            .w(px(ANNOTATION_GUTTER_WIDTH))
+            .flex_shrink_0()
+            .flex()
+            .justify_center();
+        let Some(line_no) = new_line_no else {
+            return Some(gutter.into_any_element());
+        };
+        let note = self
+            .review_notes
+            .get(oid, file_path, line_no)
+            .map(|note| SharedString::from(note.to_string()));
+        let path = file_path.to_string();
+        let muted = cx.theme().muted_foreground;
+        Some(
+            gutter
+                .id(SharedString::from(format!(
+                    "review-note-{file_index}-{line_no}"
+                )))
+                .cursor_pointer()
+                .text_color(cx.theme().link)
+                .when_some(note, |el, note| {
+                    el.debug_selector(move || format!("review-note-{file_index}-{line_no}"))
+                        .child(REVIEW_NOTE_GLYPH)
+                        .tooltip(move |window, cx| Tooltip::new(note.clone()).build(window, cx))
+                })
+                .hover(move |style| style.bg(muted.opacity(0.2)))
+                .on_click(cx.listener(move |view, _event: &ClickEvent, window, cx| {
+                    cx.stop_propagation();
+                    view.open_note_editor(&path, line_no, window, cx);
+                }))
+                .into_any_element(),
+        )
+    }
+
+    /// `row` followed by the note editor when it is open under
+    /// `new_line_no`.
+    fn with_note_editor(
+        &self,
+        row: gpui::AnyElement,
+        file_path: &str,
+        new_line_no: Option<u32>,
+        cx: &Context<Self>,
+    ) -> gpui::AnyElement {
+        match self.render_note_editor(file_path, new_line_no, cx) {
+            Some(editor) => v_flex()
+                .w_full()
+                .child(row)
+                .child(editor)
+                .into_any_element(),
+            None => row,
+        }
+    }
+
+    /// The note editor row, when it is open under this line.
+    fn render_note_editor(
+        &self,
+        file_path: &str,
+        new_line_no: Option<u32>,
+        cx: &Context<Self>,
+    ) -> Option<gpui::AnyElement> {
+        let editor = self.note_editor.as_ref()?;
+        if editor.path != file_path || Some(editor.line) != new_line_no {
+            return None;
+        }
+        let theme = cx.theme();
+        let action = |id: &'static str, label: &'static str| {
+            gpui::div()
+                .id(id)
+                .cursor_pointer()
+                .text_color(theme.link)
+                .child(label)
+        };
+        Some(
+            h_flex()
+                .w_full()
+                .gap_2()
+                .px_2()
+                .py_1()
+                .bg(theme.secondary)
+                .text_xs()
+                .debug_selector(|| "review-note-editor".into())
+                .child(gpui::div().flex_1().child(Input::new(&editor.input)))
+                .child(action("review-note-save", "Save").on_click(
+                    cx.listener(|view, _event, window, cx| view.save_note_editor(window, cx)),
+                ))
+                .child(
+                    action("review-note-cancel", "Cancel").on_click(
+                        cx.listener(|view, _event, _window, cx| view.close_note_editor(cx)),
+                    ),
+                )
+                .into_any_element(),
+        )
+    }
+
+    /// Controls above a working-changes diff: how many files are picked,
+    /// with the button that stashes them, and the "Show ignored" switch
+    /// with the ignored paths under it. Staged changes say what an amend
+    /// would do with them.
+    fn render_working_changes_bar(&self, cx: &Context<Self>) -> Option<gpui::AnyElement> {
+        let unstaged = self.working_changes == Some(WorkingChanges::Unstaged);
+        let can_show_ignored = unstaged && self.on_show_ignored_change.is_some();
+        let amend_note = (self.working_changes == Some(WorkingChanges::Staged)).then_some(
+            if self.diffs.is_empty() {
+                "Nothing staged: amending would only reword the last commit."
+            } else {
+                "Amending would fold these staged changes into the last commit."
+            },
+        );
+        if self.selected_files.is_empty() && !can_show_ignored && amend_note.is_none() {
+            return None;
+        }
+        let theme = cx.theme();
+        let count = self.selected_files.len();
+        let noun = if count == 1 { "file" } else { "files" };
+        let ignored = self.ignored_files.iter().enumerate().map(|(i, path)| {
+            gpui::div()
+                .debug_selector(move || format!("ignored-file-{i}"))
+                .child(path.clone())
+        });
+        Some(
+            v_flex()
+                .px_3()
+                .py_0p5()
+                .text_xs()
+                .text_color(theme.muted_foreground)
+                .child(
+                    h_flex()
+                        .gap_3()
+                        .when(count > 0, |el| {
+                            el.child(format!("{count} {noun} selected")).child(
+                                gpui::div()
+                                    .id("stash-selected")
+                                    .debug_selector(|| "stash-selected".into())
+                                    .cursor_pointer()
+                                    .hover(|el| el.text_color(theme.foreground))
+                                    .on_click(cx.listener(|view, _event, window, cx| {
+                                        view.stash_selected_files(window, cx);
+                                    }))
+                                    .child("Stash selected"),
+                            )
+                        })
+                        .when(can_show_ignored, |el| {
+                            el.child(
+                                gpui::div()
+                                    .id("show-ignored")
+                                    .debug_selector(|| "show-ignored".into())
+                                    .cursor_pointer()
+                                    .hover(|el| el.text_color(theme.foreground))
+                                    .on_click(cx.listener(|view, _event, window, cx| {
+                                        view.toggle_show_ignored(window, cx);
+                                    }))
+                                    .child(if self.show_ignored {
+                                        "Hide ignored"
+                                    } else {
+                                        "Show ignored"
+                                    }),
+                            )
+                        }),
+                )
+                .children(ignored)
+                .when_some(amend_note, |el, note| {
+                    el.child(
+                        gpui::div()
+                            .debug_selector(|| "amend-note".into())
+                            .child(note),
+                    )
+                })
+                .into_any_element(),
+        )
+    }
+
+    fn render_file_palette(&self, cx: &Context<Self>) -> Option<gpui::AnyElement> {
+        let palette = self.file_palette.as_ref()?;
+        let theme = cx.theme();
+        let rows = palette
+            .matches
+            .iter()
+            .take(FILE_PALETTE_MAX_ROWS)
+            .enumerate()
+            .map(|(rank, &file_index)| {
+                gpui::div()
+                    .id(("file-palette-row", rank))
+                    .debug_selector(move || format!("file-palette-{rank}"))
+                    .px_2()
+                    .cursor_pointer()
+                    .when(rank == 0, |el| el.bg(theme.secondary))
+                    .hover(|el| el.bg(theme.secondary))
+                    .child(self.diffs[file_index].path.clone())
+                    .on_click(cx.listener(move |view, _event, window, cx| {
+                        view.confirm_file_palette(rank, window, cx);
+                    }))
+            });
+        Some(
+            v_flex()
+                .w_full()
+                .flex_shrink_0()
+                .p_1()
+                .border_b_1()
+                .border_color(theme.border)
+                .text_xs()
+                .debug_selector(|| "file-palette".into())
+                .child(Input::new(&palette.input))
+                .children(rows)
+                .into_any_element(),
+        )
+    }
+
+    /// Right-clicking a line opens its context menu.
+    fn line_menu_listener(
+        &self,
+        file_index: usize,
+        line: &DiffLine,
+        cx: &Context<Self>,
+    ) -> impl Fn(&MouseDownEvent, &mut Window, &mut App) + 'static {
+        let line = line.clone();
+        let view = cx.entity().downgrade();
+        move |event: &MouseDownEvent, _window, cx| {
+            let _ = view.update(cx, |view, cx| {
+                view.open_line_menu(file_index, &line, event.position, cx);
+            });
+        }
+    }
+
+    fn render_line_menu(&self, cx: &Context<Self>) -> Option<gpui::AnyElement> {
+        let menu = self.line_menu.as_ref()?;
+        let theme = cx.theme();
+        let file_index = menu.file_index;
+        let line = menu.line.clone();
+        Some(
+            deferred(
+                anchored().position(menu.position).child(
+                    v_flex()
+                        .min_w(px(140.0))
+                        .py_1()
+                        .bg(theme.popover)
+                        .border_1()
+                        .border_color(theme.border)
+                        .rounded_md()
+                        .shadow_md()
+                        .text_xs()
+                        .on_mouse_down_out(cx.listener(|view, _event, _window, cx| {
+                            view.close_line_menu(cx);
+                        }))
+                        .child(
+                            gpui::div()
+                                .id("line-menu-open-on-remote")
+                                .debug_selector(|| "line-menu-open-on-remote".into())
+                                .px_2()
+                                .py_0p5()
+                                .cursor_pointer()
+                                .hover(|el| el.bg(theme.secondary))
+                                .child("Open on Remote")
+                                .on_click(cx.listener(move |view, _event, _window, cx| {
+                                    view.close_line_menu(cx);
+                                    view.open_line_on_remote(file_index, &line, cx);
+                                })),
+                        ),
+                ),
+            )
+            .with_priority(1)
+            .into_any_element(),
+        )
+    }
+
+    fn render_no_newline_marker(&self, cx: &Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+        gpui::div()
+            .text_xs()
+            .line_height(gpui::rems(1.0))
+            .font_family(theme.font_family.clone())
+            .italic()
+            .text_color(theme.muted_foreground)
+            .child(NO_NEWLINE_LABEL)
+    }
+
+    // -- Commit header -----------------------------------------------------
+}
+
+fn compute_stats(diffs: &[FileDiff]) -> (usize, usize, usize) {
+    let files = diffs.len();
+    let mut additions = 0usize;
+    let mut deletions = 0usize;
+    for file in diffs {
+        for hunk in &file.hunks {
+            for line in &hunk.lines {
+                match line.origin {
+                    LineOrigin::Addition => additions += 1,
+                    LineOrigin::Deletion => deletions += 1,
+                    LineOrigin::Context => {}
+                }
+            }
+        }
+    }
+    (files, additions, deletions)
+}
+
+fn format_commit_date(timestamp: i64) -> String {
+    use chrono::{DateTime, Local, TimeZone};
+    match Local.timestamp_opt(timestamp, 0) {
+        chrono::LocalResult::Single(dt) => dt.format("%a, %b %-d, %Y, %-I:%M %p").to_string(),
+        _ => match DateTime::from_timestamp(timestamp, 0) {
+            Some(dt) => dt.format("%a, %b %-d, %Y, %-I:%M %p UTC").to_string(),
+            None => "unknown".to_string(),
+        },
+    }
+}
+
+/// Format a git identity as `Name <email>`, dropping whichever half is
+/// empty (imported history sometimes has no email).
+pub(crate) fn format_person(name: &str, email: &str) -> String {
+    match (name.is_empty(), email.is_empty()) {
+        (false, false) => format!("{name} <{email}>"),
+        (false, true) => name.to_string(),
+        (true, false) => format!("<{email}>"),
+        (true, true) => "unknown".to_string(),
+    }
+}
+
+const LABEL_WIDTH: f32 = 100.0;
+
+/// Parent subjects longer than this are cut in merge headers.
+const PARENT_SUBJECT_MAX_LEN: usize = 48;
+
+fn short_oid(oid: &str) -> &str {
+    &oid[..7.min(oid.len())]
+}
+
+impl DiffView {
+    /// A merge's parents as clickable chips, with their subjects once
+    /// loaded, followed by a "merge of N commits" note.
+    fn render_merge_parents(&self, commit: &CommitInfo, cx: &Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+        let chips = commit.parent_oids.iter().enumerate().map(|(i, oid)| {
+            let subject = self
+                .parent_subjects
+                .get(i)
+                .map(|s| truncate_end(s, PARENT_SUBJECT_MAX_LEN))
+                .unwrap_or_default();
+            gpui::div()
+                .id(gpui::ElementId::Name(format!("commit-parent-{i}").into()))
+                .debug_selector(move || format!("commit-parent-{i}"))
+                .flex()
+                .gap_1()
+                .px_1()
+                .rounded_sm()
+                .cursor_pointer()
+                .bg(theme.accent.opacity(0.2))
+                .hover(|el| el.bg(theme.accent.opacity(0.4)))
+                .on_click(cx.listener(move |view, _event, window, cx| {
+                    view.navigate_to_parent(i, window, cx);
+                }))
+                .child(
+                    gpui::div()
+                        .text_color(theme.foreground)
+                        .child(format!("\u{2190} {}", short_oid(oid))),
+                )
+                .when(!subject.is_empty(), |el| {
+                    el.child(
+                        gpui::div()
+                            .text_color(theme.muted_foreground)
+                            .child(subject),
+                    )
+                })
+        });
+
+        gpui::div()
+            .flex()
+            .flex_wrap()
+            .items_center()
+            .gap_1()
+            .children(chips)
+            .child(
+                gpui::div()
+                    .debug_selector(|| "merge-note".into())
+                    .text_color(theme.muted_foreground)
+                    .child(format!("merge of {} commits", commit.parent_oids.len())),
+            )
+    }
+
+    fn render_commit_header(&self, cx: &Context<Self>) -> impl IntoElement {
+        let theme = cx.theme();
+        let commit = self.commit_info.as_ref().unwrap();
+        let signature = self.signature_status.unwrap_or(SignatureStatus::None);
+        let conventional = commit.conventional();
+        let subject = conventional
+            .as_ref()
+            .map(|cc| cc.description.clone())
+            .unwrap_or_else(|| commit.subject.clone());
+
+        let parents_str = if commit.parent_oids.is_empty() {
+            "(root commit)".to_string()
+        } else {
+            commit
+                .parent_oids
+                .iter()
+                .map(|p| short_oid(p))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let is_merge = commit.parent_oids.len() > 1;
+
+        let (files, additions, deletions) = compute_stats(&self.diffs);
+        let stats_str = format!(
+            "{} file{}, +{} addition{}, -{} deletion{}",
+            files,
+            if files == 1 { "" } else { "s" },
+            additions,
+            if additions == 1 { "" } else { "s" },
+            deletions,
+            if deletions == 1 { "" } else { "s" },
+        );
+
+        let sig_color = match signature {
+            SignatureStatus::Good => theme.success,
+            SignatureStatus::Bad => theme.danger,
+            _ => theme.muted_foreground,
+        };
+
+        let mut header = v_flex().w_full().px_3().py_2().gap_0p5();
+
+        let rows: Vec<(&str, String, Option<Hsla>)> = vec![
+            ("Commit", commit.oid.clone(), None),
+            ("Tree", commit.tree_oid.clone(), None),
+            (
+                "Author",
+                format_person(&commit.author_name, &commit.author_email),
+                None,
+            ),
+            (
+                "Committer",
+                format_person(&commit.committer_name, &commit.committer_email),
+                None,
+            ),
+            ("Date", format_commit_date(commit.date), None),
+            ("Parents", parents_str, None),
+            ("Signature", signature.label().to_string(), Some(sig_color)),
+            ("Stats", stats_str, None),
+        ];
+
+        for (label, value, color) in rows {
+            let value = if label == "Parents" && is_merge {
+                self.render_merge_parents(commit, cx).into_any_element()
+            } else {
+                gpui::div()
+                    .text_color(color.unwrap_or(theme.foreground))
+                    .child(value)
+                    .into_any_element()
+            };
+            header = header.child(
+                gpui::div()
+                    .flex()
+                    .w_full()
+                    .text_xs()
+                    .font_family(theme.font_family.clone())
+                    .child(
+                        gpui::div()
+                            .w(gpui::px(LABEL_WIDTH))
+                            .flex_shrink_0()
+                            .text_right()
+                            .pr_2()
+                            .text_color(theme.muted_foreground)
+                            .child(format!("{}:", label)),
+                    )
+                    .child(value),
+            );
+        }
+
+        if !self.containing_branches.is_empty() {
+            let chips = self
+                .containing_branches
+                .iter()
+                .enumerate()
+                .map(|(i, branch)| {
+                    gpui::div()
+                        .debug_selector(move || format!("commit-branch-{i}"))
+                        .px_1()
+                        .rounded_sm()
+                        .bg(theme.accent.opacity(0.2))
+                        .text_color(theme.foreground)
+                        .child(branch.clone())
+                });
+            header = header.child(
+                gpui::div()
+                    .flex()
+                    .w_full()
+                    .text_xs()
+                    .font_family(theme.font_family.clone())
+                    .child(
+                        gpui::div()
+                            .w(gpui::px(LABEL_WIDTH))
+                            .flex_shrink_0()
+                            .text_right()
+                            .pr_2()
+                            .text_color(theme.muted_foreground)
+                            .child("Branches:"),
+                    )
+                    .child(gpui::div().flex().flex_wrap().gap_1().children(chips)),
+            );
+        }
+
+        header = header.child(
+            v_flex()
+                .mt_2()
+                .px_1()
+                .gap_0p5()
+                .child(
+                    gpui::div()
+                        .flex()
+                        .items_center()
+                        .gap_2()
+                        .when_some(conventional.as_ref(), |el, cc| {
+                            el.child(render_conventional_chip(cc, cx))
+                        })
+                        .child(
+                            gpui::div()
+                                .text_sm()
+                                .font_weight(gpui::FontWeight::BOLD)
+                                .text_color(theme.foreground)
+                                .child(subject),
+                        ),
+                )
+                .when(!commit.body.is_empty(), |el| {
+                    el.child(
+                        gpui::div()
+                            .text_xs()
+                            .text_color(theme.muted_foreground)
+                            .child(commit.body.clone()),
+                    )
+                }),
+        );
+
+        header = header.child(
+            gpui::div()
+                .mt_2()
+                .w_full()
+                .h(gpui::px(1.0))
+                .bg(theme.border),
+        );
+
+        header
+    }
+
+    // -- Split rendering --------------------------------------------------
+
+    /// Cached [`split_hunk_lines`] for a hunk; the cache is dropped whenever
+    /// the hunk's lines can change.
+    fn split_rows_for(
+        &self,
+        file_index: usize,
+        hunk_index: usize,
+        hunk: &Hunk,
+    ) -> Rc<Vec<SplitRow>> {
+        self.split_rows
+            .borrow_mut()
+            .entry((file_index, hunk_index))
+            .or_insert_with(|| {
+                #[cfg(test)]
+                self.split_computations
+                    .set(self.split_computations.get() + 1);
+                Rc::new(split_hunk_lines(&hunk.lines))
+            })
+            .clone()
+    }
+
+    fn render_split(&self, cx: &Context<Self>) -> gpui::AnyElement {
+        let (files, remaining) = self.rendered_files();
+        let paths: Vec<_> = files.iter().map(|&i| self.diffs[i].path.clone()).collect();
+        let file_elements: Vec<_> = files
+            .into_iter()
+            .map(|i| self.render_file_diff_split(i, &self.diffs[i], cx))
+            .collect();
+
+        v_flex()
+            .on_children_prepainted(self.record_files(paths, cx))
+            .id("diff-files-split")
+            .flex_1()
+            .min_h_0()
+            .w_full()
+            .overflow_y_scroll()
+            .track_scroll(&self.scroll_handle)
+            .gap_2()
+            .children(file_elements)
+            .when(remaining > 0, |el| {
+                el.child(self.render_show_remaining(remaining, cx))
+            })
+            // Last, so child indices match file rows for `scroll_to_item`.
+            .vertical_scrollbar(&self.scroll_handle)
+            .into_any_element()
+    }
+
+    fn render_file_diff_split(
+        &self,
+        file_index: usize,
+        file: &FileDiff,
+        cx: &Context<Self>,
+    ) -> gpui::AnyElement {
+        if self.is_file_collapsed(file_index) {
+            return self.render_collapsed_file(file_index, file, cx);
+        }
+        if is_pure_rename(file) {
+            return self.render_pure_rename(file_index, file, cx);
+        }
+        if let Some(lfs) = &file.lfs {
+            return self.render_lfs_object(file_index, file, lfs, cx);
+        }
+        match self.full_files.get(&file_index) {
+            Some(FullFileView::BothVersions(rows)) => {
+                return self.render_full_file(file_index, file, rows, cx);
+            }
+            Some(FullFileView::Whole(whole)) => {
+                return self.render_whole_file(file_index, file, whole, cx);
+            }
+            None => {}
+        }
+        if let Some(images) = self.images.get(&file_index) {
+            return self.render_image_diff(file_index, file, images, cx);
+        }
+        let mut hunk_elements = Vec::new();
+        for (i, hunk) in file.hunks.iter().enumerate() {
+            hunk_elements.extend(self.render_expand_row(file_index, file, i, cx));
+            hunk_elements.push(
+                self.render_hunk_split(file_index, i, hunk, &file.path, cx)
+                    .into_any_element(),
+            );
+        }
+        hunk_elements.extend(self.render_expand_row(file_index, file, file.hunks.len(), cx));
+
+        v_flex()
+            .w_full()
+            .gap_1()
+            .child(self.render_file_header(file_index, file, cx))
+            .children(self.render_line_ending_warning(file_index, file, cx))
+            .children(hunk_elements)
+            .into_any_element()
+    }
+
+    fn render_hunk_split(
+        &self,
+        file_index: usize,
+        hunk_index: usize,
+        hunk: &Hunk,
+        file_path: &str,
+        cx: &Context<Self>,
+    ) -> impl IntoElement {
+        let diff_theme = DiffTheme::from_cx(cx, &self.diff_palette);
+        self.prime_syntax(&hunk.lines, file_path, &diff_theme, cx);
+        let rows = self.split_rows_for(file_index, hunk_index, hunk);
+        let row_elements = self.render_split_rows(file_index, &rows, file_path, &diff_theme, cx);
+        let lines = std::iter::once(None)
+            .chain(split_items(&rows).iter().map(SplitItem::new_line_no))
+            .collect();
+
+        v_flex()
+            .on_children_prepainted(self.record_rows(file_path, lines))
+            .w_full()
+            .debug_selector(|| format!("hunk-split-{file_index}-{hunk_index}"))
+            .child(self.render_hunk_header(file_index, hunk_index, hunk, cx))
+            .children(row_elements)
+    }
+
+    fn render_split_rows(
+        &self,
+        file_index: usize,
+        rows: &[SplitRow],
+        file_path: &str,
+        diff_theme: &DiffTheme,
+        cx: &Context<Self>,
+    ) -> Vec<gpui::AnyElement> {
+        let gutter_width = if !self.show_line_numbers {
+            0.0
+        } else {
+            match self.split_gutter {
+                SplitGutter::SideOnly => GUTTER_COLUMN_WIDTH,
+                SplitGutter::Both => GUTTER_COLUMN_WIDTH * 2.0,
+            }
+        };
+        split_items(rows)
+            .into_iter()
+            .map(|item| match item {
+                SplitItem::Row(row) => {
+                    let element = self
+                        .render_split_row(file_index, row, file_path, diff_theme, cx)
+                        .into_any_element();
+                    let new_line_no = row.right.as_ref().and_then(|line| line.new_line_no);
+                    self.with_note_editor(element, file_path, new_line_no, cx)
+                }
+                SplitItem::NoNewline { left, right } => {
+                    let half = |show: bool| {
+                        gpui::div()
+                            .flex_1()
+                            .pl(px(gutter_width + 8.0))
+                            .when(show, |el| el.child(self.render_no_newline_marker(cx)))
+                    };
+                    gpui::div()
+                        .w_full()
+                        .flex()
+                        .child(half(left))
+                        .child(gpui::div().w(px(1.0)).flex_shrink_0().bg(cx.theme().border))
+                        .child(half(right))
+                        .into_any_element()
+                }
+            })
+            .collect()
+    }
+
+    fn render_split_row(
+        &self,
+        file_index: usize,
+        row: &SplitRow,
+        file_path: &str,
+        diff_theme: &DiffTheme,
+        cx: &Context<Self>,
+    ) -> impl IntoElement {
+        let theme = cx.theme();
+
+        gpui::div()
+            .w_full()
+            .flex()
+            .text_xs()
+            .line_height(gpui::rems(1.0))
+            .font_family(theme.font_family.clone())
+            .child(self.render_split_half(
+                file_index,
+                row.left.as_deref(),
+                SplitSide::Left,
+                file_path,
+                diff_theme,
+                cx,
+            ))
+            .child(gpui::div().w(px(1.0)).flex_shrink_0().bg(theme.border))
+            .child(self.render_split_half(
+                file_index,
+                row.right.as_deref(),
+                SplitSide::Right,
+                file_path,
+                diff_theme,
+                cx,
+            ))
+    }
+
+    fn render_split_half(
+        &self,
+        file_index: usize,
+        line: Option<&DiffLine>,
+        side: SplitSide,
+        file_path: &str,
+        diff_theme: &DiffTheme,
+        cx: &Context<Self>,
+    ) -> gpui::Div {
+        let theme = cx.theme();
+
+        let Some(line) = line else {
+            return gpui::div()
+                .flex_1()
+                .flex()
+                .overflow_x_hidden()
+                .bg(theme.background);
+        };
+
+        let bg_color = match line.origin {
+            LineOrigin::Addition => diff_theme.add_bg,
+            LineOrigin::Deletion => diff_theme.del_bg,
+            LineOrigin::Context => diff_theme.ctx_bg,
+        };
+
+        let columns = if self.show_line_numbers {
+            split_gutter_columns(line, side, self.split_gutter)
+        } else {
+            Vec::new()
+        };
+        let gutter = columns.into_iter().map(|line_no| {
+            gpui::div()
+                .w(px(GUTTER_COLUMN_WIDTH))
+                .flex_shrink_0()
+                .debug_selector(|| "line-number-gutter".into())
+                .text_color(diff_theme.line_number_fg)
+                .text_right()
+                .px_1()
+                .child(line_no)
+        });
+
+        gpui::div()
+            .flex_1()
+            .flex()
+            .overflow_x_hidden()
+            .bg(bg_color)
+            .on_mouse_down(
+                MouseButton::Right,
+                self.line_menu_listener(file_index, line, cx),
+            )
+            .when(self.change_marker_style == ChangeMarkerStyle::Bar, |el| {
+                el.child(self.render_change_marker(" ", change_bar_color(line, diff_theme)))
+            })
+            .children(gutter)
+            .when(matches!(side, SplitSide::Right), |el| {
+                el.children(self.render_note_gutter(file_index, file_path, line.new_line_no, cx))
+            })
+            .child(
+                gpui::div()
+                    .px_1()
+                    .overflow_x_hidden()
+                    .whitespace_nowrap()
+                    .child(self.render_content(line, file_path, diff_theme, cx)),
+            )
+    }
+}
+
+impl Focusable for DiffView {
+    fn focus_handle(&self, _cx: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for DiffView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        self.sync_theme_generation(cx);
+        if let Some(ref error) = self.error_message {
+            return v_flex()
+                .size_full()
+                .items_center()
+                .justify_center()
+                .child(
+                    gpui::div()
+                        .text_sm()
+                        .text_color(gpui::red())
+                        .child(error.clone()),
+                )
+                .into_any_element();
+        }
+
+        if self.loading {
+            return v_flex()
+                .size_full()
+                .items_center()
+                .justify_center()
+                .child(
+                    gpui::div()
+                        .text_sm()
+                        .text_color(cx.theme().muted_foreground)
+                        .debug_selector(|| "diff-loading".into())
+                        .child("Loading diff\u{2026}"),
+                )
+                .into_any_element();
+        }
+
+        if self.diffs.is_empty() {
+            return v_flex()
+                .size_full()
+                .items_center()
+                .justify_center()
+                .child(
+                    gpui::div()
+                        .text_sm()
+                        .text_color(cx.theme().muted_foreground)
+                        .debug_selector(|| "diff-placeholder".into())
+                        .child(self.placeholder.clone()),
+                )
+                .into_any_element();
+        }
+
+        let weak = cx.entity().downgrade();
+
+        let content = match self.mode {
+            DiffViewMode::Unified => self.render_unified(cx),
+            DiffViewMode::Split => self.render_split(cx),
+        };
+
+        // Measure available width during layout and update mode for the next
+        // frame. The content uses the previous frame's mode (defaults to Unified);
+        // this is a standard one-frame-behind pattern in GPUI that converges
+        // after a single extra render cycle. Oscillation cannot occur because
+        // the diff view's width is determined by its parent resizable panel,
+        // not by the diff content.
+        v_flex()
+            .size_full()
+            .track_focus(&self.focus_handle)
+            .key_context(self.key_context())
+            .on_mouse_down(
+                MouseButton::Left,
+                cx.listener(|view, _event, window, _cx| {
+                    window.focus(&view.focus_handle);
+                }),
+            )
+            .on_action(cx.listener(|view, _: &StageHunk, window, cx| {
+                view.apply_to_focused_hunk(HunkAction::Stage, window, cx);
+            }))
+            .on_action(cx.listener(|view, _: &UnstageHunk, window, cx| {
+                view.apply_to_focused_hunk(HunkAction::Unstage, window, cx);
+            }))
+            .on_action(cx.listener(|view, _: &DiscardHunk, window, cx| {
+                view.apply_to_focused_hunk(HunkAction::Discard, window, cx);
+            }))
+            .on_action(cx.listener(|view, _: &NextHunk, _window, cx| {
+                view.focus_next_hunk(cx);
+            }))
+            .on_action(cx.listener(|view, _: &PreviousHunk, _window, cx| {
+                view.focus_previous_hunk(cx);
+            }))
+            .on_action(cx.listener(|view, _: &ToggleFilePalette, window, cx| {
+                view.toggle_file_palette(window, cx);
+            }))
+            .on_action(cx.listener(|view, _: &CycleViewMode, window, cx| {
+                view.cycle_view_mode(window, cx);
+            }))
+            .on_action(cx.listener(|view, _: &CycleWhitespaceMode, window, cx| {
+                view.cycle_whitespace_mode(window, cx);
+            }))
+            .child(
+                canvas(
+                    move |bounds: Bounds<Pixels>, _window: &mut Window, app: &mut App| {
+                        let new_mode = if bounds.size.width >= px(SPLIT_VIEW_MIN_WIDTH) {
+                            DiffViewMode::Split
+                        } else {
+                            DiffViewMode::Unified
+                        };
+                        let _ = weak.update(app, |view: &mut DiffView, cx| {
+                            if view.preferred_mode.is_none() && view.mode != new_mode {
+                                view.mode = new_mode;
+                                cx.notify();
+                            }
+                        });
+                    },
+                    |_, _, _, _| {},
+                )
+                .w_full()
+                .h(px(0.)),
+            )
+            .when(self.commit_info.is_some(), |el| {
+                el.child(self.render_commit_header(cx))
+            })
+            .when(self.whitespace_mode != WhitespaceMode::Show, |el| {
+                el.child(
+                    gpui::div()
+                        .px_3()
+                        .py_0p5()
+                        .text_xs()
+                        .text_color(cx.theme().muted_foreground)
+                        .debug_selector(|| "whitespace-mode".into())
+                        .child(self.whitespace_mode.label()),
+                )
+            })
+            .children(self.render_working_changes_bar(cx))
+            .children(self.render_file_palette(cx))
+            .child(content)
+            .children(self.render_line_menu(cx))
+            .into_any_element()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dd_git::{FileStatus, Hunk, SignatureStatus};
+
+    fn mock_diffs() -> Vec<FileDiff> {
+        vec![FileDiff {
+            path: "src/main.rs".into(),
+            old_path: None,
+            status: FileStatus::Modified,
+            lfs: None,
+            old_mode: None,
+            new_mode: None,
+            similarity: None,
+            hunks: vec![Hunk {
+                header: "@@ -1,3 +1,4 @@".into(),
+                old_start: 1,
+                old_count: 3,
+                new_start: 1,
+                new_count: 4,
+                lines: vec![
+                    DiffLine {
+                        origin: LineOrigin::Context,
+                        content: "fn main() {".into(),
+                        old_line_no: Some(1),
+                        new_line_no: Some(1),
+                        change_spans: vec![],
+                        no_newline_at_eof: false,
+                    },
+                    DiffLine {
+                        origin: LineOrigin::Deletion,
+                        content: "    println!(\"hello\");".into(),
+                        old_line_no: Some(2),
+                        new_line_no: None,
+                        change_spans: vec![],
+                        no_newline_at_eof: false,
+                    },
+                    DiffLine {
+                        origin: LineOrigin::Addition,
+                        content: "    println!(\"hello world\");".into(),
+                        old_line_no: None,
+                        new_line_no: Some(2),
+                        change_spans: vec![],
+                        no_newline_at_eof: false,
+                    },
+                    DiffLine {
+                        origin: LineOrigin::Addition,
+                        content: "    println!(\"goodbye\");".into(),
+                        old_line_no: None,
+                        new_line_no: Some(3),
+                        change_spans: vec![],
+                        no_newline_at_eof: false,
+                    },
+                    DiffLine {
+                        origin: LineOrigin::Context,
+                        content: "}".into(),
+                        old_line_no: Some(3),
+                        new_line_no: Some(4),
+                        change_spans: vec![],
+                        no_newline_at_eof: false,
+                    },
+                ],
+            }],
+        }]
+    }
+
+    #[test]
+    fn test_diff_data_model() {
+        let diffs = mock_diffs();
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].path, "src/main.rs");
+        assert_eq!(diffs[0].hunks.len(), 1);
+        assert_eq!(diffs[0].hunks[0].lines.len(), 5);
+    }
+
+    #[gpui::test]
+    fn test_set_error_clears_diffs(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| crate::test_helpers::init_test_theme(cx));
+        let window = cx.add_window(|_window, cx| DiffView::new_empty(cx));
+
+        window
+            .update(cx, |view, _window, cx| {
+                view.set_diffs(mock_diffs(), cx);
+            })
+            .unwrap();
+
+        window
+            .update(cx, |view, _window, cx| {
+                view.set_error("something broke".into(), cx);
+            })
+            .unwrap();
+
+        window
+            .read_with(cx, |view, _cx| {
+                assert_eq!(view.error_message(), Some("something broke"));
+                assert!(view.diffs().is_empty());
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    fn test_set_diffs_clears_error(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| crate::test_helpers::init_test_theme(cx));
+        let window = cx.add_window(|_window, cx| DiffView::new_empty(cx));
+
+        window
+            .update(cx, |view, _window, cx| {
+                view.set_error("an error".into(), cx);
+            })
+            .unwrap();
+
+        window
+            .update(cx, |view, _window, cx| {
+                view.set_diffs(mock_diffs(), cx);
+            })
+            .unwrap();
+
+        window
+            .read_with(cx, |view, _cx| {
+                assert!(view.error_message().is_none());
+                assert_eq!(view.diffs().len(), 1);
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    fn test_set_diffs_populates_data(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| crate::test_helpers::init_test_theme(cx));
+
+        let window = cx.add_window(|_window, cx| DiffView::new_empty(cx));
+
+        // Initially empty
+        window
+            .read_with(cx, |view, _cx| {
+                assert!(view.diffs().is_empty());
+            })
+            .unwrap();
+
+        // Set diffs
+        window
+            .update(cx, |view, _window, cx| {
+                view.set_diffs(mock_diffs(), cx);
+            })
+            .unwrap();
+
+        // Verify populated
+        window
+            .read_with(cx, |view, _cx| {
+                assert_eq!(view.diffs().len(), 1);
+                assert_eq!(view.diffs()[0].path, "src/main.rs");
+                assert_eq!(view.diffs()[0].hunks[0].lines.len(), 5);
+            })
+            .unwrap();
+    }
+
+    fn mock_commit() -> CommitInfo {
+        CommitInfo {
+            oid: "abc123def456".into(),
+            short_oid: "abc123d".into(),
+            tree_oid: "tree111aaa".into(),
+            author_name: "Alice".into(),
+            author_email: "alice@example.com".into(),
+            date: 1700000000,
+            committer_name: "Alice".into(),
+            committer_email: "alice@example.com".into(),
+            committer_date: 1700000000,
+            subject: "feat: add login".into(),
+            body: "Detailed description of the change.".into(),
+            parent_oids: vec!["def456abc789".into()],
+        }
+    }
+
+    #[test]
+    fn test_compute_stats() {
+        let diffs = mock_diffs();
+        let (files, additions, deletions) = compute_stats(&diffs);
+        assert_eq!(files, 1);
+        assert_eq!(additions, 2);
+        assert_eq!(deletions, 1);
+    }
+
+    #[test]
+    fn test_compute_stats_empty() {
+        let (files, additions, deletions) = compute_stats(&[]);
+        assert_eq!(files, 0);
+        assert_eq!(additions, 0);
+        assert_eq!(deletions, 0);
+    }
+
+    #[test]
+    fn test_format_commit_date() {
+        let formatted = format_commit_date(1700000000);
+        // Should produce a human-readable date string
+        assert!(!formatted.is_empty());
+        assert_ne!(formatted, "unknown");
+    }
+
+    #[test]
+    fn test_format_commit_date_invalid() {
+        let formatted = format_commit_date(i64::MIN);
+        assert_eq!(formatted, "unknown");
+    }
+
+    #[test]
+    fn test_format_person() {
+        assert_eq!(
+            format_person("Alice", "alice@example.com"),
+            "Alice <alice@example.com>"
+        );
+        assert_eq!(format_person("Alice", ""), "Alice");
+        assert_eq!(
+            format_person("", "alice@example.com"),
+            "<alice@example.com>"
+        );
+        assert_eq!(format_person("", ""), "unknown");
+    }
+
+    #[test]
+    fn test_signature_status_from_git_char() {
+        assert_eq!(SignatureStatus::from_git_char('G'), SignatureStatus::Good);
+        assert_eq!(SignatureStatus::from_git_char('B'), SignatureStatus::Bad);
+        assert_eq!(
+            SignatureStatus::from_git_char('U'),
+            SignatureStatus::Unknown
+        );
+        assert_eq!(SignatureStatus::from_git_char('N'), SignatureStatus::None);
+        assert_eq!(SignatureStatus::from_git_char('?'), SignatureStatus::None);
+    }
+
+    #[test]
+    fn test_signature_status_label() {
+        assert_eq!(SignatureStatus::Good.label(), "Valid");
+        assert_eq!(SignatureStatus::Bad.label(), "Invalid");
+        assert_eq!(SignatureStatus::Unknown.label(), "Unknown");
+        assert_eq!(SignatureStatus::None.label(), "None");
+    }
+
+    #[gpui::test]
+    fn test_set_commit_data(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| crate::test_helpers::init_test_theme(cx));
+        let window = cx.add_window(|_window, cx| DiffView::new_empty(cx));
+
+        window
+            .update(cx, |view, _window, cx| {
+                view.set_commit_data(mock_commit(), SignatureStatus::None, mock_diffs(), cx);
+            })
+            .unwrap();
+
+        window
+            .read_with(cx, |view, _cx| {
+                assert!(view.commit_info().is_some());
+                assert_eq!(view.commit_info().unwrap().subject, "feat: add login");
+                assert_eq!(view.diffs().len(), 1);
+                assert!(view.error_message().is_none());
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    fn test_open_line_on_remote_uses_permalink(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| crate::test_helpers::init_test_theme(cx));
+        let opened = std::rc::Rc::new(std::cell::RefCell::new(Vec::<String>::new()));
+        let opened_clone = opened.clone();
+        let window = cx.add_window(|_window, cx| DiffView::new_empty(cx));
+
+        window
+            .update(cx, |view, _window, cx| {
+                view.set_remote_url(Some("git@github.com:owner/repo.git".into()));
+                view.set_url_opener(move |url, _cx| opened_clone.borrow_mut().push(url.into()));
+                view.set_commit_data(mock_commit(), SignatureStatus::None, mock_diffs(), cx);
+
+                let lines = view.diffs()[0].hunks[0].lines.clone();
+                let deletion = lines
+                    .iter()
+                    .find(|l| l.origin == LineOrigin::Deletion)
+                    .unwrap();
+                let addition = lines
+                    .iter()
+                    .find(|l| l.origin == LineOrigin::Addition)
+                    .unwrap();
+                view.open_line_on_remote(0, addition, cx);
+                view.open_line_on_remote(0, deletion, cx);
+            })
+            .unwrap();
+
+        assert_eq!(
+            *opened.borrow(),
+            vec![
+                "https://github.com/owner/repo/blob/abc123def456/src/main.rs#L2".to_string(),
+                "https://github.com/owner/repo/blob/def456abc789/src/main.rs#L2".to_string(),
+            ]
+        );
+    }
+
+    #[gpui::test]
+    fn test_open_line_on_remote_without_remote_is_noop(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| crate::test_helpers::init_test_theme(cx));
+        let opened = std::rc::Rc::new(std::cell::Cell::new(false));
+        let opened_clone = opened.clone();
+        let window = cx.add_window(|_window, cx| DiffView::new_empty(cx));
+
+        window
+            .update(cx, |view, _window, cx| {
+                view.set_url_opener(move |_url, _cx| opened_clone.set(true));
+                view.set_commit_data(mock_commit(), SignatureStatus::None, mock_diffs(), cx);
+                let line = view.diffs()[0].hunks[0].lines[0].clone();
+                view.open_line_on_remote(0, &line, cx);
+            })
+            .unwrap();
+
+        assert!(!opened.get());
+    }
+
+    #[gpui::test]
+    fn test_line_menu_opens_line_on_remote(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| crate::test_helpers::init_test_theme(cx));
+        let opened = std::rc::Rc::new(std::cell::RefCell::new(Vec::<String>::new()));
+        let opened_clone = opened.clone();
+        let (view, cx) = cx.add_window_view(|_window, cx| DiffView::new_empty(cx));
+
+        view.update(cx, |view, cx| {
+            view.set_remote_url(Some("git@github.com:owner/repo.git".into()));
+            view.set_url_opener(move |url, _cx| opened_clone.borrow_mut().push(url.into()));
+            view.set_commit_data(mock_commit(), SignatureStatus::None, mock_diffs(), cx);
+            let addition = view.diffs()[0].hunks[0]
+                .lines
+                .iter()
+                .find(|l| l.origin == LineOrigin::Addition)
+                .cloned()
+                .unwrap();
+            view.open_line_menu(0, &addition, gpui::point(px(40.0), px(40.0)), cx);
+        });
+        cx.run_until_parked();
+        assert!(opened.borrow().is_empty(), "opening the menu opens nothing");
+
+        let item = cx
+            .debug_bounds("line-menu-open-on-remote")
+            .expect("menu offers Open on Remote");
+        cx.simulate_click(item.center(), gpui::Modifiers::none());
+
+        assert_eq!(
+            *opened.borrow(),
+            vec!["https://github.com/owner/repo/blob/abc123def456/src/main.rs#L2".to_string()]
+        );
+        assert!(!view.read_with(cx, |view, _| view.is_line_menu_open()));
+    }
+
+    #[gpui::test]
+    fn test_line_menu_needs_a_remote(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| crate::test_helpers::init_test_theme(cx));
+        let (view, cx) = cx.add_window_view(|_window, cx| DiffView::new_empty(cx));
+
+        view.update(cx, |view, cx| {
+            view.set_commit_data(mock_commit(), SignatureStatus::None, mock_diffs(), cx);
+            let line = view.diffs()[0].hunks[0].lines[0].clone();
+            view.open_line_menu(0, &line, gpui::point(px(40.0), px(40.0)), cx);
+            assert!(!view.is_line_menu_open());
+        });
+    }
+
+    #[gpui::test]
+    fn test_expand_context_uses_file_loader(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| crate::test_helpers::init_test_theme(cx));
+        let window = cx.add_window(|_window, cx| DiffView::new_empty(cx));
+
+        let mut diffs = mock_diffs();
+        // Pretend the hunk starts further down so there is context above it.
+        let hunk = &mut diffs[0].hunks[0];
+        hunk.old_start = 3;
+        hunk.new_start = 3;
+        for line in &mut hunk.lines {
+            line.old_line_no = line.old_line_no.map(|n| n + 2);
+            line.new_line_no = line.new_line_no.map(|n| n + 2);
+        }
+
+        let loads = Rc::new(RefCell::new(0));
+        let loads_clone = loads.clone();
+        window
+            .update(cx, |view, _window, cx| {
+                view.set_file_loader(move |_oid, _path| {
+                    *loads_clone.borrow_mut() += 1;
+                    Ok("// header\nuse std::io;\nfn main() {\n".to_string())
+                });
+                view.set_commit_data(mock_commit(), SignatureStatus::None, diffs, cx);
+                view.expand_context(0, 0, ExpandDirection::Up, cx);
+                view.expand_context(0, 0, ExpandDirection::Down, cx);
+            })
+            .unwrap();
+        assert_eq!(*loads.borrow(), 1, "the file is read once");
+
+        window
+            .read_with(cx, |view, _cx| {
+                let hunk = &view.diffs()[0].hunks[0];
+                assert_eq!(hunk.new_start, 1);
+                assert_eq!(hunk.lines[0].content, "// header");
+                assert_eq!(hunk.lines[1].content, "use std::io;");
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    fn test_context_expansion_keeps_the_top_row_in_place(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| crate::test_helpers::init_test_theme(cx));
+        let (view, cx) = cx.add_window_view(|_window, cx| DiffView::new_empty(cx));
+
+        // Two files showing lines 21 to 80, with 20 lines to reveal above.
+        let text: String = ["a.rs", "b.rs"]
+            .iter()
+            .map(|path| {
+                let lines: String = (21..=80).map(|n| format!(" line {n}\n")).collect();
+                format!(
+                    "diff --git a/{path} b/{path}\n--- a/{path}\n+++ b/{path}\n\
+                     @@ -21,60 +21,60 @@\n{lines}"
+                )
+            })
+            .collect();
+        let diffs = dd_git::diff::parse_unified_diff(&text).unwrap();
+        let content: String = (1..=100).map(|n| format!("line {n}\n")).collect();
+        view.update(cx, |view, cx| {
+            view.set_file_loader(move |_oid, _path| Ok(content.clone()));
+            view.set_commit_data(mock_commit(), SignatureStatus::None, diffs, cx);
+        });
+        cx.run_until_parked();
+
+        let row_top = |view: &DiffView| {
+            view.laid_out_rows
+                .borrow()
+                .iter()
+                .find(|row| row.path == "b.rs" && row.new_line_no == Some(30))
+                .map(|row| row.top)
+                .expect("row laid out")
+        };
+        // The viewport top sits 5px into b.rs's line 30.
+        let before = view.read_with(cx, |view, _cx| row_top(view));
+        view.update(cx, |view, cx| {
+            view.scroll_handle
+                .set_offset(point(px(0.0), px(-(before + 5.0))));
+            cx.notify();
+        });
+        cx.run_until_parked();
+
+        view.update(cx, |view, cx| {
+            view.expand_context(0, 0, ExpandDirection::Up, cx)
+        });
+        cx.run_until_parked();
+
+        view.read_with(cx, |view, _cx| {
+            let after = row_top(view);
+            assert!(after > before, "rows revealed above push b.rs down");
+            let offset = -f32::from(view.scroll_handle.offset().y);
+            assert!((offset - (after + 5.0)).abs() < 0.01, "{offset} vs {after}");
+        });
+    }
+
+    #[gpui::test]
+    fn test_copy_file_path(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| crate::test_helpers::init_test_theme(cx));
+        let window = cx.add_window(|_window, cx| DiffView::new_empty(cx));
+
+        window
+            .update(cx, |view, _window, cx| {
+                view.set_repo_root(PathBuf::from("/projects/demo"));
+                view.set_diffs(mock_diffs(), cx);
+                view.copy_file_path(0, false, cx);
+            })
+            .unwrap();
+        let copied = cx.read_from_clipboard().and_then(|item| item.text());
+        assert_eq!(copied.as_deref(), Some("src/main.rs"));
+
+        window
+            .update(cx, |view, _window, cx| {
+                view.copy_file_path(0, true, cx);
+            })
+            .unwrap();
+        let copied = cx.read_from_clipboard().and_then(|item| item.text());
+        let expected = PathBuf::from("/projects/demo")
+            .join("src/main.rs")
+            .display()
+            .to_string();
+        assert_eq!(copied, Some(expected));
+    }
+
+    #[gpui::test]
+    fn test_set_error_clears_commit_info(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| crate::test_helpers::init_test_theme(cx));
+        let window = cx.add_window(|_window, cx| DiffView::new_empty(cx));
+
+        window
+            .update(cx, |view, _window, cx| {
+                view.set_commit_data(mock_commit(), SignatureStatus::Good, mock_diffs(), cx);
+            })
+            .unwrap();
+
+        window
+            .update(cx, |view, _window, cx| {
+                view.set_error("oops".into(), cx);
+            })
+            .unwrap();
+
+        window
+            .read_with(cx, |view, _cx| {
+                assert!(view.commit_info().is_none());
+                assert!(view.diffs().is_empty());
+                assert_eq!(view.error_message(), Some("oops"));
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    fn test_generated_files_collapse_until_revealed(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| crate::test_helpers::init_test_theme(cx));
+        let window = cx.add_window(|_window, cx| DiffView::new_empty(cx));
+
+        window
+            .update(cx, |view, _window, cx| {
+                view.set_generated_filter(
+                    vec!["src/**".to_string()],
+                    GeneratedFileMode::Collapse,
+                    cx,
+                );
+                view.set_diffs(mock_diffs(), cx);
+            })
+            .unwrap();
+
+        window
+            .update(cx, |view, _window, cx| {
+                assert!(view.is_file_generated(0));
+                assert!(view.is_file_collapsed(0));
+                view.toggle_generated(0, cx);
+                assert!(!view.is_file_collapsed(0));
+                view.set_diffs(mock_diffs(), cx);
+                assert!(view.is_file_collapsed(0));
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    fn test_stage_shortcut_targets_focused_hunk(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| crate::test_helpers::init_test_theme(cx));
+        let window = cx.add_window(|_window, cx| DiffView::new_empty(cx));
+        let calls = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+
+        let mut diffs = mock_diffs();
+        let mut second = diffs[0].clone();
+        second.path = "src/lib.rs".into();
+        second.hunks.push(second.hunks[0].clone());
+        diffs.push(second);
+
+        let calls_clone = calls.clone();
+        window
+            .update(cx, |view, _window, cx| {
+                view.on_hunk_action(move |action, file, hunk_index, _window, _cx| {
+                    calls_clone
+                        .borrow_mut()
+                        .push((action, file.path.clone(), hunk_index));
+                });
+                view.set_diffs(diffs.clone(), cx);
+                view.focus_hunk(0, 0, cx);
+            })
+            .unwrap();
+
+        window
+            .update(cx, |view, window, cx| {
+                // Commit diffs are read-only.
+                view.apply_to_focused_hunk(HunkAction::Stage, window, cx);
+                view.set_working_changes(WorkingChanges::Unstaged, diffs, cx);
+            })
+            .unwrap();
+
+        window
+            .update(cx, |view, window, cx| {
+                // No cursor yet: nothing to act on.
+                view.apply_to_focused_hunk(HunkAction::Stage, window, cx);
+                view.focus_next_hunk(cx);
+                view.focus_next_hunk(cx);
+                view.focus_next_hunk(cx);
+                assert_eq!(view.focused_hunk(), Some((1, 1)));
+                view.focus_previous_hunk(cx);
+                view.apply_to_focused_hunk(HunkAction::Stage, window, cx);
+                view.focus_next_hunk(cx);
+                view.apply_to_focused_hunk(HunkAction::Discard, window, cx);
+                // Nothing to unstage in unstaged changes.
+                view.apply_to_focused_hunk(HunkAction::Unstage, window, cx);
+            })
+            .unwrap();
+
+        assert_eq!(
+            *calls.borrow(),
+            vec![
+                (HunkAction::Stage, "src/lib.rs".to_string(), 0),
+                (HunkAction::Discard, "src/lib.rs".to_string(), 1),
+            ]
+        );
+    }
+
+    #[gpui::test]
+    fn test_working_changes_files_can_be_picked_for_stashing(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| crate::test_helpers::init_test_theme(cx));
+        let (view, cx) = cx.add_window_view(|_window, cx| DiffView::new_empty(cx));
+        let stashed = Rc::new(RefCell::new(Vec::new()));
+        let stashed_clone = stashed.clone();
+
+        view.update(cx, |view, cx| {
+            view.on_stash_files(move |paths, _window, _cx| {
+                stashed_clone.borrow_mut().extend(paths);
+            });
+            // A commit's files can't be picked.
+            view.set_commit_data(mock_commit(), SignatureStatus::None, mock_diffs(), cx);
+            view.toggle_file_selected(0, cx);
+            assert!(view.selected_paths().is_empty());
+            view.set_working_changes(WorkingChanges::Unstaged, mock_diffs(), cx);
+        });
+        cx.run_until_parked();
+        assert!(cx.debug_bounds("select-file-0").is_some());
+        assert!(cx.debug_bounds("stash-selected").is_none());
+
+        view.update(cx, |view, cx| view.toggle_file_selected(0, cx));
+        cx.run_until_parked();
+        assert!(cx.debug_bounds("stash-selected").is_some());
+        view.update_in(cx, |view, window, cx| view.stash_selected_files(window, cx));
+        assert_eq!(*stashed.borrow(), vec!["src/main.rs".to_string()]);
+    }
+
+    #[gpui::test]
+    fn test_staged_changes_note_what_an_amend_folds_in(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| crate::test_helpers::init_test_theme(cx));
+        let (view, cx) = cx.add_window_view(|_window, cx| DiffView::new_empty(cx));
+
+        view.update(cx, |view, cx| {
+            view.set_working_changes(WorkingChanges::Unstaged, mock_diffs(), cx)
+        });
+        cx.run_until_parked();
+        assert!(cx.debug_bounds("amend-note").is_none());
+
+        view.update(cx, |view, cx| {
+            view.set_working_changes(WorkingChanges::Staged, mock_diffs(), cx)
+        });
+        cx.run_until_parked();
+        assert!(cx.debug_bounds("amend-note").is_some());
+    }
+
+    #[gpui::test]
+    fn test_reloading_working_changes_keeps_hunk_cursor(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| crate::test_helpers::init_test_theme(cx));
+        let window = cx.add_window(|_window, cx| DiffView::new_empty(cx));
+
+        let mut diffs = mock_diffs();
+        let hunk = diffs[0].hunks[0].clone();
+        diffs[0].hunks.extend([hunk.clone(), hunk]);
+        window
+            .update(cx, |view, _window, cx| {
+                view.set_working_changes(WorkingChanges::Unstaged, diffs.clone(), cx);
+                view.focus_hunk(0, 1, cx);
+
+                // The focused hunk was staged; the next one takes its place.
+                let mut remaining = diffs.clone();
+                remaining[0].hunks.remove(1);
+                view.set_working_changes(WorkingChanges::Unstaged, remaining.clone(), cx);
+                assert_eq!(view.focused_hunk(), Some((0, 1)));
+
+                remaining[0].hunks.truncate(1);
+                view.set_working_changes(WorkingChanges::Unstaged, remaining, cx);
+                assert_eq!(view.focused_hunk(), Some((0, 0)));
+
+                view.set_working_changes(WorkingChanges::Staged, diffs, cx);
+                assert_eq!(view.focused_hunk(), None);
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    fn test_no_newline_marker_rows(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| crate::test_helpers::init_test_theme(cx));
+        let window = cx.add_window(|_window, cx| DiffView::new_empty(cx));
+
+        let mut diffs = mock_diffs();
+        // Mark the deleted line as the old side's unterminated last line.
+        diffs[0].hunks[0].lines[1].no_newline_at_eof = true;
+
+        window
+            .update(cx, |view, _window, cx| view.set_diffs(diffs, cx))
+            .unwrap();
+        cx.run_until_parked();
+
+        window
+            .read_with(cx, |view, _cx| {
+                let lines = &view.diffs()[0].hunks[0].lines;
+                let rows = unified_rows(lines, false);
+                assert_eq!(rows.len(), lines.len() + 1);
+                assert!(matches!(rows[2], UnifiedRow::NoNewline));
+
+                let split = split_hunk_lines(lines);
+                let items = split_items(&split);
+                assert_eq!(items.len(), split.len() + 1);
+                assert!(items.iter().any(|item| matches!(
+                    item,
+                    SplitItem::NoNewline {
+                        left: true,
+                        right: false
+                    }
+                )));
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_no_marker_rows_without_flag() {
+        let diffs = mock_diffs();
+        let lines = &diffs[0].hunks[0].lines;
+        assert_eq!(unified_rows(lines, false).len(), lines.len());
+        let split = split_hunk_lines(lines);
+        assert_eq!(split_items(&split).len(), split.len());
+    }
+
+    #[test]
+    fn test_word_diff_rows_pair_deletions_with_additions() {
+        let diffs = mock_diffs();
+        let lines = &diffs[0].hunks[0].lines;
+        let rows = unified_rows(lines, true);
+        // One deletion pairs with the first of two additions.
+        assert_eq!(rows.len(), lines.len() - 1);
+        assert!(matches!(
+            rows[1],
+            UnifiedRow::WordDiff(del, add)
+                if del.old_line_no == Some(2) && add.new_line_no == Some(2)
+        ));
+        assert!(matches!(rows[2], UnifiedRow::Line(l) if l.new_line_no == Some(3)));
+    }
+
+    #[gpui::test]
+    fn test_file_palette_scrolls_to_fuzzy_match(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| crate::test_helpers::init_test_theme(cx));
+        let (view, cx) =
+            crate::test_helpers::add_root_window_view(cx, |_window, cx| DiffView::new_empty(cx));
+        cx.simulate_resize(gpui::size(px(800.0), px(600.0)));
+
+        // Tall enough that only the first file fits in the window.
+        let mut tall = mock_diffs().remove(0);
+        let line = tall.hunks[0].lines[0].clone();
+        tall.hunks[0].lines = (1..=60)
+            .map(|n| DiffLine {
+                old_line_no: Some(n),
+                new_line_no: Some(n),
+                ..line.clone()
+            })
+            .collect();
+        let diffs = [
+            "src/main.rs",
+            "src/lib.rs",
+            "crates/dd_ui/src/diff_view.rs",
+            "README.md",
+        ]
+        .into_iter()
+        .map(|path| FileDiff {
+            path: path.into(),
+            ..tall.clone()
+        })
+        .collect();
+        view.update(cx, |view, cx| view.set_diffs(diffs, cx));
+        view.update_in(cx, |view, window, cx| view.toggle_file_palette(window, cx));
+        cx.run_until_parked();
+        assert!(cx.debug_bounds("file-palette").is_some());
+        assert!(cx.debug_bounds("file-palette-3").is_some());
+        let header = cx.debug_bounds("file-header-2").expect("header laid out");
+        assert!(header.top() > px(600.0), "target starts below the window");
+
+        view.update_in(cx, |view, window, cx| {
+            let palette = view.file_palette.as_ref().expect("palette open");
+            palette.input.update(cx, |input, cx| {
+                input.set_value("dfvw", window, cx);
+                cx.emit(InputEvent::Change);
+            });
+        });
+        cx.run_until_parked();
+        view.read_with(cx, |view, _cx| {
+            assert_eq!(view.file_palette_matches(), &[2])
+        });
+
+        let row = cx.debug_bounds("file-palette-0").expect("match listed");
+        cx.simulate_click(row.center(), gpui::Modifiers::none());
+        cx.run_until_parked();
+        view.read_with(cx, |view, _cx| {
+            assert_eq!(view.scrolled_file(), Some(2));
+            assert!(!view.is_file_palette_open());
+            assert!(view.scroll_handle.offset().y < px(0.0), "list scrolled");
+        });
+        let header = cx.debug_bounds("file-header-2").expect("header laid out");
+        assert!(
+            header.top() >= px(0.0) && header.bottom() <= px(600.0),
+            "target header in view: {header:?}"
+        );
+    }
+
+    #[gpui::test]
+    fn test_large_diff_renders_capped_files_until_expanded(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| crate::test_helpers::init_test_theme(cx));
+        let (view, cx) = cx.add_window_view(|_window, cx| DiffView::new_empty(cx));
+        cx.simulate_resize(gpui::size(px(800.0), px(600.0)));
+
+        let diffs = (0..200)
+            .map(|i| FileDiff {
+                path: format!("src/file_{i}.rs"),
+                ..mock_diffs().remove(0)
+            })
+            .collect();
+        view.update(cx, |view, cx| {
+            view.set_max_files(50, cx);
+            view.set_diffs(diffs, cx);
+        });
+        cx.run_until_parked();
+        view.read_with(cx, |view, _cx| {
+            let (files, remaining) = view.rendered_files();
+            assert_eq!(files, (0..50).collect::<Vec<_>>());
+            assert_eq!(remaining, 150);
+        });
+        assert!(cx.debug_bounds("show-remaining-files").is_some());
+
+        view.update(cx, |view, cx| view.show_remaining_files(cx));
+        cx.run_until_parked();
+        view.read_with(cx, |view, _cx| {
+            let (files, remaining) = view.rendered_files();
+            assert_eq!(files.len(), 200);
+            assert_eq!(remaining, 0);
+        });
+        assert!(cx.debug_bounds("show-remaining-files").is_none());
+
+        // A new diff starts capped again, and jumping past the cap expands it.
+        let diffs = (0..200)
+            .map(|i| FileDiff {
+                path: format!("src/other_{i}.rs"),
+                ..mock_diffs().remove(0)
+            })
+            .collect();
+        view.update(cx, |view, cx| {
+            view.set_diffs(diffs, cx);
+            assert_eq!(view.rendered_files().1, 150);
+            view.scroll_to_file(120, cx);
+            assert_eq!(view.rendered_files().1, 0);
+        });
+    }
+
+    #[gpui::test]
+    fn test_word_diff_renders_merged_line(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| crate::test_helpers::init_test_theme(cx));
+        let (view, cx) = cx.add_window_view(|_window, cx| DiffView::new_empty(cx));
+        cx.simulate_resize(gpui::size(px(800.0), px(600.0)));
+
+        view.update(cx, |view, cx| view.set_diffs(mock_diffs(), cx));
+        cx.run_until_parked();
+        assert!(cx.debug_bounds("word-diff-0-2").is_none());
+
+        view.update(cx, |view, cx| view.set_word_diff(true, cx));
+        cx.run_until_parked();
+        assert!(cx.debug_bounds("word-diff-0-2").is_some());
+        assert!(cx.debug_bounds("word-diff-0-3").is_none());
+    }
+
+    #[gpui::test]
+    fn test_merge_header_parent_chips_navigate(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| crate::test_helpers::init_test_theme(cx));
+        let (view, cx) = cx.add_window_view(|_window, cx| DiffView::new_empty(cx));
+        let navigated = Rc::new(RefCell::new(None::<String>));
+        let navigated_clone = navigated.clone();
+
+        // Ordinary commits keep the plain parents row. Checked first, as
+        // debug bounds outlive the frame that drew them.
+        view.update(cx, |view, cx| {
+            view.set_commit_data(mock_commit(), SignatureStatus::None, mock_diffs(), cx);
+        });
+        cx.run_until_parked();
+        assert!(cx.debug_bounds("commit-parent-0").is_none());
+        assert!(cx.debug_bounds("merge-note").is_none());
+
+        let mut merge = mock_commit();
+        merge.parent_oids = vec!["1111111aaaa".into(), "2222222bbbb".into()];
+        view.update(cx, |view, cx| {
+            view.on_navigate_parent(move |oid, _window, _cx| {
+                *navigated_clone.borrow_mut() = Some(oid.to_string());
+            });
+            view.set_commit_data(merge, SignatureStatus::None, mock_diffs(), cx);
+            view.set_parent_subjects(vec!["main work".into(), "feature work".into()], cx);
+        });
+        cx.run_until_parked();
+
+        assert!(cx.debug_bounds("commit-parent-0").is_some());
+        let second = cx
+            .debug_bounds("commit-parent-1")
+            .expect("second parent chip");
+        assert!(cx.debug_bounds("commit-parent-2").is_none());
+        assert!(cx.debug_bounds("merge-note").is_some());
+
+        cx.simulate_click(second.center(), gpui::Modifiers::none());
+        assert_eq!(navigated.borrow().as_deref(), Some("2222222bbbb"));
+    }
+
+    #[gpui::test]
+    fn test_commit_header_shows_containing_branches(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| crate::test_helpers::init_test_theme(cx));
+        let (view, cx) = cx.add_window_view(|_window, cx| DiffView::new_empty(cx));
+
+        view.update(cx, |view, cx| {
+            view.set_commit_data(mock_commit(), SignatureStatus::None, mock_diffs(), cx);
+            view.set_containing_branches(vec!["feature/widgets".into(), "main".into()], cx);
+        });
+        cx.run_until_parked();
+        assert!(cx.debug_bounds("commit-branch-0").is_some());
+        assert!(cx.debug_bounds("commit-branch-1").is_some());
+
+        // A new commit starts without chips until its branches are loaded.
+        view.update(cx, |view, cx| {
+            view.set_commit_data(mock_commit(), SignatureStatus::None, mock_diffs(), cx);
+        });
+        cx.run_until_parked();
+        assert!(cx.debug_bounds("commit-branch-0").is_none());
+    }
+
+    #[gpui::test]
+    fn test_line_number_gutters_can_be_hidden(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| crate::test_helpers::init_test_theme(cx));
+
+        // A window per layout: debug bounds outlive the frame that drew
+        // them, so the hidden gutter is checked before it is ever shown.
+        for width in [800.0, 1400.0] {
+            let (view, cx) = cx.add_window_view(|_window, cx| DiffView::new_empty(cx));
+            cx.simulate_resize(gpui::size(px(width), px(600.0)));
+            view.update(cx, |view, cx| {
+                view.set_show_line_numbers(false, cx);
+                view.set_diffs(mock_diffs(), cx);
+            });
+            cx.run_until_parked();
+            assert!(cx.debug_bounds("line-number-gutter").is_none());
+
+            view.update(cx, |view, cx| view.set_show_line_numbers(true, cx));
+            cx.run_until_parked();
+            assert!(cx.debug_bounds("line-number-gutter").is_some());
+        }
+    }
+
+    #[gpui::test]
+    fn test_bar_change_markers_replace_prefix(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| crate::test_helpers::init_test_theme(cx));
+        let (view, cx) = cx.add_window_view(|_window, cx| DiffView::new_empty(cx));
+        cx.simulate_resize(gpui::size(px(800.0), px(600.0)));
+
+        view.update(cx, |view, cx| view.set_diffs(mock_diffs(), cx));
+        cx.run_until_parked();
+        assert!(cx.debug_bounds("change-prefix").is_some());
+        assert!(cx.debug_bounds("change-bar").is_none());
+
+        view.update(cx, |view, cx| {
+            view.set_change_marker_style(ChangeMarkerStyle::Bar, cx);
+        });
+        cx.run_until_parked();
+        assert!(cx.debug_bounds("change-prefix").is_none());
+        let bar = cx.debug_bounds("change-bar").expect("change bar rendered");
+        assert_eq!(bar.size.width, px(CHANGE_BAR_WIDTH));
+    }
+
+    #[gpui::test]
+    fn test_pure_rename_renders_explanation(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| crate::test_helpers::init_test_theme(cx));
+        let (view, cx) = cx.add_window_view(|_window, cx| DiffView::new_empty(cx));
+
+        let mut diffs = mock_diffs();
+        diffs.push(FileDiff {
+            path: "src/renamed.rs".into(),
+            old_path: Some("src/original.rs".into()),
+            status: FileStatus::Renamed,
+            hunks: vec![],
+            lfs: None,
+            old_mode: None,
+            new_mode: None,
+            similarity: Some(100),
+        });
+        view.update(cx, |view, cx| view.set_diffs(diffs, cx));
+        cx.run_until_parked();
+
+        assert!(cx.debug_bounds("pure-rename-1").is_some());
+        assert!(cx.debug_bounds("pure-rename-0").is_none());
+    }
+
+    #[gpui::test]
+    fn test_full_file_toggle_shows_both_versions(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| crate::test_helpers::init_test_theme(cx));
+        let (view, cx) = cx.add_window_view(|_window, cx| DiffView::new_empty(cx));
+
+        view.update(cx, |view, cx| {
+            view.set_full_file_loader(|_oid, file| {
+                Ok(dd_git::full_file_diff(file, "a\nb\nc\n", "a\nB\nc\nd\n"))
+            });
+            view.set_commit_data(mock_commit(), SignatureStatus::None, mock_diffs(), cx);
+        });
+        cx.run_until_parked();
+        assert!(cx.debug_bounds("full-file-0").is_none());
+
+        view.update(cx, |view, cx| view.toggle_full_file(0, cx));
+        cx.run_until_parked();
+        assert!(cx.debug_bounds("full-file-0").is_some());
+        view.update(cx, |view, _cx| {
+            let Some(FullFileView::BothVersions(rows)) = view.full_files.get(&0) else {
+                panic!("both versions shown");
+            };
+            assert_eq!(rows.iter().filter(|r| r.left.is_some()).count(), 3);
+            assert_eq!(rows.iter().filter(|r| r.right.is_some()).count(), 4);
+        });
+
+        view.update(cx, |view, cx| view.toggle_full_file(0, cx));
+        cx.run_until_parked();
+        assert!(cx.debug_bounds("full-file-0").is_none());
+    }
+
+    #[gpui::test]
+    fn test_whole_file_toggle_replaces_other_full_view(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| crate::test_helpers::init_test_theme(cx));
+        let (view, cx) = cx.add_window_view(|_window, cx| DiffView::new_empty(cx));
+
+        view.update(cx, |view, cx| {
+            view.set_full_file_loader(|_oid, file| {
+                Ok(dd_git::full_file_diff(file, "a\nb\nc\n", "a\nB\nc\nd\n"))
+            });
+            view.set_whole_file_loader(|_oid, file| {
+                Ok(dd_git::overlay_file_changes(file, "a\nB\nc\nd\n"))
+            });
+            view.set_commit_data(mock_commit(), SignatureStatus::None, mock_diffs(), cx);
+            view.toggle_full_file(0, cx);
+        });
+        cx.run_until_parked();
+        view.update(cx, |view, cx| view.toggle_whole_file(0, cx));
+        cx.run_until_parked();
+        view.update(cx, |view, _cx| {
+            assert!(!view.is_full_file(0));
+            let Some(FullFileView::Whole(whole)) = view.full_files.get(&0) else {
+                panic!("whole file shown");
+            };
+            assert_eq!(whole.hunks[0].lines.len(), 4);
+        });
+        cx.run_until_parked();
+        assert!(cx.debug_bounds("whole-file-0").is_some());
+        assert!(cx.debug_bounds("full-file-0").is_none());
+
+        view.update(cx, |view, cx| view.toggle_whole_file(0, cx));
+        cx.run_until_parked();
+        assert!(cx.debug_bounds("whole-file-0").is_none());
+
+        // And the other way round.
+        view.update(cx, |view, cx| {
+            view.toggle_whole_file(0, cx);
+            view.toggle_full_file(0, cx);
+        });
+        cx.run_until_parked();
+        view.update(cx, |view, _cx| {
+            assert!(view.is_full_file(0));
+            assert!(!view.is_whole_file(0));
+        });
+    }
+
+    #[gpui::test]
+    fn test_pending_full_file_load_is_dropped_with_the_diff(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| crate::test_helpers::init_test_theme(cx));
+        let (view, cx) = cx.add_window_view(|_window, cx| DiffView::new_empty(cx));
+
+        view.update(cx, |view, cx| {
+            view.set_full_file_loader(|_oid, file| Ok(dd_git::full_file_diff(file, "a\n", "b\n")));
+            view.set_commit_data(mock_commit(), SignatureStatus::None, mock_diffs(), cx);
+            view.toggle_full_file(0, cx);
+            // Still reading: nothing is shown yet.
+            assert!(!view.is_full_file(0));
+            view.set_diffs(mock_diffs(), cx);
+        });
+        cx.run_until_parked();
+        view.read_with(cx, |view, _cx| assert!(!view.is_full_file(0)));
+    }
+
+    #[gpui::test]
+    fn test_changed_image_renders_both_versions(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| crate::test_helpers::init_test_theme(cx));
+        let (view, cx) = cx.add_window_view(|_window, cx| DiffView::new_empty(cx));
+
+        let mut diffs = mock_diffs();
+        diffs.push(FileDiff {
+            path: "assets/logo.png".into(),
+            old_path: None,
+            status: FileStatus::Modified,
+            hunks: vec![],
+            lfs: None,
+            old_mode: None,
+            new_mode: None,
+            similarity: None,
+        });
+        view.update(cx, |view, cx| {
+            let png = b"\x89PNG\r\n\x1a\n".to_vec();
+            let versions = ImageVersions {
+                old: Some(png.clone()),
+                new: Some(png),
+            };
+            view.set_commit_data(mock_commit(), SignatureStatus::None, diffs, cx);
+            view.set_images(HashMap::from([(1, versions)]), cx);
+        });
+        cx.run_until_parked();
+
+        assert!(cx.debug_bounds("image-diff-1").is_some());
+        assert!(cx.debug_bounds("image-diff-0").is_none());
+        view.update(cx, |view, cx| {
+            assert!(view.images[&1].old.is_some() && view.images[&1].new.is_some());
+            view.toggle_image_compare(cx);
+            view.adjust_image_opacity(1.0, cx);
+            assert_eq!(view.image_compare, ImageCompareMode::Overlay);
+            assert_eq!(view.image_overlay_opacity, 1.0);
+        });
+        cx.run_until_parked();
+        assert!(cx.debug_bounds("image-diff-1").is_some());
+    }
+
+    #[gpui::test]
+    fn test_changed_image_rename_renders_both_versions(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| crate::test_helpers::init_test_theme(cx));
+        let (view, cx) = cx.add_window_view(|_window, cx| DiffView::new_empty(cx));
+
+        let mut diffs = mock_diffs();
+        diffs.push(FileDiff {
+            path: "assets/logo.png".into(),
+            old_path: Some("logo.png".into()),
+            status: FileStatus::Renamed,
+            hunks: vec![],
+            lfs: None,
+            old_mode: None,
+            new_mode: None,
+            similarity: Some(72),
+        });
+        view.update(cx, |view, cx| {
+            let png = b"\x89PNG\r\n\x1a\n".to_vec();
+            let versions = ImageVersions {
+                old: Some(png.clone()),
+                new: Some(png),
+            };
+            view.set_commit_data(mock_commit(), SignatureStatus::None, diffs, cx);
+            view.set_images(HashMap::from([(1, versions)]), cx);
+        });
+        cx.run_until_parked();
+
+        assert!(cx.debug_bounds("image-diff-1").is_some());
+        assert!(cx.debug_bounds("pure-rename-1").is_none());
+    }
+
+    #[gpui::test]
+    fn test_lfs_pointer_renders_object_size(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| crate::test_helpers::init_test_theme(cx));
+        let (view, cx) = cx.add_window_view(|_window, cx| DiffView::new_empty(cx));
+
+        let mut diffs = mock_diffs();
+        diffs[0].lfs = Some(LfsPointer {
+            oid: "sha256:4d7a".into(),
+            size: 2_411_724,
+        });
+        view.update(cx, |view, cx| view.set_diffs(diffs, cx));
+        cx.run_until_parked();
+
+        assert!(cx.debug_bounds("lfs-object-0").is_some());
+        assert_eq!(format_size(2_411_724), "2.3 MB");
+        assert_eq!(format_size(512), "512 B");
+    }
+
+    #[gpui::test]
+    fn test_changed_binary_rename_is_not_pure(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| crate::test_helpers::init_test_theme(cx));
+        let (view, cx) = cx.add_window_view(|_window, cx| DiffView::new_empty(cx));
 
-    fn mock_diffs() -> Vec<FileDiff> {
-        vec![FileDiff {
-            path: "src/main.rs".into(),
-            old_path: None,
-            status: FileStatus::Modified,
-            hunks: vec![Hunk {
-                header: "@@ -1,3 +1,4 @@".into(),
-                old_start: 1,
-                old_count: 3,
-                new_start: 1,
-                new_count: 4,
-                lines: vec![
-                    DiffLine {
-                        origin: LineOrigin::Context,
-                        content: "fn main() {".into(),
-                        old_line_no: Some(1),
-                        new_line_no: Some(1),
-                        change_spans: vec![],
-                    },
-                    DiffLine {
-                        origin: LineOrigin::Deletion,
-                        content: "    println!(\"hello\");".into(),
-                        old_line_no: Some(2),
-                        new_line_no: None,
-                        change_spans: vec![],
-                    },
-                    DiffLine {
-                        origin: LineOrigin::Addition,
-                        content: "    println!(\"hello world\");".into(),
-                        old_line_no: None,
-                        new_line_no: Some(2),
-                        change_spans: vec![],
-                    },
-                    DiffLine {
-                        origin: LineOrigin::Addition,
-                        content: "    println!(\"goodbye\");".into(),
-                        old_line_no: None,
-                        new_line_no: Some(3),
-                        change_spans: vec![],
-                    },
-                    DiffLine {
-                        origin: LineOrigin::Context,
-                        content: "}".into(),
-                        old_line_no: Some(3),
-                        new_line_no: Some(4),
-                        change_spans: vec![],
-                    },
-                ],
-            }],
-        }]
+        let mut diffs = mock_diffs();
+        diffs.push(FileDiff {
+            path: "assets/logo.png".into(),
+            old_path: Some("logo.png".into()),
+            status: FileStatus::Renamed,
+            hunks: vec![],
+            lfs: None,
+            old_mode: None,
+            new_mode: None,
+            similarity: Some(72),
+        });
+        view.update(cx, |view, cx| view.set_diffs(diffs, cx));
+        cx.run_until_parked();
+
+        assert!(cx.debug_bounds("pure-rename-1").is_none());
     }
 
-    #[test]
-    fn test_diff_data_model() {
-        let diffs = mock_diffs();
-        assert_eq!(diffs.len(), 1);
-        assert_eq!(diffs[0].path, "src/main.rs");
-        assert_eq!(diffs[0].hunks.len(), 1);
-        assert_eq!(diffs[0].hunks[0].lines.len(), 5);
+    #[gpui::test]
+    fn test_line_annotation_renders_on_its_line(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| crate::test_helpers::init_test_theme(cx));
+        let (view, cx) = cx.add_window_view(|_window, cx| DiffView::new_empty(cx));
+
+        let annotation = LineAnnotation {
+            glyph: "\u{25CF}".into(),
+            color: gpui::red(),
+            tooltip: "Not covered by tests".into(),
+        };
+        view.update(cx, |view, cx| {
+            view.set_diffs(mock_diffs(), cx);
+            view.set_line_annotations("src/main.rs", HashMap::from([(3, annotation)]), cx);
+        });
+        cx.run_until_parked();
+
+        assert!(cx.debug_bounds("line-annotation-0-3").is_some());
+        assert!(cx.debug_bounds("line-annotation-0-2").is_none());
+
+        view.update(cx, |view, cx| {
+            view.set_line_annotations("src/main.rs", HashMap::new(), cx)
+        });
+        cx.run_until_parked();
+        assert!(cx.debug_bounds("line-annotation-0-3").is_none());
     }
 
     #[gpui::test]
-    fn test_set_error_clears_diffs(cx: &mut gpui::TestAppContext) {
+    fn test_review_note_renders_in_gutter(cx: &mut gpui::TestAppContext) {
         cx.update(|cx| crate::test_helpers::init_test_theme(cx));
-        let window = cx.add_window(|_window, _cx| DiffView::new_empty());
+        let (view, cx) = cx.add_window_view(|_window, cx| DiffView::new_empty(cx));
+        let changes = Rc::new(std::cell::Cell::new(0));
 
-        window
-            .update(cx, |view, _window, cx| {
-                view.set_diffs(mock_diffs(), cx);
-            })
-            .unwrap();
+        let counter = changes.clone();
+        view.update_in(cx, |view, window, cx| {
+            view.on_review_notes_change(move |_notes, _window, _cx| {
+                counter.set(counter.get() + 1);
+            });
+            view.set_commit_data(mock_commit(), SignatureStatus::None, mock_diffs(), cx);
+            view.set_review_note(
+                "abc123def456",
+                "src/main.rs",
+                2,
+                Some("why not a loop?".into()),
+                window,
+                cx,
+            );
+        });
+        cx.run_until_parked();
 
-        window
-            .update(cx, |view, _window, cx| {
-                view.set_error("something broke".into(), cx);
-            })
-            .unwrap();
+        assert_eq!(changes.get(), 1);
+        assert!(cx.debug_bounds("review-note-0-2").is_some());
+        assert!(cx.debug_bounds("review-note-0-3").is_none());
 
-        window
-            .read_with(cx, |view, _cx| {
-                assert_eq!(view.error_message(), Some("something broke"));
-                assert!(view.diffs().is_empty());
-            })
-            .unwrap();
+        // Blank text deletes the note.
+        view.update_in(cx, |view, window, cx| {
+            view.set_review_note(
+                "abc123def456",
+                "src/main.rs",
+                2,
+                Some("  ".into()),
+                window,
+                cx,
+            );
+            assert!(view.review_notes().is_empty());
+        });
+        assert_eq!(changes.get(), 2);
     }
 
     #[gpui::test]
-    fn test_set_diffs_clears_error(cx: &mut gpui::TestAppContext) {
+    fn test_review_note_renders_on_word_diff_line(cx: &mut gpui::TestAppContext) {
         cx.update(|cx| crate::test_helpers::init_test_theme(cx));
-        let window = cx.add_window(|_window, _cx| DiffView::new_empty());
+        let (view, cx) =
+            crate::test_helpers::add_root_window_view(cx, |_window, cx| DiffView::new_empty(cx));
+        cx.simulate_resize(gpui::size(px(800.0), px(600.0)));
 
-        window
-            .update(cx, |view, _window, cx| {
-                view.set_error("an error".into(), cx);
-            })
-            .unwrap();
+        view.update_in(cx, |view, window, cx| {
+            view.on_review_notes_change(|_notes, _window, _cx| {});
+            view.set_commit_data(mock_commit(), SignatureStatus::None, mock_diffs(), cx);
+            view.set_word_diff(true, cx);
+            view.set_review_note(
+                "abc123def456",
+                "src/main.rs",
+                2,
+                Some("nit".into()),
+                window,
+                cx,
+            );
+            view.open_note_editor("src/main.rs", 2, window, cx);
+        });
+        cx.run_until_parked();
 
-        window
-            .update(cx, |view, _window, cx| {
-                view.set_diffs(mock_diffs(), cx);
-            })
-            .unwrap();
+        assert!(cx.debug_bounds("word-diff-0-2").is_some());
+        assert!(cx.debug_bounds("review-note-0-2").is_some());
+        assert!(cx.debug_bounds("review-note-editor").is_some());
+    }
 
-        window
-            .read_with(cx, |view, _cx| {
-                assert!(view.error_message().is_none());
-                assert_eq!(view.diffs().len(), 1);
-            })
-            .unwrap();
+    #[gpui::test]
+    fn test_review_note_renders_in_split_layout(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| crate::test_helpers::init_test_theme(cx));
+        let (view, cx) =
+            crate::test_helpers::add_root_window_view(cx, |_window, cx| DiffView::new_empty(cx));
+        cx.simulate_resize(gpui::size(px(1600.0), px(600.0)));
+
+        view.update_in(cx, |view, window, cx| {
+            view.on_review_notes_change(|_notes, _window, _cx| {});
+            view.set_preferred_mode(Some(DiffViewMode::Split), cx);
+            view.set_commit_data(mock_commit(), SignatureStatus::None, mock_diffs(), cx);
+            view.set_review_note(
+                "abc123def456",
+                "src/main.rs",
+                2,
+                Some("nit".into()),
+                window,
+                cx,
+            );
+            view.open_note_editor("src/main.rs", 2, window, cx);
+        });
+        cx.run_until_parked();
+
+        assert!(cx.debug_bounds("hunk-split-0-0").is_some());
+        assert!(cx.debug_bounds("review-note-0-2").is_some());
+        assert!(cx.debug_bounds("review-note-0-3").is_none());
+        assert!(cx.debug_bounds("review-note-editor").is_some());
     }
 
     #[gpui::test]
-    fn test_set_diffs_populates_data(cx: &mut gpui::TestAppContext) {
+    fn test_set_error_closes_note_editor(cx: &mut gpui::TestAppContext) {
         cx.update(|cx| crate::test_helpers::init_test_theme(cx));
+        let (view, cx) = cx.add_window_view(|_window, cx| DiffView::new_empty(cx));
 
-        let window = cx.add_window(|_window, _cx| DiffView::new_empty());
+        view.update_in(cx, |view, window, cx| {
+            view.on_review_notes_change(|_notes, _window, _cx| {});
+            view.set_commit_data(mock_commit(), SignatureStatus::None, mock_diffs(), cx);
+            view.open_note_editor("src/main.rs", 2, window, cx);
+            view.set_error("boom".into(), cx);
+            assert!(view.note_editor.is_none());
+        });
+    }
 
-        // Initially empty
-        window
-            .read_with(cx, |view, _cx| {
-                assert!(view.diffs().is_empty());
-            })
-            .unwrap();
+    #[gpui::test]
+    fn test_single_key_shortcuts_stand_down_while_editing_a_note(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| {
+            crate::test_helpers::init_test_theme(cx);
+            cx.bind_keys([gpui::KeyBinding::new(
+                "v",
+                CycleViewMode,
+                Some("DiffView && !note_editing"),
+            )]);
+        });
+        let (view, cx) =
+            crate::test_helpers::add_root_window_view(cx, |_window, cx| DiffView::new_empty(cx));
+        cx.simulate_resize(gpui::size(px(800.0), px(600.0)));
+        view.update_in(cx, |view, window, cx| {
+            view.on_review_notes_change(|_notes, _window, _cx| {});
+            view.set_commit_data(mock_commit(), SignatureStatus::None, mock_diffs(), cx);
+            view.open_note_editor("src/main.rs", 2, window, cx);
+        });
+        cx.run_until_parked();
+
+        cx.simulate_keystrokes("v");
+        view.read_with(cx, |view, _| assert_eq!(view.mode(), DiffViewMode::Unified));
+
+        view.update(cx, |view, cx| view.close_note_editor(cx));
+        view.update_in(cx, |view, window, _cx| window.focus(&view.focus_handle));
+        cx.run_until_parked();
+        cx.simulate_keystrokes("v");
+        view.read_with(cx, |view, _| assert_eq!(view.mode(), DiffViewMode::Split));
+    }
+
+    #[gpui::test]
+    fn test_split_rows_are_cached_across_renders(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| crate::test_helpers::init_test_theme(cx));
+        let window = cx.add_window(|_window, cx| DiffView::new_empty(cx));
 
-        // Set diffs
         window
             .update(cx, |view, _window, cx| {
                 view.set_diffs(mock_diffs(), cx);
+                let _ = view.render_split(cx);
+                let _ = view.render_split(cx);
+                assert_eq!(view.split_computations.get(), 1);
+
+                let hunk = view.diffs()[0].hunks[0].clone();
+                let first = view.split_rows_for(0, 0, &hunk);
+                let second = view.split_rows_for(0, 0, &hunk);
+                assert!(Rc::ptr_eq(&first, &second));
+                assert_eq!(view.split_computations.get(), 1);
+
+                // New diffs invalidate the cache.
+                view.set_diffs(mock_diffs(), cx);
+                let _ = view.render_split(cx);
+                assert_eq!(view.split_computations.get(), 2);
             })
             .unwrap();
+    }
+
+    #[gpui::test]
+    fn test_theme_change_recomputes_syntax_highlights(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| crate::test_helpers::init_test_theme(cx));
+        let window = cx.add_window(|_window, cx| DiffView::new_empty(cx));
 
-        // Verify populated
         window
-            .read_with(cx, |view, _cx| {
-                assert_eq!(view.diffs().len(), 1);
-                assert_eq!(view.diffs()[0].path, "src/main.rs");
-                assert_eq!(view.diffs()[0].hunks[0].lines.len(), 5);
+            .update(cx, |view, window, cx| {
+                view.set_diffs(mock_diffs(), cx);
+                let _ = view.render(window, cx);
+                let computed = view.syntax.computations.get();
+                assert!(computed > 0);
+                assert_eq!(view.syntax.cached_lines(), computed);
+
+                let _ = view.render(window, cx);
+                assert_eq!(view.syntax.computations.get(), computed);
+
+                crate::theme::change_theme(gpui_component::ThemeMode::Light, cx);
+                let _ = view.render(window, cx);
+                assert_eq!(view.syntax.computations.get(), computed * 2);
+                assert_eq!(view.syntax.cached_lines(), computed);
             })
             .unwrap();
     }
 
-    fn mock_commit() -> CommitInfo {
-        CommitInfo {
-            oid: "abc123def456".into(),
-            short_oid: "abc123d".into(),
-            tree_oid: "tree111aaa".into(),
-            author_name: "Alice".into(),
-            author_email: "alice@example.com".into(),
-            date: 1700000000,
-            committer_name: "Alice".into(),
-            committer_email: "alice@example.com".into(),
-            committer_date: 1700000000,
-            subject: "feat: add login".into(),
-            body: "Detailed description of the change.".into(),
-            parent_oids: vec!["def456abc789".into()],
-        }
-    }
+    #[gpui::test]
+    fn test_preferred_mode_overrides_width(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| crate::test_helpers::init_test_theme(cx));
+        let (view, cx) = cx.add_window_view(|_window, cx| DiffView::new_empty(cx));
+        cx.simulate_resize(gpui::size(px(800.0), px(600.0)));
 
-    #[test]
-    fn test_compute_stats() {
-        let diffs = mock_diffs();
-        let (files, additions, deletions) = compute_stats(&diffs);
-        assert_eq!(files, 1);
-        assert_eq!(additions, 2);
-        assert_eq!(deletions, 1);
-    }
+        view.update(cx, |view, cx| {
+            view.set_diffs(mock_diffs(), cx);
+            view.set_preferred_mode(Some(DiffViewMode::Split), cx);
+        });
+        cx.run_until_parked();
+        view.read_with(cx, |view, _cx| assert_eq!(view.mode(), DiffViewMode::Split));
 
-    #[test]
-    fn test_compute_stats_empty() {
-        let (files, additions, deletions) = compute_stats(&[]);
-        assert_eq!(files, 0);
-        assert_eq!(additions, 0);
-        assert_eq!(deletions, 0);
+        view.update(cx, |view, cx| view.set_preferred_mode(None, cx));
+        cx.run_until_parked();
+        view.read_with(cx, |view, _cx| {
+            assert_eq!(view.mode(), DiffViewMode::Unified)
+        });
+        assert_eq!(DiffViewMode::parse("split"), Some(DiffViewMode::Split));
+        assert_eq!(
+            DiffViewMode::parse(DiffViewMode::Unified.as_str()),
+            Some(DiffViewMode::Unified)
+        );
+        assert_eq!(DiffViewMode::parse("sideways"), None);
     }
 
-    #[test]
-    fn test_format_commit_date() {
-        let formatted = format_commit_date(1700000000);
-        // Should produce a human-readable date string
-        assert!(!formatted.is_empty());
-        assert_ne!(formatted, "unknown");
-    }
+    #[gpui::test]
+    fn test_cycle_keys_advance_and_wrap(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| {
+            crate::test_helpers::init_test_theme(cx);
+            cx.bind_keys([
+                gpui::KeyBinding::new("v", CycleViewMode, Some("DiffView")),
+                gpui::KeyBinding::new("w", CycleWhitespaceMode, Some("DiffView")),
+            ]);
+        });
+        let requested = Rc::new(RefCell::new(Vec::new()));
+        let requested_clone = requested.clone();
+        let (view, cx) = cx.add_window_view(|_window, cx| DiffView::new_empty(cx));
+        cx.simulate_resize(gpui::size(px(800.0), px(600.0)));
+        view.update_in(cx, |view, window, cx| {
+            view.set_diffs(mock_diffs(), cx);
+            view.on_whitespace_mode_change(move |mode, _window, _cx| {
+                requested_clone.borrow_mut().push(mode);
+            });
+            window.focus(&view.focus_handle);
+        });
+        cx.run_until_parked();
+        // Checked before any mode change: debug bounds outlive their frame.
+        assert!(cx.debug_bounds("whitespace-mode").is_none());
 
-    #[test]
-    fn test_format_commit_date_invalid() {
-        let formatted = format_commit_date(i64::MIN);
-        assert_eq!(formatted, "unknown");
-    }
+        let layout = |view: &DiffView| (view.mode(), view.word_diff());
+        view.read_with(cx, |view, _| {
+            assert_eq!(layout(view), (DiffViewMode::Unified, false))
+        });
+        cx.simulate_keystrokes("v");
+        view.read_with(cx, |view, _| {
+            assert_eq!(layout(view), (DiffViewMode::Split, false))
+        });
+        cx.simulate_keystrokes("v");
+        view.read_with(cx, |view, _| {
+            assert_eq!(layout(view), (DiffViewMode::Unified, true))
+        });
+        cx.simulate_keystrokes("v");
+        view.read_with(cx, |view, _| {
+            assert_eq!(layout(view), (DiffViewMode::Unified, false));
+            assert_eq!(view.preferred_mode(), Some(DiffViewMode::Unified));
+        });
 
-    #[test]
-    fn test_signature_status_from_git_char() {
-        assert_eq!(SignatureStatus::from_git_char('G'), SignatureStatus::Good);
-        assert_eq!(SignatureStatus::from_git_char('B'), SignatureStatus::Bad);
+        cx.simulate_keystrokes("w w w");
         assert_eq!(
-            SignatureStatus::from_git_char('U'),
-            SignatureStatus::Unknown
+            *requested.borrow(),
+            vec![
+                WhitespaceMode::IgnoreEol,
+                WhitespaceMode::IgnoreAll,
+                WhitespaceMode::Show
+            ]
         );
-        assert_eq!(SignatureStatus::from_git_char('N'), SignatureStatus::None);
-        assert_eq!(SignatureStatus::from_git_char('?'), SignatureStatus::None);
+        view.read_with(cx, |view, _| {
+            assert_eq!(view.whitespace_mode(), WhitespaceMode::Show)
+        });
+        cx.simulate_keystrokes("w");
+        cx.run_until_parked();
+        assert!(cx.debug_bounds("whitespace-mode").is_some());
     }
 
-    #[test]
-    fn test_signature_status_label() {
-        assert_eq!(SignatureStatus::Good.label(), "Valid");
-        assert_eq!(SignatureStatus::Bad.label(), "Invalid");
-        assert_eq!(SignatureStatus::Unknown.label(), "Unknown");
-        assert_eq!(SignatureStatus::None.label(), "None");
+    #[gpui::test]
+    fn test_hunk_split_override_in_unified_mode(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| crate::test_helpers::init_test_theme(cx));
+        let (view, cx) = cx.add_window_view(|_window, cx| DiffView::new_empty(cx));
+        // Narrow enough to stay in unified mode.
+        cx.simulate_resize(gpui::size(px(800.0), px(600.0)));
+
+        let mut diffs = mock_diffs();
+        let mut hunk = diffs[0].hunks[0].clone();
+        for line in &mut hunk.lines {
+            line.old_line_no = line.old_line_no.map(|n| n + 20);
+            line.new_line_no = line.new_line_no.map(|n| n + 20);
+        }
+        diffs[0].hunks.push(hunk);
+        view.update(cx, |view, cx| {
+            view.set_diffs(diffs, cx);
+            view.toggle_hunk_split(0, 1, cx);
+        });
+        cx.run_until_parked();
+
+        view.read_with(cx, |view, _cx| {
+            assert_eq!(view.mode, DiffViewMode::Unified);
+            assert!(view.is_hunk_split(0, 1));
+            assert!(!view.is_hunk_split(0, 0));
+        });
+        assert!(cx.debug_bounds("hunk-unified-0-0").is_some());
+        assert!(cx.debug_bounds("hunk-split-0-0").is_none());
+        assert!(cx.debug_bounds("hunk-split-0-1").is_some());
+        assert!(cx.debug_bounds("hunk-unified-0-1").is_none());
+
+        view.update(cx, |view, cx| view.toggle_hunk_split(0, 1, cx));
+        cx.run_until_parked();
+        assert!(cx.debug_bounds("hunk-unified-0-1").is_some());
     }
 
     #[gpui::test]
-    fn test_set_commit_data(cx: &mut gpui::TestAppContext) {
+    fn test_hunk_split_override_follows_its_hunk_when_hunks_merge(cx: &mut gpui::TestAppContext) {
         cx.update(|cx| crate::test_helpers::init_test_theme(cx));
-        let window = cx.add_window(|_window, _cx| DiffView::new_empty());
+        let (view, cx) = cx.add_window_view(|_window, cx| DiffView::new_empty(cx));
 
-        window
-            .update(cx, |view, _window, cx| {
-                view.set_commit_data(mock_commit(), SignatureStatus::None, mock_diffs(), cx);
+        // Hunks changing lines 11, 21 and 61 of a 100-line file.
+        let hunks: String = [10, 20, 60]
+            .iter()
+            .map(|&n| {
+                format!(
+                    "@@ -{n},3 +{n},3 @@\n line {n}\n-old {}\n+line {}\n line {}\n",
+                    n + 1,
+                    n + 1,
+                    n + 2
+                )
             })
-            .unwrap();
+            .collect();
+        let text = format!("diff --git a/a.rs b/a.rs\n--- a/a.rs\n+++ b/a.rs\n{hunks}");
+        let diffs = dd_git::diff::parse_unified_diff(&text).unwrap();
+        let content: String = (1..=100).map(|n| format!("line {n}\n")).collect();
+        view.update(cx, |view, cx| {
+            view.set_file_loader(move |_oid, _path| Ok(content.clone()));
+            view.set_commit_data(mock_commit(), SignatureStatus::None, diffs, cx);
+            view.toggle_hunk_split(0, 2, cx);
 
-        window
-            .read_with(cx, |view, _cx| {
-                assert!(view.commit_info().is_some());
-                assert_eq!(view.commit_info().unwrap().subject, "feat: add login");
-                assert_eq!(view.diffs().len(), 1);
-                assert!(view.error_message().is_none());
-            })
-            .unwrap();
+            // Revealing the lines between the first two hunks merges them.
+            view.expand_context(0, 1, ExpandDirection::Up, cx);
+            assert_eq!(view.diffs[0].hunks.len(), 2);
+            assert!(!view.is_hunk_split(0, 0));
+            assert!(view.is_hunk_split(0, 1));
+
+            // Context revealed above a hunk keeps its override.
+            view.expand_context(0, 1, ExpandDirection::Up, cx);
+            assert!(view.diffs[0].hunks[1].new_start < 60);
+            assert!(view.is_hunk_split(0, 1));
+        });
     }
 
     #[gpui::test]
-    fn test_set_error_clears_commit_info(cx: &mut gpui::TestAppContext) {
+    fn test_mixed_line_endings_warning_and_normalize(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| crate::test_helpers::init_test_theme(cx));
+        let (view, cx) = cx.add_window_view(|_window, cx| DiffView::new_empty(cx));
+
+        let content = Rc::new(RefCell::new(b"a\r\nb\n".to_vec()));
+        view.update(cx, |view, cx| {
+            let read = content.clone();
+            let write = content.clone();
+            view.set_line_ending_normalizer(move |_path, ending| {
+                let normalized = dd_git::normalize_line_endings(&write.borrow(), ending);
+                *write.borrow_mut() = normalized;
+                Ok(())
+            });
+            let stats = LineEndingStats::count(&read.borrow());
+            // A commit's files are not the ones normalizing would rewrite.
+            view.set_commit_data(mock_commit(), SignatureStatus::None, mock_diffs(), cx);
+            view.set_mixed_line_endings(HashMap::from([("src/main.rs".into(), stats)]), cx);
+            assert!(view.mixed_line_endings("src/main.rs").is_none());
+
+            view.set_working_changes(WorkingChanges::Unstaged, mock_diffs(), cx);
+            view.set_mixed_line_endings(HashMap::from([("src/main.rs".into(), stats)]), cx);
+        });
+        cx.run_until_parked();
+
+        view.read_with(cx, |view, _cx| {
+            let stats = view.mixed_line_endings("src/main.rs").unwrap();
+            assert_eq!((stats.lf, stats.crlf), (1, 1));
+        });
+        assert!(cx.debug_bounds("line-ending-warning-0").is_some());
+
+        view.update(cx, |view, cx| {
+            view.normalize_line_endings(0, LineEnding::Lf, cx);
+        });
+        cx.run_until_parked();
+
+        assert_eq!(*content.borrow(), b"a\nb\n");
+        view.read_with(cx, |view, _cx| {
+            assert!(view.mixed_line_endings("src/main.rs").is_none());
+        });
+        assert!(cx.debug_bounds("line-ending-warning-0").is_none());
+    }
+
+    #[gpui::test]
+    fn test_split_gutter_mode_controls_number_columns(cx: &mut gpui::TestAppContext) {
         cx.update(|cx| crate::test_helpers::init_test_theme(cx));
-        let window = cx.add_window(|_window, _cx| DiffView::new_empty());
+        let window = cx.add_window(|_window, cx| DiffView::new_empty(cx));
 
         window
-            .update(cx, |view, _window, cx| {
-                view.set_commit_data(mock_commit(), SignatureStatus::Good, mock_diffs(), cx);
-            })
+            .update(cx, |view, _window, cx| view.set_diffs(mock_diffs(), cx))
             .unwrap();
 
         window
             .update(cx, |view, _window, cx| {
-                view.set_error("oops".into(), cx);
-            })
-            .unwrap();
+                let context = view.diffs()[0].hunks[0].lines[0].clone();
+                let columns =
+                    |view: &DiffView, side| split_gutter_columns(&context, side, view.split_gutter);
 
-        window
-            .read_with(cx, |view, _cx| {
-                assert!(view.commit_info().is_none());
-                assert!(view.diffs().is_empty());
-                assert_eq!(view.error_message(), Some("oops"));
+                assert_eq!(columns(view, SplitSide::Left), vec!["   1"]);
+                assert_eq!(columns(view, SplitSide::Right), vec!["   1"]);
+
+                view.set_split_gutter(SplitGutter::Both, cx);
+                assert_eq!(columns(view, SplitSide::Left), vec!["   1", "   1"]);
+
+                let addition = view.diffs()[0].hunks[0].lines[2].clone();
+                assert_eq!(
+                    split_gutter_columns(&addition, SplitSide::Right, view.split_gutter),
+                    vec!["    ", "   2"]
+                );
             })
             .unwrap();
     }
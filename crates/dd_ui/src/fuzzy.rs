@@ -0,0 +1,77 @@
+/// Score `candidate` against `query`, or `None` unless every character of
+/// the query appears in order. Case is ignored. Runs of consecutive
+/// characters, matches at the start of a path segment or word, and matches
+/// in the file name score higher; shorter candidates win ties.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    let name_start = candidate.rfind('/').map_or(0, |i| i + 1);
+    let mut query_chars = query.chars().flat_map(char::to_lowercase).peekable();
+    let mut score = 0i64;
+    let mut previous: Option<(usize, char)> = None;
+    let mut last_match: Option<usize> = None;
+
+    for (i, c) in candidate.char_indices() {
+        let Some(&wanted) = query_chars.peek() else {
+            break;
+        };
+        if c.to_lowercase().eq(std::iter::once(wanted)) {
+            score += 1;
+            if last_match.is_some_and(|m| previous.is_some_and(|(p, _)| p == m)) {
+                score += 5;
+            }
+            if previous.is_none_or(|(_, p)| matches!(p, '/' | '_' | '-' | '.' | ' ')) {
+                score += 8;
+            }
+            if i >= name_start {
+                score += 2;
+            }
+            last_match = Some(i);
+            query_chars.next();
+        }
+        previous = Some((i, c));
+    }
+
+    if query_chars.peek().is_some() {
+        return None;
+    }
+    Some(score * 1000 - candidate.len() as i64)
+}
+
+/// Indices of the `candidates` matching `query`, best match first. Equal
+/// scores keep their original order; an empty query matches everything.
+pub fn fuzzy_rank<'a>(query: &str, candidates: impl IntoIterator<Item = &'a str>) -> Vec<usize> {
+    if query.is_empty() {
+        return candidates.into_iter().enumerate().map(|(i, _)| i).collect();
+    }
+    let mut scored: Vec<(i64, usize)> = candidates
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, c)| fuzzy_score(query, c).map(|s| (s, i)))
+        .collect();
+    scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    scored.into_iter().map(|(_, i)| i).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_requires_characters_in_order() {
+        assert!(fuzzy_score("dvw", "src/diff_view.rs").is_some());
+        assert!(fuzzy_score("wvd", "src/diff_view.rs").is_none());
+        assert!(fuzzy_score("DIFF", "src/diff_view.rs").is_some());
+        assert_eq!(fuzzy_score("", "anything"), Some(-8));
+    }
+
+    #[test]
+    fn test_ranks_file_name_and_word_starts_first() {
+        let paths = [
+            "docs/development/views.md",
+            "crates/dd_ui/src/diff_view.rs",
+            "crates/dd_ui/src/sidebar.rs",
+        ];
+        assert_eq!(fuzzy_rank("diffview", paths), vec![1]);
+        assert_eq!(fuzzy_rank("dv", paths), vec![1, 0]);
+        assert_eq!(fuzzy_rank("", paths), vec![0, 1, 2]);
+    }
+}
@@ -1,7 +1,11 @@
 pub mod app_view;
 pub mod commit_list;
+pub mod decorators;
+pub mod diff_cache;
 pub mod diff_view;
+pub mod fuzzy;
 pub mod repo_view;
+pub mod scroll_anchor;
 pub mod sidebar;
 pub mod syntax;
 pub mod tab_bar;
@@ -1,17 +1,41 @@
-use std::path::PathBuf;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use gpui::prelude::*;
-use gpui::{px, Context, Entity, Window};
+use gpui::{
+    px, Context, Entity, PathPromptOptions, PromptLevel, Subscription, Task, WeakEntity, Window,
+};
+use gpui_component::input::{Input, InputEvent, InputState};
 use gpui_component::resizable::{h_resizable, resizable_panel};
+use gpui_component::ActiveTheme;
 
-use dd_git::Repository;
+use dd_core::{Preferences, SidebarGroup};
+use dd_git::{
+    contiguous_range, CommitInfo, FileDiff, FileStatus, ImageVersions, LineEndingStats,
+    OperationInProgress, RepoError, Repository, WhitespaceMode,
+};
 
 use crate::commit_list::CommitList;
-use crate::diff_view::DiffView;
+use crate::diff_cache::DiffCache;
+use crate::diff_view::{DiffView, DiffViewMode, HunkAction, WorkingChanges};
 use crate::sidebar::{Sidebar, SidebarData};
 
 const COMMIT_LIMIT: usize = 100;
 
+/// Commit diffs kept around for revisiting and prefetched neighbours.
+const DIFF_CACHE_CAPACITY: usize = 32;
+
+/// Computes a commit's diff. Runs on a background thread when prefetching.
+type DiffLoader = Arc<dyn Fn(&CommitInfo) -> anyhow::Result<Vec<FileDiff>> + Send + Sync>;
+
+/// How long a dirty-state result is reused before the work tree is walked
+/// again.
+const DIRTY_PROBE_INTERVAL: Duration = Duration::from_secs(2);
+
 const SIDEBAR_INITIAL_SIZE: f32 = 250.0;
 const SIDEBAR_MIN_SIZE: f32 = 40.0;
 const SIDEBAR_MAX_SIZE: f32 = 500.0;
@@ -22,12 +46,47 @@ const COMMIT_LIST_MAX_SIZE: f32 = 800.0;
 
 const MIN_DIFF_VIEW_WIDTH: f32 = 200.0;
 
+const SIDEBAR_TOGGLE_WIDTH: f32 = 10.0;
+
 pub struct RepoView {
     path: PathBuf,
     repo_name: String,
     sidebar: Entity<Sidebar>,
     commit_list: Entity<CommitList>,
+    /// Search box above the commit list. Created on first render, since
+    /// the input needs a window.
+    commit_filter: Option<(Entity<InputState>, Subscription)>,
     diff_view: Entity<DiffView>,
+    /// Hide the sidebar and commit list so the diff gets the full width.
+    diff_focused: bool,
+    /// Hide just the sidebar, leaving a thin handle to bring it back.
+    sidebar_collapsed: bool,
+    diff_loader: DiffLoader,
+    /// Shared with the commit-selection callback and its prefetch tasks.
+    diff_cache: Rc<RefCell<DiffCache>>,
+    /// Reads the history off the UI thread; replacing it drops a stale load.
+    commit_load: Option<Task<()>>,
+    /// Computes the selected commit's diff. Selecting another commit
+    /// replaces the task, so an older diff never lands over a newer one.
+    diff_load: Rc<RefCell<Option<Task<()>>>>,
+    /// Warms the cache with the selection's neighbours. Replaced on each
+    /// selection, so prefetches never pile up behind one another.
+    diff_prefetch: Rc<RefCell<Option<Task<()>>>>,
+    /// Kept open for the dirty-state probe, which runs on every tab sync.
+    repo: Option<Repository>,
+    /// Last dirty-state result and when it was computed.
+    dirty_probe: Option<(Instant, bool)>,
+    /// Saved selection to reapply once the history has loaded.
+    pending_selection: Option<String>,
+    #[cfg(test)]
+    pub(crate) repo_opens: usize,
+    #[allow(clippy::type_complexity)]
+    on_sidebar_toggle: Option<Box<dyn Fn(bool, &mut Window, &mut Context<Self>) + 'static>>,
+    #[allow(clippy::type_complexity)]
+    on_commit_select: Option<Box<dyn Fn(&str, &mut Window, &mut Context<Self>) + 'static>>,
+    #[allow(clippy::type_complexity)]
+    on_sidebar_collapse_change:
+        Option<Box<dyn Fn(&[SidebarGroup], &[String], &mut Window, &mut Context<Self>) + 'static>>,
 }
 
 impl RepoView {
@@ -38,22 +97,385 @@ impl RepoView {
             .unwrap_or_else(|| "unknown".to_string());
 
         let sidebar = cx.new(|_cx| Sidebar::new_empty());
-        let commit_list = cx.new(|_cx| CommitList::new_empty());
-        let diff_view = cx.new(|_cx| DiffView::new_empty());
+        let stat_path = path.clone();
+        let file_count_path = path.clone();
+        let commit_list = cx.new(|cx| {
+            let mut list = CommitList::new_empty(cx);
+            list.set_stat_loader(move |oid| Repository::open(&stat_path)?.commit_stat(oid));
+            list.set_file_count_loader(move |oid| {
+                Repository::open(&file_count_path)?.commit_file_count(oid)
+            });
+            list
+        });
+        let loader_path = path.clone();
+        let full_file_path = path.clone();
+        let whole_file_path = path.clone();
+        let normalize_path = path.clone();
+        let repo_root = Repository::open(&path)
+            .ok()
+            .and_then(|repo| repo.workdir().map(|p| p.to_path_buf()))
+            .unwrap_or_else(|| path.clone());
+        let origin_url = Repository::open(&path)
+            .ok()
+            .and_then(|repo| repo.remote_url("origin").ok());
+        let diff_view = cx.new(|cx| {
+            let mut view = DiffView::new_empty(cx);
+            view.set_repo_root(repo_root);
+            view.set_remote_url(origin_url);
+            view.set_file_loader(move |oid, file_path| {
+                Repository::open(&loader_path)?.file_content_at(oid, file_path)
+            });
+            view.set_full_file_loader(move |oid, file| {
+                Repository::open(&full_file_path)?.full_file_diff(oid, file)
+            });
+            view.set_whole_file_loader(move |oid, file| {
+                Repository::open(&whole_file_path)?.file_with_changes(oid, file)
+            });
+            view.set_line_ending_normalizer(move |file_path, ending| {
+                Repository::open(&normalize_path)?.normalize_line_endings(file_path, ending)
+            });
+            view
+        });
+
+        let diff_loader = commit_diff_loader(path.clone(), WhitespaceMode::default());
 
         let mut view = Self {
             path,
             repo_name,
             sidebar,
             commit_list,
+            commit_filter: None,
             diff_view,
+            diff_focused: false,
+            sidebar_collapsed: false,
+            diff_loader,
+            diff_cache: Rc::new(RefCell::new(DiffCache::new(DIFF_CACHE_CAPACITY))),
+            commit_load: None,
+            diff_load: Rc::new(RefCell::new(None)),
+            diff_prefetch: Rc::new(RefCell::new(None)),
+            repo: None,
+            dirty_probe: None,
+            pending_selection: None,
+            #[cfg(test)]
+            repo_opens: 0,
+            on_sidebar_toggle: None,
+            on_commit_select: None,
+            on_sidebar_collapse_change: None,
         };
         view.load_repo_data(cx);
         view.setup_commit_selection(cx);
+        view.setup_checkout_files(cx);
+        view.setup_hunk_actions(cx);
+        view.setup_stash_files(cx);
+        view.setup_show_ignored(cx);
         view.setup_branch_checkout(cx);
+        view.setup_branch_select(cx);
+        view.setup_branch_compare(cx);
+        view.setup_file_browser(cx);
+        view.setup_branch_upstream(cx);
+        view.setup_remote_menu(cx);
+        view.setup_fetch_full_history(cx);
+        view.setup_operation_actions(cx);
+        view.setup_parent_navigation(cx);
+        view.setup_sidebar_collapse(cx);
+        view.setup_whitespace_mode(cx);
         view
     }
 
+    /// Push user preferences down to the child views.
+    pub fn apply_preferences(&mut self, preferences: &Preferences, cx: &mut Context<Self>) {
+        let patterns = preferences.generated_patterns.clone();
+        let mode = preferences.generated_mode;
+        let split_gutter = preferences.split_gutter;
+        let diff_palette = preferences.diff_palette;
+        let show_line_numbers = preferences.show_line_numbers;
+        let change_markers = preferences.change_marker_style;
+        let placeholder = preferences.branding.empty_diff_message().to_string();
+        let word_diff = preferences.unified_word_diff;
+        let max_files = preferences.max_diff_files;
+        self.diff_view.update(cx, |view, cx| {
+            view.set_generated_filter(patterns, mode, cx);
+            view.set_split_gutter(split_gutter, cx);
+            view.set_diff_palette(diff_palette, cx);
+            view.set_show_line_numbers(show_line_numbers, cx);
+            view.set_change_marker_style(change_markers, cx);
+            view.set_placeholder(placeholder, cx);
+            view.set_word_diff(word_diff, cx);
+            view.set_max_files(max_files, cx);
+        });
+        let compact = preferences.compact_commit_list;
+        let density = preferences.commit_row_density;
+        let show_graph = preferences.show_commit_graph;
+        let subject_max_len = preferences.commit_subject_max_len;
+        let show_files = preferences.show_file_browser;
+        self.sidebar
+            .update(cx, |sidebar, cx| sidebar.set_show_files(show_files, cx));
+        self.commit_list.update(cx, |list, cx| {
+            list.set_compact(compact, cx);
+            list.set_density(density, cx);
+            list.set_show_graph(show_graph, cx);
+            list.set_subject_max_len(subject_max_len, cx);
+        });
+    }
+
+    /// Restore the diff layout saved for this repo (`RepoTab::diff_view_mode`).
+    /// Unknown names fall back to choosing by width.
+    pub fn set_diff_view_mode(&mut self, mode: Option<&str>, cx: &mut Context<Self>) {
+        let mode = mode.and_then(DiffViewMode::parse);
+        self.diff_view
+            .update(cx, |view, cx| view.set_preferred_mode(mode, cx));
+    }
+
+    /// Whether the work tree has uncommitted changes. Reuses one repository
+    /// handle and, within [`DIRTY_PROBE_INTERVAL`], the previous result.
+    pub fn is_dirty(&mut self) -> anyhow::Result<bool> {
+        if let Some((probed_at, dirty)) = self.dirty_probe {
+            if probed_at.elapsed() < DIRTY_PROBE_INTERVAL {
+                return Ok(dirty);
+            }
+        }
+        let repo = match self.repo.take() {
+            Some(repo) => repo,
+            None => {
+                #[cfg(test)]
+                {
+                    self.repo_opens += 1;
+                }
+                Repository::open(&self.path)?
+            }
+        };
+        let dirty = repo.is_dirty();
+        self.repo = Some(repo);
+        let dirty = dirty?;
+        self.dirty_probe = Some((Instant::now(), dirty));
+        Ok(dirty)
+    }
+
+    /// Make the next [`Self::is_dirty`] call walk the work tree again.
+    pub fn expire_dirty_state(&mut self) {
+        self.dirty_probe = None;
+    }
+
+    /// Recompute state that changes outside the app, such as the dirty
+    /// flag, without resetting the commit selection.
+    pub fn refresh_status(&mut self, cx: &mut Context<Self>) {
+        self.diff_cache.borrow_mut().clear();
+        self.expire_dirty_state();
+        if let Ok(repo) = Repository::open(&self.path) {
+            self.sidebar.update(cx, |sidebar, cx| {
+                sidebar.set_summary(repo.summary().ok(), cx);
+            });
+        }
+    }
+
+    /// Ask for a directory and write the selected commits there as a
+    /// numbered patch series. The selection must be contiguous.
+    pub fn export_patch_series(&mut self, cx: &mut Context<Self>) {
+        let selected = self.commit_list.read(cx).selected_commits();
+        let (oldest, newest) = match contiguous_range(&selected) {
+            Ok((oldest, newest)) => (oldest.to_string(), newest.to_string()),
+            Err(e) => {
+                self.show_error(format!("Cannot export patches: {e}"), cx);
+                return;
+            }
+        };
+
+        let receiver = cx.prompt_for_paths(PathPromptOptions {
+            files: false,
+            directories: true,
+            multiple: false,
+            prompt: Some("Export Patch Series".into()),
+        });
+        let repo_path = self.path.clone();
+
+        cx.spawn(async move |this, cx| {
+            let Ok(Ok(Some(paths))) = receiver.await else {
+                return;
+            };
+            let Some(out_dir) = paths.into_iter().next() else {
+                return;
+            };
+            let result = Repository::open(&repo_path)
+                .and_then(|repo| repo.export_patch_series(&oldest, &newest, &out_dir));
+            if let Err(e) = result {
+                let _ = cx.update(|cx| {
+                    this.update(cx, |view, cx| {
+                        view.show_error(format!("Failed to export patches: {e}"), cx);
+                    })
+                });
+            }
+        })
+        .detach();
+    }
+
+    /// Restore the files the selected commit touched to their contents at
+    /// that commit, e.g. to recover an old version. Files the commit deleted
+    /// are skipped. Local changes to the restored files are only
+    /// overwritten once the user confirms.
+    pub fn checkout_files_from_commit(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let list = self.commit_list.read(cx);
+        let selected = list.selected_index().and_then(|i| list.commits().get(i));
+        let Some(commit) = selected.cloned() else {
+            self.show_error("Select a commit to check out files from".to_string(), cx);
+            return;
+        };
+        let result = Repository::open(&self.path).and_then(|repo| {
+            let paths: Vec<String> = repo
+                .diff_commit(&commit.oid)?
+                .into_iter()
+                .filter(|file| file.status != FileStatus::Deleted)
+                .map(|file| file.path)
+                .collect();
+            let dirty = repo.dirty_paths(&paths)?;
+            Ok((paths, dirty))
+        });
+        let (paths, dirty) = match result {
+            Ok(found) => found,
+            Err(e) => {
+                self.show_error(
+                    format!("Failed to check out files from {}: {e}", commit.short_oid),
+                    cx,
+                );
+                return;
+            }
+        };
+        if dirty.is_empty() {
+            self.checkout_paths(&commit, &paths, cx);
+            return;
+        }
+
+        let answer = window.prompt(
+            PromptLevel::Warning,
+            &format!("Overwrite local changes with {}?", commit.short_oid),
+            Some(&dirty.join("\n")),
+            &["Overwrite", "Cancel"],
+            cx,
+        );
+        cx.spawn(async move |this, cx| {
+            if answer.await != Ok(0) {
+                return;
+            }
+            let _ = cx
+                .update(|cx| this.update(cx, |view, cx| view.checkout_paths(&commit, &paths, cx)));
+        })
+        .detach();
+    }
+
+    fn checkout_paths(&mut self, commit: &CommitInfo, paths: &[String], cx: &mut Context<Self>) {
+        if paths.is_empty() {
+            return;
+        }
+        let result =
+            Repository::open(&self.path).and_then(|repo| repo.checkout_paths(&commit.oid, paths));
+        match result {
+            Ok(()) => self.refresh_status(cx),
+            Err(e) => self.show_error(
+                format!("Failed to check out files from {}: {e}", commit.short_oid),
+                cx,
+            ),
+        }
+    }
+
+    fn show_error(&mut self, message: String, cx: &mut Context<Self>) {
+        self.diff_view
+            .update(cx, |view, cx| view.set_error(message, cx));
+    }
+
+    pub fn is_diff_focused(&self) -> bool {
+        self.diff_focused
+    }
+
+    pub fn toggle_diff_focus(&mut self, cx: &mut Context<Self>) {
+        self.diff_focused = !self.diff_focused;
+        cx.notify();
+    }
+
+    pub fn is_sidebar_collapsed(&self) -> bool {
+        self.sidebar_collapsed
+    }
+
+    pub fn set_sidebar_collapsed(&mut self, collapsed: bool, cx: &mut Context<Self>) {
+        self.sidebar_collapsed = collapsed;
+        cx.notify();
+    }
+
+    /// Called with the new state when the user collapses or restores the
+    /// sidebar from this view's handle.
+    pub fn on_sidebar_toggle(
+        &mut self,
+        callback: impl Fn(bool, &mut Window, &mut Context<Self>) + 'static,
+    ) {
+        self.on_sidebar_toggle = Some(Box::new(callback));
+    }
+
+    fn toggle_sidebar(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.set_sidebar_collapsed(!self.sidebar_collapsed, cx);
+        if let Some(ref on_toggle) = self.on_sidebar_toggle {
+            on_toggle(self.sidebar_collapsed, window, cx);
+        }
+    }
+
+    /// Restore the selection and sidebar folding saved for this repo. The
+    /// commit is selected once the history has loaded, if it is still there.
+    pub fn restore_ui_state(
+        &mut self,
+        selected_oid: Option<String>,
+        collapsed_groups: &[SidebarGroup],
+        collapsed_folders: &[String],
+        cx: &mut Context<Self>,
+    ) {
+        self.pending_selection = selected_oid;
+        self.sidebar.update(cx, |sidebar, cx| {
+            sidebar.set_collapse_state(collapsed_groups, collapsed_folders, cx);
+        });
+        cx.notify();
+    }
+
+    /// Called with the OID of each commit selected in the history.
+    pub fn on_commit_select(
+        &mut self,
+        callback: impl Fn(&str, &mut Window, &mut Context<Self>) + 'static,
+    ) {
+        self.on_commit_select = Some(Box::new(callback));
+    }
+
+    /// Called with the collapsed groups and branch folders after the user
+    /// folds or unfolds one in the sidebar.
+    pub fn on_sidebar_collapse_change(
+        &mut self,
+        callback: impl Fn(&[SidebarGroup], &[String], &mut Window, &mut Context<Self>) + 'static,
+    ) {
+        self.on_sidebar_collapse_change = Some(Box::new(callback));
+    }
+
+    fn setup_sidebar_collapse(&mut self, cx: &mut Context<Self>) {
+        let this = cx.entity().downgrade();
+        self.sidebar.update(cx, |sidebar, _cx| {
+            sidebar.on_collapse_change(move |groups, folders, window, cx| {
+                let _ = this.update(cx, |view, cx| {
+                    if let Some(ref on_change) = view.on_sidebar_collapse_change {
+                        on_change(groups, folders, window, cx);
+                    }
+                });
+            });
+        });
+    }
+
+    /// Select the saved commit once the history has finished loading.
+    /// Needs a window, so it runs from `render`.
+    fn apply_pending_selection(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.commit_list.read(cx).is_loading() {
+            return;
+        }
+        let Some(oid) = self.pending_selection.take() else {
+            return;
+        };
+        let commit_list = self.commit_list.clone();
+        cx.defer_in(window, move |_view, window, cx| {
+            commit_list.update(cx, |list, cx| list.select_oid(&oid, window, cx));
+        });
+    }
+
     pub fn repo_name(&self) -> &str {
         &self.repo_name
     }
@@ -70,42 +492,308 @@ impl RepoView {
         &self.sidebar
     }
 
+    /// Replace how commit diffs are computed. Drops any cached diffs.
+    pub fn set_diff_loader(
+        &mut self,
+        loader: impl Fn(&CommitInfo) -> anyhow::Result<Vec<FileDiff>> + Send + Sync + 'static,
+        cx: &mut Context<Self>,
+    ) {
+        self.diff_loader = Arc::new(loader);
+        self.diff_cache.borrow_mut().clear();
+        // Pending loads would still run the old loader.
+        self.diff_load.borrow_mut().take();
+        self.diff_prefetch.borrow_mut().take();
+        self.setup_commit_selection(cx);
+    }
+
+    /// Recompute the selected commit's diff with `mode`. Cached diffs were
+    /// computed with the old mode and are dropped.
+    pub fn set_whitespace_mode(
+        &mut self,
+        mode: WhitespaceMode,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.diff_loader = commit_diff_loader(self.path.clone(), mode);
+        self.diff_cache.borrow_mut().clear();
+        self.setup_commit_selection(cx);
+        self.diff_view
+            .update(cx, |view, cx| view.set_whitespace_mode(mode, cx));
+        self.commit_list
+            .update(cx, |list, cx| list.reload_selection(window, cx));
+    }
+
+    fn setup_whitespace_mode(&mut self, cx: &mut Context<Self>) {
+        let this = cx.entity().downgrade();
+        self.diff_view.update(cx, |view, _cx| {
+            view.on_whitespace_mode_change(move |mode, window, cx| {
+                let this = this.clone();
+                // Deferred: reloading updates the diff view, which is
+                // still being updated here.
+                cx.defer_in(window, move |_view, window, cx| {
+                    let _ = this.update(cx, |view, cx| view.set_whitespace_mode(mode, window, cx));
+                });
+            });
+        });
+    }
+
     fn setup_commit_selection(&mut self, cx: &mut Context<Self>) {
         let diff_view = self.diff_view.clone();
         let repo_path = self.path.clone();
+        let loader = self.diff_loader.clone();
+        let cache = self.diff_cache.clone();
+        let diff_load = self.diff_load.clone();
+        let diff_prefetch = self.diff_prefetch.clone();
+        let this = cx.entity().downgrade();
 
         self.commit_list.update(cx, |list, _cx| {
-            list.on_select(move |commit, _window, cx| {
+            list.on_select(move |commit, window, cx| {
                 let commit_info = commit.clone();
-                match Repository::open(&repo_path) {
-                    Ok(repo) => {
-                        let signature = repo
-                            .commit_signature_status(&commit_info.oid)
-                            .unwrap_or(dd_git::SignatureStatus::None);
-                        match repo.diff_commit(&commit_info.oid) {
-                            Ok(diffs) => {
-                                diff_view.update(cx, |view, cx| {
-                                    view.set_commit_data(commit_info, signature, diffs, cx);
-                                });
+                let oid = commit_info.oid.clone();
+                let generation = cache.borrow().generation();
+                let cached = cache.borrow_mut().get(&oid);
+                if cached.is_none() {
+                    diff_view.update(cx, |view, cx| view.set_loading(cx));
+                }
+
+                let repo_path = repo_path.clone();
+                let task_loader = loader.clone();
+                let task_cache = cache.clone();
+                let task_diff_view = diff_view.clone();
+                let load = cx.spawn(async move |_list, cx| {
+                    let details = cx
+                        .background_executor()
+                        .spawn(async move {
+                            load_commit_details(&repo_path, commit_info, cached, task_loader)
+                        })
+                        .await;
+                    let _ = task_diff_view.update(cx, |view, cx| match details {
+                        Ok(details) => {
+                            if let Ok(diffs) = &details.diffs {
+                                task_cache.borrow_mut().insert_at(
+                                    generation,
+                                    details.commit.oid.clone(),
+                                    diffs.clone(),
+                                );
                             }
-                            Err(e) => {
-                                diff_view.update(cx, |view, cx| {
-                                    view.set_error(format!("Failed to load diff: {e}"), cx);
-                                });
+                            match details.diffs {
+                                Ok(diffs) => {
+                                    view.set_commit_data(
+                                        details.commit,
+                                        details.signature,
+                                        diffs,
+                                        cx,
+                                    );
+                                    view.set_images(details.images, cx);
+                                    view.set_containing_branches(details.branches, cx);
+                                    view.set_parent_subjects(details.parent_subjects, cx);
+                                }
+                                Err(e) => view.set_error(format!("Failed to load diff: {e}"), cx),
                             }
                         }
-                    }
-                    Err(e) => {
-                        diff_view.update(cx, |view, cx| {
-                            view.set_error(format!("Failed to open repository: {e}"), cx);
+                        Err(error) => {
+                            view.set_error(format!("Failed to open repository: {error}"), cx)
+                        }
+                    });
+                });
+                // Dropping the previous selection's tasks cancels them.
+                *diff_load.borrow_mut() = Some(load);
+                diff_prefetch.borrow_mut().take();
+
+                // Deferred: a restored selection is made while this view
+                // is still being updated.
+                let this = this.clone();
+                let selected = oid.clone();
+                cx.defer_in(window, move |_list, window, cx| {
+                    let _ = this.update(cx, |view, cx| {
+                        if let Some(ref on_select) = view.on_commit_select {
+                            on_select(&selected, window, cx);
+                        }
+                    });
+                });
+
+                // Warm the cache with the neighbouring commits so
+                // stepping to them does not wait on the diff.
+                let loader = loader.clone();
+                let cache = cache.clone();
+                let diff_prefetch = diff_prefetch.clone();
+                cx.defer_in(window, move |list, _window, cx| {
+                    let neighbours: Vec<CommitInfo> = list
+                        .adjacent_commits(&oid)
+                        .into_iter()
+                        .filter(|c| !cache.borrow().contains(&c.oid))
+                        .collect();
+                    *diff_prefetch.borrow_mut() = prefetch_diffs(neighbours, loader, cache, cx);
+                });
+            });
+        });
+    }
+
+    /// Show the uncommitted changes in the diff pane, where the hunk
+    /// shortcuts stage, unstage or discard them.
+    pub fn show_working_changes(&mut self, changes: WorkingChanges, cx: &mut Context<Self>) {
+        self.diff_view.update(cx, |view, cx| view.set_loading(cx));
+        let repo_path = self.path.clone();
+        let diff_view = self.diff_view.clone();
+        let show_ignored = diff_view.read(cx).show_ignored();
+        let load = cx.spawn(async move |_this, cx| {
+            let result = cx
+                .background_executor()
+                .spawn(async move { load_working_changes(&repo_path, changes, show_ignored) })
+                .await;
+            let _ = diff_view.update(cx, |view, cx| match result {
+                Ok(details) => {
+                    view.set_working_changes(changes, details.diffs, cx);
+                    view.set_mixed_line_endings(details.line_endings, cx);
+                    view.set_ignored_files(details.ignored, cx);
+                }
+                Err(e) => view.set_error(format!("Failed to load working changes: {e}"), cx),
+            });
+        });
+        // Shares the commit diff's slot, so whichever was asked for last
+        // is what ends up shown.
+        *self.diff_load.borrow_mut() = Some(load);
+    }
+
+    fn apply_hunk_action(
+        &mut self,
+        action: HunkAction,
+        file: &FileDiff,
+        hunk_index: usize,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(changes) = self.diff_view.read(cx).working_changes() else {
+            return;
+        };
+        let result = Repository::open(&self.path).and_then(|repo| match action {
+            HunkAction::Stage => repo.stage_hunk(file, hunk_index),
+            HunkAction::Unstage => repo.unstage_hunk(file, hunk_index),
+            HunkAction::Discard => repo.discard_hunk(file, hunk_index),
+        });
+        match result {
+            Ok(()) => {
+                self.refresh_status(cx);
+                self.show_working_changes(changes, cx);
+            }
+            Err(e) => self.show_error(format!("Failed to update {}: {e}", file.path), cx),
+        }
+    }
+
+    /// The commit list's "check out files" action restores the selected
+    /// commit's files, asking first when that would overwrite local work.
+    fn setup_checkout_files(&mut self, cx: &mut Context<Self>) {
+        let this = cx.entity().downgrade();
+
+        self.commit_list.update(cx, |list, _cx| {
+            list.on_checkout_files(move |window, cx| {
+                let this = this.clone();
+                // Deferred: the selection is read from the commit list,
+                // which is still being updated.
+                window.defer(cx, move |window, cx| {
+                    let _ = this.update(cx, |view, cx| view.checkout_files_from_commit(window, cx));
+                });
+            });
+        });
+    }
+
+    /// The diff view's stage/unstage/discard shortcuts apply the focused
+    /// hunk to the index or work tree, then reload the changes. Discarding
+    /// can't be undone, so it asks first.
+    fn setup_hunk_actions(&mut self, cx: &mut Context<Self>) {
+        let this = cx.entity().downgrade();
+
+        self.diff_view.update(cx, |view, _cx| {
+            view.on_hunk_action(move |action, file, hunk_index, window, cx| {
+                let this = this.clone();
+                let file = file.clone();
+                if action == HunkAction::Discard {
+                    let answer = window.prompt(
+                        PromptLevel::Warning,
+                        &format!("Discard this change to {}?", file.path),
+                        Some("The change is removed from the working tree and can't be restored."),
+                        &["Discard", "Cancel"],
+                        cx,
+                    );
+                    cx.spawn(async move |_view, cx| {
+                        if answer.await != Ok(0) {
+                            return;
+                        }
+                        let _ = this.update(cx, |view, cx| {
+                            view.apply_hunk_action(action, &file, hunk_index, cx);
                         });
-                    }
+                    })
+                    .detach();
+                    return;
+                }
+                // Deferred: the diff view is reloaded afterwards and is
+                // still being updated.
+                window.defer(cx, move |_window, cx| {
+                    let _ = this.update(cx, |view, cx| {
+                        view.apply_hunk_action(action, &file, hunk_index, cx);
+                    });
+                });
+            });
+        });
+    }
+
+    /// "Stash selected" shelves the picked working-changes files, leaving
+    /// the rest in the work tree.
+    fn setup_stash_files(&mut self, cx: &mut Context<Self>) {
+        let this = cx.entity().downgrade();
+
+        self.diff_view.update(cx, |view, _cx| {
+            view.on_stash_files(move |paths, window, cx| {
+                let this = this.clone();
+                // Deferred: the diff view is reloaded afterwards and is
+                // still being updated.
+                window.defer(cx, move |_window, cx| {
+                    let _ = this.update(cx, |view, cx| view.stash_files(&paths, cx));
+                });
+            });
+        });
+    }
+
+    /// Switching "Show ignored" on reloads the unstaged changes with the
+    /// ignored files; switching it off just hides them.
+    fn setup_show_ignored(&mut self, cx: &mut Context<Self>) {
+        let this = cx.entity().downgrade();
+
+        self.diff_view.update(cx, |view, _cx| {
+            view.on_show_ignored_change(move |show, window, cx| {
+                if !show {
+                    return;
                 }
+                let this = this.clone();
+                window.defer(cx, move |_window, cx| {
+                    let _ = this.update(cx, |view, cx| {
+                        view.show_working_changes(WorkingChanges::Unstaged, cx);
+                    });
+                });
             });
         });
     }
 
+    fn stash_files(&mut self, paths: &[String], cx: &mut Context<Self>) {
+        let Some(changes) = self.diff_view.read(cx).working_changes() else {
+            return;
+        };
+        let result = Repository::open(&self.path).and_then(|repo| {
+            repo.stash_push_paths(paths, None)?;
+            Ok(repo)
+        });
+        match result {
+            Ok(repo) => {
+                self.sidebar
+                    .update(cx, |sb, cx| sb.set_data(SidebarData::load(&repo), cx));
+                self.refresh_status(cx);
+                self.show_working_changes(changes, cx);
+            }
+            Err(e) => self.show_error(format!("Failed to stash: {e}"), cx),
+        }
+    }
+
     fn setup_branch_checkout(&mut self, cx: &mut Context<Self>) {
+        let this = cx.entity().downgrade();
         let commit_list = self.commit_list.clone();
         let diff_view = self.diff_view.clone();
         let repo_path = self.path.clone();
@@ -113,6 +801,7 @@ impl RepoView {
         self.sidebar.update(cx, |sb, _cx| {
             sb.on_branch_checkout(move |branch, window, cx| {
                 let branch_name = branch.name.clone();
+                let this = this.clone();
                 let repo_path = repo_path.clone();
                 let commit_list = commit_list.clone();
                 let diff_view = diff_view.clone();
@@ -128,22 +817,18 @@ impl RepoView {
                             }
                             // Re-open repo to pick up new HEAD
                             if let Ok(repo) = Repository::open(&repo_path) {
-                                let branches = repo.branches().unwrap_or_default();
-                                let remotes = repo.remotes().unwrap_or_default();
-                                let tags = repo.tags().unwrap_or_default();
-                                let stashes = repo.stashes().unwrap_or_default();
-                                sb.set_data(
-                                    SidebarData {
-                                        branches,
-                                        remotes,
-                                        tags,
-                                        stashes,
-                                    },
-                                    cx,
-                                );
+                                sb.set_data(SidebarData::load(&repo), cx);
+                                sb.set_summary(repo.summary().ok(), cx);
                                 let commits = repo.commits(COMMIT_LIMIT).unwrap_or_default();
+                                let unpushed = repo.unpushed_oids().unwrap_or_default();
+                                let line_counts = repo
+                                    .commit_line_counts("HEAD", COMMIT_LIMIT)
+                                    .unwrap_or_default();
+                                let _ = this.update(cx, |view, cx| view.cancel_commit_load(cx));
                                 commit_list.update(cx, |list, cx| {
                                     list.set_commits(commits, cx);
+                                    list.set_unpushed(unpushed, cx);
+                                    list.set_line_counts(line_counts, cx);
                                 });
                                 diff_view.update(cx, |view, cx| {
                                     view.set_diffs(vec![], cx);
@@ -157,102 +842,951 @@ impl RepoView {
         });
     }
 
-    fn load_repo_data(&mut self, cx: &mut Context<Self>) {
-        if let Ok(repo) = Repository::open(&self.path) {
-            let branches = repo.branches().unwrap_or_default();
-            let remotes = repo.remotes().unwrap_or_default();
-            let tags = repo.tags().unwrap_or_default();
-            let stashes = repo.stashes().unwrap_or_default();
+    /// Single-clicking a branch shows its history without checking it out.
+    fn setup_branch_select(&mut self, cx: &mut Context<Self>) {
+        let this = cx.entity().downgrade();
+        let commit_list = self.commit_list.clone();
+        let diff_view = self.diff_view.clone();
+        let repo_path = self.path.clone();
 
-            self.sidebar.update(cx, |sidebar, cx| {
-                sidebar.set_data(
-                    SidebarData {
-                        branches,
-                        remotes,
-                        tags,
-                        stashes,
-                    },
-                    cx,
-                );
-            });
+        self.sidebar.update(cx, |sb, _cx| {
+            sb.on_branch_select(move |branch, window, cx| {
+                let branch_name = branch.name.clone();
+                let this = this.clone();
+                let repo_path = repo_path.clone();
+                let commit_list = commit_list.clone();
+                let diff_view = diff_view.clone();
 
-            let commits = repo.commits(COMMIT_LIMIT).unwrap_or_default();
-            self.commit_list.update(cx, |list, cx| {
-                list.set_commits(commits, cx);
+                cx.defer_in(window, move |_sb, _window, cx| {
+                    let repo = match Repository::open(&repo_path) {
+                        Ok(repo) => repo,
+                        Err(e) => {
+                            eprintln!("failed to open repo: {e}");
+                            return;
+                        }
+                    };
+                    let commits = match repo.commits_for_ref(&branch_name, COMMIT_LIMIT) {
+                        Ok(commits) => commits,
+                        Err(e) => {
+                            eprintln!("failed to load history for {branch_name}: {e}");
+                            return;
+                        }
+                    };
+                    let unpushed = repo.unpushed_oids().unwrap_or_default();
+                    let line_counts = repo
+                        .commit_line_counts(&branch_name, COMMIT_LIMIT)
+                        .unwrap_or_default();
+                    let _ = this.update(cx, |view, cx| view.cancel_commit_load(cx));
+                    commit_list.update(cx, |list, cx| {
+                        list.set_commits(commits, cx);
+                        list.set_unpushed(unpushed, cx);
+                        list.set_line_counts(line_counts, cx);
+                    });
+                    diff_view.update(cx, |view, cx| {
+                        view.set_diffs(vec![], cx);
+                    });
+                });
             });
-        }
+        });
     }
-}
 
-impl Render for RepoView {
-    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
-        gpui::div()
-            .size_full()
-            .flex()
-            .child(
-                gpui::div()
-                    .flex_shrink_0()
-                    .w(px(SIDEBAR_INITIAL_SIZE + COMMIT_LIST_INITIAL_SIZE))
-                    .h_full()
-                    .child(
-                        h_resizable("left-panels")
-                            .child(
-                                resizable_panel()
-                                    .size(px(SIDEBAR_INITIAL_SIZE))
-                                    .size_range(px(SIDEBAR_MIN_SIZE)..px(SIDEBAR_MAX_SIZE))
-                                    .child(self.sidebar.clone()),
-                            )
-                            .child(
-                                resizable_panel()
-                                    .size(px(COMMIT_LIST_INITIAL_SIZE))
-                                    .size_range(px(COMMIT_LIST_MIN_SIZE)..px(COMMIT_LIST_MAX_SIZE))
-                                    .child(self.commit_list.clone()),
+    /// Alt-clicking a branch diffs its tip against the selected commit.
+    fn setup_branch_compare(&mut self, cx: &mut Context<Self>) {
+        let commit_list = self.commit_list.clone();
+        let diff_view = self.diff_view.clone();
+        let repo_path = self.path.clone();
+
+        self.sidebar.update(cx, |sb, _cx| {
+            sb.on_branch_compare(move |branch, window, cx| {
+                let branch_name = branch.name.clone();
+                let repo_path = repo_path.clone();
+                let commit_list = commit_list.clone();
+                let diff_view = diff_view.clone();
+
+                cx.defer_in(window, move |_sb, _window, cx| {
+                    let commit = commit_list
+                        .read(cx)
+                        .selected_index()
+                        .and_then(|i| commit_list.read(cx).commits().get(i).cloned());
+                    let Some(commit) = commit else {
+                        diff_view.update(cx, |view, cx| {
+                            view.set_error("Select a commit to compare with".to_string(), cx);
+                        });
+                        return;
+                    };
+                    let result = Repository::open(&repo_path).and_then(|repo| {
+                        let tip = repo.rev_parse(&branch_name)?;
+                        repo.diff_range(&tip, &commit.oid)
+                    });
+                    diff_view.update(cx, |view, cx| match result {
+                        Ok(diffs) => view.set_diffs(diffs, cx),
+                        Err(e) => view.set_error(
+                            format!(
+                                "Failed to compare {} with {branch_name}: {e}",
+                                commit.short_oid
                             ),
-                    ),
-            )
-            .child(
-                gpui::div()
-                    .flex_1()
-                    .min_w(px(MIN_DIFF_VIEW_WIDTH))
-                    .h_full()
-                    .child(self.diff_view.clone()),
-            )
+                            cx,
+                        ),
+                    });
+                });
+            });
+        });
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::test_helpers::{init_test_repo, init_test_repo_with_changes, init_test_theme};
-    use gpui::TestAppContext;
-
-    #[gpui::test]
-    fn test_repo_view_loads_branches(cx: &mut TestAppContext) {
-        cx.update(|cx| init_test_theme(cx));
-        let dir = init_test_repo();
-        let path = dir.path().to_path_buf();
+    /// The sidebar's file browser lists HEAD's tree one directory at a
+    /// time; clicking a file shows its content in the diff view.
+    fn setup_file_browser(&mut self, cx: &mut Context<Self>) {
+        let diff_view = self.diff_view.clone();
+        let repo_path = self.path.clone();
 
-        let window = cx.add_window(|_window, cx| RepoView::new(path, cx));
+        self.sidebar.update(cx, |sb, _cx| {
+            let dir_repo_path = repo_path.clone();
+            sb.on_dir_open(move |dir, window, cx| {
+                let dir = dir.to_string();
+                let repo_path = dir_repo_path.clone();
+                cx.defer_in(window, move |sb, _window, cx| {
+                    let entries = Repository::open(&repo_path).and_then(|repo| {
+                        let head = repo.rev_parse("HEAD")?;
+                        repo.tree_entries(&head, &dir)
+                    });
+                    match entries {
+                        Ok(entries) => sb.set_dir_entries(dir, entries, cx),
+                        Err(e) => eprintln!("failed to list {dir}: {e}"),
+                    }
+                });
+            });
 
-        window
-            .read_with(cx, |view, cx| {
-                let sidebar = view.sidebar().read(cx);
-                let data = sidebar.data();
-                assert!(
-                    data.branches.iter().any(|b| b.name == "main" && b.is_head),
-                    "expected 'main' branch with is_head == true"
-                );
-            })
-            .unwrap();
+            sb.on_file_open(move |path, window, cx| {
+                let path = path.to_string();
+                let repo_path = repo_path.clone();
+                let diff_view = diff_view.clone();
+                cx.defer_in(window, move |_sb, _window, cx| {
+                    let file =
+                        Repository::open(&repo_path).and_then(|repo| file_at_head(&repo, &path));
+                    diff_view.update(cx, |view, cx| match file {
+                        Ok(file) => view.set_diffs(vec![file], cx),
+                        Err(e) => view.set_error(format!("Failed to open {path}: {e}"), cx),
+                    });
+                });
+            });
+        });
     }
 
-    #[gpui::test]
-    fn test_repo_view_loads_commits(cx: &mut TestAppContext) {
-        cx.update(|cx| init_test_theme(cx));
-        let dir = init_test_repo_with_changes();
-        let path = dir.path().to_path_buf();
-
-        let window = cx.add_window(|_window, cx| RepoView::new(path, cx));
+    /// Right-clicking a branch asks which remote branch it should track.
+    fn setup_branch_upstream(&mut self, cx: &mut Context<Self>) {
+        let this = cx.entity().downgrade();
+
+        self.sidebar.update(cx, |sb, _cx| {
+            sb.on_branch_set_upstream(move |branch, window, cx| {
+                let _ = this.update(cx, |view, cx| view.pick_upstream(&branch.name, window, cx));
+            });
+        });
+    }
+
+    /// Offer every remote-tracking branch as `branch`'s upstream, those of
+    /// the same name first, and track the one picked.
+    fn pick_upstream(&mut self, branch: &str, window: &mut Window, cx: &mut Context<Self>) {
+        let candidates = Repository::open(&self.path).and_then(|repo| {
+            let mut candidates = Vec::new();
+            for remote in repo.remotes()? {
+                let prefix = format!("{}/", remote.name);
+                for remote_branch in repo.remote_branches(&remote.name)? {
+                    if let Some(name) = remote_branch.name.strip_prefix(&prefix) {
+                        candidates.push((remote.name.clone(), name.to_string()));
+                    }
+                }
+            }
+            candidates.sort_by_key(|(_, name)| name != branch);
+            Ok(candidates)
+        });
+        let candidates = match candidates {
+            Ok(candidates) if candidates.is_empty() => {
+                self.show_error(format!("No remote branch for {branch} to track"), cx);
+                return;
+            }
+            Ok(candidates) => candidates,
+            Err(e) => {
+                self.show_error(format!("Failed to list remote branches: {e}"), cx);
+                return;
+            }
+        };
+
+        let labels: Vec<String> = candidates
+            .iter()
+            .map(|(remote, name)| format!("{remote}/{name}"))
+            .collect();
+        let mut answers: Vec<&str> = labels.iter().map(String::as_str).collect();
+        answers.push("Cancel");
+        let answer = window.prompt(
+            PromptLevel::Info,
+            &format!("Set the upstream of {branch}"),
+            None,
+            &answers,
+            cx,
+        );
+        let branch = branch.to_string();
+        cx.spawn(async move |this, cx| {
+            let Ok(choice) = answer.await else {
+                return;
+            };
+            let Some((remote, remote_branch)) = candidates.get(choice) else {
+                return;
+            };
+            let _ = this.update(cx, |view, cx| {
+                let result = Repository::open(&view.path).and_then(|repo| {
+                    repo.set_upstream(&branch, remote, remote_branch)?;
+                    Ok(repo)
+                });
+                match result {
+                    Ok(repo) => view
+                        .sidebar
+                        .update(cx, |sb, cx| sb.set_data(SidebarData::load(&repo), cx)),
+                    Err(e) => view.show_error(format!("Failed to set upstream: {e}"), cx),
+                }
+            });
+        })
+        .detach();
+    }
+
+    /// Right-clicking a remote offers to repoint it at the URL on the
+    /// clipboard or to remove it.
+    fn setup_remote_menu(&mut self, cx: &mut Context<Self>) {
+        let repo_path = self.path.clone();
+
+        self.sidebar.update(cx, |sb, _cx| {
+            sb.on_remote_menu(move |remote, window, cx| {
+                let name = remote.name.clone();
+                let repo_path = repo_path.clone();
+                let answer = window.prompt(
+                    PromptLevel::Info,
+                    &format!("Remote \"{name}\""),
+                    remote.url.as_deref(),
+                    &["Set URL from Clipboard", "Remove Remote", "Cancel"],
+                    cx,
+                );
+
+                cx.spawn(async move |sb, cx| {
+                    let Ok(choice) = answer.await else {
+                        return;
+                    };
+                    let _ = cx.update(|cx| {
+                        let result = match choice {
+                            0 => {
+                                let url = cx
+                                    .read_from_clipboard()
+                                    .and_then(|item| item.text())
+                                    .map(|text| text.trim().to_string())
+                                    .filter(|text| !text.is_empty());
+                                let Some(url) = url else {
+                                    eprintln!("cannot set URL for {name}: clipboard is empty");
+                                    return;
+                                };
+                                Repository::open(&repo_path)
+                                    .and_then(|repo| repo.set_remote_url(&name, &url))
+                            }
+                            1 => Repository::open(&repo_path)
+                                .and_then(|repo| repo.remove_remote(&name)),
+                            _ => return,
+                        };
+                        if let Err(e) = result {
+                            eprintln!("updating remote {name} failed: {e}");
+                            return;
+                        }
+                        // Reopened: remotes are read from the config as of
+                        // opening.
+                        if let Ok(repo) = Repository::open(&repo_path) {
+                            let _ =
+                                sb.update(cx, |sb, cx| sb.set_data(SidebarData::load(&repo), cx));
+                        }
+                    });
+                })
+                .detach();
+            });
+        });
+    }
+
+    /// Clicking a parent in a merge header selects that parent. A parent
+    /// outside the listed history switches the list to the parent's own
+    /// history first.
+    fn setup_parent_navigation(&mut self, cx: &mut Context<Self>) {
+        let this = cx.entity().downgrade();
+        let commit_list = self.commit_list.clone();
+        let diff_view = self.diff_view.downgrade();
+        let repo_path = self.path.clone();
+
+        self.diff_view.update(cx, |view, _cx| {
+            view.on_navigate_parent(move |oid, window, cx| {
+                let oid = oid.to_string();
+                let this = this.clone();
+                let commit_list = commit_list.clone();
+                let diff_view = diff_view.clone();
+                let repo_path = repo_path.clone();
+
+                // Deferred without holding the diff view: selecting a commit
+                // updates it.
+                window.defer(cx, move |window, cx| {
+                    let listed = commit_list
+                        .read(cx)
+                        .commits()
+                        .iter()
+                        .position(|c| c.oid == oid);
+                    let index = match listed {
+                        Some(index) => index,
+                        None => {
+                            let commits = Repository::open(&repo_path)
+                                .and_then(|repo| repo.commits_for_ref(&oid, COMMIT_LIMIT));
+                            match commits {
+                                Ok(commits) => {
+                                    let _ = this.update(cx, |view, cx| view.cancel_commit_load(cx));
+                                    commit_list
+                                        .update(cx, |list, cx| list.set_commits(commits, cx));
+                                    0
+                                }
+                                Err(e) => {
+                                    let _ = diff_view.update(cx, |view, cx| {
+                                        view.set_error(format!("Failed to load parent: {e}"), cx);
+                                    });
+                                    return;
+                                }
+                            }
+                        }
+                    };
+                    commit_list.update(cx, |list, cx| list.select_commit(index, window, cx));
+                });
+            });
+        });
+    }
+
+    /// Unshallow the clone from the banner the sidebar shows for shallow
+    /// repositories, then reload the now complete history.
+    fn setup_fetch_full_history(&mut self, cx: &mut Context<Self>) {
+        let this = cx.entity().downgrade();
+        let commit_list = self.commit_list.clone();
+        let diff_view = self.diff_view.clone();
+        let repo_path = self.path.clone();
+
+        self.sidebar.update(cx, |sb, _cx| {
+            sb.on_fetch_full_history(move |_window, cx| {
+                let this = this.clone();
+                let repo_path = repo_path.clone();
+                let commit_list = commit_list.clone();
+                let diff_view = diff_view.clone();
+
+                // The fetch goes over the network, so it and the reload
+                // run off the UI thread.
+                cx.spawn(async move |sb, cx| {
+                    let loaded = cx
+                        .background_executor()
+                        .spawn(async move {
+                            let repo = Repository::open(&repo_path)?;
+                            repo.fetch_unshallow()?;
+                            anyhow::Ok((
+                                SidebarData::load(&repo),
+                                repo.summary().ok(),
+                                repo.commits(COMMIT_LIMIT).unwrap_or_default(),
+                                repo.unpushed_oids().unwrap_or_default(),
+                                repo.commit_line_counts("HEAD", COMMIT_LIMIT)
+                                    .unwrap_or_default(),
+                            ))
+                        })
+                        .await;
+                    let _ = cx.update(|cx| {
+                        let (data, summary, commits, unpushed, line_counts) = match loaded {
+                            Ok(loaded) => loaded,
+                            Err(e) => {
+                                diff_view.update(cx, |view, cx| {
+                                    view.set_error(
+                                        format!("Failed to fetch full history: {e}"),
+                                        cx,
+                                    );
+                                });
+                                return;
+                            }
+                        };
+                        let _ = sb.update(cx, |sb, cx| {
+                            sb.set_data(data, cx);
+                            sb.set_summary(summary, cx);
+                        });
+                        let _ = this.update(cx, |view, cx| view.cancel_commit_load(cx));
+                        commit_list.update(cx, |list, cx| {
+                            // The history only grew, so keep the user's place in it.
+                            list.update_commits(commits, cx);
+                            list.set_unpushed(unpushed, cx);
+                            list.set_line_counts(line_counts, cx);
+                        });
+                    });
+                })
+                .detach();
+            });
+        });
+    }
+
+    /// Abort or continue the merge, rebase, etc. from the sidebar's banner.
+    fn setup_operation_actions(&mut self, cx: &mut Context<Self>) {
+        let this = cx.entity().downgrade();
+        let abort = operation_handler(
+            this.clone(),
+            self.path.clone(),
+            self.commit_list.clone(),
+            self.diff_view.clone(),
+            true,
+        );
+        let resume = operation_handler(
+            this,
+            self.path.clone(),
+            self.commit_list.clone(),
+            self.diff_view.clone(),
+            false,
+        );
+        self.sidebar.update(cx, |sb, _cx| {
+            sb.on_abort_operation(abort);
+            sb.on_continue_operation(resume);
+        });
+    }
+
+    fn load_repo_data(&mut self, cx: &mut Context<Self>) {
+        if let Ok(repo) = Repository::open(&self.path) {
+            self.sidebar.update(cx, |sidebar, cx| {
+                sidebar.set_data(SidebarData::load(&repo), cx);
+                sidebar.set_summary(repo.summary().ok(), cx);
+            });
+            let empty_message = (!repo.has_commits().unwrap_or(true)).then(|| {
+                let branch = repo.head_branch().unwrap_or_default();
+                format!("On branch {branch} \u{2014} no commits yet")
+            });
+            self.commit_list.update(cx, |list, cx| {
+                list.set_empty_message(empty_message, cx);
+            });
+        }
+        self.load_commits(cx);
+    }
+
+    /// Drop the history load still in flight, if any, so it can't replace
+    /// the history a branch switch or reload just put in the list.
+    fn cancel_commit_load(&mut self, cx: &mut Context<Self>) {
+        if self.commit_load.take().is_some() {
+            self.commit_list
+                .update(cx, |list, cx| list.set_loading(false, cx));
+        }
+    }
+
+    /// Read HEAD's history on the background executor and show it when
+    /// done. The list shows a loading state meanwhile.
+    fn load_commits(&mut self, cx: &mut Context<Self>) {
+        let path = self.path.clone();
+        let commit_list = self.commit_list.clone();
+        commit_list.update(cx, |list, cx| list.set_loading(true, cx));
+        self.commit_load = Some(cx.spawn(async move |_this, cx| {
+            let loaded = cx
+                .background_executor()
+                .spawn(async move {
+                    let repo = Repository::open(&path)?;
+                    anyhow::Ok((
+                        repo.commits(COMMIT_LIMIT).unwrap_or_default(),
+                        repo.unpushed_oids().unwrap_or_default(),
+                        repo.commit_line_counts("HEAD", COMMIT_LIMIT)
+                            .unwrap_or_default(),
+                    ))
+                })
+                .await;
+            let _ = commit_list.update(cx, |list, cx| {
+                list.set_loading(false, cx);
+                match loaded {
+                    Ok((commits, unpushed, line_counts)) => {
+                        list.set_commits(commits, cx);
+                        list.set_unpushed(unpushed, cx);
+                        list.set_line_counts(line_counts, cx);
+                    }
+                    Err(e) => eprintln!("failed to load commits: {e}"),
+                }
+            });
+        }));
+    }
+}
+
+/// Sidebar handler that aborts (or continues) the operation in progress,
+/// then reloads the summary and history it changed.
+fn operation_handler(
+    this: WeakEntity<RepoView>,
+    repo_path: PathBuf,
+    commit_list: Entity<CommitList>,
+    diff_view: Entity<DiffView>,
+    abort: bool,
+) -> impl Fn(OperationInProgress, &mut Window, &mut Context<Sidebar>) + 'static {
+    move |operation, window, cx| {
+        let this = this.clone();
+        let repo_path = repo_path.clone();
+        let commit_list = commit_list.clone();
+        let diff_view = diff_view.clone();
+
+        // Deferred: the sidebar is still held by the click listener.
+        cx.defer_in(window, move |sb, _window, cx| {
+            let result = Repository::open(&repo_path).and_then(|repo| {
+                if abort {
+                    repo.abort_operation(operation)?;
+                } else {
+                    repo.continue_operation(operation)?;
+                }
+                Ok(repo)
+            });
+            let repo = match result {
+                Ok(repo) => repo,
+                Err(e) => {
+                    let verb = if abort { "abort" } else { "continue" };
+                    diff_view.update(cx, |view, cx| {
+                        view.set_error(
+                            format!("Failed to {verb} the {}: {e}", operation.label()),
+                            cx,
+                        );
+                    });
+                    return;
+                }
+            };
+            sb.set_data(SidebarData::load(&repo), cx);
+            sb.set_summary(repo.summary().ok(), cx);
+            let commits = repo.commits(COMMIT_LIMIT).unwrap_or_default();
+            let unpushed = repo.unpushed_oids().unwrap_or_default();
+            let line_counts = repo
+                .commit_line_counts("HEAD", COMMIT_LIMIT)
+                .unwrap_or_default();
+            let _ = this.update(cx, |view, cx| view.cancel_commit_load(cx));
+            commit_list.update(cx, |list, cx| {
+                list.set_commits(commits, cx);
+                list.set_unpushed(unpushed, cx);
+                list.set_line_counts(line_counts, cx);
+            });
+            diff_view.update(cx, |view, cx| view.set_diffs(vec![], cx));
+        });
+    }
+}
+
+/// `path` as of HEAD, as a diff of unchanged lines so the diff view can
+/// show it like any other file.
+fn file_at_head(repo: &Repository, path: &str) -> anyhow::Result<FileDiff> {
+    let head = repo.rev_parse("HEAD")?;
+    let size = repo.file_size_at(&head, path)?;
+    anyhow::ensure!(
+        size <= dd_git::repository::MAX_FULL_FILE_SIZE,
+        "{path} is too large to show ({size} bytes)"
+    );
+    let content = repo.file_content_at(&head, path)?;
+    let file = FileDiff {
+        path: path.to_string(),
+        old_path: None,
+        status: FileStatus::Modified,
+        hunks: Vec::new(),
+        lfs: None,
+        old_mode: None,
+        new_mode: None,
+        similarity: None,
+    };
+    Ok(dd_git::overlay_file_changes(&file, &content))
+}
+
+/// Everything the diff view shows for a selected commit.
+struct CommitDetails {
+    commit: CommitInfo,
+    signature: dd_git::SignatureStatus,
+    diffs: anyhow::Result<Vec<FileDiff>>,
+    /// Both versions of each changed image, by file index.
+    images: HashMap<usize, ImageVersions>,
+    branches: Vec<String>,
+    parent_subjects: Vec<String>,
+}
+
+/// Everything the diff view shows for uncommitted changes.
+struct WorkingChangesDetails {
+    diffs: Vec<FileDiff>,
+    /// LF and CRLF counts of each changed working-tree file, by path.
+    line_endings: HashMap<String, LineEndingStats>,
+    /// Paths excluded by ignore rules, when asked for.
+    ignored: Vec<String>,
+}
+
+/// Uncommitted changes plus, for unstaged ones, their line endings and
+/// (with `show_ignored`) the ignored files. Runs on the background
+/// executor.
+fn load_working_changes(
+    repo_path: &Path,
+    changes: WorkingChanges,
+    show_ignored: bool,
+) -> anyhow::Result<WorkingChangesDetails> {
+    let repo = Repository::open(repo_path)?;
+    let diffs = match changes {
+        WorkingChanges::Unstaged => repo.diff_workdir()?,
+        WorkingChanges::Staged => {
+            return Ok(WorkingChangesDetails {
+                diffs: repo.diff_staged()?,
+                line_endings: HashMap::new(),
+                ignored: Vec::new(),
+            })
+        }
+    };
+    let line_endings = diffs
+        .iter()
+        // Binary changes have no hunks and no lines to count.
+        .filter(|file| file.status != FileStatus::Deleted && !file.hunks.is_empty())
+        .filter_map(|file| {
+            let stats = repo.detect_mixed_line_endings(&file.path).ok()?;
+            Some((file.path.clone(), stats))
+        })
+        .collect();
+    let ignored = if show_ignored {
+        repo.ignored_files()?
+    } else {
+        Vec::new()
+    };
+    Ok(WorkingChangesDetails {
+        diffs,
+        line_endings,
+        ignored,
+    })
+}
+
+/// Gather a commit's diff and header details. Runs on the background
+/// executor; `cached` skips recomputing the diff.
+fn load_commit_details(
+    repo_path: &Path,
+    commit: CommitInfo,
+    cached: Option<Vec<FileDiff>>,
+    loader: DiffLoader,
+) -> Result<CommitDetails, RepoError> {
+    let repo = Repository::open(repo_path).map_err(|e| RepoError::classify(repo_path, &e))?;
+    let signature = repo
+        .commit_signature_status(&commit.oid)
+        .unwrap_or(dd_git::SignatureStatus::None);
+    let diffs = match cached {
+        Some(diffs) => Ok(diffs),
+        None => loader(&commit),
+    };
+    let images = diffs
+        .iter()
+        .flatten()
+        .enumerate()
+        .filter(|(_, file)| file.image_format().is_some() && file.hunks.is_empty())
+        .filter_map(
+            |(index, file)| match repo.image_versions(&commit.oid, file) {
+                Ok(versions) => Some((index, versions)),
+                Err(e) => {
+                    eprintln!("failed to load image {}: {e}", file.path);
+                    None
+                }
+            },
+        )
+        .collect();
+    let branches = repo.branches_containing(&commit.oid).unwrap_or_default();
+    let parent_subjects = if commit.parent_oids.len() > 1 {
+        commit
+            .parent_oids
+            .iter()
+            .map(|parent| {
+                repo.commits_for_ref(parent, 1)
+                    .ok()
+                    .and_then(|commits| commits.into_iter().next())
+                    .map(|c| c.subject)
+                    .unwrap_or_default()
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+    Ok(CommitDetails {
+        commit,
+        signature,
+        diffs,
+        images,
+        branches,
+        parent_subjects,
+    })
+}
+
+impl RepoView {
+    /// Narrow strip at the sidebar's edge that collapses or restores it.
+    fn render_sidebar_toggle(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let collapsed = self.sidebar_collapsed;
+        gpui::div()
+            .id("sidebar-toggle")
+            .debug_selector(|| "sidebar-toggle".into())
+            .flex_shrink_0()
+            .w(px(SIDEBAR_TOGGLE_WIDTH))
+            .h_full()
+            .flex()
+            .items_center()
+            .justify_center()
+            .text_xs()
+            .text_color(cx.theme().muted_foreground)
+            .cursor_pointer()
+            .hover(|el| el.bg(cx.theme().muted))
+            .on_click(cx.listener(|view, _event, window, cx| {
+                view.toggle_sidebar(window, cx);
+            }))
+            .child(if collapsed { "\u{203a}" } else { "\u{2039}" })
+    }
+
+    fn commit_filter_input(
+        &mut self,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Entity<InputState> {
+        if let Some((input, _)) = &self.commit_filter {
+            return input.clone();
+        }
+        let input = cx
+            .new(|cx| InputState::new(window, cx).placeholder("Filter by subject, author or OID"));
+        let subscription = cx.subscribe_in(&input, window, |view, input, event, _window, cx| {
+            if let InputEvent::Change = event {
+                let query = input.read(cx).value().to_string();
+                view.commit_list
+                    .update(cx, |list, cx| list.set_filter(query, cx));
+            }
+        });
+        self.commit_filter = Some((input.clone(), subscription));
+        input
+    }
+}
+
+impl Render for RepoView {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        self.apply_pending_selection(window, cx);
+        let filter_input = self.commit_filter_input(window, cx);
+        let commit_list_panel = resizable_panel()
+            .size(px(COMMIT_LIST_INITIAL_SIZE))
+            .size_range(px(COMMIT_LIST_MIN_SIZE)..px(COMMIT_LIST_MAX_SIZE))
+            .child(
+                gpui::div()
+                    .size_full()
+                    .flex()
+                    .flex_col()
+                    .child(
+                        gpui::div()
+                            .debug_selector(|| "commit-filter".into())
+                            .flex_shrink_0()
+                            .p_1()
+                            .border_b_1()
+                            .border_color(cx.theme().border)
+                            .child(Input::new(&filter_input)),
+                    )
+                    .child(
+                        gpui::div()
+                            .flex_1()
+                            .min_h_0()
+                            .child(self.commit_list.clone()),
+                    ),
+            );
+        let side_panels = if self.sidebar_collapsed {
+            gpui::div()
+                .flex_shrink_0()
+                .w(px(COMMIT_LIST_INITIAL_SIZE))
+                .h_full()
+                .child(h_resizable("left-panels-collapsed").child(commit_list_panel))
+        } else {
+            gpui::div()
+                .flex_shrink_0()
+                .w(px(SIDEBAR_INITIAL_SIZE + COMMIT_LIST_INITIAL_SIZE))
+                .h_full()
+                .child(
+                    h_resizable("left-panels")
+                        .child(
+                            resizable_panel()
+                                .size(px(SIDEBAR_INITIAL_SIZE))
+                                .size_range(px(SIDEBAR_MIN_SIZE)..px(SIDEBAR_MAX_SIZE))
+                                .child(
+                                    gpui::div()
+                                        .debug_selector(|| "repo-sidebar".into())
+                                        .size_full()
+                                        .child(self.sidebar.clone()),
+                                ),
+                        )
+                        .child(commit_list_panel),
+                )
+        };
+
+        gpui::div()
+            .size_full()
+            .flex()
+            .when(!self.diff_focused, |el| {
+                el.child(
+                    gpui::div()
+                        .debug_selector(|| "repo-side-panels".into())
+                        .flex_shrink_0()
+                        .h_full()
+                        .flex()
+                        .child(self.render_sidebar_toggle(cx))
+                        .child(side_panels),
+                )
+            })
+            .child(
+                gpui::div()
+                    .debug_selector(|| "repo-diff-pane".into())
+                    .flex_1()
+                    .min_w(px(MIN_DIFF_VIEW_WIDTH))
+                    .h_full()
+                    .child(self.diff_view.clone()),
+            )
+    }
+}
+
+/// Loads a commit's diff from the repository at `path`, ignoring
+/// whitespace as `whitespace` says.
+fn commit_diff_loader(path: PathBuf, whitespace: WhitespaceMode) -> DiffLoader {
+    Arc::new(move |commit: &CommitInfo| {
+        let repo = Repository::open(&path)?;
+        // Octopus merges read best against all parents at once.
+        if commit.parent_oids.len() > 2 {
+            Ok(repo
+                .diff_combined_with_whitespace(&commit.oid, whitespace)?
+                .iter()
+                .map(|f| f.simplified())
+                .collect())
+        } else {
+            repo.diff_commit_with_whitespace(&commit.oid, whitespace)
+        }
+    })
+}
+
+/// Compute `commits`' diffs on the background executor, one at a time,
+/// and store them in `cache` unless it was cleared in the meantime.
+fn prefetch_diffs(
+    commits: Vec<CommitInfo>,
+    loader: DiffLoader,
+    cache: Rc<RefCell<DiffCache>>,
+    cx: &mut Context<CommitList>,
+) -> Option<Task<()>> {
+    if commits.is_empty() {
+        return None;
+    }
+    let generation = cache.borrow().generation();
+    let task = cx.spawn(async move |_list, cx| {
+        for commit in commits {
+            let loader = loader.clone();
+            let oid = commit.oid.clone();
+            let result = cx
+                .background_executor()
+                .spawn(async move { loader(&commit) })
+                .await;
+            if let Ok(diffs) = result {
+                cache.borrow_mut().insert_at(generation, oid, diffs);
+            }
+        }
+    });
+    Some(task)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{init_test_repo, init_test_repo_with_changes, init_test_theme};
+    use gpui::{TestAppContext, VisualTestContext};
+
+    #[gpui::test]
+    fn test_repo_view_loads_branches(cx: &mut TestAppContext) {
+        cx.update(|cx| init_test_theme(cx));
+        let dir = init_test_repo();
+        let path = dir.path().to_path_buf();
+
+        let window = cx.add_window(|_window, cx| RepoView::new(path, cx));
+
+        window
+            .read_with(cx, |view, cx| {
+                let sidebar = view.sidebar().read(cx);
+                let data = sidebar.data();
+                assert!(
+                    data.branches.iter().any(|b| b.name == "main" && b.is_head),
+                    "expected 'main' branch with is_head == true"
+                );
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    fn test_filter_box_filters_commit_list(cx: &mut TestAppContext) {
+        cx.update(|cx| init_test_theme(cx));
+        let dir = init_test_repo_with_changes();
+        let path = dir.path().to_path_buf();
+
+        let (view, cx) = cx.add_window_view(|_window, cx| RepoView::new(path, cx));
+        cx.run_until_parked();
+        assert!(cx.debug_bounds("commit-filter").is_some());
+
+        view.update_in(cx, |view, window, cx| {
+            let (input, _) = view.commit_filter.as_ref().expect("filter box rendered");
+            input.update(cx, |input, cx| {
+                input.set_value("SECOND", window, cx);
+                cx.emit(InputEvent::Change);
+            });
+        });
+        cx.run_until_parked();
+
+        view.read_with(cx, |view, cx| {
+            let list = view.commit_list().read(cx);
+            assert_eq!(list.commits().len(), 2);
+            assert_eq!(list.visible_count(), 1);
+            assert_eq!(
+                list.visible_commit(0).map(|c| c.subject.as_str()),
+                Some("second commit")
+            );
+        });
+    }
+
+    #[gpui::test]
+    fn test_repo_view_loads_summary(cx: &mut TestAppContext) {
+        cx.update(|cx| init_test_theme(cx));
+        let dir = init_test_repo_with_changes();
+        let path = dir.path().to_path_buf();
+
+        let window = cx.add_window(|_window, cx| RepoView::new(path, cx));
+
+        window
+            .read_with(cx, |view, cx| {
+                let summary = view.sidebar().read(cx).summary().expect("summary loaded");
+                assert_eq!(summary.branch, "main");
+                assert_eq!(summary.commit_count, 2);
+                assert!(!summary.dirty);
+                assert_eq!(summary.stash_count, 0);
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    fn test_repo_view_unborn_head_shows_empty_state(cx: &mut TestAppContext) {
+        cx.update(|cx| init_test_theme(cx));
+        let dir = tempfile::TempDir::new().unwrap();
+        let status = std::process::Command::new("git")
+            .args(["init", "-b", "main"])
+            .current_dir(dir.path())
+            .status()
+            .unwrap();
+        assert!(status.success());
+        let path = dir.path().to_path_buf();
+
+        let window = cx.add_window(|_window, cx| RepoView::new(path, cx));
+
+        window
+            .read_with(cx, |view, cx| {
+                let commit_list = view.commit_list().read(cx);
+                assert!(commit_list.commits().is_empty());
+                assert_eq!(
+                    commit_list.empty_message(),
+                    Some("On branch main \u{2014} no commits yet")
+                );
+                let sidebar = view.sidebar().read(cx);
+                assert!(sidebar
+                    .data()
+                    .branches
+                    .iter()
+                    .any(|b| b.name == "main" && b.is_head));
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    fn test_repo_view_loads_commits(cx: &mut TestAppContext) {
+        cx.update(|cx| init_test_theme(cx));
+        let dir = init_test_repo_with_changes();
+        let path = dir.path().to_path_buf();
+
+        let window = cx.add_window(|_window, cx| RepoView::new(path, cx));
+        cx.run_until_parked();
 
         window
             .read_with(cx, |view, cx| {
@@ -266,6 +1800,88 @@ mod tests {
             .unwrap();
     }
 
+    /// Commit `count` empty commits on top of HEAD with one `git
+    /// fast-import`, rather than a process per commit.
+    fn add_empty_commits(path: &Path, count: usize) {
+        use std::io::Write;
+
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let mut stream = String::new();
+        for i in 1..=count {
+            let message = format!("commit {i}");
+            stream.push_str(&format!(
+                "commit refs/heads/main\ncommitter Test <test@test.com> {} +0000\ndata {}\n{message}\n",
+                now + i as u64,
+                message.len()
+            ));
+            if i == 1 {
+                stream.push_str("from refs/heads/main^0\n");
+            }
+        }
+        let mut import = std::process::Command::new("git")
+            .args(["fast-import", "--quiet"])
+            .current_dir(path)
+            .stdin(std::process::Stdio::piped())
+            .spawn()
+            .unwrap();
+        import
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(stream.as_bytes())
+            .unwrap();
+        assert!(import.wait().unwrap().success());
+    }
+
+    #[gpui::test]
+    fn test_commits_load_in_background(cx: &mut TestAppContext) {
+        cx.update(|cx| init_test_theme(cx));
+        let dir = init_test_repo();
+        add_empty_commits(dir.path(), 150);
+        let path = dir.path().to_path_buf();
+
+        // Not `add_window_view`, which would run the load to completion.
+        let window = cx.add_window(|_window, cx| RepoView::new(path, cx));
+        let view = window.root(cx).unwrap();
+        window
+            .read_with(cx, |view, cx| {
+                let list = view.commit_list().read(cx);
+                assert!(list.is_loading());
+                assert!(list.commits().is_empty());
+            })
+            .unwrap();
+
+        let cx = VisualTestContext::from_window(window.into(), cx).into_mut();
+        cx.run_until_parked();
+        view.read_with(cx, |view, cx| {
+            let list = view.commit_list().read(cx);
+            assert!(!list.is_loading());
+            assert_eq!(list.commits().len(), COMMIT_LIMIT);
+            assert_eq!(list.commits()[0].subject, "commit 150");
+        });
+
+        // Only the last of several quick selections reaches the diff view.
+        cx.update(|window, cx| {
+            let list = view.read(cx).commit_list().clone();
+            for index in [3, 2, 1] {
+                list.update(cx, |list, cx| list.select_commit(index, window, cx));
+            }
+        });
+        cx.run_until_parked();
+        view.read_with(cx, |view, cx| {
+            let shown = view
+                .diff_view()
+                .read(cx)
+                .commit_info()
+                .map(|c| c.subject.clone());
+            assert_eq!(shown.as_deref(), Some("commit 149"));
+            assert!(!view.diff_view().read(cx).is_loading());
+        });
+    }
+
     #[gpui::test]
     fn test_commit_selection_loads_diff(cx: &mut TestAppContext) {
         cx.update(|cx| init_test_theme(cx));
@@ -273,6 +1889,7 @@ mod tests {
         let path = dir.path().to_path_buf();
 
         let window = cx.add_window(|_window, cx| RepoView::new(path, cx));
+        cx.run_until_parked();
 
         // Select the first commit (most recent = "second commit")
         window
@@ -283,6 +1900,7 @@ mod tests {
                 });
             })
             .unwrap();
+        cx.run_until_parked();
 
         // Verify diff was loaded
         window
@@ -292,8 +1910,331 @@ mod tests {
                     !diff_view.diffs().is_empty(),
                     "expected non-empty diffs after selecting a commit"
                 );
+                // The commit header is filled in alongside the diff.
+                let commit = diff_view.commit_info().expect("commit header data");
+                assert_eq!(commit.subject, "second commit");
+                assert_eq!(
+                    diff_view.signature_status(),
+                    Some(dd_git::SignatureStatus::None)
+                );
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    fn test_adjacent_commit_diffs_are_prefetched(cx: &mut TestAppContext) {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        cx.update(|cx| init_test_theme(cx));
+        let dir = init_test_repo_with_changes();
+        let path = dir.path().to_path_buf();
+        let loads = Arc::new(AtomicUsize::new(0));
+
+        let window = cx.add_window(|_window, cx| RepoView::new(path, cx));
+        cx.run_until_parked();
+        let counter = loads.clone();
+        window
+            .update(cx, |view, window, cx| {
+                view.set_diff_loader(
+                    move |_commit| {
+                        counter.fetch_add(1, Ordering::SeqCst);
+                        Ok(Vec::new())
+                    },
+                    cx,
+                );
+                view.commit_list()
+                    .clone()
+                    .update(cx, |list, cx| list.select_commit(0, window, cx));
+            })
+            .unwrap();
+        cx.run_until_parked();
+        // The selected commit plus its one neighbour.
+        assert_eq!(loads.load(Ordering::SeqCst), 2);
+
+        window
+            .update(cx, |view, window, cx| {
+                view.commit_list()
+                    .clone()
+                    .update(cx, |list, cx| list.select_commit(1, window, cx));
+            })
+            .unwrap();
+        cx.run_until_parked();
+        assert_eq!(loads.load(Ordering::SeqCst), 2);
+        window
+            .read_with(cx, |view, cx| {
+                let list = view.commit_list().read(cx);
+                let shown = view
+                    .diff_view()
+                    .read(cx)
+                    .commit_info()
+                    .map(|c| c.oid.clone());
+                assert_eq!(shown, Some(list.commits()[1].oid.clone()));
+            })
+            .unwrap();
+
+        // Refreshing drops the cache, so the next visit recomputes.
+        window
+            .update(cx, |view, window, cx| {
+                view.refresh_status(cx);
+                view.commit_list()
+                    .clone()
+                    .update(cx, |list, cx| list.select_commit(0, window, cx));
+            })
+            .unwrap();
+        cx.run_until_parked();
+        assert_eq!(loads.load(Ordering::SeqCst), 4);
+    }
+
+    #[gpui::test]
+    fn test_loader_change_drops_pending_prefetch(cx: &mut TestAppContext) {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        cx.update(|cx| init_test_theme(cx));
+        let dir = init_test_repo_with_changes();
+        let path = dir.path().to_path_buf();
+        let stale_loads = Arc::new(AtomicUsize::new(0));
+
+        let window = cx.add_window(|_window, cx| RepoView::new(path, cx));
+        cx.run_until_parked();
+        let counter = stale_loads.clone();
+        window
+            .update(cx, |view, window, cx| {
+                view.set_diff_loader(
+                    move |_commit| {
+                        counter.fetch_add(1, Ordering::SeqCst);
+                        Ok(Vec::new())
+                    },
+                    cx,
+                );
+                view.commit_list()
+                    .clone()
+                    .update(cx, |list, cx| list.select_commit(0, window, cx));
             })
             .unwrap();
+        // The load and the neighbour's prefetch are queued but not yet run.
+        window
+            .update(cx, |view, _window, cx| {
+                view.set_diff_loader(|_commit| Ok(Vec::new()), cx);
+            })
+            .unwrap();
+        cx.run_until_parked();
+        assert_eq!(stale_loads.load(Ordering::SeqCst), 0);
+        window
+            .read_with(cx, |view, _cx| assert!(view.diff_cache.borrow().is_empty()))
+            .unwrap();
+    }
+
+    #[gpui::test]
+    fn test_checkout_files_from_selected_commit(cx: &mut TestAppContext) {
+        cx.update(|cx| init_test_theme(cx));
+        let dir = init_test_repo_with_changes();
+        let path = dir.path().to_path_buf();
+
+        let window = cx.add_window(|_window, cx| RepoView::new(path, cx));
+        cx.run_until_parked();
+
+        // The oldest commit wrote file.txt = "hello".
+        window
+            .update(cx, |view, window, cx| {
+                view.commit_list()
+                    .clone()
+                    .update(cx, |list, cx| list.select_commit(1, window, cx));
+                view.checkout_files_from_commit(window, cx);
+            })
+            .unwrap();
+
+        let file = dir.path().join("file.txt");
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "hello");
+        window
+            .read_with(cx, |view, cx| {
+                let summary = view.sidebar().read(cx).summary().expect("summary loaded");
+                assert!(summary.dirty);
+            })
+            .unwrap();
+
+        // Now file.txt has local changes: the commit list's action asks
+        // before overwriting them.
+        let list = window
+            .read_with(cx, |view, _cx| view.commit_list().clone())
+            .unwrap();
+        let check_out_newest = |cx: &mut TestAppContext| {
+            window
+                .update(cx, |_view, window, cx| {
+                    list.update(cx, |list, cx| {
+                        list.select_commit(0, window, cx);
+                        list.checkout_files(window, cx);
+                    });
+                })
+                .unwrap();
+            cx.run_until_parked();
+        };
+        check_out_newest(cx);
+        cx.simulate_prompt_answer("Cancel");
+        cx.run_until_parked();
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "hello");
+
+        check_out_newest(cx);
+        cx.simulate_prompt_answer("Overwrite");
+        cx.run_until_parked();
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "hello world");
+    }
+
+    #[gpui::test]
+    fn test_fetch_full_history_unshallows_clone(cx: &mut TestAppContext) {
+        cx.update(|cx| init_test_theme(cx));
+        let origin = init_test_repo_with_changes();
+        let clone = tempfile::TempDir::new().unwrap();
+        let source = format!("file://{}", origin.path().display());
+        let status = std::process::Command::new("git")
+            .args(["clone", "-q", "--depth", "1", &source, "."])
+            .current_dir(clone.path())
+            .status()
+            .unwrap();
+        assert!(status.success());
+        let path = clone.path().to_path_buf();
+
+        let (view, cx) = cx.add_window_view(|_window, cx| RepoView::new(path, cx));
+        cx.run_until_parked();
+        assert!(cx.debug_bounds("shallow-clone-banner").is_some());
+        view.read_with(cx, |view, cx| {
+            assert_eq!(view.commit_list().read(cx).commits().len(), 1);
+        });
+
+        let sidebar = view.read_with(cx, |view, _cx| view.sidebar().clone());
+        cx.update(|window, cx| {
+            sidebar.update(cx, |sb, cx| sb.fetch_full_history(window, cx));
+        });
+        cx.run_until_parked();
+
+        view.read_with(cx, |view, cx| {
+            let summary = view.sidebar().read(cx).summary().expect("summary loaded");
+            assert!(!summary.shallow);
+            assert_eq!(view.commit_list().read(cx).commits().len(), 2);
+        });
+    }
+
+    #[gpui::test]
+    fn test_upstream_is_picked_from_remote_branches(cx: &mut TestAppContext) {
+        cx.update(|cx| init_test_theme(cx));
+        let origin = init_test_repo_with_changes();
+        let clone = tempfile::TempDir::new().unwrap();
+        let source = origin.path().to_str().unwrap();
+        for (args, dir) in [
+            (vec!["branch", "topic"], origin.path()),
+            (vec!["clone", "-q", source, "."], clone.path()),
+        ] {
+            let status = std::process::Command::new("git")
+                .args(&args)
+                .current_dir(dir)
+                .status()
+                .unwrap();
+            assert!(status.success());
+        }
+        let path = clone.path().to_path_buf();
+
+        let (view, cx) = cx.add_window_view(|_window, cx| RepoView::new(path.clone(), cx));
+        cx.run_until_parked();
+        view.update_in(cx, |view, window, cx| {
+            view.pick_upstream("main", window, cx)
+        });
+        cx.simulate_prompt_answer("origin/topic");
+        cx.run_until_parked();
+
+        let branches = Repository::open(&path).unwrap().branches().unwrap();
+        let main = branches.iter().find(|b| b.name == "main").unwrap();
+        assert_eq!(main.upstream.as_deref(), Some("origin/topic"));
+        view.read_with(cx, |view, cx| {
+            let data = view.sidebar().read(cx).data();
+            let main = data.branches.iter().find(|b| b.name == "main").unwrap();
+            assert_eq!(main.upstream.as_deref(), Some("origin/topic"));
+        });
+    }
+
+    #[gpui::test]
+    fn test_merge_in_progress_banner_aborts_merge(cx: &mut TestAppContext) {
+        cx.update(|cx| init_test_theme(cx));
+        let dir = init_test_repo();
+        let p = dir.path();
+        let git = |args: &[&str]| {
+            std::process::Command::new("git")
+                .args(args)
+                .current_dir(p)
+                .output()
+                .unwrap()
+        };
+        git(&["checkout", "-q", "-b", "other"]);
+        std::fs::write(p.join("file.txt"), "other").unwrap();
+        git(&["commit", "-qam", "other"]);
+        git(&["checkout", "-q", "main"]);
+        std::fs::write(p.join("file.txt"), "main").unwrap();
+        git(&["commit", "-qam", "main"]);
+        assert!(!git(&["merge", "other"]).status.success());
+        let path = p.to_path_buf();
+
+        let (view, cx) = cx.add_window_view(|_window, cx| RepoView::new(path, cx));
+        cx.run_until_parked();
+        assert!(cx.debug_bounds("operation-banner").is_some());
+        assert!(cx.debug_bounds("operation-continue").is_some());
+
+        let abort = cx.debug_bounds("operation-abort").expect("abort action");
+        cx.simulate_click(abort.center(), gpui::Modifiers::none());
+        cx.run_until_parked();
+
+        // The banner follows the summary; its stale debug bounds would not.
+        view.read_with(cx, |view, cx| {
+            let summary = view.sidebar().read(cx).summary().expect("summary loaded");
+            assert_eq!(summary.operation, None);
+            assert!(!summary.dirty);
+        });
+    }
+
+    #[gpui::test]
+    fn test_diff_focus_hides_side_panels(cx: &mut TestAppContext) {
+        cx.update(|cx| init_test_theme(cx));
+        let dir = init_test_repo();
+        let path = dir.path().to_path_buf();
+
+        let (view, cx) = cx.add_window_view(|_window, cx| RepoView::new(path, cx));
+        cx.run_until_parked();
+        assert!(cx.debug_bounds("repo-side-panels").is_some());
+        assert!(cx.debug_bounds("repo-diff-pane").unwrap().origin.x > px(0.));
+
+        // The diff pane is drawn in every frame, so its bounds are current.
+        view.update(cx, |view, cx| view.toggle_diff_focus(cx));
+        cx.run_until_parked();
+        assert!(view.read_with(cx, |view, _cx| view.is_diff_focused()));
+        assert_eq!(cx.debug_bounds("repo-diff-pane").unwrap().origin.x, px(0.));
+
+        view.update(cx, |view, cx| view.toggle_diff_focus(cx));
+        cx.run_until_parked();
+        assert!(cx.debug_bounds("repo-diff-pane").unwrap().origin.x > px(0.));
+    }
+
+    #[gpui::test]
+    fn test_sidebar_collapse_hides_sidebar_only(cx: &mut TestAppContext) {
+        cx.update(|cx| init_test_theme(cx));
+        let dir = init_test_repo();
+        let path = dir.path().to_path_buf();
+
+        let (view, cx) = cx.add_window_view(|_window, cx| RepoView::new(path, cx));
+        cx.run_until_parked();
+        assert!(cx.debug_bounds("repo-sidebar").is_some());
+        let expanded = cx.debug_bounds("repo-side-panels").unwrap();
+
+        view.update(cx, |view, cx| view.set_sidebar_collapsed(true, cx));
+        cx.run_until_parked();
+        assert!(view.read_with(cx, |view, _cx| view.is_sidebar_collapsed()));
+        let collapsed = cx.debug_bounds("repo-side-panels").unwrap();
+        assert_eq!(
+            collapsed.size.width,
+            px(SIDEBAR_TOGGLE_WIDTH + COMMIT_LIST_INITIAL_SIZE)
+        );
+        assert!(collapsed.size.width < expanded.size.width);
+        assert!(cx.debug_bounds("sidebar-toggle").is_some());
+
+        view.update(cx, |view, cx| view.set_sidebar_collapsed(false, cx));
+        cx.run_until_parked();
+        assert_eq!(cx.debug_bounds("repo-side-panels").unwrap(), expanded);
     }
 
     #[gpui::test]
@@ -316,4 +2257,199 @@ mod tests {
             })
             .unwrap();
     }
+
+    #[gpui::test]
+    fn test_hunk_shortcuts_stage_and_unstage_working_changes(cx: &mut TestAppContext) {
+        cx.update(|cx| init_test_theme(cx));
+        let dir = init_test_repo_with_changes();
+        let path = dir.path().to_path_buf();
+        // `git apply` needs the hunk to end in a newline on both sides.
+        std::fs::write(dir.path().join("file.txt"), "hello world\n").unwrap();
+        let status = std::process::Command::new("git")
+            .args(["commit", "-qam", "newline"])
+            .current_dir(dir.path())
+            .status()
+            .unwrap();
+        assert!(status.success());
+        std::fs::write(dir.path().join("file.txt"), "hello there\n").unwrap();
+
+        let window = cx.add_window(|_window, cx| RepoView::new(path.clone(), cx));
+        cx.run_until_parked();
+
+        let apply = |changes: WorkingChanges, action: HunkAction, cx: &mut TestAppContext| {
+            window
+                .update(cx, |view, _window, cx| {
+                    view.show_working_changes(changes, cx)
+                })
+                .unwrap();
+            cx.run_until_parked();
+            window
+                .update(cx, |view, window, cx| {
+                    view.diff_view().clone().update(cx, |diff_view, cx| {
+                        assert_eq!(diff_view.diffs().len(), 1);
+                        diff_view.focus_hunk(0, 0, cx);
+                        diff_view.apply_to_focused_hunk(action, window, cx);
+                    });
+                })
+                .unwrap();
+            cx.run_until_parked();
+        };
+
+        apply(WorkingChanges::Unstaged, HunkAction::Stage, cx);
+        let repo = Repository::open(&path).unwrap();
+        assert_eq!(repo.diff_staged().unwrap().len(), 1);
+        window
+            .read_with(cx, |view, cx| {
+                let diff_view = view.diff_view().read(cx);
+                assert_eq!(diff_view.working_changes(), Some(WorkingChanges::Unstaged));
+                assert!(diff_view.diffs().is_empty());
+            })
+            .unwrap();
+
+        apply(WorkingChanges::Staged, HunkAction::Unstage, cx);
+        assert!(repo.diff_staged().unwrap().is_empty());
+        assert_eq!(repo.diff_workdir().unwrap().len(), 1);
+
+        // Discarding asks first and leaves the change alone when cancelled.
+        apply(WorkingChanges::Unstaged, HunkAction::Discard, cx);
+        cx.simulate_prompt_answer("Cancel");
+        cx.run_until_parked();
+        assert_eq!(repo.diff_workdir().unwrap().len(), 1);
+
+        apply(WorkingChanges::Unstaged, HunkAction::Discard, cx);
+        cx.simulate_prompt_answer("Discard");
+        cx.run_until_parked();
+        assert!(repo.diff_workdir().unwrap().is_empty());
+    }
+
+    #[gpui::test]
+    fn test_stash_selected_shelves_only_picked_files(cx: &mut TestAppContext) {
+        cx.update(|cx| init_test_theme(cx));
+        let dir = init_test_repo_with_changes();
+        let path = dir.path().to_path_buf();
+        std::fs::write(dir.path().join("notes.txt"), "one\n").unwrap();
+        for args in [&["add", "notes.txt"][..], &["commit", "-qm", "notes"]] {
+            let status = std::process::Command::new("git")
+                .args(args)
+                .current_dir(dir.path())
+                .status()
+                .unwrap();
+            assert!(status.success());
+        }
+        std::fs::write(dir.path().join("file.txt"), "hello there\n").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "two\n").unwrap();
+
+        let window = cx.add_window(|_window, cx| RepoView::new(path.clone(), cx));
+        cx.run_until_parked();
+        window
+            .update(cx, |view, _window, cx| {
+                view.show_working_changes(WorkingChanges::Unstaged, cx)
+            })
+            .unwrap();
+        cx.run_until_parked();
+        window
+            .update(cx, |view, window, cx| {
+                view.diff_view().clone().update(cx, |diff_view, cx| {
+                    let notes = diff_view
+                        .diffs()
+                        .iter()
+                        .position(|d| d.path == "notes.txt")
+                        .unwrap();
+                    diff_view.toggle_file_selected(notes, cx);
+                    diff_view.stash_selected_files(window, cx);
+                });
+            })
+            .unwrap();
+        cx.run_until_parked();
+
+        let repo = Repository::open(&path).unwrap();
+        assert_eq!(repo.stashes().unwrap().len(), 1);
+        let left: Vec<_> = repo
+            .diff_workdir()
+            .unwrap()
+            .into_iter()
+            .map(|d| d.path)
+            .collect();
+        assert_eq!(left, vec!["file.txt".to_string()]);
+        window
+            .read_with(cx, |view, cx| {
+                assert_eq!(view.sidebar().read(cx).data().stashes.len(), 1);
+                let diff_view = view.diff_view().read(cx);
+                assert_eq!(diff_view.diffs().len(), 1);
+                assert!(diff_view.selected_paths().is_empty());
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    fn test_show_ignored_lists_excluded_files(cx: &mut TestAppContext) {
+        cx.update(|cx| init_test_theme(cx));
+        let dir = init_test_repo_with_changes();
+        let path = dir.path().to_path_buf();
+        std::fs::write(dir.path().join(".git/info/exclude"), "*.log\n").unwrap();
+        std::fs::write(dir.path().join("debug.log"), "noise\n").unwrap();
+
+        let window = cx.add_window(|_window, cx| RepoView::new(path, cx));
+        cx.run_until_parked();
+        window
+            .update(cx, |view, _window, cx| {
+                view.show_working_changes(WorkingChanges::Unstaged, cx)
+            })
+            .unwrap();
+        cx.run_until_parked();
+        let ignored = |cx: &mut TestAppContext| {
+            window
+                .read_with(cx, |view, cx| {
+                    let diff_view = view.diff_view().read(cx);
+                    assert!(diff_view.diffs().is_empty());
+                    diff_view.ignored_files().to_vec()
+                })
+                .unwrap()
+        };
+        assert!(ignored(cx).is_empty());
+
+        let toggle = |cx: &mut TestAppContext| {
+            window
+                .update(cx, |view, window, cx| {
+                    view.diff_view().clone().update(cx, |diff_view, cx| {
+                        diff_view.toggle_show_ignored(window, cx)
+                    });
+                })
+                .unwrap();
+            cx.run_until_parked();
+        };
+        toggle(cx);
+        assert_eq!(ignored(cx), vec!["debug.log".to_string()]);
+        toggle(cx);
+        assert!(ignored(cx).is_empty());
+    }
+
+    #[gpui::test]
+    fn test_line_endings_are_counted_in_the_working_copy(cx: &mut TestAppContext) {
+        cx.update(|cx| init_test_theme(cx));
+        let dir = init_test_repo_with_changes();
+        let path = dir.path().to_path_buf();
+        std::fs::write(dir.path().join("file.txt"), "hello\r\nworld\n").unwrap();
+
+        let window = cx.add_window(|_window, cx| RepoView::new(path, cx));
+        cx.run_until_parked();
+
+        for (changes, warned) in [
+            (WorkingChanges::Unstaged, true),
+            (WorkingChanges::Staged, false),
+        ] {
+            window
+                .update(cx, |view, _window, cx| {
+                    view.show_working_changes(changes, cx)
+                })
+                .unwrap();
+            cx.run_until_parked();
+            window
+                .read_with(cx, |view, cx| {
+                    let stats = view.diff_view().read(cx).mixed_line_endings("file.txt");
+                    assert_eq!(stats.is_some(), warned);
+                })
+                .unwrap();
+        }
+    }
 }
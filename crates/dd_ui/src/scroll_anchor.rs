@@ -0,0 +1,139 @@
+/// A row as it was last laid out. `top` is in content coordinates: pixels
+/// below the top of the scrolled content, whatever the scroll position.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LaidOutRow {
+    pub path: String,
+    /// New-side line of the row, or `None` for the file header.
+    pub new_line_no: Option<u32>,
+    pub top: f32,
+}
+
+/// A diff row pinned by file and new-side line number, so the same text can
+/// be found again after the diff is recomputed with different context.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Anchor {
+    pub path: String,
+    /// New-side line of the anchored row, or `None` for the file header.
+    pub new_line_no: Option<u32>,
+    /// How far below the top of the anchored row the offset lies.
+    pub offset_in_row: f32,
+}
+
+/// Anchor the last row starting at or above `scroll_offset`, or the first
+/// row when the offset is above them all. `None` when nothing is laid out.
+pub fn find_anchor(rows: &[LaidOutRow], scroll_offset: f32) -> Option<Anchor> {
+    let above = rows
+        .iter()
+        .filter(|row| row.top <= scroll_offset)
+        .max_by(|a, b| a.top.total_cmp(&b.top));
+    let row = above.or_else(|| rows.iter().min_by(|a, b| a.top.total_cmp(&b.top)))?;
+    Some(Anchor {
+        path: row.path.clone(),
+        new_line_no: row.new_line_no,
+        offset_in_row: scroll_offset - row.top,
+    })
+}
+
+/// Scroll offset that puts `anchor` back where [`find_anchor`] found it.
+/// When its line is no longer laid out the closest line above it in the
+/// same file is used, then the file's header. `None` when the file is gone.
+pub fn offset_for_anchor(rows: &[LaidOutRow], anchor: &Anchor) -> Option<f32> {
+    rows.iter()
+        .filter(|row| row.path == anchor.path)
+        .filter(|row| match (row.new_line_no, anchor.new_line_no) {
+            (None, _) => true,
+            (Some(line), Some(target)) => line <= target,
+            (Some(_), None) => false,
+        })
+        // `None` sorts first, so any remaining line beats the header.
+        .max_by_key(|row| row.new_line_no)
+        .map(|row| row.top + anchor.offset_in_row)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(path: &str, new_line_no: Option<u32>, top: f32) -> LaidOutRow {
+        LaidOutRow {
+            path: path.into(),
+            new_line_no,
+            top,
+        }
+    }
+
+    /// Two files each showing lines 19 to 21; the gaps stand in for hunk
+    /// headers and deleted lines, which are not anchors.
+    fn rows() -> Vec<LaidOutRow> {
+        vec![
+            row("a.rs", None, 0.0),
+            row("a.rs", Some(19), 50.0),
+            row("a.rs", Some(20), 82.0),
+            row("a.rs", Some(21), 98.0),
+            row("b.rs", None, 122.0),
+            row("b.rs", Some(19), 172.0),
+            row("b.rs", Some(20), 204.0),
+            row("b.rs", Some(21), 220.0),
+        ]
+    }
+
+    #[test]
+    fn test_anchor_round_trips_on_the_same_layout() {
+        let rows = rows();
+        for offset in [0.0, 10.0, 60.0, 95.0, 150.0, 230.0] {
+            let anchor = find_anchor(&rows, offset).expect("anchor");
+            assert_eq!(
+                offset_for_anchor(&rows, &anchor),
+                Some(offset),
+                "{anchor:?}"
+            );
+        }
+        // Inside the deleted line, the context line above is the anchor.
+        let anchor = find_anchor(&rows, 70.0).unwrap();
+        assert_eq!(anchor.path, "a.rs");
+        assert_eq!(anchor.new_line_no, Some(19));
+        assert!(find_anchor(&[], 0.0).is_none());
+    }
+
+    #[test]
+    fn test_anchor_follows_its_row_to_a_new_layout() {
+        let before = rows();
+        // The viewport top sits 5px into b.rs's added line 20.
+        let anchor = find_anchor(&before, 209.0).unwrap();
+        assert_eq!(anchor.path, "b.rs");
+        assert_eq!(anchor.new_line_no, Some(20));
+        assert_eq!(anchor.offset_in_row, 5.0);
+
+        // Ten rows revealed in a.rs push b.rs down.
+        let after: Vec<_> = before
+            .iter()
+            .map(|r| {
+                let shift = if r.path == "b.rs" { 160.0 } else { 0.0 };
+                row(&r.path, r.new_line_no, r.top + shift)
+            })
+            .collect();
+        assert_eq!(offset_for_anchor(&after, &anchor), Some(369.0));
+        assert_eq!(find_anchor(&after, 369.0), Some(anchor));
+    }
+
+    #[test]
+    fn test_missing_line_falls_back_within_file() {
+        let rows = rows();
+        let anchor = Anchor {
+            path: "a.rs".into(),
+            new_line_no: Some(30),
+            offset_in_row: 0.0,
+        };
+        assert_eq!(offset_for_anchor(&rows, &anchor), Some(98.0));
+
+        // A collapsed file lays out only its header.
+        let collapsed = vec![row("a.rs", None, 0.0)];
+        assert_eq!(offset_for_anchor(&collapsed, &anchor), Some(0.0));
+
+        let gone = Anchor {
+            path: "deleted.rs".into(),
+            ..anchor
+        };
+        assert_eq!(offset_for_anchor(&rows, &gone), None);
+    }
+}
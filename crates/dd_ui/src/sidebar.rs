@@ -2,25 +2,41 @@ use std::collections::{HashMap, HashSet};
 use std::time::Duration;
 
 use gpui::prelude::*;
-use gpui::{ease_in_out, Animation, AnimationExt, ClickEvent, Context, Window};
-use gpui_component::{h_flex, scroll::ScrollableElement, v_flex, ActiveTheme};
-
-use dd_git::{BranchInfo, RemoteInfo, StashInfo, TagInfo};
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub enum SidebarGroup {
-    Branches,
-    Remotes,
-    Tags,
-    Stashes,
-    Submodules,
+use gpui::{
+    ease_in_out, Animation, AnimationExt, ClickEvent, Context, MouseButton, MouseDownEvent,
+    SharedString, Window,
+};
+use gpui_component::{h_flex, scroll::ScrollableElement, tooltip::Tooltip, v_flex, ActiveTheme};
+
+pub use dd_core::SidebarGroup;
+use dd_git::{
+    BranchInfo, OperationInProgress, RemoteInfo, RepoSummary, Repository, StashInfo, SubmoduleInfo,
+    TagInfo, TreeEntry,
+};
+
+/// Tree paths of remote-tracking branches start with this segment so they
+/// never share collapse state or element ids with local branch folders. The
+/// colon can't appear in a ref name, so no local branch path starts with it.
+const REMOTE_TREE_ROOT: &str = ":remotes";
+
+/// Whether a branch tree path belongs to the remote tree.
+fn is_remote_tree_path(path: &str) -> bool {
+    path.strip_prefix(REMOTE_TREE_ROOT)
+        .is_some_and(|rest| rest.starts_with('/'))
 }
 
 pub struct SidebarData {
     pub branches: Vec<BranchInfo>,
     pub remotes: Vec<RemoteInfo>,
+    /// Remote-tracking branches of every remote, named like `origin/main`.
+    pub remote_branches: Vec<BranchInfo>,
     pub tags: Vec<TagInfo>,
+    /// Messages of annotated tags, by tag name.
+    pub tag_messages: HashMap<String, String>,
     pub stashes: Vec<StashInfo>,
+    pub submodules: Vec<SubmoduleInfo>,
+    /// Top level of HEAD's tree, for the file browser.
+    pub files: Vec<TreeEntry>,
 }
 
 impl SidebarData {
@@ -28,12 +44,56 @@ impl SidebarData {
         Self {
             branches: Vec::new(),
             remotes: Vec::new(),
+            remote_branches: Vec::new(),
             tags: Vec::new(),
+            tag_messages: HashMap::new(),
             stashes: Vec::new(),
+            submodules: Vec::new(),
+            files: Vec::new(),
+        }
+    }
+
+    /// Read everything the sidebar shows from `repo`. Lists that fail to
+    /// load are left empty.
+    pub fn load(repo: &Repository) -> Self {
+        let remotes = repo.remotes().unwrap_or_default();
+        let remote_branches = remotes
+            .iter()
+            .flat_map(|remote| repo.remote_branches(&remote.name).unwrap_or_default())
+            .collect();
+        let tags = repo.tags().unwrap_or_default();
+        let tag_messages = tags
+            .iter()
+            .filter_map(|tag| {
+                let message = repo.tag_message(&tag.name).ok().flatten()?;
+                Some((tag.name.clone(), message))
+            })
+            .collect();
+        Self {
+            branches: repo.branches().unwrap_or_default(),
+            remotes,
+            remote_branches,
+            tags,
+            tag_messages,
+            stashes: repo.stashes().unwrap_or_default(),
+            submodules: repo.submodules().unwrap_or_default(),
+            files: repo
+                .rev_parse("HEAD")
+                .and_then(|head| repo.tree_entries(&head, ""))
+                .unwrap_or_default(),
         }
     }
 }
 
+/// Path of `name` inside directory `parent` (`""` for the root).
+fn child_path(parent: &str, name: &str) -> String {
+    if parent.is_empty() {
+        name.to_string()
+    } else {
+        format!("{parent}/{name}")
+    }
+}
+
 #[derive(Debug, Clone)]
 struct BranchTreeNode {
     segment: String,
@@ -55,6 +115,20 @@ impl BranchTreeNode {
         roots
     }
 
+    /// Like [`build`](Self::build), with every path under
+    /// [`REMOTE_TREE_ROOT`]. The roots are the remotes.
+    fn build_remote(branches: &[BranchInfo]) -> Vec<BranchTreeNode> {
+        fn prefix(nodes: &mut [BranchTreeNode]) {
+            for node in nodes {
+                node.path = format!("{REMOTE_TREE_ROOT}/{}", node.path);
+                prefix(&mut node.children);
+            }
+        }
+        let mut roots = Self::build(branches);
+        prefix(&mut roots);
+        roots
+    }
+
     fn insert(
         nodes: &mut Vec<BranchTreeNode>,
         segments: &[&str],
@@ -115,9 +189,38 @@ pub struct Sidebar {
     data: SidebarData,
     collapsed: HashMap<SidebarGroup, bool>,
     branch_tree: Vec<BranchTreeNode>,
+    remote_tree: Vec<BranchTreeNode>,
     collapsed_folders: HashSet<String>,
+    summary: Option<RepoSummary>,
+    show_files: bool,
+    /// Expanded directories of the file browser and their entries, by path.
+    open_dirs: HashMap<String, Vec<TreeEntry>>,
     #[allow(clippy::type_complexity)]
     on_branch_checkout: Option<Box<dyn Fn(&BranchInfo, &mut Window, &mut Context<Self>) + 'static>>,
+    #[allow(clippy::type_complexity)]
+    on_branch_select: Option<Box<dyn Fn(&BranchInfo, &mut Window, &mut Context<Self>) + 'static>>,
+    #[allow(clippy::type_complexity)]
+    on_branch_compare: Option<Box<dyn Fn(&BranchInfo, &mut Window, &mut Context<Self>) + 'static>>,
+    #[allow(clippy::type_complexity)]
+    on_branch_set_upstream:
+        Option<Box<dyn Fn(&BranchInfo, &mut Window, &mut Context<Self>) + 'static>>,
+    #[allow(clippy::type_complexity)]
+    on_remote_menu: Option<Box<dyn Fn(&RemoteInfo, &mut Window, &mut Context<Self>) + 'static>>,
+    #[allow(clippy::type_complexity)]
+    on_fetch_full_history: Option<Box<dyn Fn(&mut Window, &mut Context<Self>) + 'static>>,
+    #[allow(clippy::type_complexity)]
+    on_abort_operation:
+        Option<Box<dyn Fn(OperationInProgress, &mut Window, &mut Context<Self>) + 'static>>,
+    #[allow(clippy::type_complexity)]
+    on_continue_operation:
+        Option<Box<dyn Fn(OperationInProgress, &mut Window, &mut Context<Self>) + 'static>>,
+    #[allow(clippy::type_complexity)]
+    on_dir_open: Option<Box<dyn Fn(&str, &mut Window, &mut Context<Self>) + 'static>>,
+    #[allow(clippy::type_complexity)]
+    on_file_open: Option<Box<dyn Fn(&str, &mut Window, &mut Context<Self>) + 'static>>,
+    #[allow(clippy::type_complexity)]
+    on_collapse_change:
+        Option<Box<dyn Fn(&[SidebarGroup], &[String], &mut Window, &mut Context<Self>) + 'static>>,
 }
 
 impl Sidebar {
@@ -126,8 +229,22 @@ impl Sidebar {
             data: SidebarData::empty(),
             collapsed: HashMap::new(),
             branch_tree: Vec::new(),
+            remote_tree: Vec::new(),
             collapsed_folders: HashSet::new(),
+            summary: None,
+            show_files: false,
+            open_dirs: HashMap::new(),
             on_branch_checkout: None,
+            on_branch_select: None,
+            on_branch_compare: None,
+            on_branch_set_upstream: None,
+            on_remote_menu: None,
+            on_fetch_full_history: None,
+            on_abort_operation: None,
+            on_continue_operation: None,
+            on_dir_open: None,
+            on_file_open: None,
+            on_collapse_change: None,
         }
     }
 
@@ -147,10 +264,22 @@ impl Sidebar {
 
     pub fn set_data(&mut self, data: SidebarData, cx: &mut Context<Self>) {
         self.branch_tree = BranchTreeNode::build(&data.branches);
+        self.remote_tree = BranchTreeNode::build_remote(&data.remote_branches);
+        // Directory listings may be stale once HEAD moves.
+        self.open_dirs.clear();
         self.data = data;
         cx.notify();
     }
 
+    pub fn summary(&self) -> Option<&RepoSummary> {
+        self.summary.as_ref()
+    }
+
+    pub fn set_summary(&mut self, summary: Option<RepoSummary>, cx: &mut Context<Self>) {
+        self.summary = summary;
+        cx.notify();
+    }
+
     pub fn toggle_folder(&mut self, path: String, cx: &mut Context<Self>) {
         if self.collapsed_folders.contains(&path) {
             self.collapsed_folders.remove(&path);
@@ -164,6 +293,58 @@ impl Sidebar {
         self.collapsed_folders.contains(path)
     }
 
+    /// Collapsed groups in a stable order, for saving with the session.
+    pub fn collapsed_groups(&self) -> Vec<SidebarGroup> {
+        let mut groups: Vec<SidebarGroup> = self
+            .collapsed
+            .iter()
+            .filter(|(_, collapsed)| **collapsed)
+            .map(|(group, _)| *group)
+            .collect();
+        groups.sort();
+        groups
+    }
+
+    /// Collapsed branch folders in a stable order, for saving with the
+    /// session.
+    pub fn collapsed_folders(&self) -> Vec<String> {
+        let mut folders: Vec<String> = self.collapsed_folders.iter().cloned().collect();
+        folders.sort();
+        folders
+    }
+
+    /// Restore collapse state saved with the session.
+    pub fn set_collapse_state(
+        &mut self,
+        groups: &[SidebarGroup],
+        folders: &[String],
+        cx: &mut Context<Self>,
+    ) {
+        self.collapsed = groups.iter().map(|group| (*group, true)).collect();
+        self.collapsed_folders = folders.iter().cloned().collect();
+        cx.notify();
+    }
+
+    /// Called with the collapsed groups and folders after the user folds
+    /// or unfolds one.
+    pub fn on_collapse_change(
+        &mut self,
+        callback: impl Fn(&[SidebarGroup], &[String], &mut Window, &mut Context<Self>) + 'static,
+    ) {
+        self.on_collapse_change = Some(Box::new(callback));
+    }
+
+    fn collapse_changed(&self, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(ref on_change) = self.on_collapse_change {
+            on_change(
+                &self.collapsed_groups(),
+                &self.collapsed_folders(),
+                window,
+                cx,
+            );
+        }
+    }
+
     pub fn on_branch_checkout(
         &mut self,
         callback: impl Fn(&BranchInfo, &mut Window, &mut Context<Self>) + 'static,
@@ -171,6 +352,135 @@ impl Sidebar {
         self.on_branch_checkout = Some(Box::new(callback));
     }
 
+    /// Called when a branch is single-clicked to browse its history.
+    pub fn on_branch_select(
+        &mut self,
+        callback: impl Fn(&BranchInfo, &mut Window, &mut Context<Self>) + 'static,
+    ) {
+        self.on_branch_select = Some(Box::new(callback));
+    }
+
+    /// Called when a branch is alt-clicked to compare it with the selected
+    /// commit.
+    pub fn on_branch_compare(
+        &mut self,
+        callback: impl Fn(&BranchInfo, &mut Window, &mut Context<Self>) + 'static,
+    ) {
+        self.on_branch_compare = Some(Box::new(callback));
+    }
+
+    /// Called when a branch is right-clicked to (re)point its upstream.
+    pub fn on_branch_set_upstream(
+        &mut self,
+        callback: impl Fn(&BranchInfo, &mut Window, &mut Context<Self>) + 'static,
+    ) {
+        self.on_branch_set_upstream = Some(Box::new(callback));
+    }
+
+    /// Called when a remote is right-clicked to edit or remove it.
+    pub fn on_remote_menu(
+        &mut self,
+        callback: impl Fn(&RemoteInfo, &mut Window, &mut Context<Self>) + 'static,
+    ) {
+        self.on_remote_menu = Some(Box::new(callback));
+    }
+
+    /// Called when the user asks to download the rest of a shallow clone.
+    pub fn on_fetch_full_history(
+        &mut self,
+        callback: impl Fn(&mut Window, &mut Context<Self>) + 'static,
+    ) {
+        self.on_fetch_full_history = Some(Box::new(callback));
+    }
+
+    pub fn fetch_full_history(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(ref on_fetch) = self.on_fetch_full_history {
+            on_fetch(window, cx);
+        }
+    }
+
+    /// Called when the user aborts the merge, rebase, etc. in progress.
+    pub fn on_abort_operation(
+        &mut self,
+        callback: impl Fn(OperationInProgress, &mut Window, &mut Context<Self>) + 'static,
+    ) {
+        self.on_abort_operation = Some(Box::new(callback));
+    }
+
+    /// Called when the user continues the merge, rebase, etc. in progress.
+    pub fn on_continue_operation(
+        &mut self,
+        callback: impl Fn(OperationInProgress, &mut Window, &mut Context<Self>) + 'static,
+    ) {
+        self.on_continue_operation = Some(Box::new(callback));
+    }
+
+    pub fn abort_operation(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let operation = self.summary.as_ref().and_then(|s| s.operation);
+        if let (Some(operation), Some(on_abort)) = (operation, &self.on_abort_operation) {
+            on_abort(operation, window, cx);
+        }
+    }
+
+    pub fn continue_operation(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let operation = self.summary.as_ref().and_then(|s| s.operation);
+        if let (Some(operation), Some(on_continue)) = (operation, &self.on_continue_operation) {
+            on_continue(operation, window, cx);
+        }
+    }
+
+    pub fn set_show_files(&mut self, show: bool, cx: &mut Context<Self>) {
+        self.show_files = show;
+        cx.notify();
+    }
+
+    /// Called with a directory's path when it is expanded in the file
+    /// browser; the owner answers with [`Self::set_dir_entries`].
+    pub fn on_dir_open(
+        &mut self,
+        callback: impl Fn(&str, &mut Window, &mut Context<Self>) + 'static,
+    ) {
+        self.on_dir_open = Some(Box::new(callback));
+    }
+
+    /// Called with a file's path when it is clicked in the file browser.
+    pub fn on_file_open(
+        &mut self,
+        callback: impl Fn(&str, &mut Window, &mut Context<Self>) + 'static,
+    ) {
+        self.on_file_open = Some(Box::new(callback));
+    }
+
+    /// Show `entries` under the expanded directory `dir`.
+    pub fn set_dir_entries(
+        &mut self,
+        dir: String,
+        entries: Vec<TreeEntry>,
+        cx: &mut Context<Self>,
+    ) {
+        self.open_dirs.insert(dir, entries);
+        cx.notify();
+    }
+
+    pub fn is_dir_open(&self, dir: &str) -> bool {
+        self.open_dirs.contains_key(dir)
+    }
+
+    /// Collapse `dir` if it is expanded, otherwise ask for its entries.
+    pub fn toggle_dir(&mut self, dir: &str, window: &mut Window, cx: &mut Context<Self>) {
+        if self.open_dirs.remove(dir).is_some() {
+            cx.notify();
+        } else if let Some(ref on_open) = self.on_dir_open {
+            on_open(dir, window, cx);
+        }
+    }
+
+    pub fn open_file(&mut self, path: &str, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(ref on_open) = self.on_file_open {
+            on_open(path, window, cx);
+        }
+    }
+
     fn render_section(
         &self,
         group: SidebarGroup,
@@ -195,8 +505,9 @@ impl Sidebar {
                     .cursor_pointer()
                     .text_xs()
                     .text_color(cx.theme().muted_foreground)
-                    .on_click(cx.listener(move |view, _event, _window, cx| {
+                    .on_click(cx.listener(move |view, _event, window, cx| {
                         view.toggle_group(group, cx);
+                        view.collapse_changed(window, cx);
                     }))
                     .child(arrow)
                     .child(format!("{} ({})", title, display_count)),
@@ -272,9 +583,20 @@ impl Sidebar {
                         cx.theme().muted_foreground
                     })
                     .when(is_active, |el| el.font_weight(gpui::FontWeight::BOLD))
-                    .on_click(cx.listener(move |view, _event, _window, cx| {
+                    .on_click(cx.listener(move |view, _event, window, cx| {
                         view.toggle_folder(path.clone(), cx);
+                        view.collapse_changed(window, cx);
                     }))
+                    .when_some(self.remote_for_node(node).cloned(), |el, remote| {
+                        el.on_mouse_down(
+                            MouseButton::Right,
+                            cx.listener(move |view, _event: &MouseDownEvent, window, cx| {
+                                if let Some(ref on_menu) = view.on_remote_menu {
+                                    on_menu(&remote, window, cx);
+                                }
+                            }),
+                        )
+                    })
                     .child(format!("{}{}", arrow, node.segment))
                     .into_any_element(),
             );
@@ -317,13 +639,26 @@ impl Sidebar {
         } else {
             // Leaf node — no arrow, extra indent to align with folder text
             let branch_info = node.branch.clone().unwrap();
+            let upstream_target = branch_info.clone();
+            // Only local branches can be checked out or track another branch.
+            let is_local = !is_remote_tree_path(&node.path);
+            let upstream = branch_info.upstream.clone();
+            let tracking = [
+                (branch_info.ahead, '\u{2191}'),
+                (branch_info.behind, '\u{2193}'),
+            ]
+            .into_iter()
+            .filter_map(|(count, arrow)| count.filter(|&n| n > 0).map(|n| format!("{arrow}{n}")))
+            .collect::<Vec<_>>()
+            .join(" ");
             elements.push(
-                gpui::div()
+                h_flex()
                     .id(gpui::ElementId::Name(
                         format!("branch-{}", node.path).into(),
                     ))
                     .pl(gpui::px(indent + 12.0 + 16.0)) // base + indent + arrow space
                     .py_0p5()
+                    .gap_1()
                     .text_sm()
                     .w_full()
                     .cursor_pointer()
@@ -335,14 +670,55 @@ impl Sidebar {
                     .when(is_active, |el| el.font_weight(gpui::FontWeight::BOLD))
                     .on_click(cx.listener(move |view, event: &ClickEvent, window, cx| {
                         if let ClickEvent::Mouse(mouse) = event {
-                            if mouse.down.click_count == 2 {
+                            if mouse.down.modifiers.alt {
+                                if let Some(ref on_compare) = view.on_branch_compare {
+                                    on_compare(&branch_info, window, cx);
+                                }
+                            } else if mouse.down.click_count == 1 {
+                                if let Some(ref on_select) = view.on_branch_select {
+                                    on_select(&branch_info, window, cx);
+                                }
+                            } else if mouse.down.click_count == 2 && is_local {
                                 if let Some(ref on_checkout) = view.on_branch_checkout {
                                     on_checkout(&branch_info, window, cx);
                                 }
                             }
                         }
                     }))
+                    .on_mouse_down(
+                        MouseButton::Right,
+                        cx.listener(move |view, _event: &MouseDownEvent, window, cx| {
+                            if !is_local {
+                                return;
+                            }
+                            if let Some(ref on_set_upstream) = view.on_branch_set_upstream {
+                                on_set_upstream(&upstream_target, window, cx);
+                            }
+                        }),
+                    )
                     .child(node.segment.clone())
+                    .when_some(upstream, |el, upstream| {
+                        el.child(
+                            gpui::div()
+                                .text_xs()
+                                .text_color(cx.theme().muted_foreground)
+                                .font_weight(gpui::FontWeight::NORMAL)
+                                .child(format!("\u{2192} {upstream}")),
+                        )
+                    })
+                    .when(!tracking.is_empty(), |el| {
+                        el.child(
+                            gpui::div()
+                                .text_xs()
+                                .text_color(cx.theme().muted_foreground)
+                                .font_weight(gpui::FontWeight::NORMAL)
+                                .debug_selector({
+                                    let path = node.path.clone();
+                                    move || format!("branch-tracking-{path}")
+                                })
+                                .child(tracking),
+                        )
+                    })
                     .into_any_element(),
             );
         }
@@ -350,7 +726,214 @@ impl Sidebar {
         elements
     }
 
-    fn render_item(&self, label: String, is_active: bool, cx: &Context<Self>) -> impl IntoElement {
+    /// The remote a root of the remote tree stands for.
+    fn remote_for_node(&self, node: &BranchTreeNode) -> Option<&RemoteInfo> {
+        let name = node
+            .path
+            .strip_prefix(REMOTE_TREE_ROOT)?
+            .strip_prefix('/')?;
+        self.data.remotes.iter().find(|remote| remote.name == name)
+    }
+
+    /// A remote's row: an expandable folder of its tracking branches, or a
+    /// plain item when it has none.
+    fn render_remote(&self, remote: &RemoteInfo, cx: &Context<Self>) -> Vec<gpui::AnyElement> {
+        if let Some(node) = self
+            .remote_tree
+            .iter()
+            .find(|node| node.segment == remote.name)
+        {
+            return self.render_branch_tree_node(node, 0, cx);
+        }
+        let remote = remote.clone();
+        vec![self
+            .render_item(remote.name.clone(), false, cx)
+            .on_mouse_down(
+                MouseButton::Right,
+                cx.listener(move |view, _event: &MouseDownEvent, window, cx| {
+                    if let Some(ref on_menu) = view.on_remote_menu {
+                        on_menu(&remote, window, cx);
+                    }
+                }),
+            )
+            .into_any_element()]
+    }
+
+    /// One-line repository health header, e.g. `main · clean · 1 stash`.
+    fn render_summary(&self, summary: &RepoSummary, cx: &Context<Self>) -> impl IntoElement {
+        let mut parts = vec![
+            summary.branch.clone(),
+            if summary.dirty { "modified" } else { "clean" }.to_string(),
+            format!("{} commits", summary.commit_count),
+        ];
+        if summary.stash_count > 0 {
+            let noun = if summary.stash_count == 1 {
+                "stash"
+            } else {
+                "stashes"
+            };
+            parts.push(format!("{} {noun}", summary.stash_count));
+        }
+
+        v_flex()
+            .debug_selector(|| "sidebar-summary".into())
+            .px_2()
+            .gap_0p5()
+            .text_xs()
+            .text_color(cx.theme().muted_foreground)
+            .child(parts.join(" \u{b7} "))
+            .when_some(summary.operation, |el, op| {
+                let action = |id: &'static str, label: &'static str| {
+                    gpui::div()
+                        .id(id)
+                        .debug_selector(move || id.into())
+                        .cursor_pointer()
+                        .text_color(cx.theme().link)
+                        .child(label)
+                };
+                el.child(
+                    h_flex()
+                        .debug_selector(|| "operation-banner".into())
+                        .flex_wrap()
+                        .gap_2()
+                        .px_1()
+                        .py_0p5()
+                        .rounded_sm()
+                        .bg(cx.theme().warning.opacity(0.15))
+                        .child(
+                            gpui::div()
+                                .font_weight(gpui::FontWeight::BOLD)
+                                .text_color(cx.theme().warning)
+                                .child(format!("{} in progress", op.label())),
+                        )
+                        .when(op.can_continue(), |el| {
+                            el.child(action("operation-continue", "Continue").on_click(
+                                cx.listener(|view, _event, window, cx| {
+                                    view.continue_operation(window, cx);
+                                }),
+                            ))
+                        })
+                        .child(action("operation-abort", "Abort").on_click(cx.listener(
+                            |view, _event, window, cx| {
+                                view.abort_operation(window, cx);
+                            },
+                        ))),
+                )
+            })
+            .when(summary.shallow, |el| {
+                el.child(
+                    gpui::div()
+                        .debug_selector(|| "shallow-clone-banner".into())
+                        .flex()
+                        .flex_wrap()
+                        .gap_1()
+                        .text_color(cx.theme().warning)
+                        .child("Shallow clone, history is incomplete.")
+                        .child(
+                            gpui::div()
+                                .id("fetch-full-history")
+                                .cursor_pointer()
+                                .text_color(cx.theme().link)
+                                .on_click(cx.listener(|view, _event, window, cx| {
+                                    view.fetch_full_history(window, cx);
+                                }))
+                                .child("Fetch full history"),
+                        ),
+                )
+            })
+    }
+
+    /// A tag row; annotated tags show their message on hover.
+    fn render_tag(&self, tag: &TagInfo, cx: &Context<Self>) -> gpui::AnyElement {
+        let item = self.render_item(tag.name.clone(), false, cx);
+        let Some(message) = self.data.tag_messages.get(&tag.name) else {
+            return item.into_any_element();
+        };
+        let name = tag.name.clone();
+        let message: SharedString = message.clone().into();
+        item.id(gpui::ElementId::Name(format!("tag-{name}").into()))
+            .debug_selector(move || format!("tag-annotated-{name}"))
+            .child(
+                gpui::div()
+                    .text_xs()
+                    .text_color(cx.theme().muted_foreground)
+                    .overflow_hidden()
+                    .whitespace_nowrap()
+                    .child(message.lines().next().unwrap_or_default().to_string()),
+            )
+            .tooltip(move |window, cx| Tooltip::new(message.clone()).build(window, cx))
+            .into_any_element()
+    }
+
+    /// Rows for `entries` of directory `parent` and, below each expanded
+    /// subdirectory, its own entries.
+    fn render_file_entries(
+        &self,
+        entries: &[TreeEntry],
+        parent: &str,
+        depth: usize,
+        cx: &Context<Self>,
+    ) -> Vec<gpui::AnyElement> {
+        let mut elements = Vec::new();
+        for entry in entries {
+            let path = child_path(parent, &entry.name);
+            let children = entry.is_dir.then(|| self.open_dirs.get(&path)).flatten();
+            let label = match (entry.is_dir, children.is_some()) {
+                (true, true) => format!("▼ {}", entry.name),
+                (true, false) => format!("▶ {}", entry.name),
+                (false, _) => entry.name.clone(),
+            };
+            let is_dir = entry.is_dir;
+            let target = path.clone();
+            elements.push(
+                h_flex()
+                    .id(gpui::ElementId::Name(format!("file-{path}").into()))
+                    .debug_selector({
+                        let path = path.clone();
+                        move || format!("file-{path}")
+                    })
+                    // Files get the arrow's width so names line up.
+                    .pl(gpui::px(
+                        depth as f32 * 12.0 + if is_dir { 12.0 } else { 28.0 },
+                    ))
+                    .py_0p5()
+                    .w_full()
+                    .cursor_pointer()
+                    .text_sm()
+                    .text_color(cx.theme().muted_foreground)
+                    .on_click(cx.listener(move |view, _event, window, cx| {
+                        if is_dir {
+                            view.toggle_dir(&target, window, cx);
+                        } else {
+                            view.open_file(&target, window, cx);
+                        }
+                    }))
+                    .child(label)
+                    .into_any_element(),
+            );
+            if let Some(children) = children {
+                elements.extend(self.render_file_entries(children, &path, depth + 1, cx));
+            }
+        }
+        elements
+    }
+
+    /// Rows the file browser shows under `parent`, counting expanded
+    /// subdirectories.
+    fn file_row_count(&self, entries: &[TreeEntry], parent: &str) -> usize {
+        entries
+            .iter()
+            .map(|entry| {
+                let path = child_path(parent, &entry.name);
+                1 + self
+                    .open_dirs
+                    .get(&path)
+                    .map_or(0, |children| self.file_row_count(children, &path))
+            })
+            .sum()
+    }
+
+    fn render_item(&self, label: String, is_active: bool, cx: &Context<Self>) -> gpui::Div {
         gpui::div()
             .px_3()
             .py_0p5()
@@ -380,14 +963,25 @@ impl Render for Sidebar {
             .data
             .remotes
             .iter()
-            .map(|r| self.render_item(r.name.clone(), false, cx))
+            .flat_map(|r| self.render_remote(r, cx))
             .collect();
+        let remote_visible_count: usize = self
+            .data
+            .remotes
+            .iter()
+            .map(|r| {
+                self.remote_tree
+                    .iter()
+                    .find(|node| node.segment == r.name)
+                    .map_or(1, |node| node.visible_count(&self.collapsed_folders))
+            })
+            .sum();
 
         let tag_items: Vec<_> = self
             .data
             .tags
             .iter()
-            .map(|t| self.render_item(t.name.clone(), false, cx))
+            .map(|t| self.render_tag(t, cx))
             .collect();
 
         let stash_items: Vec<_> = self
@@ -397,9 +991,27 @@ impl Render for Sidebar {
             .map(|s| self.render_item(s.message.clone(), false, cx))
             .collect();
 
+        let submodule_items: Vec<_> = self
+            .data
+            .submodules
+            .iter()
+            .map(|s| self.render_item(s.path.clone(), false, cx))
+            .collect();
+
         let remote_count = self.data.remotes.len();
         let tag_count = self.data.tags.len();
         let stash_count = self.data.stashes.len();
+        let submodule_count = self.data.submodules.len();
+        let file_section = self.show_files.then(|| {
+            self.render_section(
+                SidebarGroup::Files,
+                "FILES",
+                self.data.files.len(),
+                self.file_row_count(&self.data.files, ""),
+                self.render_file_entries(&self.data.files, "", 0, cx),
+                cx,
+            )
+        });
 
         v_flex()
             .size_full()
@@ -407,6 +1019,9 @@ impl Render for Sidebar {
             .py_2()
             .gap_2()
             .overflow_y_scrollbar()
+            .when_some(self.summary.as_ref(), |el, summary| {
+                el.child(self.render_summary(summary, cx))
+            })
             .child(self.render_section(
                 SidebarGroup::Branches,
                 "BRANCHES",
@@ -419,7 +1034,7 @@ impl Render for Sidebar {
                 SidebarGroup::Remotes,
                 "REMOTES",
                 remote_count,
-                remote_count,
+                remote_visible_count,
                 remote_items,
                 cx,
             ))
@@ -442,11 +1057,12 @@ impl Render for Sidebar {
             .child(self.render_section(
                 SidebarGroup::Submodules,
                 "SUBMODULES",
-                0,
-                0,
-                Vec::<gpui::AnyElement>::new(),
+                submodule_count,
+                submodule_count,
+                submodule_items,
                 cx,
             ))
+            .children(file_section)
     }
 }
 
@@ -472,12 +1088,20 @@ mod tests {
                         branches: vec![BranchInfo {
                             name: "main".into(),
                             is_head: true,
+                            upstream: None,
+                            ahead: None,
+                            behind: None,
                         }],
                         remotes: vec![RemoteInfo {
                             name: "origin".into(),
+                            url: None,
                         }],
+                        remote_branches: vec![],
                         tags: vec![],
+                        tag_messages: HashMap::new(),
                         stashes: vec![],
+                        submodules: vec![],
+                        files: vec![],
                     },
                     cx,
                 );
@@ -544,26 +1168,42 @@ mod tests {
                 BranchInfo {
                     name: "main".into(),
                     is_head: true,
+                    upstream: None,
+                    ahead: None,
+                    behind: None,
                 },
                 BranchInfo {
                     name: "feature".into(),
                     is_head: false,
+                    upstream: None,
+                    ahead: None,
+                    behind: None,
                 },
             ],
             remotes: vec![RemoteInfo {
                 name: "origin".into(),
+                url: None,
             }],
+            remote_branches: vec![],
             tags: vec![TagInfo {
                 name: "v1.0".into(),
             }],
+            tag_messages: HashMap::new(),
             stashes: vec![StashInfo {
                 message: "WIP".into(),
             }],
+            submodules: vec![SubmoduleInfo {
+                name: "vendor/lib".into(),
+                path: "vendor/lib".into(),
+                head_oid: None,
+            }],
+            files: vec![],
         };
         assert_eq!(data.branches.len(), 2);
         assert_eq!(data.remotes.len(), 1);
         assert_eq!(data.tags.len(), 1);
         assert_eq!(data.stashes.len(), 1);
+        assert_eq!(data.submodules.len(), 1);
     }
 
     #[test]
@@ -572,10 +1212,16 @@ mod tests {
             BranchInfo {
                 name: "main".into(),
                 is_head: true,
+                upstream: None,
+                ahead: None,
+                behind: None,
             },
             BranchInfo {
                 name: "develop".into(),
                 is_head: false,
+                upstream: None,
+                ahead: None,
+                behind: None,
             },
         ];
         let tree = BranchTreeNode::build(&branches);
@@ -592,11 +1238,43 @@ mod tests {
         assert!(tree[1].branch.as_ref().unwrap().is_head);
     }
 
+    #[test]
+    fn test_build_remote_tree_roots_are_remotes() {
+        let branch = |name: &str| BranchInfo {
+            name: name.into(),
+            is_head: false,
+            upstream: None,
+            ahead: None,
+            behind: None,
+        };
+        let tree = BranchTreeNode::build_remote(&[
+            branch("origin/main"),
+            branch("origin/feature/x"),
+            branch("fork/main"),
+        ]);
+        let roots: Vec<_> = tree.iter().map(|n| n.segment.as_str()).collect();
+        assert_eq!(roots, vec!["fork", "origin"]);
+        assert_eq!(tree[1].path, ":remotes/origin");
+        assert_eq!(tree[1].children[0].path, ":remotes/origin/feature");
+        assert_eq!(
+            tree[1].children[0].children[0].path,
+            ":remotes/origin/feature/x"
+        );
+        assert!(is_remote_tree_path(&tree[1].children[0].children[0].path));
+
+        // A local branch named like the remote tree stays local.
+        let local = BranchTreeNode::build(&[branch("remotes/origin/main")]);
+        assert!(!is_remote_tree_path(&local[0].children[0].children[0].path));
+    }
+
     #[test]
     fn test_build_tree_nested() {
         let branches = vec![BranchInfo {
             name: "checkpoints/260214/feat/mvp-baseline1/1".into(),
             is_head: false,
+            upstream: None,
+            ahead: None,
+            behind: None,
         }];
         let tree = BranchTreeNode::build(&branches);
         assert_eq!(tree.len(), 1);
@@ -617,10 +1295,16 @@ mod tests {
             BranchInfo {
                 name: "feat/a".into(),
                 is_head: false,
+                upstream: None,
+                ahead: None,
+                behind: None,
             },
             BranchInfo {
                 name: "feat/b".into(),
                 is_head: false,
+                upstream: None,
+                ahead: None,
+                behind: None,
             },
         ];
         let tree = BranchTreeNode::build(&branches);
@@ -638,14 +1322,23 @@ mod tests {
             BranchInfo {
                 name: "feat/a".into(),
                 is_head: false,
+                upstream: None,
+                ahead: None,
+                behind: None,
             },
             BranchInfo {
                 name: "feat/b".into(),
                 is_head: false,
+                upstream: None,
+                ahead: None,
+                behind: None,
             },
             BranchInfo {
                 name: "main".into(),
                 is_head: true,
+                upstream: None,
+                ahead: None,
+                behind: None,
             },
         ];
         let tree = BranchTreeNode::build(&branches);
@@ -669,10 +1362,16 @@ mod tests {
             BranchInfo {
                 name: "main".into(),
                 is_head: true,
+                upstream: None,
+                ahead: None,
+                behind: None,
             },
             BranchInfo {
                 name: "main/hotfix".into(),
                 is_head: false,
+                upstream: None,
+                ahead: None,
+                behind: None,
             },
         ];
         let tree = BranchTreeNode::build(&branches);
@@ -723,6 +1422,101 @@ mod tests {
             .unwrap();
     }
 
+    #[gpui::test]
+    fn test_annotated_tag_shows_message(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| crate::test_helpers::init_test_theme(cx));
+        let (view, cx) = cx.add_window_view(|_window, _cx| Sidebar::new_empty());
+
+        view.update(cx, |view, cx| {
+            let mut data = SidebarData::empty();
+            data.tags = vec![
+                TagInfo {
+                    name: "v0.1".into(),
+                },
+                TagInfo {
+                    name: "v1.0".into(),
+                },
+            ];
+            data.tag_messages
+                .insert("v1.0".into(), "Release 1.0".into());
+            view.set_data(data, cx);
+        });
+        cx.run_until_parked();
+
+        assert!(cx.debug_bounds("tag-annotated-v1.0").is_some());
+        assert!(cx.debug_bounds("tag-annotated-v0.1").is_none());
+    }
+
+    #[gpui::test]
+    fn test_branch_shows_ahead_behind_counts(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| crate::test_helpers::init_test_theme(cx));
+        let (view, cx) = cx.add_window_view(|_window, _cx| Sidebar::new_empty());
+
+        let branch = |name: &str, ahead, behind| BranchInfo {
+            name: name.into(),
+            is_head: false,
+            upstream: Some(format!("origin/{name}")),
+            ahead,
+            behind,
+        };
+        view.update(cx, |view, cx| {
+            let mut data = SidebarData::empty();
+            data.branches = vec![
+                branch("main", Some(2), Some(1)),
+                branch("synced", Some(0), Some(0)),
+                branch("gone", None, None),
+            ];
+            view.set_data(data, cx);
+        });
+        cx.run_until_parked();
+
+        assert!(cx.debug_bounds("branch-tracking-main").is_some());
+        assert!(cx.debug_bounds("branch-tracking-synced").is_none());
+        assert!(cx.debug_bounds("branch-tracking-gone").is_none());
+    }
+
+    #[gpui::test]
+    fn test_file_browser_expands_directories(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| crate::test_helpers::init_test_theme(cx));
+        let (view, cx) = cx.add_window_view(|_window, _cx| Sidebar::new_empty());
+        let entry = |name: &str, is_dir| TreeEntry {
+            name: name.into(),
+            is_dir,
+            oid: String::new(),
+        };
+        let opened = std::rc::Rc::new(std::cell::RefCell::new(None));
+
+        let sink = opened.clone();
+        view.update(cx, |view, cx| {
+            let mut data = SidebarData::empty();
+            data.files = vec![entry("src", true), entry("README.md", false)];
+            view.set_data(data, cx);
+            view.set_show_files(true, cx);
+            view.on_dir_open(|dir, _window, _cx| assert_eq!(dir, "src"));
+            view.on_file_open(move |path, _window, _cx| {
+                *sink.borrow_mut() = Some(path.to_string());
+            });
+        });
+        cx.run_until_parked();
+        assert!(cx.debug_bounds("file-README.md").is_some());
+        assert!(cx.debug_bounds("file-src/lib.rs").is_none());
+
+        view.update_in(cx, |view, window, cx| {
+            view.toggle_dir("src", window, cx);
+            view.set_dir_entries("src".into(), vec![entry("lib.rs", false)], cx);
+        });
+        cx.run_until_parked();
+        assert!(cx.debug_bounds("file-src/lib.rs").is_some());
+
+        view.update_in(cx, |view, window, cx| {
+            view.open_file("src/lib.rs", window, cx);
+            view.toggle_dir("src", window, cx);
+        });
+        cx.run_until_parked();
+        assert_eq!(opened.borrow().as_deref(), Some("src/lib.rs"));
+        assert!(cx.debug_bounds("file-src/lib.rs").is_none());
+    }
+
     #[gpui::test]
     fn test_set_data_rebuilds_tree(cx: &mut gpui::TestAppContext) {
         cx.update(|cx| crate::test_helpers::init_test_theme(cx));
@@ -744,15 +1538,25 @@ mod tests {
                             BranchInfo {
                                 name: "feat/a".into(),
                                 is_head: false,
+                                upstream: None,
+                                ahead: None,
+                                behind: None,
                             },
                             BranchInfo {
                                 name: "feat/b".into(),
                                 is_head: false,
+                                upstream: None,
+                                ahead: None,
+                                behind: None,
                             },
                         ],
                         remotes: vec![],
+                        remote_branches: vec![],
                         tags: vec![],
+                        tag_messages: HashMap::new(),
                         stashes: vec![],
+                        submodules: vec![],
+                        files: vec![],
                     },
                     cx,
                 );
@@ -775,10 +1579,17 @@ mod tests {
                         branches: vec![BranchInfo {
                             name: "main".into(),
                             is_head: true,
+                            upstream: None,
+                            ahead: None,
+                            behind: None,
                         }],
                         remotes: vec![],
+                        remote_branches: vec![],
                         tags: vec![],
+                        tag_messages: HashMap::new(),
                         stashes: vec![],
+                        submodules: vec![],
+                        files: vec![],
                     },
                     cx,
                 );
@@ -1,9 +1,12 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
 use std::ops::Range;
 use std::path::Path;
 use std::sync::LazyLock;
 
 use gpui::Hsla;
-use syntect::highlighting::{Style, ThemeSet};
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{Style, Theme, ThemeSet};
 use syntect::parsing::SyntaxSet;
 
 /// A byte-range highlight produced by syntax highlighting.
@@ -16,6 +19,21 @@ pub struct SyntaxHighlight {
 
 static SYNTAX_SET: LazyLock<SyntaxSet> = LazyLock::new(SyntaxSet::load_defaults_newlines);
 static THEME_SET: LazyLock<ThemeSet> = LazyLock::new(ThemeSet::load_defaults);
+/// Used when even the default theme is missing; every style is the same,
+/// but lines still get highlights.
+static EMPTY_THEME: LazyLock<Theme> = LazyLock::new(Theme::default);
+
+const DARK_THEME: &str = "base16-ocean.dark";
+const LIGHT_THEME: &str = "base16-ocean.light";
+
+/// The named theme, falling back to the dark default when it is missing.
+fn theme_or_default(name: &str) -> &'static Theme {
+    THEME_SET
+        .themes
+        .get(name)
+        .or_else(|| THEME_SET.themes.get(DARK_THEME))
+        .unwrap_or(&*EMPTY_THEME)
+}
 
 /// Highlight a single line of code, returning byte-range highlights.
 /// Falls back to a single range covering the entire line with `fallback_color`
@@ -26,31 +44,162 @@ pub fn highlight_line(
     fallback_color: Hsla,
     is_dark: bool,
 ) -> Vec<SyntaxHighlight> {
-    let ext = Path::new(file_path)
+    let theme_name = if is_dark { DARK_THEME } else { LIGHT_THEME };
+    highlight_line_with_theme(file_path, line, fallback_color, theme_name)
+}
+
+/// [`highlight_line`] with a syntect theme chosen by name. Unknown names use
+/// the default theme instead of failing. Results are memoized per thread
+/// by file extension, theme and line content.
+pub fn highlight_line_with_theme(
+    file_path: &str,
+    line: &str,
+    fallback_color: Hsla,
+    theme_name: &str,
+) -> Vec<SyntaxHighlight> {
+    let key = (
+        extension(file_path).to_string(),
+        theme_name.to_string(),
+        line.to_string(),
+    );
+    if let Some(highlights) = LINE_CACHE.with_borrow_mut(|cache| cache.get(&key)) {
+        return highlights;
+    }
+    let mut highlighter = highlighter_for(file_path, theme_name);
+    match highlight_ranges(&mut highlighter, line) {
+        Some(highlights) => {
+            LINE_CACHE.with_borrow_mut(|cache| cache.insert(key, highlights.clone()));
+            highlights
+        }
+        None => fallback_highlight(line, fallback_color),
+    }
+}
+
+/// Highlight consecutive lines of one file, such as one side of a hunk,
+/// carrying syntect's parse and highlight state from line to line so
+/// constructs spanning lines (block comments, raw strings, here-docs) keep
+/// their coloring. Returns one set of highlights per line; a line that
+/// fails to highlight is covered by `fallback_color`. Not memoized, since
+/// a line's colors depend on the lines before it.
+pub fn highlight_hunk(
+    file_path: &str,
+    lines: &[&str],
+    fallback_color: Hsla,
+    is_dark: bool,
+) -> Vec<Vec<SyntaxHighlight>> {
+    let theme_name = if is_dark { DARK_THEME } else { LIGHT_THEME };
+    // One highlighter for all lines: it holds the parse state between them.
+    let mut highlighter = highlighter_for(file_path, theme_name);
+    lines
+        .iter()
+        .map(|line| {
+            highlight_ranges(&mut highlighter, line)
+                .unwrap_or_else(|| fallback_highlight(line, fallback_color))
+        })
+        .collect()
+}
+
+/// Single-line highlights kept per thread; see [`LineCache`].
+const LINE_CACHE_CAPACITY: usize = 4096;
+
+/// File extension, theme name and line content.
+type LineKey = (String, String, String);
+
+/// Memoized single-line highlights. Holds at most `capacity` lines; the
+/// least recently used is evicted.
+struct LineCache {
+    capacity: usize,
+    /// Highlights and the tick they were last used at.
+    entries: HashMap<LineKey, (u64, Vec<SyntaxHighlight>)>,
+    /// Every use in tick order. An element is stale once its key has been
+    /// used again, so the oldest live one is the least recently used entry.
+    uses: VecDeque<(u64, LineKey)>,
+    clock: u64,
+    #[cfg(test)]
+    hits: usize,
+}
+
+impl LineCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: HashMap::new(),
+            uses: VecDeque::new(),
+            clock: 0,
+            #[cfg(test)]
+            hits: 0,
+        }
+    }
+
+    fn get(&mut self, key: &LineKey) -> Option<Vec<SyntaxHighlight>> {
+        self.clock += 1;
+        let (used, highlights) = self.entries.get_mut(key)?;
+        *used = self.clock;
+        let highlights = highlights.clone();
+        self.record_use(key.clone());
+        #[cfg(test)]
+        {
+            self.hits += 1;
+        }
+        Some(highlights)
+    }
+
+    fn insert(&mut self, key: LineKey, highlights: Vec<SyntaxHighlight>) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.len() >= self.capacity && !self.entries.contains_key(&key) {
+            while let Some((tick, oldest)) = self.uses.pop_front() {
+                if self.is_live(tick, &oldest) {
+                    self.entries.remove(&oldest);
+                    break;
+                }
+            }
+        }
+        self.clock += 1;
+        self.entries.insert(key.clone(), (self.clock, highlights));
+        self.record_use(key);
+    }
+
+    fn is_live(&self, tick: u64, key: &LineKey) -> bool {
+        self.entries.get(key).is_some_and(|(used, _)| *used == tick)
+    }
+
+    /// Queue the use at the current tick, dropping stale uses once they
+    /// outnumber the entries so the queue stays proportional to the cache.
+    fn record_use(&mut self, key: LineKey) {
+        self.uses.push_back((self.clock, key));
+        if self.uses.len() > 2 * self.capacity {
+            let mut uses = std::mem::take(&mut self.uses);
+            uses.retain(|(tick, key)| self.is_live(*tick, key));
+            self.uses = uses;
+        }
+    }
+}
+
+thread_local! {
+    static LINE_CACHE: RefCell<LineCache> = RefCell::new(LineCache::new(LINE_CACHE_CAPACITY));
+}
+
+fn extension(file_path: &str) -> &str {
+    Path::new(file_path)
         .extension()
         .and_then(|e| e.to_str())
-        .unwrap_or("");
+        .unwrap_or("")
+}
 
+fn highlighter_for(file_path: &str, theme_name: &str) -> HighlightLines<'static> {
     let syntax = SYNTAX_SET
-        .find_syntax_by_extension(ext)
+        .find_syntax_by_extension(extension(file_path))
         .unwrap_or_else(|| SYNTAX_SET.find_syntax_plain_text());
+    HighlightLines::new(syntax, theme_or_default(theme_name))
+}
 
-    let theme_name = if is_dark {
-        "base16-ocean.dark"
-    } else {
-        "base16-ocean.light"
-    };
-    let theme = &THEME_SET.themes[theme_name];
-    let mut highlighter = syntect::easy::HighlightLines::new(syntax, theme);
-
+/// Highlight the next line with `highlighter`, or `None` if syntect fails.
+fn highlight_ranges(highlighter: &mut HighlightLines, line: &str) -> Option<Vec<SyntaxHighlight>> {
     // Append a newline because syntect expects newline-terminated lines
     let input = format!("{}\n", line);
-    let Ok(ranges) = highlighter.highlight_line(&input, &SYNTAX_SET) else {
-        return vec![SyntaxHighlight {
-            range: 0..line.len(),
-            color: fallback_color,
-        }];
-    };
+    let ranges = highlighter.highlight_line(&input, &SYNTAX_SET).ok()?;
 
     let mut result = Vec::new();
     let mut offset = 0usize;
@@ -66,8 +215,14 @@ pub fn highlight_line(
         }
         offset = end;
     }
+    Some(result)
+}
 
-    result
+fn fallback_highlight(line: &str, fallback_color: Hsla) -> Vec<SyntaxHighlight> {
+    vec![SyntaxHighlight {
+        range: 0..line.len(),
+        color: fallback_color,
+    }]
 }
 
 fn style_to_hsla(style: Style) -> Hsla {
@@ -155,6 +310,85 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_unknown_theme_falls_back_to_default() {
+        let line = "fn main() {}";
+        let highlights =
+            highlight_line_with_theme("test.rs", line, Hsla::default(), "no-such-theme");
+        let combined: String = highlights.iter().map(|h| &line[h.range.clone()]).collect();
+        assert_eq!(combined, line);
+
+        let default = highlight_line("test.rs", line, Hsla::default(), true);
+        let colors = |hs: &[SyntaxHighlight]| hs.iter().map(|h| h.color).collect::<Vec<_>>();
+        assert_eq!(colors(&highlights), colors(&default));
+    }
+
+    #[test]
+    fn test_hunk_keeps_block_comment_state_across_lines() {
+        let lines = ["/* start of comment", "let x = 42;", "end */", "let y = 1;"];
+        let highlights = highlight_hunk("test.rs", &lines, Hsla::default(), true);
+        assert_eq!(highlights.len(), lines.len());
+
+        // The comment's text, past its opening punctuation.
+        let comment_color = highlights[0].last().unwrap().color;
+        assert!(
+            highlights[1].iter().all(|h| h.color == comment_color),
+            "expected comment coloring, got {:?}",
+            highlights[1]
+        );
+        assert_eq!(highlights[2][0].color, comment_color);
+        // After the comment closes, code is highlighted as code again.
+        assert!(highlights[3].len() > 1);
+        assert!(highlight_line("test.rs", lines[1], Hsla::default(), true).len() > 1);
+    }
+
+    #[test]
+    fn test_repeated_line_is_served_from_cache() {
+        let hits = || LINE_CACHE.with_borrow(|cache| cache.hits);
+        let before = hits();
+        let line = "let cached = vec![1, 2, 3];";
+        let first = highlight_line("bench.rs", line, Hsla::default(), true);
+        for _ in 1..10_000 {
+            let again = highlight_line("bench.rs", line, Hsla::default(), true);
+            assert_eq!(again.len(), first.len());
+        }
+        assert_eq!(hits() - before, 9_999);
+
+        // Another theme is a different entry.
+        highlight_line("bench.rs", line, Hsla::default(), false);
+        assert_eq!(hits() - before, 9_999);
+    }
+
+    #[test]
+    fn test_line_cache_evicts_least_recently_used() {
+        let key = |content: &str| {
+            (
+                "rs".to_string(),
+                DARK_THEME.to_string(),
+                content.to_string(),
+            )
+        };
+        let mut cache = LineCache::new(2);
+        cache.insert(key("a"), Vec::new());
+        cache.insert(key("b"), Vec::new());
+        assert!(cache.get(&key("a")).is_some());
+        cache.insert(key("c"), Vec::new());
+
+        assert_eq!(cache.entries.len(), 2);
+        assert!(cache.get(&key("a")).is_some());
+        assert!(cache.get(&key("b")).is_none());
+        assert!(cache.get(&key("c")).is_some());
+
+        // Repeated hits don't let the use queue outgrow the cache.
+        for _ in 0..100 {
+            cache.get(&key("a"));
+        }
+        assert!(cache.uses.len() <= 4);
+        cache.insert(key("d"), Vec::new());
+        assert!(cache.get(&key("c")).is_none());
+        assert!(cache.get(&key("a")).is_some());
+    }
+
     #[test]
     fn test_rgb_to_hsla_white() {
         let c = rgb_to_hsla(1.0, 1.0, 1.0, 1.0);
@@ -1,11 +1,14 @@
 use gpui::prelude::*;
-use gpui::{Context, ScrollHandle, Window};
+use gpui::{Context, Hsla, MouseButton, ScrollHandle, Window};
 use gpui_component::{h_flex, ActiveTheme};
 
 pub struct TabInfo {
     pub name: String,
     pub is_active: bool,
     pub is_dirty: bool,
+    pub pinned: bool,
+    /// Accent rendered as the tab's bottom border.
+    pub color: Hsla,
 }
 
 #[derive(Clone)]
@@ -42,6 +45,8 @@ pub struct TabBar {
     on_close: Option<Box<dyn Fn(usize, &mut Window, &mut Context<Self>) + 'static>>,
     #[allow(clippy::type_complexity)]
     on_reorder: Option<Box<dyn Fn(usize, usize, &mut Window, &mut Context<Self>) + 'static>>,
+    #[allow(clippy::type_complexity)]
+    on_toggle_pin: Option<Box<dyn Fn(usize, &mut Window, &mut Context<Self>) + 'static>>,
 }
 
 impl Default for TabBar {
@@ -59,9 +64,14 @@ impl TabBar {
             on_select: None,
             on_close: None,
             on_reorder: None,
+            on_toggle_pin: None,
         }
     }
 
+    pub fn tabs(&self) -> &[TabInfo] {
+        &self.tabs
+    }
+
     pub fn set_tabs(&mut self, tabs: Vec<TabInfo>, cx: &mut Context<Self>) {
         if let Some(active_index) = tabs.iter().position(|t| t.is_active) {
             self.scroll_handle.scroll_to_item(active_index);
@@ -92,6 +102,13 @@ impl TabBar {
         self.on_reorder = Some(Box::new(callback));
     }
 
+    pub fn on_toggle_pin(
+        &mut self,
+        callback: impl Fn(usize, &mut Window, &mut Context<Self>) + 'static,
+    ) {
+        self.on_toggle_pin = Some(Box::new(callback));
+    }
+
     pub fn select_tab(&mut self, index: usize, window: &mut Window, cx: &mut Context<Self>) {
         if let Some(ref on_select) = self.on_select {
             on_select(index, window, cx);
@@ -104,6 +121,12 @@ impl TabBar {
         }
     }
 
+    pub fn toggle_pin(&mut self, index: usize, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(ref on_toggle_pin) = self.on_toggle_pin {
+            on_toggle_pin(index, window, cx);
+        }
+    }
+
     pub fn reorder_tab(
         &mut self,
         from: usize,
@@ -130,6 +153,8 @@ impl Render for TabBar {
             .map(|(i, tab)| {
                 let is_active = tab.is_active;
                 let is_dirty = tab.is_dirty;
+                let pinned = tab.pinned;
+                let color = tab.color;
                 let name = tab.name.clone();
                 let show_close = !is_dirty || self.hovered_close == Some(i);
 
@@ -141,13 +166,20 @@ impl Render for TabBar {
                     .gap_2()
                     .cursor_pointer()
                     .border_b_2()
-                    .when(is_active, |el| el.border_color(cx.theme().primary))
-                    .when(!is_active, |el| el.border_color(gpui::transparent_black()))
+                    .when(is_active, |el| el.border_color(color))
+                    .when(!is_active, |el| el.border_color(color.opacity(0.35)))
                     .when(is_active, |el| el.bg(cx.theme().muted))
                     .hover(|el| el.bg(cx.theme().muted))
                     .on_click(cx.listener(move |view, _event, window, cx| {
                         view.select_tab(i, window, cx);
                     }))
+                    // Right-click pins or unpins the tab.
+                    .on_mouse_down(
+                        MouseButton::Right,
+                        cx.listener(move |view, _event, window, cx| {
+                            view.toggle_pin(i, window, cx);
+                        }),
+                    )
                     .on_drag(
                         DraggedTab {
                             index: i,
@@ -165,6 +197,15 @@ impl Render for TabBar {
                     .drag_over::<DraggedTab>(|style, _, _, _| {
                         style.bg(gpui::hsla(0.6, 0.3, 0.5, 0.15))
                     })
+                    .when(pinned, |el| {
+                        el.child(
+                            gpui::div()
+                                .debug_selector(move || format!("tab-pin-{i}"))
+                                .text_xs()
+                                .text_color(color)
+                                .child("📌"),
+                        )
+                    })
                     .child(
                         gpui::div()
                             .text_sm()
@@ -232,11 +273,15 @@ mod tests {
                 name: "repo1".into(),
                 is_active: true,
                 is_dirty: false,
+                pinned: false,
+                color: gpui::blue(),
             },
             TabInfo {
                 name: "repo2".into(),
                 is_active: false,
                 is_dirty: false,
+                pinned: false,
+                color: gpui::blue(),
             },
         ];
         assert_eq!(tabs.len(), 2);
@@ -261,11 +306,15 @@ mod tests {
                             name: "repo1".into(),
                             is_active: true,
                             is_dirty: false,
+                            pinned: false,
+                            color: gpui::blue(),
                         },
                         TabInfo {
                             name: "repo2".into(),
                             is_active: false,
                             is_dirty: false,
+                            pinned: false,
+                            color: gpui::blue(),
                         },
                     ],
                     cx,
@@ -302,11 +351,15 @@ mod tests {
                             name: "repo1".into(),
                             is_active: true,
                             is_dirty: false,
+                            pinned: false,
+                            color: gpui::blue(),
                         },
                         TabInfo {
                             name: "repo2".into(),
                             is_active: false,
                             is_dirty: false,
+                            pinned: false,
+                            color: gpui::blue(),
                         },
                     ],
                     cx,
@@ -343,16 +396,22 @@ mod tests {
                             name: "repo1".into(),
                             is_active: true,
                             is_dirty: false,
+                            pinned: false,
+                            color: gpui::blue(),
                         },
                         TabInfo {
                             name: "repo2".into(),
                             is_active: false,
                             is_dirty: false,
+                            pinned: false,
+                            color: gpui::blue(),
                         },
                         TabInfo {
                             name: "repo3".into(),
                             is_active: false,
                             is_dirty: false,
+                            pinned: false,
+                            color: gpui::blue(),
                         },
                     ],
                     cx,
@@ -384,6 +443,8 @@ mod tests {
                 name: format!("repo{}", i),
                 is_active: i == active_index,
                 is_dirty: false,
+                pinned: false,
+                color: gpui::blue(),
             })
             .collect();
 
@@ -1,6 +1,8 @@
 use std::path::Path;
 use std::process::Command;
 
+use gpui::{AppContext, Context, Entity, Render, TestAppContext, VisualTestContext, Window};
+use gpui_component::Root;
 use tempfile::TempDir;
 
 /// Initialize gpui-component globals and dark theme for tests.
@@ -10,6 +12,21 @@ pub fn init_test_theme(cx: &mut gpui::App) {
     crate::theme::setup_dark_theme(cx);
 }
 
+/// Open a window whose root is a gpui-component `Root` around the view
+/// `build` creates, as the app does. Views that render inputs need it.
+pub fn add_root_window_view<V: Render>(
+    cx: &mut TestAppContext,
+    build: impl FnOnce(&mut Window, &mut Context<V>) -> V,
+) -> (Entity<V>, &mut VisualTestContext) {
+    let mut view = None;
+    let (_root, cx) = cx.add_window_view(|window, cx| {
+        let inner = cx.new(|cx| build(window, cx));
+        view = Some(inner.clone());
+        Root::new(inner, window, cx)
+    });
+    (view.expect("window built"), cx)
+}
+
 /// Run a git command in the given directory, panicking if it fails.
 fn run_git(path: &Path, args: &[&str]) {
     let output = Command::new("git")
@@ -28,7 +45,14 @@ fn run_git(path: &Path, args: &[&str]) {
 /// Create a temp git repo with a single commit (file.txt = "hello").
 pub fn init_test_repo() -> TempDir {
     let dir = TempDir::new().unwrap();
-    let path = dir.path();
+    init_test_repo_at(dir.path());
+    dir
+}
+
+/// Initialize a git repo with a single commit (file.txt = "hello") at
+/// `path`, creating the directory if needed.
+pub fn init_test_repo_at(path: &Path) {
+    std::fs::create_dir_all(path).unwrap();
 
     run_git(path, &["init", "-b", "main"]);
     run_git(path, &["config", "user.email", "test@test.com"]);
@@ -38,8 +62,6 @@ pub fn init_test_repo() -> TempDir {
 
     run_git(path, &["add", "."]);
     run_git(path, &["commit", "-m", "initial commit"]);
-
-    dir
 }
 
 /// Create a temp git repo with 2 commits (for diff testing).
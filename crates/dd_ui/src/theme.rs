@@ -1,10 +1,90 @@
 use std::any::Any;
+use std::path::Path;
 
-use gpui::{App, Context, Hsla};
+use gpui::{App, Context, Global, Hsla};
 use gpui_component::{ActiveTheme, Theme, ThemeMode};
 
+use dd_core::{Appearance, DiffPalette};
+
+/// Counts theme changes so views can tell when output they memoized with
+/// the old colors is stale.
+#[derive(Default)]
+struct ThemeGeneration(u64);
+
+impl Global for ThemeGeneration {}
+
 pub fn setup_dark_theme(cx: &mut App) {
-    Theme::change(ThemeMode::Dark, None, cx);
+    change_theme(ThemeMode::Dark, cx);
+}
+
+pub fn setup_light_theme(cx: &mut App) {
+    change_theme(ThemeMode::Light, cx);
+}
+
+/// Switch to the theme for a saved [`Appearance`].
+pub fn setup_theme(appearance: Appearance, cx: &mut App) {
+    match appearance {
+        Appearance::Dark => setup_dark_theme(cx),
+        Appearance::Light => setup_light_theme(cx),
+    }
+}
+
+/// Switch the theme mode and bump the [`theme_generation`]. Use this rather
+/// than `Theme::change` so cached rendering is invalidated.
+pub fn change_theme(mode: ThemeMode, cx: &mut App) {
+    Theme::change(mode, None, cx);
+    cx.default_global::<ThemeGeneration>().0 += 1;
+    cx.refresh_windows();
+}
+
+/// The current theme generation; changes every time [`change_theme`] runs.
+pub fn theme_generation(cx: &App) -> u64 {
+    cx.try_global::<ThemeGeneration>().map_or(0, |g| g.0)
+}
+
+/// Derive a stable accent color for a repository tab from its path.
+///
+/// Uses FNV-1a over the path bytes so the hue is identical across runs and
+/// toolchain versions (unlike `DefaultHasher`).
+pub fn repo_accent_color(path: &Path) -> Hsla {
+    let mut hash: u32 = 0x811c_9dc5;
+    for byte in path.as_os_str().as_encoded_bytes() {
+        hash ^= *byte as u32;
+        hash = hash.wrapping_mul(0x0100_0193);
+    }
+    Hsla {
+        h: (hash % 360) as f32 / 360.0,
+        s: 0.65,
+        l: 0.55,
+        a: 1.0,
+    }
+}
+
+/// Chip color for a conventional-commit type. Breaking changes always use the
+/// danger color so they stand out regardless of type.
+pub fn conventional_type_color(kind: &str, breaking: bool, theme: &Theme) -> Hsla {
+    if breaking {
+        return theme.danger;
+    }
+    match kind {
+        "feat" => theme.success,
+        "fix" => theme.warning,
+        "docs" => theme.info,
+        "refactor" | "perf" => theme.primary,
+        _ => theme.muted_foreground,
+    }
+}
+
+/// Color of lane `lane` in the commit graph, cycling through the accents.
+pub fn graph_lane_color(lane: usize, theme: &Theme) -> Hsla {
+    let palette = [
+        theme.info,
+        theme.success,
+        theme.warning,
+        theme.primary,
+        theme.danger,
+    ];
+    palette[lane % palette.len()]
 }
 
 pub struct DiffTheme {
@@ -17,40 +97,35 @@ pub struct DiffTheme {
     pub ctx_fg: Hsla,
 }
 
+/// Background saturation when the palette does not override it.
+const DEFAULT_BG_SATURATION: f32 = 0.30;
+/// How much more saturated word-level highlights are than line backgrounds.
+const HIGHLIGHT_SATURATION_BOOST: f32 = 0.25;
+
 impl DiffTheme {
-    pub fn from_cx(cx: &Context<impl Any>) -> Self {
-        let theme = cx.theme();
-        let success_h = theme.success.h;
-        let danger_h = theme.danger.h;
+    pub fn from_cx(cx: &Context<impl Any>, palette: &DiffPalette) -> Self {
+        Self::new(cx.theme(), palette)
+    }
+
+    pub fn new(theme: &Theme, palette: &DiffPalette) -> Self {
+        let add_h = palette.add_hue.map_or(theme.success.h, hue_from_degrees);
+        let del_h = palette.del_hue.map_or(theme.danger.h, hue_from_degrees);
+        let add_s = palette
+            .add_saturation
+            .map_or(DEFAULT_BG_SATURATION, |s| s.clamp(0.0, 1.0));
+        let del_s = palette
+            .del_saturation
+            .map_or(DEFAULT_BG_SATURATION, |s| s.clamp(0.0, 1.0));
 
         let is_dark = theme.background.l < 0.5;
         let (bg_l, hl_l) = if is_dark { (0.10, 0.28) } else { (0.92, 0.78) };
+        let color = |h: f32, s: f32, l: f32| Hsla { h, s, l, a: 1.0 };
 
         Self {
-            add_bg: Hsla {
-                h: success_h,
-                s: 0.30,
-                l: bg_l,
-                a: 1.0,
-            },
-            add_highlight_bg: Hsla {
-                h: success_h,
-                s: 0.55,
-                l: hl_l,
-                a: 1.0,
-            },
-            del_bg: Hsla {
-                h: danger_h,
-                s: 0.30,
-                l: bg_l,
-                a: 1.0,
-            },
-            del_highlight_bg: Hsla {
-                h: danger_h,
-                s: 0.55,
-                l: hl_l,
-                a: 1.0,
-            },
+            add_bg: color(add_h, add_s, bg_l),
+            add_highlight_bg: color(add_h, (add_s + HIGHLIGHT_SATURATION_BOOST).min(1.0), hl_l),
+            del_bg: color(del_h, del_s, bg_l),
+            del_highlight_bg: color(del_h, (del_s + HIGHLIGHT_SATURATION_BOOST).min(1.0), hl_l),
             ctx_bg: Hsla {
                 h: 0.0,
                 s: 0.0,
@@ -63,6 +138,11 @@ impl DiffTheme {
     }
 }
 
+/// Convert a hue in degrees to gpui's 0-1 range, wrapping out-of-range values.
+fn hue_from_degrees(degrees: f32) -> f32 {
+    degrees.rem_euclid(360.0) / 360.0
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -72,4 +152,75 @@ mod tests {
         let mode = ThemeMode::Dark;
         assert!(mode.is_dark());
     }
+
+    #[gpui::test]
+    fn test_change_theme_bumps_generation(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| {
+            crate::test_helpers::init_test_theme(cx);
+            let before = theme_generation(cx);
+            change_theme(ThemeMode::Light, cx);
+            assert_eq!(theme_generation(cx), before + 1);
+            assert!(!cx.theme().mode.is_dark());
+        });
+    }
+
+    #[test]
+    fn test_repo_accent_color_is_deterministic() {
+        let a = repo_accent_color(Path::new("/projects/alpha"));
+        let b = repo_accent_color(Path::new("/projects/alpha"));
+        assert_eq!(a, b);
+        assert!((0.0..1.0).contains(&a.h));
+    }
+
+    #[gpui::test]
+    fn test_conventional_type_color(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| {
+            crate::test_helpers::init_test_theme(cx);
+            let theme = cx.theme();
+            assert_eq!(conventional_type_color("feat", false, theme), theme.success);
+            assert_eq!(conventional_type_color("feat", true, theme), theme.danger);
+            assert_eq!(
+                conventional_type_color("merge", false, theme),
+                theme.muted_foreground
+            );
+        });
+    }
+
+    #[gpui::test]
+    fn test_diff_palette_overrides_hues(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| {
+            crate::test_helpers::init_test_theme(cx);
+            let palette = DiffPalette {
+                add_hue: Some(210.0),
+                add_saturation: Some(0.5),
+                del_hue: Some(30.0),
+                del_saturation: None,
+            };
+            let diff_theme = DiffTheme::new(cx.theme(), &palette);
+            assert!((diff_theme.add_bg.h - 210.0 / 360.0).abs() < 1e-6);
+            assert!((diff_theme.add_highlight_bg.h - 210.0 / 360.0).abs() < 1e-6);
+            assert!((diff_theme.add_bg.s - 0.5).abs() < 1e-6);
+            assert!((diff_theme.del_bg.h - 30.0 / 360.0).abs() < 1e-6);
+            assert!((diff_theme.del_bg.s - DEFAULT_BG_SATURATION).abs() < 1e-6);
+
+            let default_theme = DiffTheme::new(cx.theme(), &DiffPalette::default());
+            assert_eq!(default_theme.add_bg.h, cx.theme().success.h);
+            assert_eq!(default_theme.del_bg.h, cx.theme().danger.h);
+        });
+    }
+
+    #[test]
+    fn test_hue_from_degrees_wraps() {
+        assert_eq!(hue_from_degrees(0.0), 0.0);
+        assert_eq!(hue_from_degrees(180.0), 0.5);
+        assert_eq!(hue_from_degrees(540.0), 0.5);
+        assert_eq!(hue_from_degrees(-90.0), 0.75);
+    }
+
+    #[test]
+    fn test_repo_accent_color_differs_by_path() {
+        let a = repo_accent_color(Path::new("/projects/alpha"));
+        let b = repo_accent_color(Path::new("/projects/beta"));
+        assert_ne!(a.h, b.h);
+    }
 }